@@ -0,0 +1,120 @@
+// Compares the Vec-backed DenseMatrix with the former HashMap-backed storage.
+// Run with `cargo bench --bench dense_matrix`.
+use evolutionary_algebra::linear_algebra::matrix::dense::DenseMatrix;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+// the original storage: one hashed entry per (row, col)
+struct HashMapMatrix {
+    row_num: usize,
+    col_num: usize,
+    elements: HashMap<(usize, usize), f64>,
+}
+
+impl HashMapMatrix {
+    fn from_fn<F: Fn(usize, usize) -> f64>(row_num: usize, col_num: usize, f: F) -> HashMapMatrix {
+        let mut elements = HashMap::with_capacity(row_num * col_num);
+        for i in 0 .. row_num {
+            for j in 0 .. col_num {
+                elements.insert((i, j), f(i, j));
+            }
+        }
+        HashMapMatrix { row_num, col_num, elements }
+    }
+
+    fn get_v(&self, row: usize, col: usize) -> f64 {
+        *self.elements.get(&(row, col)).expect("not find key!")
+    }
+
+    fn set_v(&mut self, row: usize, col: usize, v: f64) {
+        self.elements.insert((row, col), v);
+    }
+
+    fn dot_mul(&self, mat: &HashMapMatrix) -> HashMapMatrix {
+        HashMapMatrix::from_fn(self.row_num, mat.col_num, |i, j| {
+            let mut sum = 0.0;
+            for k in 0 .. self.col_num {
+                sum += self.get_v(i, k) * mat.get_v(k, j);
+            }
+            sum
+        })
+    }
+
+    fn solve_ge(mat_a: &HashMapMatrix, mat_b: &HashMapMatrix) -> HashMapMatrix {
+        let n = mat_a.row_num;
+        let cols = mat_a.col_num + mat_b.col_num;
+        let mut mat = HashMapMatrix::from_fn(n, cols, |i, j| {
+            if j < mat_a.col_num { mat_a.get_v(i, j) } else { mat_b.get_v(i, j - mat_a.col_num) }
+        });
+        for r in 0 .. n {
+            let mut index = r;
+            for row in r + 1 .. n {
+                if mat.get_v(row, r).abs() > mat.get_v(index, r).abs() {
+                    index = row;
+                }
+            }
+            for c in 0 .. cols {
+                let tmp = mat.get_v(r, c);
+                mat.set_v(r, c, mat.get_v(index, c));
+                mat.set_v(index, c, tmp);
+            }
+            let head = mat.get_v(r, r);
+            for c in r .. cols {
+                mat.set_v(r, c, mat.get_v(r, c) / head);
+            }
+            for nr in r + 1 .. n {
+                let row_head = mat.get_v(nr, r);
+                for c in r .. cols {
+                    mat.set_v(nr, c, mat.get_v(nr, c) - row_head * mat.get_v(r, c));
+                }
+            }
+        }
+        for r in (0 .. n).rev() {
+            for nr in 0 .. r {
+                let row_head = mat.get_v(nr, r);
+                for c in r .. cols {
+                    mat.set_v(nr, c, mat.get_v(nr, c) - row_head * mat.get_v(r, c));
+                }
+            }
+        }
+        HashMapMatrix::from_fn(n, mat_b.col_num, |i, j| mat.get_v(i, j + mat_a.col_num))
+    }
+}
+
+fn entry(i: usize, j: usize) -> f64 {
+    let v = ((i * 31 + j * 17) % 101) as f64 / 101.0;
+    if i == j { v + 4.0 } else { v }
+}
+
+fn time<R, F: FnMut() -> R>(f: F) -> Duration {
+    let mut f = f;
+    let start = Instant::now();
+    let _ = f();
+    start.elapsed()
+}
+
+fn main() {
+    for &n in &[100, 250, 500] {
+        let mut dense_a = DenseMatrix::<f64>::new(n, n);
+        let mut dense_b = DenseMatrix::<f64>::new(n, 1);
+        for i in 0 .. n {
+            for j in 0 .. n {
+                dense_a.set_v(i, j, entry(i, j));
+            }
+            dense_b.set_v(i, 0, i as f64);
+        }
+        let hash_a = HashMapMatrix::from_fn(n, n, entry);
+        let hash_b = HashMapMatrix::from_fn(n, 1, |i, _| i as f64);
+
+        let dense_mul = time(|| dense_a.dot_mul(&dense_a));
+        let hash_mul = time(|| hash_a.dot_mul(&hash_a));
+        let dense_ge = time(|| DenseMatrix::solve_ge(&dense_a, &dense_b));
+        let hash_ge = time(|| HashMapMatrix::solve_ge(&hash_a, &hash_b));
+
+        println!("n = {}", n);
+        println!("  dot_mul  Vec: {:>10.3?}  HashMap: {:>10.3?}  speedup: {:.1}x",
+                 dense_mul, hash_mul, hash_mul.as_secs_f64() / dense_mul.as_secs_f64());
+        println!("  solve_ge Vec: {:>10.3?}  HashMap: {:>10.3?}  speedup: {:.1}x",
+                 dense_ge, hash_ge, hash_ge.as_secs_f64() / dense_ge.as_secs_f64());
+    }
+}
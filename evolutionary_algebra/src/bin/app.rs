@@ -1,4 +1,3 @@
-use rand::prelude::*;
 use evolutionary_algebra::linear_algebra::matrix::dense::DenseMatrix;
 use evolutionary_algebra::linear_algebra::matrix::sparse::SparseMatrix;
 
@@ -50,7 +49,7 @@ fn main() {
     }
     println!("A =");
     rmat.show();
-    let bmat = rmat.dot_mul(&matx);
+    let bmat = &rmat * &matx;
     println!("b =");
     bmat.show();
     println!("real x is");
@@ -59,9 +58,9 @@ fn main() {
     println!("x_ calculated by GE is");
     xmat.show();
     println!("||A * x_ - x|| is");
-    println!("{}", xmat.sub(&matx).norm2());
+    println!("{}", (&xmat - &matx).norm2());
 
-    println!("trace(inv(A) * A)  by GE is {}", rmat.inv_ge().unwrap().dot_mul(&rmat).trace());
+    println!("trace(inv(A) * A)  by GE is {}", (rmat.inv_ge().unwrap() * &rmat).trace());
     println!("Hello, world!");
 
     SparseMatrix::<f32>::eye(5).show();
@@ -109,10 +108,10 @@ fn main() {
         }
     }
     // make it diagonally dominant to test the SOR method!
-    srmat = srmat.add(&SparseMatrix::eye(sr).scalar_mul(2.0 * sr as f32));
+    srmat += SparseMatrix::eye(sr) * (2.0 * sr as f32);
     println!("A =");
     srmat.show();
-    let sbmat = srmat.dot_mul(&smatx);
+    let sbmat = &srmat * &smatx;
     println!("b =");
     sbmat.show();
     println!("real x is");
@@ -127,11 +126,11 @@ fn main() {
     let gmres_x = SparseMatrix::solve_gmres(&srmat, &sbmat, 5, 100);
     gmres_x.show();
     println!("(GE) ||A * x_ - x|| is");
-    println!("{}", sxmat.sub(&smatx).norm2());
+    println!("{}", (&sxmat - &smatx).norm2());
     println!("(SOR) ||A * x_ - x|| is");
-    println!("{}", sor_x.sub(&smatx).norm2());
+    println!("{}", (&sor_x - &smatx).norm2());
     println!("(GMRES) ||A * x_ - x|| is");
-    println!("{}", gmres_x.sub(&smatx).norm2());
-    println!("trace(inv(A) * A)  by GE is {}", srmat.inv_ge().unwrap().dot_mul(&srmat).trace());
+    println!("{}", (&gmres_x - &smatx).norm2());
+    println!("trace(inv(A) * A)  by GE is {}", (srmat.inv_ge().unwrap() * &srmat).trace());
 
 }
\ No newline at end of file
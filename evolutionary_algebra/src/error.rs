@@ -0,0 +1,190 @@
+use super::linear_algebra::matrix::{MatIndex, MatOps};
+use num::Float;
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AlgebraError {
+    // the (rows, cols) of the two operands
+    DimensionMismatch { left: MatIndex, right: MatIndex },
+    IndexOutOfBounds { index: MatIndex, shape: MatIndex },
+    NotSquare { shape: MatIndex },
+    // elimination met a zero pivot in this column
+    SingularPivot { index: usize },
+    // the determinant is exactly zero
+    Singular,
+    NotSymmetric,
+    // the leading minor of this order is not positive
+    NotPositiveDefinite { index: usize },
+    NotConverged { iterations: usize, residual: f64 },
+    // a NaN or infinite entry at this index
+    NotFinite { index: MatIndex },
+    // an eigenvalue on the closed negative real axis: the principal logarithm,
+    // square root or power of the real matrix is not real
+    NegativeEigenvalue,
+    // the shapes of two tensors that do not broadcast or contract together
+    ShapeMismatch { left: Vec<usize>, right: Vec<usize> },
+    AxisOutOfBounds { axis: usize, ndim: usize },
+    // an axis named twice where each may appear once
+    DuplicateAxis { axis: usize },
+    // the numbers of axes paired up for a contraction
+    AxesMismatch { left: usize, right: usize },
+    // the number of axes a tensor has against the number required
+    RankMismatch { expected: usize, found: usize },
+    // a malformed Einstein summation, e.g. "ij,jk->il"
+    InvalidSubscripts { subscripts: String },
+    // reading or writing a matrix file failed
+    Io { kind: io::ErrorKind, message: String },
+    // a malformed line of a text matrix file, counted from 1
+    Parse { line: usize, message: String },
+    // a header, version or entry type the reader does not handle
+    UnsupportedFormat { format: String },
+}
+
+pub type AlgebraResult<T> = Result<T, AlgebraError>;
+
+impl fmt::Display for AlgebraError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            AlgebraError::DimensionMismatch { left, right } =>
+                write!(f, "dimension mismatch: {}x{} vs {}x{}", left.0, left.1, right.0, right.1),
+            AlgebraError::IndexOutOfBounds { index, shape } =>
+                write!(f, "index ({}, {}) is out of bounds for a {}x{} matrix", index.0, index.1, shape.0, shape.1),
+            AlgebraError::NotSquare { shape } =>
+                write!(f, "a square matrix is required, got {}x{}", shape.0, shape.1),
+            AlgebraError::SingularPivot { index } =>
+                write!(f, "the matrix is singular: zero pivot at column {}", index),
+            AlgebraError::Singular =>
+                write!(f, "the matrix is singular"),
+            AlgebraError::NotSymmetric =>
+                write!(f, "the matrix is not symmetric"),
+            AlgebraError::NotPositiveDefinite { index } =>
+                write!(f, "the matrix is not positive definite: nonpositive pivot at column {}", index),
+            AlgebraError::NotConverged { iterations, residual } =>
+                write!(f, "no convergence after {} iterations, residual {:e}", iterations, residual),
+            AlgebraError::NotFinite { index } =>
+                write!(f, "the matrix has a non-finite entry at ({}, {})", index.0, index.1),
+            AlgebraError::NegativeEigenvalue =>
+                write!(f, "the matrix has an eigenvalue on the negative real axis"),
+            AlgebraError::ShapeMismatch { ref left, ref right } =>
+                write!(f, "shape mismatch: {:?} vs {:?}", left, right),
+            AlgebraError::AxisOutOfBounds { axis, ndim } =>
+                write!(f, "axis {} is out of bounds for a tensor with {} axes", axis, ndim),
+            AlgebraError::DuplicateAxis { axis } =>
+                write!(f, "axis {} is repeated", axis),
+            AlgebraError::AxesMismatch { left, right } =>
+                write!(f, "{} axes can not be paired with {}", left, right),
+            AlgebraError::RankMismatch { expected, found } =>
+                write!(f, "a tensor with {} axes is required, got {}", expected, found),
+            AlgebraError::InvalidSubscripts { ref subscripts } =>
+                write!(f, "invalid subscripts \"{}\"", subscripts),
+            AlgebraError::Io { ref message, .. } =>
+                write!(f, "i/o error: {}", message),
+            AlgebraError::Parse { line, ref message } =>
+                write!(f, "parse error at line {}: {}", line, message),
+            AlgebraError::UnsupportedFormat { ref format } =>
+                write!(f, "unsupported format: {}", format),
+        }
+    }
+}
+
+impl Error for AlgebraError {}
+
+impl From<io::Error> for AlgebraError {
+    fn from(err: io::Error) -> AlgebraError {
+        AlgebraError::Io { kind: err.kind(), message: err.to_string() }
+    }
+}
+
+pub(crate) fn check_same_shape(left: MatIndex, right: MatIndex) -> AlgebraResult<()>
+{
+    if left == right {
+        Ok(())
+    }
+    else {
+        Err(AlgebraError::DimensionMismatch { left, right })
+    }
+}
+
+pub(crate) fn check_square(shape: MatIndex) -> AlgebraResult<()>
+{
+    if shape.0 == shape.1 {
+        Ok(())
+    }
+    else {
+        Err(AlgebraError::NotSquare { shape })
+    }
+}
+
+// the product left * right needs left.cols == right.rows
+pub(crate) fn check_product(left: MatIndex, right: MatIndex) -> AlgebraResult<()>
+{
+    if left.1 == right.0 {
+        Ok(())
+    }
+    else {
+        Err(AlgebraError::DimensionMismatch { left, right })
+    }
+}
+
+// a linear system A x = b needs a square A with as many rows as b
+pub(crate) fn check_system(mat_a: MatIndex, mat_b: MatIndex) -> AlgebraResult<()>
+{
+    check_square(mat_a)?;
+    if mat_a.0 == mat_b.0 {
+        Ok(())
+    }
+    else {
+        Err(AlgebraError::DimensionMismatch { left: mat_a, right: mat_b })
+    }
+}
+
+pub(crate) fn check_slice(shape: MatIndex, row_from: usize, row_to: usize, col_from: usize, col_to: usize) -> AlgebraResult<()>
+{
+    if row_to >= shape.0 || col_to >= shape.1 {
+        Err(AlgebraError::IndexOutOfBounds { index: (row_to, col_to), shape })
+    }
+    else if row_from > row_to || col_from > col_to {
+        Err(AlgebraError::IndexOutOfBounds { index: (row_from, col_from), shape })
+    }
+    else {
+        Ok(())
+    }
+}
+
+// the iterative decompositions sort and split on comparisons that a NaN makes
+// meaningless, so they reject such input up front
+pub(crate) fn check_finite<M, T>(mat: &M) -> AlgebraResult<()>
+where M: MatOps<T>, T: Float
+{
+    for i in 0 .. mat.row() {
+        for (j, v) in mat.row_entries(i) {
+            if !v.is_finite() {
+                return Err(AlgebraError::NotFinite { index: (i, j) });
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn algebra_error_test() {
+    let err = AlgebraError::DimensionMismatch { left: (2, 3), right: (4, 1) };
+    assert_eq!(format!("{}", err), "dimension mismatch: 2x3 vs 4x1");
+    assert_eq!(check_product((2, 3), (3, 5)), Ok(()));
+    assert_eq!(check_system((3, 3), (2, 1)), Err(AlgebraError::DimensionMismatch { left: (3, 3), right: (2, 1) }));
+    assert_eq!(check_system((3, 2), (3, 1)), Err(AlgebraError::NotSquare { shape: (3, 2) }));
+    assert!(check_slice((3, 3), 1, 3, 0, 0).is_err());
+    use super::linear_algebra::matrix::dense::DenseMatrix;
+    use super::linear_algebra::matrix::sparse::SparseMatrix;
+    let mut mat = DenseMatrix::<f64>::new(2, 2);
+    assert_eq!(check_finite(&mat), Ok(()));
+    mat.set_v(1, 0, f64::INFINITY);
+    assert_eq!(check_finite(&mat), Err(AlgebraError::NotFinite { index: (1, 0) }));
+    assert_eq!(check_finite(&SparseMatrix::from_dense(&mat)), Err(AlgebraError::NotFinite { index: (1, 0) }));
+    let boxed: Box<dyn Error> = Box::new(AlgebraError::SingularPivot { index: 2 });
+    assert_eq!(boxed.to_string(), "the matrix is singular: zero pivot at column 2");
+    let err = AlgebraError::from(io::Error::new(io::ErrorKind::UnexpectedEof, "early eof"));
+    assert_eq!(err, AlgebraError::Io { kind: io::ErrorKind::UnexpectedEof, message: "early eof".to_string() });
+}
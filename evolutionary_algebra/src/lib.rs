@@ -1,5 +1,7 @@
+pub mod error;
 pub mod linear_algebra;
 pub mod lie_group;
+pub mod parallel;
 
 #[cfg(test)]
 mod tests {
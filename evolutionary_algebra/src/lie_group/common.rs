@@ -0,0 +1,302 @@
+extern crate num;
+extern crate evolutionary_geometry;
+
+use super::super::linear_algebra::matrix::dense::DenseMatrix;
+use evolutionary_geometry::euclidean::{Point2D, Point3D, Vector2D, Vector3D};
+use num::Float;
+use std::fmt::Display;
+
+// A matrix Lie group with its Lie algebra identified with R^DOF. Tangent
+// vectors are slices of DOF coordinates: exp and log map them to and from the
+// group, hat and vee to and from the DIM x DIM matrices of the algebra. The
+// Jacobians are those of exp, to first order in d
+//     exp(x + d) = exp(J_l(x) d) exp(x) = exp(x) exp(J_r(x) d)
+pub trait LieGroup<T>: Sized + Clone
+where T: Float + Display
+{
+    // the dimension of the algebra
+    const DOF: usize;
+    // the size of the matrices the group acts by
+    const DIM: usize;
+
+    fn identity() -> Self;
+    fn compose(&self, other: &Self) -> Self;
+    fn inverse(&self) -> Self;
+    fn exp(tangent: &[T]) -> Self;
+    fn log(&self) -> Vec<T>;
+    fn hat(tangent: &[T]) -> DenseMatrix<T>;
+    fn vee(mat: &DenseMatrix<T>) -> Vec<T>;
+    fn matrix(&self) -> DenseMatrix<T>;
+    // Ad_g, with g exp(x) g^-1 = exp(Ad_g x)
+    fn adjoint(&self) -> DenseMatrix<T>;
+    fn left_jacobian(tangent: &[T]) -> DenseMatrix<T>;
+
+    // infinite entries where J_l is singular
+    fn left_jacobian_inv(tangent: &[T]) -> DenseMatrix<T>
+    {
+        invert(&Self::left_jacobian(tangent))
+    }
+
+    fn right_jacobian(tangent: &[T]) -> DenseMatrix<T>
+    {
+        Self::left_jacobian(&negate(tangent))
+    }
+
+    fn right_jacobian_inv(tangent: &[T]) -> DenseMatrix<T>
+    {
+        Self::left_jacobian_inv(&negate(tangent))
+    }
+
+    // self^-1 other
+    fn between(&self, other: &Self) -> Self
+    {
+        self.inverse().compose(other)
+    }
+
+    // along the geodesic from self at t = 0 to other at t = 1
+    fn interpolate(&self, other: &Self, t: T) -> Self
+    {
+        let step: Vec<T> = self.between(other).log().into_iter().map(|v| v * t).collect();
+        self.compose(&Self::exp(&step))
+    }
+}
+
+pub(crate) fn negate<T>(x: &[T]) -> Vec<T>
+where T: Float
+{
+    x.iter().map(|&v| -v).collect()
+}
+
+pub(crate) fn check_tangent(tangent_len: usize, dof: usize)
+{
+    assert_eq!(tangent_len, dof, "the length of the tangent vector does not match the group!");
+}
+
+// the ratios of sines and cosines below, near 0 by their Taylor series where
+// the closed forms lose digits to cancellation
+fn below<T>(x: T, bound: f64) -> bool
+where T: Float
+{
+    x.abs() < T::from(bound).unwrap()
+}
+
+fn series<T>(x: T, coeffs: &[f64]) -> T
+where T: Float
+{
+    let x2 = x * x;
+    coeffs.iter().rev().fold(T::zero(), |sum, &c| sum * x2 + T::from(c).unwrap())
+}
+
+// sin(x) / x
+pub(crate) fn sinc<T>(x: T) -> T
+where T: Float
+{
+    if below(x, 1e-4) { series(x, &[1.0, -1.0 / 6.0]) } else { x.sin() / x }
+}
+
+// (1 - cos(x)) / x^2, through 2 sin(x/2)^2 to keep it accurate
+pub(crate) fn cosc<T>(x: T) -> T
+where T: Float
+{
+    let s = sinc(x / (T::one() + T::one()));
+    s * s / (T::one() + T::one())
+}
+
+// (x - sin(x)) / x^3
+pub(crate) fn sinc3<T>(x: T) -> T
+where T: Float
+{
+    if below(x, 0.05) { series(x, &[1.0 / 6.0, -1.0 / 120.0, 1.0 / 5040.0]) } else { (x - x.sin()) / (x * x * x) }
+}
+
+// (x^2 + 2 cos(x) - 2) / (2 x^4)
+pub(crate) fn cosc4<T>(x: T) -> T
+where T: Float
+{
+    if below(x, 0.1) {
+        series(x, &[1.0 / 24.0, -1.0 / 720.0, 1.0 / 40320.0])
+    }
+    else {
+        let two = T::one() + T::one();
+        (x * x + two * x.cos() - two) / (two * x.powi(4))
+    }
+}
+
+// (2 x - 3 sin(x) + x cos(x)) / (2 x^5)
+pub(crate) fn sinc5<T>(x: T) -> T
+where T: Float
+{
+    if below(x, 0.1) {
+        series(x, &[1.0 / 120.0, -1.0 / 2520.0, 1.0 / 120960.0])
+    }
+    else {
+        let two = T::one() + T::one();
+        (two * x - T::from(3).unwrap() * x.sin() + x * x.cos()) / (two * x.powi(5))
+    }
+}
+
+// 1 / x^2 - cos(x/2) / (2 x sin(x/2)), the x^2 coefficient of the inverse
+// left Jacobian of SO(3)
+pub(crate) fn jinv_coeff<T>(x: T) -> T
+where T: Float
+{
+    if below(x, 0.05) {
+        series(x, &[1.0 / 12.0, 1.0 / 720.0, 1.0 / 30240.0])
+    }
+    else {
+        let half = x / (T::one() + T::one());
+        T::one() / (x * x) - half.cos() / ((T::one() + T::one()) * x * half.sin())
+    }
+}
+
+// Gauss-Jordan with partial pivoting, for the small Jacobians
+pub(crate) fn invert<T>(mat: &DenseMatrix<T>) -> DenseMatrix<T>
+where T: Float + Display
+{
+    let n = mat.row();
+    let mut a = mat.clone();
+    let mut inv = DenseMatrix::eye(n);
+    for c in 0 .. n {
+        let p = (c .. n).fold(c, |p, r| if a.get_v(r, c).abs() > a.get_v(p, c).abs() { r } else { p });
+        for j in 0 .. n {
+            let (x, y) = (a.get_v(c, j), a.get_v(p, j));
+            a.set_v(c, j, y);
+            a.set_v(p, j, x);
+            let (x, y) = (inv.get_v(c, j), inv.get_v(p, j));
+            inv.set_v(c, j, y);
+            inv.set_v(p, j, x);
+        }
+        let head = a.get_v(c, c);
+        for j in 0 .. n {
+            a.set_v(c, j, a.get_v(c, j) / head);
+            inv.set_v(c, j, inv.get_v(c, j) / head);
+        }
+        for r in (0 .. n).filter(|&r| r != c) {
+            let f = a.get_v(r, c);
+            if f != T::zero() {
+                for j in 0 .. n {
+                    a.set_v(r, j, a.get_v(r, j) - f * a.get_v(c, j));
+                    inv.set_v(r, j, inv.get_v(r, j) - f * inv.get_v(c, j));
+                }
+            }
+        }
+    }
+    inv
+}
+
+// the single-precision geometry types in and out of the group's precision
+pub(crate) fn from_vector3d<T>(v: &Vector3D) -> [T; 3]
+where T: Float
+{
+    [T::from(v.v1).unwrap(), T::from(v.v2).unwrap(), T::from(v.v3).unwrap()]
+}
+
+pub(crate) fn to_vector3d<T>(v: [T; 3]) -> Vector3D
+where T: Float
+{
+    Vector3D::new(v[0].to_f32().unwrap(), v[1].to_f32().unwrap(), v[2].to_f32().unwrap())
+}
+
+pub(crate) fn from_point3d<T>(p: &Point3D) -> [T; 3]
+where T: Float
+{
+    [T::from(p.x1).unwrap(), T::from(p.x2).unwrap(), T::from(p.x3).unwrap()]
+}
+
+pub(crate) fn to_point3d<T>(p: [T; 3]) -> Point3D
+where T: Float
+{
+    Point3D::new(p[0].to_f32().unwrap(), p[1].to_f32().unwrap(), p[2].to_f32().unwrap())
+}
+
+pub(crate) fn from_vector2d<T>(v: &Vector2D) -> [T; 2]
+where T: Float
+{
+    [T::from(v.v1).unwrap(), T::from(v.v2).unwrap()]
+}
+
+pub(crate) fn to_vector2d<T>(v: [T; 2]) -> Vector2D
+where T: Float
+{
+    Vector2D::new(v[0].to_f32().unwrap(), v[1].to_f32().unwrap())
+}
+
+pub(crate) fn from_point2d<T>(p: &Point2D) -> [T; 2]
+where T: Float
+{
+    [T::from(p.x1).unwrap(), T::from(p.x2).unwrap()]
+}
+
+pub(crate) fn to_point2d<T>(p: [T; 2]) -> Point2D
+where T: Float
+{
+    Point2D::new(p[0].to_f32().unwrap(), p[1].to_f32().unwrap())
+}
+
+#[test]
+fn lie_group_test() {
+    use super::{SE2, SE3, SO2, SO3};
+    use super::super::linear_algebra::matrix::MatOps;
+
+    fn dist(a: &DenseMatrix<f64>, b: &DenseMatrix<f64>) -> f64 {
+        a.sub(b).as_slice().iter().fold(0.0, |m: f64, v| m.max(v.abs()))
+    }
+
+    // the group axioms, exp/log, hat/vee, Ad and the Jacobians by central
+    // differences of log(exp(x + d) exp(x)^-1) and log(exp(x)^-1 exp(x + d))
+    fn check<G: LieGroup<f64>>(x: &[f64], y: &[f64]) {
+        let (g, h) = (G::exp(x), G::exp(y));
+        let eye = DenseMatrix::eye(G::DIM);
+        assert!(G::exp(&G::log(&g)).matrix().sub(&g.matrix()).norm_inf() < 1e-12);
+        assert!(G::log(&g).iter().zip(x.iter()).all(|(a, b)| (a - b).abs() < 1e-12));
+        assert_eq!(G::vee(&G::hat(x)), x.to_vec());
+        assert!(dist(&g.compose(&h).matrix(), &g.matrix().dot_mul(&h.matrix())) < 1e-12);
+        assert!(dist(&g.compose(&g.inverse()).matrix(), &eye) < 1e-12);
+        assert!(dist(&G::identity().matrix(), &eye) == 0.0);
+        assert!(dist(&G::exp(&vec![0.0; G::DOF]).matrix(), &eye) < 1e-15);
+        // the matrix exponential of the algebra element
+        assert!(dist(&G::hat(x).expm().unwrap(), &g.matrix()) < 1e-12);
+        let ad_y = g.adjoint().mat_vec(y);
+        assert!(dist(&G::exp(&ad_y).matrix(), &g.compose(&h).compose(&g.inverse()).matrix()) < 1e-12);
+        assert!(dist(&g.between(&h).matrix(), &g.inverse().compose(&h).matrix()) < 1e-12);
+        assert!(dist(&g.interpolate(&h, 0.0).matrix(), &g.matrix()) < 1e-12);
+        assert!(dist(&g.interpolate(&h, 1.0).matrix(), &h.matrix()) < 1e-12);
+
+        let delta = 1e-6;
+        let (jl, jr) = (G::left_jacobian(x), G::right_jacobian(x));
+        for i in 0 .. G::DOF {
+            let shifted = |s: f64| {
+                let mut z = x.to_vec();
+                z[i] += s * delta;
+                G::exp(&z)
+            };
+            let (plus, minus) = (shifted(1.0), shifted(-1.0));
+            let left: Vec<f64> = plus.compose(&g.inverse()).log().iter().zip(minus.compose(&g.inverse()).log().iter())
+                .map(|(a, b)| (a - b) / (2.0 * delta)).collect();
+            let right: Vec<f64> = g.inverse().compose(&plus).log().iter().zip(g.inverse().compose(&minus).log().iter())
+                .map(|(a, b)| (a - b) / (2.0 * delta)).collect();
+            for k in 0 .. G::DOF {
+                assert!((left[k] - jl.get_v(k, i)).abs() < 1e-8);
+                assert!((right[k] - jr.get_v(k, i)).abs() < 1e-8);
+            }
+        }
+        let id = DenseMatrix::eye(G::DOF);
+        assert!(dist(&G::left_jacobian_inv(x).dot_mul(&jl), &id) < 1e-12);
+        assert!(dist(&G::right_jacobian_inv(x).dot_mul(&jr), &id) < 1e-12);
+    }
+
+    check::<SO2<f64>>(&[0.7], &[-2.5]);
+    check::<SO3<f64>>(&[0.3, -1.2, 0.8], &[-0.5, 0.1, 2.0]);
+    check::<SO3<f64>>(&[1e-3, 2e-3, -1e-3], &[0.0, 0.0, 3.0]);
+    check::<SE2<f64>>(&[1.0, -2.0, 0.9], &[0.5, 0.3, -1.7]);
+    check::<SE2<f64>>(&[1.0, -2.0, 1e-3], &[0.5, 0.3, 0.0]);
+    check::<SE3<f64>>(&[1.0, -0.5, 2.0, 0.3, -1.2, 0.8], &[0.2, 0.1, -0.4, -0.5, 0.1, 2.0]);
+    check::<SE3<f64>>(&[1.0, -0.5, 2.0, 0.02, -0.05, 0.04], &[0.0, 0.0, 1.0, 0.0, 0.0, 0.0]);
+
+    let series = [sinc3(0.049f64), cosc4(0.099f64), sinc5(0.099f64), jinv_coeff(0.049f64)];
+    let closed = [(0.049f64 - 0.049f64.sin()) / 0.049f64.powi(3),
+                  (0.099f64.powi(2) + 2.0 * 0.099f64.cos() - 2.0) / (2.0 * 0.099f64.powi(4)),
+                  (2.0 * 0.099 - 3.0 * 0.099f64.sin() + 0.099 * 0.099f64.cos()) / (2.0 * 0.099f64.powi(5)),
+                  1.0 / 0.049f64.powi(2) - 0.0245f64.cos() / (2.0 * 0.049 * 0.0245f64.sin())];
+    assert!(series.iter().zip(closed.iter()).all(|(a, b)| (a - b).abs() < 1e-7 * b.abs()));
+}
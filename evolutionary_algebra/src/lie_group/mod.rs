@@ -0,0 +1,13 @@
+pub mod common;
+pub mod quaternion;
+pub mod so2;
+pub mod so3;
+pub mod se2;
+pub mod se3;
+
+pub use self::common::LieGroup;
+pub use self::quaternion::Quaternion;
+pub use self::so2::SO2;
+pub use self::so3::SO3;
+pub use self::se2::SE2;
+pub use self::se3::SE3;
@@ -0,0 +1,206 @@
+extern crate num;
+
+use super::common;
+use num::Float;
+use std::fmt::Display;
+
+pub(crate) type Mat3<T> = [[T; 3]; 3];
+
+// w + x i + y j + z k. The unit ones stand for rotations, q and -q for the
+// same one; rotate, the rotation vector and the matrix assume |q| = 1.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Quaternion<T>
+where T: Float + Display
+{
+    pub w: T,
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+impl<T> Quaternion<T>
+where T: Float + Display
+{
+    pub fn new(w: T, x: T, y: T, z: T) -> Quaternion<T>
+    {
+        Quaternion { w, x, y, z }
+    }
+
+    pub fn identity() -> Quaternion<T>
+    {
+        Quaternion::new(T::one(), T::zero(), T::zero(), T::zero())
+    }
+
+    // the rotation by angle about axis, which need not be normalised
+    pub fn from_axis_angle(axis: [T; 3], angle: T) -> Quaternion<T>
+    {
+        let norm = (axis[0] * axis[0] + axis[1] * axis[1] + axis[2] * axis[2]).sqrt();
+        let scale = if norm == T::zero() { T::zero() } else { angle / norm };
+        Quaternion::from_rotation_vector([axis[0] * scale, axis[1] * scale, axis[2] * scale])
+    }
+
+    // the rotation by |v| about v
+    pub fn from_rotation_vector(v: [T; 3]) -> Quaternion<T>
+    {
+        let half = T::one() / (T::one() + T::one());
+        let theta = (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt();
+        // sin(theta / 2) / theta
+        let s = half * common::sinc(half * theta);
+        Quaternion::new((half * theta).cos(), s * v[0], s * v[1], s * v[2])
+    }
+
+    // the rotation vector of angle in [0, pi]
+    pub fn to_rotation_vector(&self) -> [T; 3]
+    {
+        let q = if self.w < T::zero() { self.neg() } else { *self };
+        let n = (q.x * q.x + q.y * q.y + q.z * q.z).sqrt();
+        let two = T::one() + T::one();
+        // theta / sin(theta / 2) = 2 atan2(n, w) / n
+        let scale = if n < T::epsilon().sqrt() {
+            two / q.w * (T::one() - n * n / (T::from(3).unwrap() * q.w * q.w))
+        }
+        else {
+            two * n.atan2(q.w) / n
+        };
+        [scale * q.x, scale * q.y, scale * q.z]
+    }
+
+    // Shepperd's method: the division goes by the largest of the four
+    pub fn from_rotation_matrix(m: &Mat3<T>) -> Quaternion<T>
+    {
+        let one = T::one();
+        let quarter = one / T::from(4).unwrap();
+        let trace = m[0][0] + m[1][1] + m[2][2];
+        let q = if trace > T::zero() {
+            let s = (trace + one).sqrt() * (one + one);
+            Quaternion::new(quarter * s, (m[2][1] - m[1][2]) / s, (m[0][2] - m[2][0]) / s, (m[1][0] - m[0][1]) / s)
+        }
+        else if m[0][0] > m[1][1] && m[0][0] > m[2][2] {
+            let s = (one + m[0][0] - m[1][1] - m[2][2]).sqrt() * (one + one);
+            Quaternion::new((m[2][1] - m[1][2]) / s, quarter * s, (m[0][1] + m[1][0]) / s, (m[0][2] + m[2][0]) / s)
+        }
+        else if m[1][1] > m[2][2] {
+            let s = (one + m[1][1] - m[0][0] - m[2][2]).sqrt() * (one + one);
+            Quaternion::new((m[0][2] - m[2][0]) / s, (m[0][1] + m[1][0]) / s, quarter * s, (m[1][2] + m[2][1]) / s)
+        }
+        else {
+            let s = (one + m[2][2] - m[0][0] - m[1][1]).sqrt() * (one + one);
+            Quaternion::new((m[1][0] - m[0][1]) / s, (m[0][2] + m[2][0]) / s, (m[1][2] + m[2][1]) / s, quarter * s)
+        };
+        q.normalize()
+    }
+
+    pub fn to_rotation_matrix(&self) -> Mat3<T>
+    {
+        let (w, x, y, z) = (self.w, self.x, self.y, self.z);
+        let (one, two) = (T::one(), T::one() + T::one());
+        [[one - two * (y * y + z * z), two * (x * y - w * z), two * (x * z + w * y)],
+         [two * (x * y + w * z), one - two * (x * x + z * z), two * (y * z - w * x)],
+         [two * (x * z - w * y), two * (y * z + w * x), one - two * (x * x + y * y)]]
+    }
+
+    // the Hamilton product self * q: rotate by q first, then by self
+    pub fn mul(&self, q: &Quaternion<T>) -> Quaternion<T>
+    {
+        Quaternion::new(self.w * q.w - self.x * q.x - self.y * q.y - self.z * q.z,
+                        self.w * q.x + self.x * q.w + self.y * q.z - self.z * q.y,
+                        self.w * q.y - self.x * q.z + self.y * q.w + self.z * q.x,
+                        self.w * q.z + self.x * q.y - self.y * q.x + self.z * q.w)
+    }
+
+    pub fn neg(&self) -> Quaternion<T>
+    {
+        Quaternion::new(-self.w, -self.x, -self.y, -self.z)
+    }
+
+    pub fn conj(&self) -> Quaternion<T>
+    {
+        Quaternion::new(self.w, -self.x, -self.y, -self.z)
+    }
+
+    pub fn dot(&self, q: &Quaternion<T>) -> T
+    {
+        self.w * q.w + self.x * q.x + self.y * q.y + self.z * q.z
+    }
+
+    pub fn norm(&self) -> T
+    {
+        self.dot(self).sqrt()
+    }
+
+    pub fn normalize(&self) -> Quaternion<T>
+    {
+        let n = self.norm();
+        Quaternion::new(self.w / n, self.x / n, self.y / n, self.z / n)
+    }
+
+    pub fn inverse(&self) -> Quaternion<T>
+    {
+        let n2 = self.dot(self);
+        let c = self.conj();
+        Quaternion::new(c.w / n2, c.x / n2, c.y / n2, c.z / n2)
+    }
+
+    // q v q^-1
+    pub fn rotate(&self, v: [T; 3]) -> [T; 3]
+    {
+        let two = T::one() + T::one();
+        let u = [self.x, self.y, self.z];
+        let cross = |a: [T; 3], b: [T; 3]| [a[1] * b[2] - a[2] * b[1], a[2] * b[0] - a[0] * b[2], a[0] * b[1] - a[1] * b[0]];
+        // v + 2 w (u x v) + 2 u x (u x v)
+        let uv = cross(u, v);
+        let uuv = cross(u, uv);
+        [v[0] + two * (self.w * uv[0] + uuv[0]),
+         v[1] + two * (self.w * uv[1] + uuv[1]),
+         v[2] + two * (self.w * uv[2] + uuv[2])]
+    }
+
+    // along the shorter great arc from self (t = 0) to q (t = 1)
+    pub fn slerp(&self, q: &Quaternion<T>, t: T) -> Quaternion<T>
+    {
+        let (q, cos) = if self.dot(q) < T::zero() { (q.neg(), -self.dot(q)) } else { (*q, self.dot(q)) };
+        let (a, b) = if cos > T::one() - T::epsilon().sqrt() {
+            // nearly parallel: the chord will do
+            (T::one() - t, t)
+        }
+        else {
+            let theta = cos.min(T::one()).acos();
+            (((T::one() - t) * theta).sin() / theta.sin(), (t * theta).sin() / theta.sin())
+        };
+        Quaternion::new(a * self.w + b * q.w, a * self.x + b * q.x, a * self.y + b * q.y, a * self.z + b * q.z).normalize()
+    }
+}
+
+#[test]
+fn quaternion_test() {
+    use std::f64::consts::{FRAC_PI_2, PI};
+
+    let close = |a: [f64; 3], b: [f64; 3]| a.iter().zip(b.iter()).all(|(x, y)| (x - y).abs() < 1e-14);
+    let qz = Quaternion::from_axis_angle([0.0, 0.0, 2.0], FRAC_PI_2);
+    assert!(close(qz.rotate([1.0, 0.0, 0.0]), [0.0, 1.0, 0.0]));
+    let qx = Quaternion::from_axis_angle([1.0, 0.0, 0.0], FRAC_PI_2);
+    // x first, then z: y -> z -> z
+    assert!(close(qz.mul(&qx).rotate([0.0, 1.0, 0.0]), [0.0, 0.0, 1.0]));
+    assert!(close(qz.mul(&qx).inverse().rotate(qz.mul(&qx).rotate([0.3, -1.0, 2.0])), [0.3, -1.0, 2.0]));
+    assert!((qz.norm() - 1.0).abs() < 1e-15);
+
+    let q = Quaternion::from_rotation_vector([0.3, -1.2, 0.8]);
+    let m = q.to_rotation_matrix();
+    let v = [0.5, 2.0, -1.0];
+    let mv = [0, 1, 2].iter().map(|&i| m[i][0] * v[0] + m[i][1] * v[1] + m[i][2] * v[2]).collect::<Vec<f64>>();
+    assert!(close(q.rotate(v), [mv[0], mv[1], mv[2]]));
+    assert!((Quaternion::from_rotation_matrix(&m).dot(&q).abs() - 1.0).abs() < 1e-14);
+    assert!(close(q.to_rotation_vector(), [0.3, -1.2, 0.8]));
+    // -q is the same rotation, and the half turn goes through the other branches
+    assert!(close(q.neg().to_rotation_vector(), [0.3, -1.2, 0.8]));
+    let half_turn = Quaternion::from_axis_angle([0.0, 1.0, 1.0], PI);
+    let back = Quaternion::from_rotation_matrix(&half_turn.to_rotation_matrix());
+    assert!((back.dot(&half_turn).abs() - 1.0).abs() < 1e-14);
+    assert!(close(Quaternion::<f64>::identity().to_rotation_vector(), [0.0; 3]));
+
+    let p = Quaternion::from_axis_angle([0.0, 0.0, 1.0], 0.2);
+    let r = Quaternion::from_axis_angle([0.0, 0.0, 1.0], 1.0);
+    assert!((p.slerp(&r, 0.25).dot(&Quaternion::from_axis_angle([0.0, 0.0, 1.0], 0.4)) - 1.0).abs() < 1e-14);
+    assert!((p.slerp(&r.neg(), 1.0).dot(&r).abs() - 1.0).abs() < 1e-14);
+    assert!((p.slerp(&p, 0.5).dot(&p) - 1.0).abs() < 1e-14);
+}
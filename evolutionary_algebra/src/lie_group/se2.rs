@@ -0,0 +1,178 @@
+extern crate num;
+extern crate evolutionary_geometry;
+
+use super::common::{self, LieGroup};
+use super::so2::SO2;
+use super::super::linear_algebra::matrix::dense::DenseMatrix;
+use super::super::error::{self, AlgebraResult};
+use evolutionary_geometry::euclidean::{Point2D, Vector2D};
+use num::Float;
+use std::fmt::Display;
+
+// Rigid motions p -> R p + t of the plane. The tangent vector is
+// (rho_1, rho_2, theta) with t = V(theta) rho.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SE2<T>
+where T: Float + Display
+{
+    rotation: SO2<T>,
+    translation: [T; 2],
+}
+
+// V(theta) = [a -b; b a], the left Jacobian of the translation part
+fn v_coeffs<T>(theta: T) -> (T, T)
+where T: Float
+{
+    (common::sinc(theta), theta * common::cosc(theta))
+}
+
+impl<T> SE2<T>
+where T: Float + Display
+{
+    pub fn new(rotation: SO2<T>, translation: [T; 2]) -> SE2<T>
+    {
+        SE2 { rotation, translation }
+    }
+
+    pub fn from_vector2d(rotation: SO2<T>, translation: &Vector2D) -> SE2<T>
+    {
+        SE2::new(rotation, common::from_vector2d(translation))
+    }
+
+    // a 3 x 3 homogeneous matrix; the last row is not looked at
+    pub fn from_matrix(mat: &DenseMatrix<T>) -> AlgebraResult<SE2<T>>
+    {
+        error::check_same_shape(mat.shape(), (3, 3))?;
+        let rotation = SO2::from_matrix(&mat.slice(0, 1, 0, 1))?;
+        Ok(SE2::new(rotation, [mat.get_v(0, 2), mat.get_v(1, 2)]))
+    }
+
+    pub fn rotation(&self) -> SO2<T>
+    {
+        self.rotation
+    }
+
+    pub fn translation(&self) -> [T; 2]
+    {
+        self.translation
+    }
+
+    pub fn act(&self, p: [T; 2]) -> [T; 2]
+    {
+        let r = self.rotation.act(p);
+        [r[0] + self.translation[0], r[1] + self.translation[1]]
+    }
+
+    pub fn transform_point(&self, p: &Point2D) -> Point2D
+    {
+        common::to_point2d(self.act(common::from_point2d(p)))
+    }
+
+    // directions only rotate
+    pub fn transform_vector(&self, v: &Vector2D) -> Vector2D
+    {
+        self.rotation.rotate_vector(v)
+    }
+}
+
+impl<T> LieGroup<T> for SE2<T>
+where T: Float + Display
+{
+    const DOF: usize = 3;
+    const DIM: usize = 3;
+
+    fn identity() -> SE2<T>
+    {
+        SE2::new(SO2::identity(), [T::zero(); 2])
+    }
+
+    fn compose(&self, other: &SE2<T>) -> SE2<T>
+    {
+        SE2::new(self.rotation.compose(&other.rotation), self.act(other.translation))
+    }
+
+    fn inverse(&self) -> SE2<T>
+    {
+        let inv = self.rotation.inverse();
+        let t = inv.act(self.translation);
+        SE2::new(inv, [-t[0], -t[1]])
+    }
+
+    fn exp(tangent: &[T]) -> SE2<T>
+    {
+        common::check_tangent(tangent.len(), 3);
+        let (rho, theta) = ([tangent[0], tangent[1]], tangent[2]);
+        let (a, b) = v_coeffs(theta);
+        SE2::new(SO2::from_angle(theta), [a * rho[0] - b * rho[1], b * rho[0] + a * rho[1]])
+    }
+
+    fn log(&self) -> Vec<T>
+    {
+        let theta = self.rotation.angle();
+        let (a, b) = v_coeffs(theta);
+        let det = a * a + b * b;
+        let t = self.translation;
+        vec![(a * t[0] + b * t[1]) / det, (a * t[1] - b * t[0]) / det, theta]
+    }
+
+    fn hat(tangent: &[T]) -> DenseMatrix<T>
+    {
+        common::check_tangent(tangent.len(), 3);
+        let o = T::zero();
+        DenseMatrix::from(3, 3, &[o, -tangent[2], tangent[0],
+                                  tangent[2], o, tangent[1],
+                                  o, o, o])
+    }
+
+    fn vee(mat: &DenseMatrix<T>) -> Vec<T>
+    {
+        vec![mat.get_v(0, 2), mat.get_v(1, 2), mat.get_v(1, 0)]
+    }
+
+    fn matrix(&self) -> DenseMatrix<T>
+    {
+        let (c, s) = self.rotation.cos_sin();
+        let (o, l) = (T::zero(), T::one());
+        DenseMatrix::from(3, 3, &[c, -s, self.translation[0],
+                                  s, c, self.translation[1],
+                                  o, o, l])
+    }
+
+    fn adjoint(&self) -> DenseMatrix<T>
+    {
+        let (c, s) = self.rotation.cos_sin();
+        let (o, l) = (T::zero(), T::one());
+        DenseMatrix::from(3, 3, &[c, -s, self.translation[1],
+                                  s, c, -self.translation[0],
+                                  o, o, l])
+    }
+
+    fn left_jacobian(tangent: &[T]) -> DenseMatrix<T>
+    {
+        common::check_tangent(tangent.len(), 3);
+        let (rho, theta) = ([tangent[0], tangent[1]], tangent[2]);
+        let (a, b) = v_coeffs(theta);
+        // (theta - sin) / theta^2 and (1 - cos) / theta^2
+        let (d, e) = (theta * common::sinc3(theta), common::cosc(theta));
+        let (o, l) = (T::zero(), T::one());
+        DenseMatrix::from(3, 3, &[a, -b, d * rho[0] + e * rho[1],
+                                  b, a, d * rho[1] - e * rho[0],
+                                  o, o, l])
+    }
+}
+
+#[test]
+fn se2_test() {
+    use std::f64::consts::FRAC_PI_2;
+
+    let g = SE2::from_vector2d(SO2::<f64>::from_angle(FRAC_PI_2), &Vector2D::new(1.0, 0.0));
+    let p = g.transform_point(&Point2D::new(1.0, 0.0));
+    assert!((p.x1 - 1.0).abs() < 1e-6 && (p.x2 - 1.0).abs() < 1e-6);
+    let v = g.transform_vector(&Vector2D::new(1.0, 0.0));
+    assert!(v.v1.abs() < 1e-6 && (v.v2 - 1.0).abs() < 1e-6);
+    // a quarter circle of radius 1 about (0, 1) from the origin
+    let arc = SE2::<f64>::exp(&[FRAC_PI_2, 0.0, FRAC_PI_2]);
+    assert!((arc.translation()[0] - 1.0).abs() < 1e-15 && (arc.translation()[1] - 1.0).abs() < 1e-15);
+    assert!(SE2::from_matrix(&g.matrix()).unwrap().matrix().sub(&g.matrix()).norm_inf() < 1e-15);
+    assert!(SE2::<f64>::from_matrix(&DenseMatrix::eye(2)).is_err());
+}
@@ -0,0 +1,231 @@
+extern crate num;
+extern crate evolutionary_geometry;
+
+use super::common::{self, LieGroup};
+use super::quaternion::Mat3;
+use super::so3::{self, SO3};
+use super::super::linear_algebra::matrix::dense::DenseMatrix;
+use super::super::error::{self, AlgebraResult};
+use evolutionary_geometry::euclidean::{Point3D, Vector3D};
+use num::Float;
+use std::fmt::Display;
+
+// Rigid motions p -> R p + t of R^3. The tangent vector is (rho, omega):
+// omega the rotation vector and rho the translation before the left Jacobian
+// of SO(3) bends it, t = J_l(omega) rho.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SE3<T>
+where T: Float + Display
+{
+    rotation: SO3<T>,
+    translation: [T; 3],
+}
+
+fn add3<T>(a: &Mat3<T>, b: &Mat3<T>, k: T) -> Mat3<T>
+where T: Float
+{
+    let mut c = *a;
+    for i in 0 .. 3 {
+        for j in 0 .. 3 {
+            c[i][j] = c[i][j] + k * b[i][j];
+        }
+    }
+    c
+}
+
+// the upper right block of the left Jacobian (Barfoot's Q)
+fn q_block<T>(rho: [T; 3], omega: [T; 3]) -> Mat3<T>
+where T: Float
+{
+    let theta = so3::norm3(omega);
+    let (p, w) = (so3::hat3(rho), so3::hat3(omega));
+    let mul = |a: &Mat3<T>, b: &Mat3<T>| so3::mat3_mul(a, b);
+    let (wp, pw) = (mul(&w, &p), mul(&p, &w));
+    let wpw = mul(&wp, &w);
+    let (wwp, pww) = (mul(&w, &wp), mul(&pw, &w));
+    let (wpww, wwpw) = (mul(&wpw, &w), mul(&w, &wpw));
+    let half = T::one() / (T::one() + T::one());
+    let mut q = [[T::zero(); 3]; 3];
+    q = add3(&q, &p, half);
+    q = add3(&q, &add3(&add3(&wp, &pw, T::one()), &wpw, T::one()), common::sinc3(theta));
+    q = add3(&q, &add3(&add3(&wwp, &pww, T::one()), &wpw, -T::from(3).unwrap()), common::cosc4(theta));
+    add3(&q, &add3(&wpww, &wwpw, T::one()), common::sinc5(theta))
+}
+
+fn split<T>(tangent: &[T]) -> ([T; 3], [T; 3])
+where T: Float
+{
+    common::check_tangent(tangent.len(), 6);
+    ([tangent[0], tangent[1], tangent[2]], [tangent[3], tangent[4], tangent[5]])
+}
+
+impl<T> SE3<T>
+where T: Float + Display
+{
+    pub fn new(rotation: SO3<T>, translation: [T; 3]) -> SE3<T>
+    {
+        SE3 { rotation, translation }
+    }
+
+    pub fn from_vector3d(rotation: SO3<T>, translation: &Vector3D) -> SE3<T>
+    {
+        SE3::new(rotation, common::from_vector3d(translation))
+    }
+
+    // a 4 x 4 homogeneous matrix; the last row is not looked at
+    pub fn from_matrix(mat: &DenseMatrix<T>) -> AlgebraResult<SE3<T>>
+    {
+        error::check_same_shape(mat.shape(), (4, 4))?;
+        let rotation = SO3::from_matrix(&mat.slice(0, 2, 0, 2))?;
+        Ok(SE3::new(rotation, [mat.get_v(0, 3), mat.get_v(1, 3), mat.get_v(2, 3)]))
+    }
+
+    pub fn rotation(&self) -> SO3<T>
+    {
+        self.rotation
+    }
+
+    pub fn translation(&self) -> [T; 3]
+    {
+        self.translation
+    }
+
+    pub fn act(&self, p: [T; 3]) -> [T; 3]
+    {
+        let r = self.rotation.act(p);
+        [r[0] + self.translation[0], r[1] + self.translation[1], r[2] + self.translation[2]]
+    }
+
+    pub fn transform_point(&self, p: &Point3D) -> Point3D
+    {
+        common::to_point3d(self.act(common::from_point3d(p)))
+    }
+
+    // directions only rotate
+    pub fn transform_vector(&self, v: &Vector3D) -> Vector3D
+    {
+        self.rotation.rotate_vector(v)
+    }
+}
+
+impl<T> LieGroup<T> for SE3<T>
+where T: Float + Display
+{
+    const DOF: usize = 6;
+    const DIM: usize = 4;
+
+    fn identity() -> SE3<T>
+    {
+        SE3::new(SO3::identity(), [T::zero(); 3])
+    }
+
+    fn compose(&self, other: &SE3<T>) -> SE3<T>
+    {
+        SE3::new(self.rotation.compose(&other.rotation), self.act(other.translation))
+    }
+
+    fn inverse(&self) -> SE3<T>
+    {
+        let inv = self.rotation.inverse();
+        let t = inv.act(self.translation);
+        SE3::new(inv, [-t[0], -t[1], -t[2]])
+    }
+
+    fn exp(tangent: &[T]) -> SE3<T>
+    {
+        let (rho, omega) = split(tangent);
+        SE3::new(SO3::exp(&omega), so3::mat3_vec(&so3::left_jacobian3(omega), rho))
+    }
+
+    fn log(&self) -> Vec<T>
+    {
+        let omega = self.rotation.log();
+        let omega = [omega[0], omega[1], omega[2]];
+        let rho = so3::mat3_vec(&so3::left_jacobian_inv3(omega), self.translation);
+        rho.iter().chain(omega.iter()).cloned().collect()
+    }
+
+    fn hat(tangent: &[T]) -> DenseMatrix<T>
+    {
+        let (rho, omega) = split(tangent);
+        let w = so3::hat3(omega);
+        let mut res = DenseMatrix::new(4, 4);
+        for (i, (row, &rho_i)) in w.iter().zip(rho.iter()).enumerate() {
+            for (j, &v) in row.iter().enumerate() {
+                res.set_v(i, j, v);
+            }
+            res.set_v(i, 3, rho_i);
+        }
+        res
+    }
+
+    fn vee(mat: &DenseMatrix<T>) -> Vec<T>
+    {
+        vec![mat.get_v(0, 3), mat.get_v(1, 3), mat.get_v(2, 3), mat.get_v(2, 1), mat.get_v(0, 2), mat.get_v(1, 0)]
+    }
+
+    fn matrix(&self) -> DenseMatrix<T>
+    {
+        let r = self.rotation.rotation_matrix();
+        let mut res = DenseMatrix::eye(4);
+        for (i, (row, &t_i)) in r.iter().zip(self.translation.iter()).enumerate() {
+            for (j, &v) in row.iter().enumerate() {
+                res.set_v(i, j, v);
+            }
+            res.set_v(i, 3, t_i);
+        }
+        res
+    }
+
+    // [R  t^ R]
+    // [0  R   ]
+    fn adjoint(&self) -> DenseMatrix<T>
+    {
+        let r = self.rotation.rotation_matrix();
+        let tr = so3::mat3_mul(&so3::hat3(self.translation), &r);
+        so3::to_dense(&[&[&r, &tr], &[&[[T::zero(); 3]; 3], &r]])
+    }
+
+    // [J  Q]
+    // [0  J] with J the left Jacobian of SO(3)
+    fn left_jacobian(tangent: &[T]) -> DenseMatrix<T>
+    {
+        let (rho, omega) = split(tangent);
+        let j = so3::left_jacobian3(omega);
+        so3::to_dense(&[&[&j, &q_block(rho, omega)], &[&[[T::zero(); 3]; 3], &j]])
+    }
+
+    fn left_jacobian_inv(tangent: &[T]) -> DenseMatrix<T>
+    {
+        let (rho, omega) = split(tangent);
+        let j_inv = so3::left_jacobian_inv3(omega);
+        let off = so3::mat3_mul(&so3::mat3_mul(&j_inv, &q_block(rho, omega)), &j_inv);
+        let off = add3(&[[T::zero(); 3]; 3], &off, -T::one());
+        so3::to_dense(&[&[&j_inv, &off], &[&[[T::zero(); 3]; 3], &j_inv]])
+    }
+}
+
+#[test]
+fn se3_test() {
+    use std::f64::consts::FRAC_PI_2;
+
+    // a quarter turn about z, then a shift along x
+    let rz = SO3::<f64>::from_axis_angle([0.0, 0.0, 1.0], FRAC_PI_2);
+    let g = SE3::from_vector3d(rz, &Vector3D::new(1.0, 0.0, 0.0));
+    let p = g.transform_point(&Point3D::new(1.0, 0.0, 2.0));
+    assert!((p.x1 - 1.0).abs() < 1e-6 && (p.x2 - 1.0).abs() < 1e-6 && (p.x3 - 2.0).abs() < 1e-6);
+    let v = g.transform_vector(&Vector3D::new(1.0, 0.0, 2.0));
+    assert!(v.v1.abs() < 1e-6 && (v.v2 - 1.0).abs() < 1e-6 && (v.v3 - 2.0).abs() < 1e-6);
+    let back = g.inverse().act(g.act([0.3, -2.0, 5.0]));
+    assert!((back[0] - 0.3).abs() < 1e-14 && (back[1] + 2.0).abs() < 1e-14 && (back[2] - 5.0).abs() < 1e-14);
+
+    // a pure translation has rho = t, and the matrix round trip
+    let shift = SE3::new(SO3::<f64>::identity(), [1.0, 2.0, 3.0]);
+    assert_eq!(shift.log(), vec![1.0, 2.0, 3.0, 0.0, 0.0, 0.0]);
+    let h = SE3::<f64>::exp(&[0.5, -1.0, 2.0, 0.4, 0.1, -0.7]);
+    assert!(SE3::from_matrix(&h.matrix()).unwrap().matrix().sub(&h.matrix()).norm_inf() < 1e-14);
+    assert!(SE3::<f64>::from_matrix(&DenseMatrix::eye(3)).is_err());
+    // screw motion: halfway along the geodesic twice is the whole way
+    let half = SE3::<f64>::identity().interpolate(&h, 0.5);
+    assert!(half.compose(&half).matrix().sub(&h.matrix()).norm_inf() < 1e-14);
+}
@@ -0,0 +1,143 @@
+extern crate num;
+extern crate evolutionary_geometry;
+
+use super::common::{self, LieGroup};
+use super::super::linear_algebra::matrix::dense::DenseMatrix;
+use super::super::error::{self, AlgebraResult};
+use evolutionary_geometry::euclidean::{Point2D, Vector2D};
+use num::Float;
+use std::fmt::Display;
+
+// Rotations of the plane, kept as the unit complex number cos + i sin; the
+// tangent vector is the angle.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SO2<T>
+where T: Float + Display
+{
+    cos: T,
+    sin: T,
+}
+
+impl<T> SO2<T>
+where T: Float + Display
+{
+    pub fn from_angle(angle: T) -> SO2<T>
+    {
+        SO2 { cos: angle.cos(), sin: angle.sin() }
+    }
+
+    // a 2 x 2 rotation matrix, renormalised
+    pub fn from_matrix(mat: &DenseMatrix<T>) -> AlgebraResult<SO2<T>>
+    {
+        error::check_same_shape(mat.shape(), (2, 2))?;
+        Ok(SO2::from_angle(mat.get_v(1, 0).atan2(mat.get_v(0, 0))))
+    }
+
+    // in (-pi, pi]
+    pub fn angle(&self) -> T
+    {
+        self.sin.atan2(self.cos)
+    }
+
+    pub fn cos_sin(&self) -> (T, T)
+    {
+        (self.cos, self.sin)
+    }
+
+    pub fn act(&self, p: [T; 2]) -> [T; 2]
+    {
+        [self.cos * p[0] - self.sin * p[1], self.sin * p[0] + self.cos * p[1]]
+    }
+
+    pub fn rotate_vector(&self, v: &Vector2D) -> Vector2D
+    {
+        common::to_vector2d(self.act(common::from_vector2d(v)))
+    }
+
+    // about the origin
+    pub fn rotate_point(&self, p: &Point2D) -> Point2D
+    {
+        common::to_point2d(self.act(common::from_point2d(p)))
+    }
+}
+
+impl<T> LieGroup<T> for SO2<T>
+where T: Float + Display
+{
+    const DOF: usize = 1;
+    const DIM: usize = 2;
+
+    fn identity() -> SO2<T>
+    {
+        SO2 { cos: T::one(), sin: T::zero() }
+    }
+
+    fn compose(&self, other: &SO2<T>) -> SO2<T>
+    {
+        let (c, s) = (self.cos * other.cos - self.sin * other.sin, self.sin * other.cos + self.cos * other.sin);
+        // keep |z| = 1 over long chains of products
+        let n = c.hypot(s);
+        SO2 { cos: c / n, sin: s / n }
+    }
+
+    fn inverse(&self) -> SO2<T>
+    {
+        SO2 { cos: self.cos, sin: -self.sin }
+    }
+
+    fn exp(tangent: &[T]) -> SO2<T>
+    {
+        common::check_tangent(tangent.len(), 1);
+        SO2::from_angle(tangent[0])
+    }
+
+    fn log(&self) -> Vec<T>
+    {
+        vec![self.angle()]
+    }
+
+    fn hat(tangent: &[T]) -> DenseMatrix<T>
+    {
+        common::check_tangent(tangent.len(), 1);
+        DenseMatrix::from(2, 2, &[T::zero(), -tangent[0], tangent[0], T::zero()])
+    }
+
+    fn vee(mat: &DenseMatrix<T>) -> Vec<T>
+    {
+        vec![mat.get_v(1, 0)]
+    }
+
+    fn matrix(&self) -> DenseMatrix<T>
+    {
+        DenseMatrix::from(2, 2, &[self.cos, -self.sin, self.sin, self.cos])
+    }
+
+    // the plane rotations commute
+    fn adjoint(&self) -> DenseMatrix<T>
+    {
+        DenseMatrix::eye(1)
+    }
+
+    fn left_jacobian(tangent: &[T]) -> DenseMatrix<T>
+    {
+        common::check_tangent(tangent.len(), 1);
+        DenseMatrix::eye(1)
+    }
+}
+
+#[test]
+fn so2_test() {
+    use std::f64::consts::{FRAC_PI_2, PI};
+
+    let r = SO2::<f64>::from_angle(FRAC_PI_2);
+    let v = r.rotate_vector(&Vector2D::new(1.0, 2.0));
+    assert!((v.v1 + 2.0).abs() < 1e-6 && (v.v2 - 1.0).abs() < 1e-6);
+    let p = r.rotate_point(&Point2D::new(0.0, -1.0));
+    assert!((p.x1 - 1.0).abs() < 1e-6 && p.x2.abs() < 1e-6);
+    // the angle wraps into (-pi, pi]
+    let three_quarters = r.compose(&r).compose(&r);
+    assert!((three_quarters.angle() + FRAC_PI_2).abs() < 1e-15);
+    assert!((SO2::<f64>::from_angle(PI).angle() - PI).abs() < 1e-15);
+    assert!((SO2::from_matrix(&r.matrix()).unwrap().angle() - FRAC_PI_2).abs() < 1e-15);
+    assert!(SO2::<f64>::from_matrix(&DenseMatrix::eye(3)).is_err());
+}
@@ -0,0 +1,265 @@
+extern crate num;
+extern crate evolutionary_geometry;
+
+use super::common::{self, LieGroup};
+use super::quaternion::{Mat3, Quaternion};
+use super::super::linear_algebra::matrix::dense::DenseMatrix;
+use super::super::error::{self, AlgebraResult};
+use evolutionary_geometry::euclidean::{Point3D, Vector3D};
+use num::Float;
+use std::fmt::Display;
+
+// Rotations of R^3, kept as unit quaternions; the tangent vector is the
+// rotation vector, angle times unit axis.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SO3<T>
+where T: Float + Display
+{
+    q: Quaternion<T>,
+}
+
+pub(crate) fn hat3<T>(w: [T; 3]) -> Mat3<T>
+where T: Float
+{
+    let o = T::zero();
+    [[o, -w[2], w[1]],
+     [w[2], o, -w[0]],
+     [-w[1], w[0], o]]
+}
+
+pub(crate) fn mat3_mul<T>(a: &Mat3<T>, b: &Mat3<T>) -> Mat3<T>
+where T: Float
+{
+    let mut c = [[T::zero(); 3]; 3];
+    for i in 0 .. 3 {
+        for j in 0 .. 3 {
+            c[i][j] = a[i][0] * b[0][j] + a[i][1] * b[1][j] + a[i][2] * b[2][j];
+        }
+    }
+    c
+}
+
+pub(crate) fn mat3_vec<T>(a: &Mat3<T>, v: [T; 3]) -> [T; 3]
+where T: Float
+{
+    [0, 1, 2].iter().fold([T::zero(); 3], |mut res, &i| {
+        res[i] = a[i][0] * v[0] + a[i][1] * v[1] + a[i][2] * v[2];
+        res
+    })
+}
+
+// I + a W + b W^2
+pub(crate) fn rodrigues<T>(w: [T; 3], a: T, b: T) -> Mat3<T>
+where T: Float
+{
+    let w1 = hat3(w);
+    let w2 = mat3_mul(&w1, &w1);
+    let mut res = [[T::zero(); 3]; 3];
+    for i in 0 .. 3 {
+        for j in 0 .. 3 {
+            res[i][j] = a * w1[i][j] + b * w2[i][j];
+        }
+        res[i][i] = res[i][i] + T::one();
+    }
+    res
+}
+
+pub(crate) fn to_dense<T>(blocks: &[&[&Mat3<T>]]) -> DenseMatrix<T>
+where T: Float + Display
+{
+    let (rows, cols) = (blocks.len(), blocks[0].len());
+    let mut res = DenseMatrix::new(3 * rows, 3 * cols);
+    for (bi, row) in blocks.iter().enumerate() {
+        for (bj, block) in row.iter().enumerate() {
+            for i in 0 .. 3 {
+                for j in 0 .. 3 {
+                    res.set_v(3 * bi + i, 3 * bj + j, block[i][j]);
+                }
+            }
+        }
+    }
+    res
+}
+
+pub(crate) fn norm3<T>(w: [T; 3]) -> T
+where T: Float
+{
+    (w[0] * w[0] + w[1] * w[1] + w[2] * w[2]).sqrt()
+}
+
+pub(crate) fn left_jacobian3<T>(w: [T; 3]) -> Mat3<T>
+where T: Float
+{
+    let theta = norm3(w);
+    rodrigues(w, common::cosc(theta), common::sinc3(theta))
+}
+
+pub(crate) fn left_jacobian_inv3<T>(w: [T; 3]) -> Mat3<T>
+where T: Float
+{
+    let half = T::one() / (T::one() + T::one());
+    rodrigues(w, -half, common::jinv_coeff(norm3(w)))
+}
+
+impl<T> SO3<T>
+where T: Float + Display
+{
+    pub fn from_quaternion(q: &Quaternion<T>) -> SO3<T>
+    {
+        SO3 { q: q.normalize() }
+    }
+
+    pub fn from_axis_angle(axis: [T; 3], angle: T) -> SO3<T>
+    {
+        SO3 { q: Quaternion::from_axis_angle(axis, angle) }
+    }
+
+    // a 3 x 3 rotation matrix; one a little off orthogonal comes back as the
+    // rotation of its renormalised quaternion
+    pub fn from_matrix(mat: &DenseMatrix<T>) -> AlgebraResult<SO3<T>>
+    {
+        error::check_same_shape(mat.shape(), (3, 3))?;
+        let mut m = [[T::zero(); 3]; 3];
+        for (i, row) in m.iter_mut().enumerate() {
+            for (j, v) in row.iter_mut().enumerate() {
+                *v = mat.get_v(i, j);
+            }
+        }
+        Ok(SO3 { q: Quaternion::from_rotation_matrix(&m) })
+    }
+
+    pub fn quaternion(&self) -> Quaternion<T>
+    {
+        self.q
+    }
+
+    pub fn rotation_matrix(&self) -> Mat3<T>
+    {
+        self.q.to_rotation_matrix()
+    }
+
+    pub fn act(&self, p: [T; 3]) -> [T; 3]
+    {
+        self.q.rotate(p)
+    }
+
+    pub fn rotate_vector(&self, v: &Vector3D) -> Vector3D
+    {
+        common::to_vector3d(self.act(common::from_vector3d(v)))
+    }
+
+    // about the origin
+    pub fn rotate_point(&self, p: &Point3D) -> Point3D
+    {
+        common::to_point3d(self.act(common::from_point3d(p)))
+    }
+
+    pub fn exp_vector3d(v: &Vector3D) -> SO3<T>
+    {
+        SO3::exp(&common::from_vector3d::<T>(v))
+    }
+
+    pub fn log_vector3d(&self) -> Vector3D
+    {
+        common::to_vector3d(self.q.to_rotation_vector())
+    }
+
+    pub fn slerp(&self, other: &SO3<T>, t: T) -> SO3<T>
+    {
+        SO3 { q: self.q.slerp(&other.q, t) }
+    }
+}
+
+impl<T> LieGroup<T> for SO3<T>
+where T: Float + Display
+{
+    const DOF: usize = 3;
+    const DIM: usize = 3;
+
+    fn identity() -> SO3<T>
+    {
+        SO3 { q: Quaternion::identity() }
+    }
+
+    fn compose(&self, other: &SO3<T>) -> SO3<T>
+    {
+        SO3 { q: self.q.mul(&other.q).normalize() }
+    }
+
+    fn inverse(&self) -> SO3<T>
+    {
+        SO3 { q: self.q.conj() }
+    }
+
+    fn exp(tangent: &[T]) -> SO3<T>
+    {
+        common::check_tangent(tangent.len(), 3);
+        SO3 { q: Quaternion::from_rotation_vector([tangent[0], tangent[1], tangent[2]]) }
+    }
+
+    fn log(&self) -> Vec<T>
+    {
+        self.q.to_rotation_vector().to_vec()
+    }
+
+    fn hat(tangent: &[T]) -> DenseMatrix<T>
+    {
+        common::check_tangent(tangent.len(), 3);
+        to_dense(&[&[&hat3([tangent[0], tangent[1], tangent[2]])]])
+    }
+
+    fn vee(mat: &DenseMatrix<T>) -> Vec<T>
+    {
+        vec![mat.get_v(2, 1), mat.get_v(0, 2), mat.get_v(1, 0)]
+    }
+
+    fn matrix(&self) -> DenseMatrix<T>
+    {
+        to_dense(&[&[&self.rotation_matrix()]])
+    }
+
+    fn adjoint(&self) -> DenseMatrix<T>
+    {
+        self.matrix()
+    }
+
+    fn left_jacobian(tangent: &[T]) -> DenseMatrix<T>
+    {
+        common::check_tangent(tangent.len(), 3);
+        to_dense(&[&[&left_jacobian3([tangent[0], tangent[1], tangent[2]])]])
+    }
+
+    fn left_jacobian_inv(tangent: &[T]) -> DenseMatrix<T>
+    {
+        common::check_tangent(tangent.len(), 3);
+        to_dense(&[&[&left_jacobian_inv3([tangent[0], tangent[1], tangent[2]])]])
+    }
+}
+
+#[test]
+fn so3_test() {
+    use std::f64::consts::{FRAC_PI_2, PI};
+
+    let rz = SO3::<f64>::from_axis_angle([0.0, 0.0, 1.0], FRAC_PI_2);
+    let v = rz.rotate_vector(&Vector3D::new(1.0, 2.0, 3.0));
+    assert!((v.v1 + 2.0).abs() < 1e-6 && (v.v2 - 1.0).abs() < 1e-6 && (v.v3 - 3.0).abs() < 1e-6);
+    let p = rz.rotate_point(&Point3D::new(0.0, -1.0, 0.5));
+    assert!((p.x1 - 1.0).abs() < 1e-6 && p.x2.abs() < 1e-6 && (p.x3 - 0.5).abs() < 1e-6);
+    let w = rz.log_vector3d();
+    assert!((w.v3 - FRAC_PI_2 as f32).abs() < 1e-6);
+    assert_eq!(SO3::<f64>::exp_vector3d(&w).log()[2], (FRAC_PI_2 as f32) as f64);
+
+    // the matrix round trip, and log near the half turn
+    let r = SO3::<f64>::exp(&[0.3, -1.2, 0.8]);
+    let back = SO3::from_matrix(&r.matrix()).unwrap();
+    assert!(back.matrix().sub(&r.matrix()).norm_inf() < 1e-14);
+    assert!(SO3::<f64>::from_matrix(&DenseMatrix::eye(4)).is_err());
+    let axis = [2.0 / 3.0, -1.0 / 3.0, 2.0 / 3.0];
+    let near = SO3::<f64>::from_axis_angle(axis, PI - 1e-9);
+    let log = SO3::from_matrix(&near.matrix()).unwrap().log();
+    assert!(log.iter().zip(axis.iter()).all(|(a, b)| (a - b * (PI - 1e-9)).abs() < 1e-7));
+    // slerp and the geodesic interpolation agree
+    let s = SO3::<f64>::exp(&[0.1, 0.2, -0.3]);
+    let mid = s.slerp(&r, 0.3).matrix().sub(&s.interpolate(&r, 0.3).matrix());
+    assert!(mid.norm_inf() < 1e-14);
+}
@@ -0,0 +1,690 @@
+extern crate num;
+
+use super::super::matrix::MatOps;
+use super::super::matrix::dense::{DenseMatrix, Layout};
+use super::super::super::parallel::Element;
+use super::super::matrix::sparse::{CsrMatrix, SparseMatrix};
+use super::super::super::error::{self, AlgebraError, AlgebraResult};
+use num::Float;
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+// Only the lower triangle is read by the factorisations, so the check allows
+// the rounding that assembling A^T A or a stiffness matrix may leave behind.
+fn check_symmetric<M, T>(mat: &M) -> AlgebraResult<()>
+where M: MatOps<T>, T: Float
+{
+    error::check_square(mat.shape())?;
+    let tol = T::from(16).unwrap() * T::epsilon() * mat.norm_inf();
+    if mat.is_symmetric(tol) {
+        Ok(())
+    }
+    else {
+        Err(AlgebraError::NotSymmetric)
+    }
+}
+
+fn solve_columns<M, T, F>(mat_b: &M, solve: F) -> M
+where M: MatOps<T>, T: Float, F: Fn(&[T]) -> Vec<T>
+{
+    let mut xmat = M::new(mat_b.row(), mat_b.col());
+    for k in 0 .. mat_b.col() {
+        for (i, v) in solve(&mat_b.get_col_vec(k)).into_iter().enumerate() {
+            if v != T::zero() {
+                xmat.set_v(i, k, v);
+            }
+        }
+    }
+    xmat
+}
+
+// A = L L^T with L lower triangular, stored row-major
+#[derive(Clone)]
+pub struct Cholesky<T>
+where T: Float + Display + Element
+{
+    size: usize,
+    l: Vec<T>,
+}
+
+impl<T> Cholesky<T>
+where T: Float + Display + Element
+{
+    pub fn new(mat: &DenseMatrix<T>) -> AlgebraResult<Cholesky<T>>
+    {
+        check_symmetric(mat)?;
+        let n = mat.row();
+        let a = mat.to_layout(Layout::RowMajor).into_vec();
+        let mut l = vec![T::zero(); n * n];
+        for j in 0 .. n {
+            let mut s = a[j * n + j];
+            for k in 0 .. j {
+                s = s - l[j * n + k] * l[j * n + k];
+            }
+            if s <= T::zero() || s.is_nan() {
+                return Err(AlgebraError::NotPositiveDefinite { index: j });
+            }
+            let l_jj = s.sqrt();
+            l[j * n + j] = l_jj;
+            for i in j + 1 .. n {
+                let mut s = a[i * n + j];
+                for k in 0 .. j {
+                    s = s - l[i * n + k] * l[j * n + k];
+                }
+                l[i * n + j] = s / l_jj;
+            }
+        }
+        Ok(Cholesky {
+            size: n,
+            l,
+        })
+    }
+
+    pub fn size(&self) -> usize
+    {
+        self.size
+    }
+
+    pub fn l(&self) -> DenseMatrix<T>
+    {
+        DenseMatrix::from_vec(self.size, self.size, Layout::RowMajor, self.l.clone())
+    }
+
+    pub fn solve_vec(&self, b: &[T]) -> Vec<T>
+    {
+        let n = self.size;
+        assert_eq!(b.len(), n, "the length of the vector does not match the matrix!");
+        let mut x = b.to_vec();
+        for i in 0 .. n {
+            for k in 0 .. i {
+                x[i] = x[i] - self.l[i * n + k] * x[k];
+            }
+            x[i] = x[i] / self.l[i * n + i];
+        }
+        for i in (0 .. n).rev() {
+            for k in i + 1 .. n {
+                x[i] = x[i] - self.l[k * n + i] * x[k];
+            }
+            x[i] = x[i] / self.l[i * n + i];
+        }
+        x
+    }
+
+    pub fn solve(&self, mat_b: &DenseMatrix<T>) -> DenseMatrix<T>
+    {
+        solve_columns(mat_b, |b| self.solve_vec(b))
+    }
+
+    pub fn det(&self) -> T
+    {
+        let n = self.size;
+        (0 .. n).fold(T::one(), |product, i| product * self.l[i * n + i] * self.l[i * n + i])
+    }
+
+    // log(det A), finite where det itself would overflow or underflow
+    pub fn log_det(&self) -> T
+    {
+        let n = self.size;
+        let two = T::one() + T::one();
+        (0 .. n).fold(T::zero(), |sum, i| sum + two * self.l[i * n + i].ln())
+    }
+
+    pub fn inverse(&self) -> DenseMatrix<T>
+    {
+        self.solve(&DenseMatrix::eye(self.size))
+    }
+}
+
+// P A P^T = L D L^T with Bunch-Kaufman pivoting: L is unit lower triangular and
+// D is block diagonal with 1x1 and 2x2 blocks, so any nonsingular symmetric A
+// factorises, definite or not. Row i of P A P^T is row perm[i] of A.
+#[derive(Clone)]
+pub struct LDLT<T>
+where T: Float + Display + Element
+{
+    size: usize,
+    // the strict lower part of L, row-major; zero inside the 2x2 blocks
+    l: Vec<T>,
+    d: Vec<T>,
+    // d_off[k] is the off-diagonal entry of the 2x2 block starting at k
+    d_off: Vec<T>,
+    perm: Vec<usize>,
+}
+
+impl<T> LDLT<T>
+where T: Float + Display + Element
+{
+    pub fn new(mat: &DenseMatrix<T>) -> AlgebraResult<LDLT<T>>
+    {
+        check_symmetric(mat)?;
+        let n = mat.row();
+        // the trailing block is updated in full so that pivot rows can be
+        // searched directly; the eliminated columns hold L
+        let mut a = mat.to_layout(Layout::RowMajor).into_vec();
+        for i in 0 .. n {
+            for j in i + 1 .. n {
+                a[i * n + j] = a[j * n + i];
+            }
+        }
+        let alpha = (T::one() + T::from(17).unwrap().sqrt()) / T::from(8).unwrap();
+        let mut d = vec![T::zero(); n];
+        let mut d_off = vec![T::zero(); n];
+        let mut perm: Vec<usize> = (0 .. n).collect();
+        let swap = |a: &mut Vec<T>, perm: &mut Vec<usize>, p: usize, q: usize| {
+            if p != q {
+                for j in 0 .. n {
+                    a.swap(p * n + j, q * n + j);
+                }
+                for i in 0 .. n {
+                    a.swap(i * n + p, i * n + q);
+                }
+                perm.swap(p, q);
+            }
+        };
+        let mut k = 0;
+        while k < n {
+            let abs_kk = a[k * n + k].abs();
+            let mut col_max = T::zero();
+            let mut imax = k;
+            for i in k + 1 .. n {
+                if a[i * n + k].abs() > col_max {
+                    col_max = a[i * n + k].abs();
+                    imax = i;
+                }
+            }
+            if abs_kk == T::zero() && col_max == T::zero() {
+                return Err(AlgebraError::SingularPivot { index: k });
+            }
+            let block = if abs_kk >= alpha * col_max {
+                1
+            }
+            else {
+                let mut row_max = T::zero();
+                for j in k .. n {
+                    if j != imax {
+                        row_max = row_max.max(a[imax * n + j].abs());
+                    }
+                }
+                if abs_kk * row_max >= alpha * col_max * col_max {
+                    1
+                }
+                else if a[imax * n + imax].abs() >= alpha * row_max {
+                    swap(&mut a, &mut perm, k, imax);
+                    1
+                }
+                else {
+                    swap(&mut a, &mut perm, k + 1, imax);
+                    2
+                }
+            };
+            if block == 1 {
+                let d_k = a[k * n + k];
+                d[k] = d_k;
+                for i in k + 1 .. n {
+                    let l_ik = a[i * n + k] / d_k;
+                    if l_ik != T::zero() {
+                        for j in k + 1 .. n {
+                            a[i * n + j] = a[i * n + j] - l_ik * a[j * n + k];
+                        }
+                    }
+                }
+                for i in k + 1 .. n {
+                    a[i * n + k] = a[i * n + k] / d_k;
+                }
+            }
+            else {
+                let (d_11, d_21, d_22) = (a[k * n + k], a[(k + 1) * n + k], a[(k + 1) * n + k + 1]);
+                let det = d_11 * d_22 - d_21 * d_21;
+                d[k] = d_11;
+                d[k + 1] = d_22;
+                d_off[k] = d_21;
+                // [l_i1 l_i2] = [a_i1 a_i2] D^-1
+                let ls: Vec<(T, T)> = (k + 2 .. n).map(|i| {
+                    let (a_1, a_2) = (a[i * n + k], a[i * n + k + 1]);
+                    ((a_1 * d_22 - a_2 * d_21) / det, (a_2 * d_11 - a_1 * d_21) / det)
+                }).collect();
+                for i in k + 2 .. n {
+                    let (l_1, l_2) = ls[i - k - 2];
+                    for j in k + 2 .. n {
+                        a[i * n + j] = a[i * n + j] - l_1 * a[j * n + k] - l_2 * a[j * n + k + 1];
+                    }
+                }
+                for i in k + 2 .. n {
+                    let (l_1, l_2) = ls[i - k - 2];
+                    a[i * n + k] = l_1;
+                    a[i * n + k + 1] = l_2;
+                }
+                a[(k + 1) * n + k] = T::zero();
+            }
+            k += block;
+        }
+        let mut l = vec![T::zero(); n * n];
+        for i in 0 .. n {
+            for j in 0 .. i {
+                l[i * n + j] = a[i * n + j];
+            }
+        }
+        Ok(LDLT {
+            size: n,
+            l,
+            d,
+            d_off,
+            perm,
+        })
+    }
+
+    pub fn size(&self) -> usize
+    {
+        self.size
+    }
+
+    pub fn perm(&self) -> &[usize]
+    {
+        &self.perm
+    }
+
+    pub fn l(&self) -> DenseMatrix<T>
+    {
+        DenseMatrix::from_vec(self.size, self.size, Layout::RowMajor, self.l.clone()).add(&DenseMatrix::eye(self.size))
+    }
+
+    pub fn d(&self) -> DenseMatrix<T>
+    {
+        let mut mat = DenseMatrix::new(self.size, self.size);
+        for i in 0 .. self.size {
+            mat.set_v(i, i, self.d[i]);
+            if self.d_off[i] != T::zero() {
+                mat.set_v(i + 1, i, self.d_off[i]);
+                mat.set_v(i, i + 1, self.d_off[i]);
+            }
+        }
+        mat
+    }
+
+    pub fn solve_vec(&self, b: &[T]) -> Vec<T>
+    {
+        let n = self.size;
+        assert_eq!(b.len(), n, "the length of the vector does not match the matrix!");
+        let mut x: Vec<T> = self.perm.iter().map(|&p| b[p]).collect();
+        for i in 0 .. n {
+            for k in 0 .. i {
+                x[i] = x[i] - self.l[i * n + k] * x[k];
+            }
+        }
+        let mut i = 0;
+        while i < n {
+            if self.d_off[i] == T::zero() {
+                x[i] = x[i] / self.d[i];
+                i += 1;
+            }
+            else {
+                let (d_11, d_21, d_22) = (self.d[i], self.d_off[i], self.d[i + 1]);
+                let det = d_11 * d_22 - d_21 * d_21;
+                let (x_1, x_2) = (x[i], x[i + 1]);
+                x[i] = (x_1 * d_22 - x_2 * d_21) / det;
+                x[i + 1] = (x_2 * d_11 - x_1 * d_21) / det;
+                i += 2;
+            }
+        }
+        for i in (0 .. n).rev() {
+            for k in i + 1 .. n {
+                x[i] = x[i] - self.l[k * n + i] * x[k];
+            }
+        }
+        let mut res = vec![T::zero(); n];
+        for (i, &p) in self.perm.iter().enumerate() {
+            res[p] = x[i];
+        }
+        res
+    }
+
+    pub fn solve(&self, mat_b: &DenseMatrix<T>) -> DenseMatrix<T>
+    {
+        solve_columns(mat_b, |b| self.solve_vec(b))
+    }
+
+    // the symmetric permutation does not change the determinant
+    pub fn det(&self) -> T
+    {
+        self.blocks().into_iter().fold(T::one(), |product, det| product * det)
+    }
+
+    // log|det A|
+    pub fn log_abs_det(&self) -> T
+    {
+        self.blocks().into_iter().fold(T::zero(), |sum, det| sum + det.abs().ln())
+    }
+
+    // the numbers of positive, negative and zero eigenvalues of A
+    pub fn inertia(&self) -> (usize, usize, usize)
+    {
+        let mut res = (0, 0, 0);
+        let mut i = 0;
+        while i < self.size {
+            if self.d_off[i] == T::zero() {
+                if self.d[i] > T::zero() {
+                    res.0 += 1;
+                }
+                else if self.d[i] < T::zero() {
+                    res.1 += 1;
+                }
+                else {
+                    res.2 += 1;
+                }
+                i += 1;
+            }
+            else {
+                // a 2x2 block with a negative determinant has one eigenvalue of each sign
+                res.0 += 1;
+                res.1 += 1;
+                i += 2;
+            }
+        }
+        res
+    }
+
+    // the determinants of the diagonal blocks of D
+    fn blocks(&self) -> Vec<T>
+    {
+        let mut dets = Vec::new();
+        let mut i = 0;
+        while i < self.size {
+            if self.d_off[i] == T::zero() {
+                dets.push(self.d[i]);
+                i += 1;
+            }
+            else {
+                dets.push(self.d[i] * self.d[i + 1] - self.d_off[i] * self.d_off[i]);
+                i += 2;
+            }
+        }
+        dets
+    }
+}
+
+// the rows of the strict lower part of L and the diagonal D
+type SparseFactors<T> = (Vec<Vec<(usize, T)>>, Vec<T>);
+
+// right-looking LDL^T without pivoting on the lower triangle, column by column
+fn sparse_ldlt<T>(mat: &SparseMatrix<T>, definite: bool) -> AlgebraResult<SparseFactors<T>>
+where T: Float + Display + Element
+{
+    check_symmetric(mat)?;
+    let n = mat.row();
+    let mut cols = vec![BTreeMap::new(); n];
+    for (i, j, v) in mat.iter() {
+        if i >= j {
+            cols[j].insert(i, v);
+        }
+    }
+    let mut rows = vec![Vec::new(); n];
+    let mut d = Vec::with_capacity(n);
+    for k in 0 .. n {
+        let d_k = cols[k].get(&k).cloned().unwrap_or_else(T::zero);
+        if definite && (d_k <= T::zero() || d_k.is_nan()) {
+            return Err(AlgebraError::NotPositiveDefinite { index: k });
+        }
+        if d_k == T::zero() {
+            return Err(AlgebraError::SingularPivot { index: k });
+        }
+        let below: Vec<(usize, T)> = cols[k].range(k + 1 ..).map(|(&i, &v)| (i, v)).collect();
+        for (idx, &(j, a_jk)) in below.iter().enumerate() {
+            let l_jk = a_jk / d_k;
+            for &(i, a_ik) in &below[idx ..] {
+                let entry = cols[j].entry(i).or_insert_with(T::zero);
+                *entry = *entry - a_ik * l_jk;
+            }
+            rows[j].push((k, l_jk));
+        }
+        cols[k].clear();
+        d.push(d_k);
+    }
+    Ok((rows, d))
+}
+
+fn rows_to_csr<T>(n: usize, rows: Vec<Vec<(usize, T)>>) -> CsrMatrix<T>
+where T: Float + Display + Element
+{
+    let mut indptr = vec![0];
+    let mut indices = Vec::new();
+    let mut data = Vec::new();
+    for row in rows {
+        for (j, v) in row {
+            indices.push(j);
+            data.push(v);
+        }
+        indptr.push(indices.len());
+    }
+    CsrMatrix::from_raw(n, n, indptr, indices, data)
+}
+
+// the sparse variants do not pivot, which keeps the pattern of L fixed by that of A
+#[derive(Clone)]
+pub struct SparseCholesky<T>
+where T: Float + Display + Element
+{
+    size: usize,
+    // L in CSR, the diagonal is the last entry of every row
+    lower: CsrMatrix<T>,
+}
+
+impl<T> SparseCholesky<T>
+where T: Float + Display + Element
+{
+    pub fn new(mat: &SparseMatrix<T>) -> AlgebraResult<SparseCholesky<T>>
+    {
+        let (mut rows, d) = sparse_ldlt(mat, true)?;
+        let sqrt_d: Vec<T> = d.iter().map(|v| v.sqrt()).collect();
+        for (i, row) in rows.iter_mut().enumerate() {
+            for entry in row.iter_mut() {
+                entry.1 = entry.1 * sqrt_d[entry.0];
+            }
+            row.push((i, sqrt_d[i]));
+        }
+        Ok(SparseCholesky {
+            size: d.len(),
+            lower: rows_to_csr(d.len(), rows),
+        })
+    }
+
+    pub fn size(&self) -> usize
+    {
+        self.size
+    }
+
+    pub fn nnz(&self) -> usize
+    {
+        self.lower.nnz()
+    }
+
+    pub fn l(&self) -> SparseMatrix<T>
+    {
+        self.lower.to_sparse()
+    }
+
+    pub fn solve_vec(&self, b: &[T]) -> Vec<T>
+    {
+        let n = self.size;
+        assert_eq!(b.len(), n, "the length of the vector does not match the matrix!");
+        let mut x = b.to_vec();
+        for i in 0 .. n {
+            let indices = self.lower.row_indices(i);
+            let values = self.lower.row_values(i);
+            let last = values.len() - 1;
+            for (&k, &v) in indices[.. last].iter().zip(values[.. last].iter()) {
+                x[i] = x[i] - v * x[k];
+            }
+            x[i] = x[i] / values[last];
+        }
+        // L^T x = y, sweeping the rows of L as the columns of L^T
+        for i in (0 .. n).rev() {
+            let indices = self.lower.row_indices(i);
+            let values = self.lower.row_values(i);
+            let last = values.len() - 1;
+            x[i] = x[i] / values[last];
+            for (&k, &v) in indices[.. last].iter().zip(values[.. last].iter()) {
+                x[k] = x[k] - v * x[i];
+            }
+        }
+        x
+    }
+
+    pub fn solve(&self, mat_b: &SparseMatrix<T>) -> SparseMatrix<T>
+    {
+        solve_columns(mat_b, |b| self.solve_vec(b))
+    }
+
+    pub fn det(&self) -> T
+    {
+        self.log_det().exp()
+    }
+
+    pub fn log_det(&self) -> T
+    {
+        let two = T::one() + T::one();
+        (0 .. self.size).fold(T::zero(), |sum, i| sum + two * self.lower.row_values(i).last().unwrap().ln())
+    }
+}
+
+#[derive(Clone)]
+pub struct SparseLDLT<T>
+where T: Float + Display + Element
+{
+    size: usize,
+    // the strict lower part of the unit lower triangular L
+    lower: CsrMatrix<T>,
+    d: Vec<T>,
+}
+
+impl<T> SparseLDLT<T>
+where T: Float + Display + Element
+{
+    pub fn new(mat: &SparseMatrix<T>) -> AlgebraResult<SparseLDLT<T>>
+    {
+        let (rows, d) = sparse_ldlt(mat, false)?;
+        Ok(SparseLDLT {
+            size: d.len(),
+            lower: rows_to_csr(d.len(), rows),
+            d,
+        })
+    }
+
+    pub fn size(&self) -> usize
+    {
+        self.size
+    }
+
+    pub fn nnz(&self) -> usize
+    {
+        self.lower.nnz() + self.size
+    }
+
+    pub fn l(&self) -> SparseMatrix<T>
+    {
+        self.lower.to_sparse().add(&SparseMatrix::eye(self.size))
+    }
+
+    pub fn d(&self) -> &[T]
+    {
+        &self.d
+    }
+
+    pub fn solve_vec(&self, b: &[T]) -> Vec<T>
+    {
+        let n = self.size;
+        assert_eq!(b.len(), n, "the length of the vector does not match the matrix!");
+        let mut x = b.to_vec();
+        for i in 0 .. n {
+            for (&k, &v) in self.lower.row_indices(i).iter().zip(self.lower.row_values(i).iter()) {
+                x[i] = x[i] - v * x[k];
+            }
+        }
+        for (x_i, &d_i) in x.iter_mut().zip(self.d.iter()) {
+            *x_i = *x_i / d_i;
+        }
+        for i in (0 .. n).rev() {
+            for (&k, &v) in self.lower.row_indices(i).iter().zip(self.lower.row_values(i).iter()) {
+                x[k] = x[k] - v * x[i];
+            }
+        }
+        x
+    }
+
+    pub fn solve(&self, mat_b: &SparseMatrix<T>) -> SparseMatrix<T>
+    {
+        solve_columns(mat_b, |b| self.solve_vec(b))
+    }
+
+    pub fn det(&self) -> T
+    {
+        self.d.iter().fold(T::one(), |product, &v| product * v)
+    }
+
+    pub fn log_abs_det(&self) -> T
+    {
+        self.d.iter().fold(T::zero(), |sum, &v| sum + v.abs().ln())
+    }
+}
+
+#[test]
+fn cholesky_test() {
+    let n = 6;
+    let mut poisson = SparseMatrix::<f64>::new(n, n);
+    for i in 0 .. n {
+        poisson.set_v(i, i, 2.0);
+        if i > 0 {
+            poisson.set_v(i, i - 1, -1.0);
+            poisson.set_v(i - 1, i, -1.0);
+        }
+    }
+    poisson.set_v(0, n - 1, -0.5);
+    poisson.set_v(n - 1, 0, -0.5);
+    let dense = poisson.to_dense();
+    let b = DenseMatrix::<f64>::from(n, 1, &[1.0, -2.0, 3.0, 0.0, 1.0, 2.0]);
+    let x = dense.lu().unwrap().solve(&b);
+
+    let chol = Cholesky::new(&dense).unwrap();
+    assert!(chol.l().dot_mul(&chol.l().t()).sub(&dense).norm_inf() < 1e-12);
+    assert!(chol.solve(&b).sub(&x).norm_inf() < 1e-12);
+    assert!((chol.det() - dense.det_ge()).abs() < 1e-10);
+    assert!((chol.log_det() - dense.det_ge().ln()).abs() < 1e-12);
+    let schol = SparseCholesky::new(&poisson).unwrap();
+    assert!(schol.l().to_dense().sub(&chol.l()).norm_inf() < 1e-12);
+    assert!(schol.solve(&SparseMatrix::from_dense(&b)).to_dense().sub(&x).norm_inf() < 1e-12);
+    assert!((schol.log_det() - chol.log_det()).abs() < 1e-12);
+
+    // indefinite, with a zero leading entry that forces a 2x2 pivot
+    let sym = DenseMatrix::<f64>::from(4, 4, &[0.0, 1.0, 2.0, 0.0,
+                                               1.0, 0.0, 1.0, 3.0,
+                                               2.0, 1.0, -1.0, 1.0,
+                                               0.0, 3.0, 1.0, 4.0]);
+    assert_eq!(Cholesky::new(&sym).err(), Some(AlgebraError::NotPositiveDefinite { index: 0 }));
+    let ldlt = LDLT::new(&sym).unwrap();
+    let mut pap = DenseMatrix::new(4, 4);
+    for (i, &p) in ldlt.perm().iter().enumerate() {
+        for (j, &q) in ldlt.perm().iter().enumerate() {
+            pap.set_v(i, j, sym.get_v(p, q));
+        }
+    }
+    assert!(ldlt.l().dot_mul(&ldlt.d()).dot_mul(&ldlt.l().t()).sub(&pap).norm_inf() < 1e-12);
+    let b = DenseMatrix::<f64>::from(4, 1, &[1.0, 2.0, 3.0, 4.0]);
+    assert!(sym.dot_mul(&ldlt.solve(&b)).sub(&b).norm_inf() < 1e-12);
+    assert!((ldlt.det() - sym.det_ge()).abs() < 1e-10);
+    assert!((ldlt.log_abs_det() - sym.det_ge().abs().ln()).abs() < 1e-12);
+    // the eigenvalues are about -2.68, -1.84, 1.51 and 6.01
+    assert_eq!(ldlt.inertia(), (2, 2, 0));
+    assert_eq!(SparseLDLT::new(&SparseMatrix::from_dense(&sym)).err(), Some(AlgebraError::SingularPivot { index: 0 }));
+
+    let shifted = poisson.sub(&SparseMatrix::eye(n).scalar_mul(1.5));
+    let sldlt = SparseLDLT::new(&shifted).unwrap();
+    assert!(sldlt.d().iter().any(|&v| v < 0.0));
+    let rhs: Vec<f64> = (0 .. n).map(|i| i as f64).collect();
+    assert!(shifted.mat_vec(&sldlt.solve_vec(&rhs)).iter().zip(rhs.iter()).all(|(a, b)| (a - b).abs() < 1e-10));
+    assert!((sldlt.det() - shifted.det_ge()).abs() < 1e-10);
+
+    let mut skew = dense.clone();
+    skew.set_v(0, 1, 5.0);
+    assert!(!skew.is_symmetric(1e-12) && dense.is_symmetric(0.0));
+    assert_eq!(Cholesky::new(&skew).err(), Some(AlgebraError::NotSymmetric));
+    assert_eq!(SparseCholesky::new(&SparseMatrix::from_dense(&skew)).err(), Some(AlgebraError::NotSymmetric));
+}
@@ -0,0 +1,537 @@
+extern crate num;
+
+use super::super::matrix::MatOps;
+use super::super::matrix::dense::{DenseMatrix, Layout};
+use super::super::super::parallel::Element;
+use super::super::super::error::{self, AlgebraError, AlgebraResult};
+use num::Float;
+use num::complex::Complex;
+use std::cmp::Ordering;
+use std::fmt::Display;
+
+// the QR iterations allowed per eigenvalue before giving up
+const MAX_SWEEPS: usize = 30;
+
+// A = V diag(values) V^T for a symmetric A, values ascending, the
+// orthonormal eigenvectors in the columns of V when they were asked for
+#[derive(Clone)]
+pub struct SymmetricEigen<T>
+where T: Float + Display + Element
+{
+    pub values: Vec<T>,
+    pub vectors: Option<DenseMatrix<T>>,
+}
+
+impl<T> SymmetricEigen<T>
+where T: Float + Display + Element
+{
+    // Householder tridiagonalisation followed by the implicit QL iteration
+    pub fn new(mat: &DenseMatrix<T>, vectors: bool) -> AlgebraResult<SymmetricEigen<T>>
+    {
+        error::check_square(mat.shape())?;
+        error::check_finite(mat)?;
+        let tol = T::from(16).unwrap() * T::epsilon() * mat.norm_inf();
+        if !mat.is_symmetric(tol) {
+            return Err(AlgebraError::NotSymmetric);
+        }
+        let n = mat.row();
+        let mut a = mat.to_layout(Layout::RowMajor).into_vec();
+        // Q, accumulated only when the vectors are wanted
+        let mut z = if vectors {
+            DenseMatrix::<T>::eye(n).into_vec()
+        }
+        else {
+            Vec::new()
+        };
+        tridiagonalize(n, &mut a, &mut z);
+        let mut d: Vec<T> = (0 .. n).map(|i| a[i * n + i]).collect();
+        let mut e: Vec<T> = (0 .. n).map(|i| if i + 1 < n { a[(i + 1) * n + i] } else { T::zero() }).collect();
+        tql(n, &mut d, &mut e, &mut z)?;
+
+        let mut order: Vec<usize> = (0 .. n).collect();
+        order.sort_by(|&i, &j| d[i].partial_cmp(&d[j]).unwrap_or(Ordering::Equal));
+        let values = order.iter().map(|&i| d[i]).collect();
+        let vectors = if vectors {
+            let mut mat = DenseMatrix::new(n, n);
+            for (j, &k) in order.iter().enumerate() {
+                for i in 0 .. n {
+                    mat.set_v(i, j, z[i * n + k]);
+                }
+            }
+            Some(mat)
+        }
+        else {
+            None
+        };
+        Ok(SymmetricEigen {
+            values,
+            vectors,
+        })
+    }
+}
+
+// overwrites the symmetric a with Q^T a Q tridiagonal, and z with z Q when z
+// is not empty
+fn tridiagonalize<T>(n: usize, a: &mut [T], z: &mut [T])
+where T: Float
+{
+    for k in 0 .. n.saturating_sub(2) {
+        let norm = (k + 1 .. n).fold(T::zero(), |sum, i| sum + a[i * n + k] * a[i * n + k]).sqrt();
+        if norm == T::zero() {
+            continue;
+        }
+        let alpha = if a[(k + 1) * n + k] > T::zero() { -norm } else { norm };
+        let mut v: Vec<T> = (k + 1 .. n).map(|i| a[i * n + k]).collect();
+        v[0] = v[0] - alpha;
+        let tau = (T::one() + T::one()) / v.iter().fold(T::zero(), |sum, &x| sum + x * x);
+        // H A H on the trailing block as the rank-2 update A - v w^T - w v^T
+        let p: Vec<T> = (k + 1 .. n).map(|i| {
+            v.iter().enumerate().fold(T::zero(), |sum, (j, &x)| sum + a[i * n + k + 1 + j] * x) * tau
+        }).collect();
+        let half = tau / (T::one() + T::one()) * v.iter().zip(p.iter()).fold(T::zero(), |sum, (&x, &y)| sum + x * y);
+        let w: Vec<T> = p.iter().zip(v.iter()).map(|(&y, &x)| y - half * x).collect();
+        for i in 0 .. v.len() {
+            for j in 0 .. v.len() {
+                let idx = (k + 1 + i) * n + k + 1 + j;
+                a[idx] = a[idx] - v[i] * w[j] - w[i] * v[j];
+            }
+        }
+        a[(k + 1) * n + k] = alpha;
+        a[k * n + k + 1] = alpha;
+        for i in k + 2 .. n {
+            a[i * n + k] = T::zero();
+            a[k * n + i] = T::zero();
+        }
+        if !z.is_empty() {
+            for r in 0 .. n {
+                let s = v.iter().enumerate().fold(T::zero(), |sum, (j, &x)| sum + z[r * n + k + 1 + j] * x) * tau;
+                for (j, &x) in v.iter().enumerate() {
+                    z[r * n + k + 1 + j] = z[r * n + k + 1 + j] - s * x;
+                }
+            }
+        }
+    }
+}
+
+// implicit QL with Wilkinson shifts on the tridiagonal (d, e), e[i] coupling
+// i and i + 1; the rotations are applied to the columns of z when not empty
+fn tql<T>(n: usize, d: &mut [T], e: &mut [T], z: &mut [T]) -> AlgebraResult<()>
+where T: Float
+{
+    let two = T::one() + T::one();
+    for l in 0 .. n {
+        let mut iter = 0;
+        loop {
+            let mut m = l;
+            while m + 1 < n {
+                let dd = d[m].abs() + d[m + 1].abs();
+                if e[m].abs() <= T::epsilon() * dd {
+                    break;
+                }
+                m += 1;
+            }
+            if m == l {
+                break;
+            }
+            if iter == MAX_SWEEPS {
+                return Err(AlgebraError::NotConverged { iterations: iter, residual: e[l].abs().to_f64().unwrap_or(f64::NAN) });
+            }
+            iter += 1;
+            let mut g = (d[l + 1] - d[l]) / (two * e[l]);
+            let mut r = g.hypot(T::one());
+            g = d[m] - d[l] + e[l] / (g + if g >= T::zero() { r } else { -r });
+            let (mut s, mut c, mut p) = (T::one(), T::one(), T::zero());
+            let mut underflow = false;
+            for i in (l .. m).rev() {
+                let f = s * e[i];
+                let b = c * e[i];
+                r = f.hypot(g);
+                e[i + 1] = r;
+                if r == T::zero() {
+                    d[i + 1] = d[i + 1] - p;
+                    e[m] = T::zero();
+                    underflow = true;
+                    break;
+                }
+                s = f / r;
+                c = g / r;
+                g = d[i + 1] - p;
+                r = (d[i] - g) * s + two * c * b;
+                p = s * r;
+                d[i + 1] = g + p;
+                g = c * r - b;
+                if !z.is_empty() {
+                    for k in 0 .. n {
+                        let f = z[k * n + i + 1];
+                        z[k * n + i + 1] = s * z[k * n + i] + c * f;
+                        z[k * n + i] = c * z[k * n + i] - s * f;
+                    }
+                }
+            }
+            if underflow {
+                continue;
+            }
+            d[l] = d[l] - p;
+            e[l] = g;
+            e[m] = T::zero();
+        }
+    }
+    Ok(())
+}
+
+// The eigenvalues of a general real A, complex conjugate pairs adjacent with
+// the positive imaginary part first; the unit eigenvectors when asked for.
+#[derive(Clone)]
+pub struct Eigen<T>
+where T: Float + Display + Element
+{
+    pub values: Vec<Complex<T>>,
+    pub vectors: Option<Vec<Vec<Complex<T>>>>,
+}
+
+impl<T> Eigen<T>
+where T: Float + Display + Element
+{
+    // Hessenberg reduction and the Francis double-shift QR iteration for the
+    // values, then inverse iteration on A for the vectors
+    pub fn new(mat: &DenseMatrix<T>, vectors: bool) -> AlgebraResult<Eigen<T>>
+    {
+        error::check_square(mat.shape())?;
+        error::check_finite(mat)?;
+        let n = mat.row();
+        let mut h = mat.to_layout(Layout::RowMajor).into_vec();
+        hessenberg(n, &mut h);
+        let values = francis(n, &mut h)?;
+        let vectors = if vectors {
+            let a = mat.to_layout(Layout::RowMajor).into_vec();
+            let norm = mat.norm_inf().max(T::min_positive_value());
+            Some(values.iter().map(|&lambda| inverse_iteration(n, &a, lambda, norm)).collect())
+        }
+        else {
+            None
+        };
+        Ok(Eigen {
+            values,
+            vectors,
+        })
+    }
+}
+
+// overwrites a with the upper Hessenberg Q^T a Q
+fn hessenberg<T>(n: usize, a: &mut [T])
+where T: Float
+{
+    for k in 0 .. n.saturating_sub(2) {
+        let norm = (k + 1 .. n).fold(T::zero(), |sum, i| sum + a[i * n + k] * a[i * n + k]).sqrt();
+        if norm == T::zero() {
+            continue;
+        }
+        let alpha = if a[(k + 1) * n + k] > T::zero() { -norm } else { norm };
+        let mut v: Vec<T> = (k + 1 .. n).map(|i| a[i * n + k]).collect();
+        v[0] = v[0] - alpha;
+        let tau = (T::one() + T::one()) / v.iter().fold(T::zero(), |sum, &x| sum + x * x);
+        // H A from the left, then (H A) H from the right
+        for j in k .. n {
+            let s = v.iter().enumerate().fold(T::zero(), |sum, (i, &x)| sum + a[(k + 1 + i) * n + j] * x) * tau;
+            for (i, &x) in v.iter().enumerate() {
+                a[(k + 1 + i) * n + j] = a[(k + 1 + i) * n + j] - s * x;
+            }
+        }
+        for r in 0 .. n {
+            let s = v.iter().enumerate().fold(T::zero(), |sum, (j, &x)| sum + a[r * n + k + 1 + j] * x) * tau;
+            for (j, &x) in v.iter().enumerate() {
+                a[r * n + k + 1 + j] = a[r * n + k + 1 + j] - s * x;
+            }
+        }
+        for i in k + 2 .. n {
+            a[i * n + k] = T::zero();
+        }
+    }
+}
+
+// the eigenvalues of the upper Hessenberg h, which is destroyed
+fn francis<T>(n: usize, h: &mut [T]) -> AlgebraResult<Vec<Complex<T>>>
+where T: Float
+{
+    let zero = T::zero();
+    let half = T::from(0.5).unwrap();
+    let at = |i: usize, j: usize| i * n + j;
+    let sign = |a: T, b: T| if b >= zero { a.abs() } else { -a.abs() };
+    let mut values = vec![Complex::new(zero, zero); n];
+    let mut anorm = zero;
+    for i in 0 .. n {
+        for j in i.saturating_sub(1) .. n {
+            anorm = anorm + h[at(i, j)].abs();
+        }
+    }
+    // nn is the last row of the active block, t the accumulated exceptional shifts
+    let mut nn = n as isize - 1;
+    let mut t = zero;
+    while nn >= 0 {
+        let mut its = 0;
+        loop {
+            let u = nn as usize;
+            let mut l = u;
+            while l >= 1 {
+                let mut s = h[at(l - 1, l - 1)].abs() + h[at(l, l)].abs();
+                if s == zero {
+                    s = anorm;
+                }
+                if h[at(l, l - 1)].abs() + s == s {
+                    h[at(l, l - 1)] = zero;
+                    break;
+                }
+                l -= 1;
+            }
+            let mut x = h[at(u, u)];
+            if l == u {
+                values[u] = Complex::new(x + t, zero);
+                nn -= 1;
+                break;
+            }
+            let mut y = h[at(u - 1, u - 1)];
+            let mut w = h[at(u, u - 1)] * h[at(u - 1, u)];
+            if l == u - 1 {
+                let p = half * (y - x);
+                let q = p * p + w;
+                let mut z = q.abs().sqrt();
+                x = x + t;
+                if q >= zero {
+                    z = p + sign(z, p);
+                    values[u - 1] = Complex::new(x + z, zero);
+                    values[u] = Complex::new(if z != zero { x - w / z } else { x + z }, zero);
+                }
+                else {
+                    values[u - 1] = Complex::new(x + p, z);
+                    values[u] = Complex::new(x + p, -z);
+                }
+                nn -= 2;
+                break;
+            }
+            if its == 2 * MAX_SWEEPS {
+                return Err(AlgebraError::NotConverged { iterations: its, residual: h[at(u, u - 1)].abs().to_f64().unwrap_or(f64::NAN) });
+            }
+            if its == 10 || its == 20 {
+                t = t + x;
+                for i in 0 ..= u {
+                    h[at(i, i)] = h[at(i, i)] - x;
+                }
+                let s = h[at(u, u - 1)].abs() + h[at(u - 1, u - 2)].abs();
+                x = T::from(0.75).unwrap() * s;
+                y = x;
+                w = T::from(-0.4375).unwrap() * s * s;
+            }
+            its += 1;
+            // look for two consecutive small subdiagonal elements
+            let mut p;
+            let mut q;
+            let mut r;
+            let mut m = u - 2;
+            loop {
+                let z = h[at(m, m)];
+                let rr = x - z;
+                let s = y - z;
+                p = (rr * s - w) / h[at(m + 1, m)] + h[at(m, m + 1)];
+                q = h[at(m + 1, m + 1)] - z - rr - s;
+                r = h[at(m + 2, m + 1)];
+                let s = p.abs() + q.abs() + r.abs();
+                p = p / s;
+                q = q / s;
+                r = r / s;
+                if m == l {
+                    break;
+                }
+                let a = h[at(m, m - 1)].abs() * (q.abs() + r.abs());
+                let b = p.abs() * (h[at(m - 1, m - 1)].abs() + z.abs() + h[at(m + 1, m + 1)].abs());
+                if a + b == b {
+                    break;
+                }
+                m -= 1;
+            }
+            for i in m + 2 ..= u {
+                h[at(i, i - 2)] = zero;
+                if i != m + 2 {
+                    h[at(i, i - 3)] = zero;
+                }
+            }
+            // the double-shift QR step on rows and columns l ..= u
+            for k in m .. u {
+                if k != m {
+                    p = h[at(k, k - 1)];
+                    q = h[at(k + 1, k - 1)];
+                    r = if k + 1 != u { h[at(k + 2, k - 1)] } else { zero };
+                    x = p.abs() + q.abs() + r.abs();
+                    if x != zero {
+                        p = p / x;
+                        q = q / x;
+                        r = r / x;
+                    }
+                }
+                let s = sign((p * p + q * q + r * r).sqrt(), p);
+                if s == zero {
+                    continue;
+                }
+                if k == m {
+                    if l != m {
+                        h[at(k, k - 1)] = -h[at(k, k - 1)];
+                    }
+                }
+                else {
+                    h[at(k, k - 1)] = -s * x;
+                }
+                p = p + s;
+                x = p / s;
+                y = q / s;
+                let z = r / s;
+                q = q / p;
+                r = r / p;
+                for j in k ..= u {
+                    let mut p = h[at(k, j)] + q * h[at(k + 1, j)];
+                    if k + 1 != u {
+                        p = p + r * h[at(k + 2, j)];
+                        h[at(k + 2, j)] = h[at(k + 2, j)] - p * z;
+                    }
+                    h[at(k + 1, j)] = h[at(k + 1, j)] - p * y;
+                    h[at(k, j)] = h[at(k, j)] - p * x;
+                }
+                for i in l ..= u.min(k + 3) {
+                    let mut p = x * h[at(i, k)] + y * h[at(i, k + 1)];
+                    if k + 1 != u {
+                        p = p + z * h[at(i, k + 2)];
+                        h[at(i, k + 2)] = h[at(i, k + 2)] - p * r;
+                    }
+                    h[at(i, k + 1)] = h[at(i, k + 1)] - p * q;
+                    h[at(i, k)] = h[at(i, k)] - p;
+                }
+            }
+        }
+    }
+    Ok(values)
+}
+
+// a unit vector in the null space of A - lambda I: a few solves with the
+// (complex) shifted matrix, nudged off lambda so that it stays nonsingular
+fn inverse_iteration<T>(n: usize, a: &[T], lambda: Complex<T>, norm: T) -> Vec<Complex<T>>
+where T: Float
+{
+    let zero = Complex::new(T::zero(), T::zero());
+    let tiny = T::epsilon() * norm;
+    let mu = lambda + Complex::new(tiny, T::zero());
+    let mut lu: Vec<Complex<T>> = a.iter().map(|&v| Complex::new(v, T::zero())).collect();
+    for i in 0 .. n {
+        lu[i * n + i] = lu[i * n + i] - mu;
+    }
+    let mut perm: Vec<usize> = (0 .. n).collect();
+    for k in 0 .. n {
+        let mut p = k;
+        for i in k + 1 .. n {
+            if lu[i * n + k].norm() > lu[p * n + k].norm() {
+                p = i;
+            }
+        }
+        if p != k {
+            for j in 0 .. n {
+                lu.swap(p * n + j, k * n + j);
+            }
+            perm.swap(p, k);
+        }
+        if lu[k * n + k] == zero {
+            lu[k * n + k] = Complex::new(tiny, T::zero());
+        }
+        let head = lu[k * n + k];
+        for i in k + 1 .. n {
+            let factor = lu[i * n + k] / head;
+            lu[i * n + k] = factor;
+            for j in k + 1 .. n {
+                lu[i * n + j] = lu[i * n + j] - factor * lu[k * n + j];
+            }
+        }
+    }
+    let scale = T::one() / T::from(n).unwrap().sqrt();
+    let mut x = vec![Complex::new(scale, T::zero()); n];
+    for _ in 0 .. 3 {
+        let mut y: Vec<Complex<T>> = perm.iter().map(|&p| x[p]).collect();
+        for i in 0 .. n {
+            for j in 0 .. i {
+                y[i] = y[i] - lu[i * n + j] * y[j];
+            }
+        }
+        for i in (0 .. n).rev() {
+            for j in i + 1 .. n {
+                y[i] = y[i] - lu[i * n + j] * y[j];
+            }
+            y[i] = y[i] / lu[i * n + i];
+        }
+        let len = y.iter().fold(T::zero(), |sum, v| sum + v.norm_sqr()).sqrt();
+        x = y.into_iter().map(|v| v / len).collect();
+    }
+    // fix the phase: the largest component is real and positive
+    let mut big = 0;
+    for i in 1 .. n {
+        if x[i].norm() > x[big].norm() {
+            big = i;
+        }
+    }
+    if n > 0 {
+        let phase = x[big].conj() / x[big].norm();
+        x = x.into_iter().map(|v| v * phase).collect();
+    }
+    x
+}
+
+#[test]
+fn eigen_test() {
+    let n = 8;
+    let mut poisson = DenseMatrix::<f64>::new(n, n);
+    for i in 0 .. n {
+        poisson.set_v(i, i, 2.0);
+        if i > 0 {
+            poisson.set_v(i, i - 1, -1.0);
+            poisson.set_v(i - 1, i, -1.0);
+        }
+    }
+    let eig = SymmetricEigen::new(&poisson, true).unwrap();
+    let v = eig.vectors.clone().unwrap();
+    for k in 0 .. n {
+        let exact = 2.0 - 2.0 * ((k + 1) as f64 * std::f64::consts::PI / (n + 1) as f64).cos();
+        assert!((eig.values[k] - exact).abs() < 1e-12);
+        let v_k = v.get_col_vec(k);
+        let av = poisson.mat_vec(&v_k);
+        assert!(av.iter().zip(v_k.iter()).all(|(&a, &x)| (a - eig.values[k] * x).abs() < 1e-12));
+    }
+    assert!(v.t().dot_mul(&v).sub(&DenseMatrix::eye(n)).norm_inf() < 1e-12);
+    assert!(SymmetricEigen::new(&poisson, false).unwrap().vectors.is_none());
+    let dense = DenseMatrix::<f64>::from(3, 3, &[4.0, 1.0, -2.0, 1.0, 2.0, 0.0, -2.0, 0.0, 3.0]);
+    let values = SymmetricEigen::new(&dense, false).unwrap().values;
+    assert!((values.iter().sum::<f64>() - dense.trace()).abs() < 1e-12);
+    assert!((values.iter().product::<f64>() - dense.det_ge()).abs() < 1e-10);
+
+    // the companion matrix of (x - 1)(x - 2)(x^2 + 1) = x^4 - 3x^3 + 3x^2 - 3x + 2
+    let companion = DenseMatrix::<f64>::from(4, 4, &[3.0, -3.0, 3.0, -2.0,
+                                                     1.0, 0.0, 0.0, 0.0,
+                                                     0.0, 1.0, 0.0, 0.0,
+                                                     0.0, 0.0, 1.0, 0.0]);
+    let eig = Eigen::new(&companion, true).unwrap();
+    let mut found = [false; 4];
+    let roots = [Complex::new(1.0, 0.0), Complex::new(2.0, 0.0), Complex::new(0.0, 1.0), Complex::new(0.0, -1.0)];
+    for lambda in &eig.values {
+        for (f, root) in found.iter_mut().zip(roots.iter()) {
+            if (lambda - root).norm() < 1e-10 {
+                *f = true;
+            }
+        }
+    }
+    assert!(found.iter().all(|&f| f));
+    for (lambda, x) in eig.values.iter().zip(eig.vectors.unwrap().iter()) {
+        for i in 0 .. 4 {
+            let ax = (0 .. 4).fold(Complex::new(0.0, 0.0), |sum, j| sum + x[j] * companion.get_v(i, j));
+            assert!((ax - lambda * x[i]).norm() < 1e-10);
+        }
+    }
+    assert!(Eigen::new(&companion, false).unwrap().vectors.is_none());
+    assert_eq!(Eigen::new(&DenseMatrix::<f64>::new(2, 3), false).err(), Some(AlgebraError::NotSquare { shape: (2, 3) }));
+    let mut nan = DenseMatrix::<f64>::eye(3);
+    nan.set_v(1, 1, f64::NAN);
+    assert_eq!(SymmetricEigen::new(&nan, true).err(), Some(AlgebraError::NotFinite { index: (1, 1) }));
+    assert_eq!(Eigen::new(&nan, true).err(), Some(AlgebraError::NotFinite { index: (1, 1) }));
+}
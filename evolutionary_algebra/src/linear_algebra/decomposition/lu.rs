@@ -0,0 +1,508 @@
+extern crate num;
+
+use super::condition;
+use super::super::matrix::MatOps;
+use super::super::matrix::dense::{DenseMatrix, Layout};
+use super::super::matrix::sparse::{CsrMatrix, SparseMatrix};
+use super::super::scalar::Scalar;
+use super::super::super::error::{self, AlgebraError, AlgebraResult};
+use num::{Float, Num};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Display;
+
+// P A = L U with partial pivoting. L (unit lower, diagonal not stored) and U
+// share one row-major array; row i of P A is row perm[i] of A. Complex
+// matrices are pivoted by the modulus of their entries.
+#[derive(Clone)]
+pub struct LU<T>
+where T: Scalar
+{
+    size: usize,
+    lu: Vec<T>,
+    perm: Vec<usize>,
+    odd: bool,
+    norm_1: T::Real,
+}
+
+impl<T> LU<T>
+where T: Scalar
+{
+    pub fn new(mat: &DenseMatrix<T>) -> AlgebraResult<LU<T>>
+    {
+        error::check_square(mat.shape())?;
+        let n = mat.row();
+        let mut lu = mat.to_layout(Layout::RowMajor).into_vec();
+        let mut perm: Vec<usize> = (0 .. n).collect();
+        let mut odd = false;
+        for k in 0 .. n {
+            let mut p = k;
+            for i in k + 1 .. n {
+                if lu[i * n + k].modulus() > lu[p * n + k].modulus() {
+                    p = i;
+                }
+            }
+            if lu[p * n + k] == T::zero() {
+                return Err(AlgebraError::SingularPivot { index: k });
+            }
+            if p != k {
+                for j in 0 .. n {
+                    lu.swap(p * n + j, k * n + j);
+                }
+                perm.swap(p, k);
+                odd = !odd;
+            }
+            let head = lu[k * n + k];
+            for i in k + 1 .. n {
+                let factor = lu[i * n + k] / head;
+                lu[i * n + k] = factor;
+                if factor != T::zero() {
+                    for j in k + 1 .. n {
+                        lu[i * n + j] = lu[i * n + j] - factor * lu[k * n + j];
+                    }
+                }
+            }
+        }
+        Ok(LU {
+            size: n,
+            lu,
+            perm,
+            odd,
+            norm_1: mat.norm_1(),
+        })
+    }
+
+    pub fn size(&self) -> usize
+    {
+        self.size
+    }
+
+    pub fn perm(&self) -> &[usize]
+    {
+        &self.perm
+    }
+
+    pub fn l(&self) -> DenseMatrix<T>
+    {
+        let n = self.size;
+        let mut mat = DenseMatrix::eye(n);
+        for i in 0 .. n {
+            for j in 0 .. i {
+                mat.set_v(i, j, self.lu[i * n + j]);
+            }
+        }
+        mat
+    }
+
+    pub fn u(&self) -> DenseMatrix<T>
+    {
+        let n = self.size;
+        let mut mat = DenseMatrix::new(n, n);
+        for i in 0 .. n {
+            for j in i .. n {
+                mat.set_v(i, j, self.lu[i * n + j]);
+            }
+        }
+        mat
+    }
+
+    // x with A x = b
+    pub fn solve_vec(&self, b: &[T]) -> Vec<T>
+    {
+        let n = self.size;
+        assert_eq!(b.len(), n, "the length of the vector does not match the matrix!");
+        let mut x: Vec<T> = self.perm.iter().map(|&p| b[p]).collect();
+        for i in 0 .. n {
+            for j in 0 .. i {
+                x[i] = x[i] - self.lu[i * n + j] * x[j];
+            }
+        }
+        for i in (0 .. n).rev() {
+            for j in i + 1 .. n {
+                x[i] = x[i] - self.lu[i * n + j] * x[j];
+            }
+            x[i] = x[i] / self.lu[i * n + i];
+        }
+        x
+    }
+
+    // x with A^T x = b, that is U^T L^T P x = b
+    pub fn solve_transpose_vec(&self, b: &[T]) -> Vec<T>
+    {
+        let n = self.size;
+        assert_eq!(b.len(), n, "the length of the vector does not match the matrix!");
+        let mut w = b.to_vec();
+        for i in 0 .. n {
+            for j in 0 .. i {
+                w[i] = w[i] - self.lu[j * n + i] * w[j];
+            }
+            w[i] = w[i] / self.lu[i * n + i];
+        }
+        for i in (0 .. n).rev() {
+            for j in i + 1 .. n {
+                w[i] = w[i] - self.lu[j * n + i] * w[j];
+            }
+        }
+        let mut x = vec![T::zero(); n];
+        for (i, &p) in self.perm.iter().enumerate() {
+            x[p] = w[i];
+        }
+        x
+    }
+
+    pub fn solve(&self, mat_b: &DenseMatrix<T>) -> DenseMatrix<T>
+    {
+        solve_columns(mat_b, |b| self.solve_vec(b))
+    }
+
+    pub fn solve_transpose(&self, mat_b: &DenseMatrix<T>) -> DenseMatrix<T>
+    {
+        solve_columns(mat_b, |b| self.solve_transpose_vec(b))
+    }
+
+    pub fn det(&self) -> T
+    {
+        let n = self.size;
+        let product = (0 .. n).fold(T::one(), |product, i| product * self.lu[i * n + i]);
+        if self.odd {
+            -product
+        }
+        else {
+            product
+        }
+    }
+
+    pub fn inverse(&self) -> DenseMatrix<T>
+    {
+        self.solve(&DenseMatrix::eye(self.size))
+    }
+
+    // an estimate of the 1-norm condition number ||A||_1 ||A^-1||_1
+    pub fn cond_est(&self) -> T::Real
+    {
+        // A^H x = b is A^T conj(x) = conj(b)
+        let conj = |v: Vec<T>| v.into_iter().map(T::conj).collect::<Vec<T>>();
+        self.norm_1 * condition::inv_norm_1_est(self.size, |b| self.solve_vec(b),
+                                                |b| conj(self.solve_transpose_vec(&conj(b.to_vec()))))
+    }
+}
+
+fn solve_columns<M, T, F>(mat_b: &M, solve: F) -> M
+where M: MatOps<T>, T: Num + Copy, F: Fn(&[T]) -> Vec<T>
+{
+    let mut xmat = M::new(mat_b.row(), mat_b.col());
+    for k in 0 .. mat_b.col() {
+        for (i, v) in solve(&mat_b.get_col_vec(k)).into_iter().enumerate() {
+            if v != T::zero() {
+                xmat.set_v(i, k, v);
+            }
+        }
+    }
+    xmat
+}
+
+// The sparse variant: right-looking elimination that only touches stored
+// entries. L (strict lower, columns are elimination steps) and U (rows in
+// pivot order) are kept in CSR, so the triangular solves visit nonzeros only.
+#[derive(Clone)]
+pub struct SparseLU<T>
+where T: Float + Display
+{
+    size: usize,
+    lower: CsrMatrix<T>,
+    upper: CsrMatrix<T>,
+    perm: Vec<usize>,
+    odd: bool,
+    norm_1: T,
+}
+
+fn to_csr<T>(n: usize, rows: Vec<Vec<(usize, T)>>) -> CsrMatrix<T>
+where T: Float + Display
+{
+    let mut indptr = vec![0];
+    let mut indices = Vec::new();
+    let mut data = Vec::new();
+    for row in rows {
+        for (j, v) in row {
+            indices.push(j);
+            data.push(v);
+        }
+        indptr.push(indices.len());
+    }
+    CsrMatrix::from_raw(n, n, indptr, indices, data)
+}
+
+impl<T> SparseLU<T>
+where T: Float + Display
+{
+    pub fn new(mat: &SparseMatrix<T>) -> AlgebraResult<SparseLU<T>>
+    {
+        error::check_square(mat.shape())?;
+        let n = mat.row();
+        // the active rows, and for every column the active rows storing it
+        let mut rows = vec![BTreeMap::new(); n];
+        let mut cols = vec![BTreeSet::new(); n];
+        for (i, j, v) in mat.iter() {
+            rows[i].insert(j, v);
+            cols[j].insert(i);
+        }
+        let mut lower_of = vec![Vec::new(); n];
+        let mut upper = Vec::with_capacity(n);
+        let mut perm = Vec::with_capacity(n);
+        for k in 0 .. n {
+            let mut pivot = None;
+            let mut best = T::zero();
+            for &r in &cols[k] {
+                let v = rows[r][&k].abs();
+                if v > best {
+                    best = v;
+                    pivot = Some(r);
+                }
+            }
+            let p = match pivot {
+                Some(p) => p,
+                None => return Err(AlgebraError::SingularPivot { index: k }),
+            };
+            let pivot_row: Vec<(usize, T)> = rows[p].iter().map(|(&j, &v)| (j, v)).collect();
+            rows[p].clear();
+            for &(j, _) in &pivot_row {
+                cols[j].remove(&p);
+            }
+            let head = pivot_row[0].1;
+            let targets: Vec<usize> = cols[k].iter().cloned().collect();
+            for r in targets {
+                let factor = rows[r].remove(&k).unwrap() / head;
+                cols[k].remove(&r);
+                lower_of[r].push((k, factor));
+                for &(j, u) in &pivot_row[1 ..] {
+                    let entry = rows[r].entry(j).or_insert_with(|| {
+                        cols[j].insert(r);
+                        T::zero()
+                    });
+                    *entry = *entry - factor * u;
+                }
+            }
+            upper.push(pivot_row);
+            perm.push(p);
+        }
+        let lower = perm.iter().map(|&p| lower_of[p].clone()).collect();
+        Ok(SparseLU {
+            size: n,
+            lower: to_csr(n, lower),
+            upper: to_csr(n, upper),
+            odd: odd_permutation(&perm),
+            perm,
+            norm_1: mat.norm_1(),
+        })
+    }
+
+    pub fn size(&self) -> usize
+    {
+        self.size
+    }
+
+    pub fn perm(&self) -> &[usize]
+    {
+        &self.perm
+    }
+
+    pub fn nnz(&self) -> usize
+    {
+        self.lower.nnz() + self.upper.nnz()
+    }
+
+    pub fn l(&self) -> SparseMatrix<T>
+    {
+        self.lower.to_sparse().add(&SparseMatrix::eye(self.size))
+    }
+
+    pub fn u(&self) -> SparseMatrix<T>
+    {
+        self.upper.to_sparse()
+    }
+
+    pub fn solve_vec(&self, b: &[T]) -> Vec<T>
+    {
+        let n = self.size;
+        assert_eq!(b.len(), n, "the length of the vector does not match the matrix!");
+        let mut x: Vec<T> = self.perm.iter().map(|&p| b[p]).collect();
+        for i in 0 .. n {
+            for (&j, &v) in self.lower.row_indices(i).iter().zip(self.lower.row_values(i).iter()) {
+                x[i] = x[i] - v * x[j];
+            }
+        }
+        for i in (0 .. n).rev() {
+            let indices = self.upper.row_indices(i);
+            let values = self.upper.row_values(i);
+            for (&j, &v) in indices[1 ..].iter().zip(values[1 ..].iter()) {
+                x[i] = x[i] - v * x[j];
+            }
+            x[i] = x[i] / values[0];
+        }
+        x
+    }
+
+    pub fn solve_transpose_vec(&self, b: &[T]) -> Vec<T>
+    {
+        let n = self.size;
+        assert_eq!(b.len(), n, "the length of the vector does not match the matrix!");
+        // column-oriented sweeps: row i of U is column i of U^T
+        let mut w = b.to_vec();
+        for i in 0 .. n {
+            let indices = self.upper.row_indices(i);
+            let values = self.upper.row_values(i);
+            w[i] = w[i] / values[0];
+            for (&j, &v) in indices[1 ..].iter().zip(values[1 ..].iter()) {
+                w[j] = w[j] - v * w[i];
+            }
+        }
+        for i in (0 .. n).rev() {
+            for (&j, &v) in self.lower.row_indices(i).iter().zip(self.lower.row_values(i).iter()) {
+                w[j] = w[j] - v * w[i];
+            }
+        }
+        let mut x = vec![T::zero(); n];
+        for (i, &p) in self.perm.iter().enumerate() {
+            x[p] = w[i];
+        }
+        x
+    }
+
+    pub fn solve(&self, mat_b: &SparseMatrix<T>) -> SparseMatrix<T>
+    {
+        solve_columns(mat_b, |b| self.solve_vec(b))
+    }
+
+    pub fn solve_transpose(&self, mat_b: &SparseMatrix<T>) -> SparseMatrix<T>
+    {
+        solve_columns(mat_b, |b| self.solve_transpose_vec(b))
+    }
+
+    pub fn det(&self) -> T
+    {
+        let product = (0 .. self.size).fold(T::one(), |product, i| product * self.upper.row_values(i)[0]);
+        if self.odd {
+            -product
+        }
+        else {
+            product
+        }
+    }
+
+    pub fn inverse(&self) -> SparseMatrix<T>
+    {
+        self.solve(&SparseMatrix::eye(self.size))
+    }
+
+    pub fn cond_est(&self) -> T
+    where T: Scalar<Real = T>
+    {
+        self.norm_1 * condition::inv_norm_1_est(self.size, |b| self.solve_vec(b), |b| self.solve_transpose_vec(b))
+    }
+}
+
+// the parity of a permutation, from the lengths of its cycles
+fn odd_permutation(perm: &[usize]) -> bool
+{
+    let mut seen = vec![false; perm.len()];
+    let mut odd = false;
+    for start in 0 .. perm.len() {
+        let mut i = start;
+        let mut len = 0;
+        while !seen[i] {
+            seen[i] = true;
+            i = perm[i];
+            len += 1;
+        }
+        if len > 0 && len % 2 == 0 {
+            odd = !odd;
+        }
+    }
+    odd
+}
+
+#[test]
+fn lu_test() {
+    let values = [0.0, 2.0, 1.0, 0.0,
+                  3.0, 0.0, -4.0, 2.0,
+                  2.0, 1.0, 8.0, 0.0,
+                  0.0, 1.0, 0.0, 3.0];
+    let dense = DenseMatrix::<f64>::from(4, 4, &values);
+    let sparse = SparseMatrix::from_dense(&dense);
+    let b = DenseMatrix::<f64>::from(4, 2, &[1.0, 0.0, 2.0, 1.0, 3.0, 0.0, 4.0, -1.0]);
+
+    let lu = LU::new(&dense).unwrap();
+    let slu = SparseLU::new(&sparse).unwrap();
+    let mut pa = DenseMatrix::new(4, 4);
+    for (i, &p) in lu.perm().iter().enumerate() {
+        for j in 0 .. 4 {
+            pa.set_v(i, j, dense.get_v(p, j));
+        }
+    }
+    assert!(lu.l().dot_mul(&lu.u()).sub(&pa).norm_inf() < 1e-12);
+    assert!(slu.l().dot_mul(&slu.u()).to_dense().sub(&pa).norm_inf() < 1e-12);
+
+    let x = lu.solve(&b);
+    assert!(dense.dot_mul(&x).sub(&b).norm_inf() < 1e-12);
+    assert!(slu.solve(&SparseMatrix::from_dense(&b)).to_dense().sub(&x).norm_inf() < 1e-12);
+    let xt = lu.solve_transpose(&b);
+    assert!(dense.t().dot_mul(&xt).sub(&b).norm_inf() < 1e-12);
+    assert!(slu.solve_transpose(&SparseMatrix::from_dense(&b)).to_dense().sub(&xt).norm_inf() < 1e-12);
+
+    assert!((lu.det() - dense.det_ge()).abs() < 1e-10);
+    assert!((slu.det() - dense.det_ge()).abs() < 1e-10);
+    let inv = lu.inverse();
+    assert!(inv.dot_mul(&dense).sub(&DenseMatrix::eye(4)).norm_inf() < 1e-12);
+    assert!(slu.inverse().to_dense().sub(&inv).norm_inf() < 1e-12);
+    // Hager's estimate is a lower bound that is exact for small matrices
+    let cond = dense.norm_1() * inv.norm_1();
+    assert!(lu.cond_est() <= cond * (1.0 + 1e-12) && lu.cond_est() > 0.5 * cond);
+    assert!((slu.cond_est() - lu.cond_est()).abs() < 1e-8 * cond);
+
+    let singular = DenseMatrix::<f64>::from(3, 3, &[1.0, 2.0, 3.0, 2.0, 4.0, 6.0, 1.0, 0.0, 1.0]);
+    assert_eq!(LU::new(&singular).err(), Some(AlgebraError::SingularPivot { index: 2 }));
+    assert_eq!(SparseLU::new(&SparseMatrix::from_dense(&singular)).err(), Some(AlgebraError::SingularPivot { index: 2 }));
+    assert_eq!(LU::new(&b).err(), Some(AlgebraError::NotSquare { shape: (4, 2) }));
+}
+
+#[test]
+fn complex_lu_test() {
+    use num::complex::Complex;
+
+    let c = |re, im| Complex::new(re, im);
+    let mat = DenseMatrix::from(3, 3, &[c(1.0, 2.0), c(0.0, -1.0), c(3.0, 0.0),
+                                        c(2.0, 0.0), c(4.0, 1.0), c(0.0, 1.0),
+                                        c(0.0, -3.0), c(1.0, 1.0), c(2.0, -2.0)]);
+    let x = [c(1.0, -1.0), c(0.5, 2.0), c(-1.0, 0.0)];
+    let close = |a: &[Complex<f64>], b: &[Complex<f64>]| a.iter().zip(b.iter()).all(|(&a, &b)| (a - b).modulus() < 1e-12);
+
+    let lu = mat.lu().unwrap();
+    let mut pa = DenseMatrix::new(3, 3);
+    for (i, &p) in lu.perm().iter().enumerate() {
+        for j in 0 .. 3 {
+            pa.set_v(i, j, mat.get_v(p, j));
+        }
+    }
+    assert!(lu.l().dot_mul(&lu.u()).sub(&pa).norm_inf() < 1e-12);
+    assert!(close(&lu.solve_vec(&mat.mat_vec(&x)), &x));
+    assert!(close(&lu.solve_transpose_vec(&mat.t().mat_vec(&x)), &x));
+    let ge = DenseMatrix::solve_ge(&mat, &DenseMatrix::from(3, 1, &mat.mat_vec(&x))).unwrap();
+    assert!(close(ge.as_slice(), &x));
+    assert!((lu.det() - mat.det_ge()).modulus() < 1e-12);
+    assert!((mat.det_ge() - mat.det_adj()).modulus() < 1e-12);
+    let inv = lu.inverse();
+    assert!(inv.dot_mul(&mat).sub(&DenseMatrix::eye(3)).norm2() < 1e-12);
+    assert!(inv.sub(&mat.inv_ge().unwrap()).norm2() < 1e-12);
+    let cond = mat.norm_1() * inv.norm_1();
+    assert!(lu.cond_est() <= cond * (1.0 + 1e-12) && lu.cond_est() > 0.5 * cond);
+
+    // the pivot is the entry of the largest modulus, here an imaginary one
+    let pivot = DenseMatrix::from(2, 2, &[c(1.0, 0.0), c(1.0, 0.0), c(0.0, 2.0), c(1.0, 0.0)]);
+    assert_eq!(pivot.lu().unwrap().perm(), &[1, 0]);
+    assert_eq!(mat.conj_t().get_v(0, 2), c(0.0, 3.0));
+    assert!((mat.norm_inf() - (3.0 + 3.0 * 2f64.sqrt())).abs() < 1e-12);
+    let gram = mat.conj_t().dot_mul(&mat);
+    assert!(gram.is_hermitian(1e-12) && !gram.scalar_mul(c(0.0, 1.0)).is_hermitian(1e-12));
+    assert!(!mat.is_hermitian(1e-12));
+    let singular = DenseMatrix::from(2, 2, &[c(1.0, 1.0), c(2.0, 0.0), c(2.0, 0.0), c(2.0, -2.0)]);
+    assert_eq!(singular.lu().err(), Some(AlgebraError::SingularPivot { index: 1 }));
+}
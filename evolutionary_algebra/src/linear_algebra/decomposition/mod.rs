@@ -0,0 +1,14 @@
+pub mod lu;
+pub mod cholesky;
+pub mod qr;
+pub mod eigen;
+pub mod svd;
+pub mod schur;
+pub(crate) mod condition;
+
+pub use self::lu::{LU, SparseLU};
+pub use self::cholesky::{Cholesky, LDLT, SparseCholesky, SparseLDLT};
+pub use self::qr::{LeastSquares, QrMethod, QR};
+pub use self::eigen::{Eigen, SymmetricEigen};
+pub use self::svd::SVD;
+pub use self::schur::Schur;
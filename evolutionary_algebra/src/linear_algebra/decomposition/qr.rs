@@ -0,0 +1,366 @@
+extern crate num;
+
+use super::super::matrix::MatOps;
+use super::super::matrix::dense::{DenseMatrix, Layout};
+use super::super::super::parallel::Element;
+use num::Float;
+use std::fmt::Display;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum QrMethod {
+    Householder,
+    Givens,
+}
+
+// how Q is kept: as the sequence of transformations that built R
+#[derive(Clone)]
+enum Orthogonal<T> {
+    // (k, v, tau): H_k = I - tau v v^T acting on the rows k ..
+    Householder(Vec<(usize, Vec<T>, T)>),
+    // (i, j, c, s): a rotation in the plane of the rows i < j
+    Givens(Vec<(usize, usize, T, T)>),
+}
+
+// A P = Q R with Q orthogonal (m x m), R upper trapezoidal (m x n) and P a
+// column permutation, the identity unless pivoting was asked for. Column k of
+// A P is column perm[k] of A.
+#[derive(Clone)]
+pub struct QR<T>
+where T: Float + Display + Element
+{
+    row_num: usize,
+    col_num: usize,
+    // R, row-major
+    r: Vec<T>,
+    q: Orthogonal<T>,
+    perm: Vec<usize>,
+}
+
+// x, the residual norm ||b - A x|| of every column of b, and the numerical rank
+// of A used to compute them
+pub struct LeastSquares<T>
+where T: Float + Display + Element
+{
+    pub x: DenseMatrix<T>,
+    pub residual_norm: Vec<T>,
+    pub rank: usize,
+}
+
+impl<T> QR<T>
+where T: Float + Display + Element
+{
+    pub fn new(mat: &DenseMatrix<T>) -> QR<T>
+    {
+        QR::with_method(mat, QrMethod::Householder, false)
+    }
+
+    // column pivoting puts the largest remaining column first at every step,
+    // so |r_kk| decreases and the trailing small ones reveal the rank
+    pub fn pivoted(mat: &DenseMatrix<T>) -> QR<T>
+    {
+        QR::with_method(mat, QrMethod::Householder, true)
+    }
+
+    pub fn with_method(mat: &DenseMatrix<T>, method: QrMethod, pivoting: bool) -> QR<T>
+    {
+        let (m, n) = (mat.row(), mat.col());
+        let mut a = mat.to_layout(Layout::RowMajor).into_vec();
+        let mut perm: Vec<usize> = (0 .. n).collect();
+        let mut reflectors = Vec::new();
+        let mut rotations = Vec::new();
+        for k in 0 .. m.min(n) {
+            if pivoting {
+                let col_norm = |a: &Vec<T>, j: usize| (k .. m).fold(T::zero(), |sum, i| sum + a[i * n + j] * a[i * n + j]);
+                let mut best = k;
+                for j in k + 1 .. n {
+                    if col_norm(&a, j) > col_norm(&a, best) {
+                        best = j;
+                    }
+                }
+                if best != k {
+                    for i in 0 .. m {
+                        a.swap(i * n + k, i * n + best);
+                    }
+                    perm.swap(k, best);
+                }
+            }
+            match method {
+                QrMethod::Householder => {
+                    let norm = (k .. m).fold(T::zero(), |sum, i| sum + a[i * n + k] * a[i * n + k]).sqrt();
+                    if norm == T::zero() {
+                        continue;
+                    }
+                    let alpha = if a[k * n + k] > T::zero() { -norm } else { norm };
+                    let mut v: Vec<T> = (k .. m).map(|i| a[i * n + k]).collect();
+                    v[0] = v[0] - alpha;
+                    let tau = (T::one() + T::one()) / v.iter().fold(T::zero(), |sum, &x| sum + x * x);
+                    for j in k + 1 .. n {
+                        let w = v.iter().enumerate().fold(T::zero(), |sum, (i, &x)| sum + x * a[(k + i) * n + j]) * tau;
+                        for (i, &x) in v.iter().enumerate() {
+                            a[(k + i) * n + j] = a[(k + i) * n + j] - w * x;
+                        }
+                    }
+                    a[k * n + k] = alpha;
+                    for i in k + 1 .. m {
+                        a[i * n + k] = T::zero();
+                    }
+                    reflectors.push((k, v, tau));
+                },
+                QrMethod::Givens => {
+                    for i in k + 1 .. m {
+                        let (x, y) = (a[k * n + k], a[i * n + k]);
+                        if y == T::zero() {
+                            continue;
+                        }
+                        let r = x.hypot(y);
+                        let (c, s) = (x / r, y / r);
+                        for j in k .. n {
+                            let (u, w) = (a[k * n + j], a[i * n + j]);
+                            a[k * n + j] = c * u + s * w;
+                            a[i * n + j] = c * w - s * u;
+                        }
+                        a[i * n + k] = T::zero();
+                        rotations.push((k, i, c, s));
+                    }
+                },
+            }
+        }
+        QR {
+            row_num: m,
+            col_num: n,
+            r: a,
+            q: match method {
+                QrMethod::Householder => Orthogonal::Householder(reflectors),
+                QrMethod::Givens => Orthogonal::Givens(rotations),
+            },
+            perm,
+        }
+    }
+
+    pub fn perm(&self) -> &[usize]
+    {
+        &self.perm
+    }
+
+    // b <- Q^T b
+    pub fn apply_qt(&self, b: &mut [T])
+    {
+        assert_eq!(b.len(), self.row_num, "the length of the vector does not match the matrix!");
+        match self.q {
+            Orthogonal::Householder(ref reflectors) => {
+                for &(k, ref v, tau) in reflectors {
+                    let w = v.iter().zip(b[k ..].iter()).fold(T::zero(), |sum, (&x, &y)| sum + x * y) * tau;
+                    for (y, &x) in b[k ..].iter_mut().zip(v.iter()) {
+                        *y = *y - w * x;
+                    }
+                }
+            },
+            Orthogonal::Givens(ref rotations) => {
+                for &(i, j, c, s) in rotations {
+                    let (u, w) = (b[i], b[j]);
+                    b[i] = c * u + s * w;
+                    b[j] = c * w - s * u;
+                }
+            },
+        }
+    }
+
+    // b <- Q b
+    pub fn apply_q(&self, b: &mut [T])
+    {
+        assert_eq!(b.len(), self.row_num, "the length of the vector does not match the matrix!");
+        match self.q {
+            Orthogonal::Householder(ref reflectors) => {
+                for &(k, ref v, tau) in reflectors.iter().rev() {
+                    let w = v.iter().zip(b[k ..].iter()).fold(T::zero(), |sum, (&x, &y)| sum + x * y) * tau;
+                    for (y, &x) in b[k ..].iter_mut().zip(v.iter()) {
+                        *y = *y - w * x;
+                    }
+                }
+            },
+            Orthogonal::Givens(ref rotations) => {
+                for &(i, j, c, s) in rotations.iter().rev() {
+                    let (u, w) = (b[i], b[j]);
+                    b[i] = c * u - s * w;
+                    b[j] = s * u + c * w;
+                }
+            },
+        }
+    }
+
+    fn q_columns(&self, cols: usize) -> DenseMatrix<T>
+    {
+        let m = self.row_num;
+        let mut mat = DenseMatrix::new(m, cols);
+        for j in 0 .. cols {
+            let mut e = vec![T::zero(); m];
+            e[j] = T::one();
+            self.apply_q(&mut e);
+            for (i, v) in e.into_iter().enumerate() {
+                mat.set_v(i, j, v);
+            }
+        }
+        mat
+    }
+
+    pub fn q(&self) -> DenseMatrix<T>
+    {
+        self.q_columns(self.row_num)
+    }
+
+    pub fn r(&self) -> DenseMatrix<T>
+    {
+        DenseMatrix::from_vec(self.row_num, self.col_num, Layout::RowMajor, self.r.clone())
+    }
+
+    // the economy factors of a tall A: Q is m x min(m, n) and R is min(m, n) x n
+    pub fn thin_q(&self) -> DenseMatrix<T>
+    {
+        self.q_columns(self.row_num.min(self.col_num))
+    }
+
+    pub fn thin_r(&self) -> DenseMatrix<T>
+    {
+        let p = self.row_num.min(self.col_num);
+        DenseMatrix::from_vec(p, self.col_num, Layout::RowMajor, self.r[.. p * self.col_num].to_vec())
+    }
+
+    fn rank_tol(&self) -> T
+    {
+        T::from(self.row_num.max(self.col_num)).unwrap() * T::epsilon() * self.r[0].abs()
+    }
+
+    // the number of |r_kk| above max(m, n) eps |r_00|; reliable with pivoting
+    pub fn rank(&self) -> usize
+    {
+        let n = self.col_num;
+        let p = self.row_num.min(n);
+        if p == 0 {
+            return 0;
+        }
+        let tol = self.rank_tol();
+        (0 .. p).filter(|&k| self.r[k * n + k].abs() > tol).count()
+    }
+
+    // the leading r_kk above the tolerance of rank, those back-substitution
+    // can divide by; the same as rank with pivoting
+    fn leading_rank(&self) -> usize
+    {
+        let n = self.col_num;
+        let p = self.row_num.min(n);
+        if p == 0 {
+            return 0;
+        }
+        let tol = self.rank_tol();
+        (0 .. p).take_while(|&k| self.r[k * n + k].abs() > tol).count()
+    }
+
+    // the basic least-squares solution of A x = b: the trailing columns of A P
+    // from the first negligible r_kk on get zero weight. With pivoting that is
+    // the numerical rank; without it a rank-deficient A may lose independent
+    // columns behind a dependent one, so factorise those with QR::pivoted.
+    pub fn lstsq(&self, mat_b: &DenseMatrix<T>) -> LeastSquares<T>
+    {
+        let (m, n) = (self.row_num, self.col_num);
+        assert_eq!(mat_b.row(), m, "the rows of the right-hand side do not match the matrix!");
+        let rank = self.leading_rank();
+        let mut x = DenseMatrix::new(n, mat_b.col());
+        let mut residual_norm = Vec::with_capacity(mat_b.col());
+        for col in 0 .. mat_b.col() {
+            let mut c = mat_b.get_col_vec(col);
+            self.apply_qt(&mut c);
+            let mut y = vec![T::zero(); rank];
+            for k in (0 .. rank).rev() {
+                let mut s = c[k];
+                for (j, &y_j) in y.iter().enumerate().skip(k + 1) {
+                    s = s - self.r[k * n + j] * y_j;
+                }
+                y[k] = s / self.r[k * n + k];
+            }
+            for (k, v) in y.into_iter().enumerate() {
+                x.set_v(self.perm[k], col, v);
+            }
+            // exact for full rank, and up to the neglected block of R otherwise
+            residual_norm.push(c[rank ..].iter().fold(T::zero(), |sum, &v| sum + v * v).sqrt());
+        }
+        LeastSquares {
+            x,
+            residual_norm,
+            rank,
+        }
+    }
+}
+
+#[test]
+fn qr_test() {
+    // fit y = 1 + 2 t - t^2 through noisy samples
+    let m = 8;
+    let mut mat_a = DenseMatrix::<f64>::new(m, 3);
+    let mut b = DenseMatrix::<f64>::new(m, 1);
+    for i in 0 .. m {
+        let t = i as f64 / 2.0;
+        mat_a.set_v(i, 0, 1.0);
+        mat_a.set_v(i, 1, t);
+        mat_a.set_v(i, 2, t * t);
+        b.set_v(i, 0, 1.0 + 2.0 * t - t * t + if i % 2 == 0 { 0.01 } else { -0.01 });
+    }
+    let at = mat_a.t();
+    let normal = at.dot_mul(&mat_a).cholesky().unwrap().solve(&at.dot_mul(&b));
+
+    for &method in &[QrMethod::Householder, QrMethod::Givens] {
+        for &pivoting in &[false, true] {
+            let qr = QR::with_method(&mat_a, method, pivoting);
+            let q = qr.q();
+            assert!(q.t().dot_mul(&q).sub(&DenseMatrix::eye(m)).norm_inf() < 1e-12);
+            let mut ap = DenseMatrix::new(m, 3);
+            for (k, &p) in qr.perm().iter().enumerate() {
+                for i in 0 .. m {
+                    ap.set_v(i, k, mat_a.get_v(i, p));
+                }
+            }
+            assert!(q.dot_mul(&qr.r()).sub(&ap).norm_inf() < 1e-12);
+            assert!(qr.thin_q().dot_mul(&qr.thin_r()).sub(&ap).norm_inf() < 1e-12);
+            assert!(qr.thin_q().col() == 3 && qr.thin_r().row() == 3);
+
+            let res = qr.lstsq(&b);
+            assert_eq!(res.rank, 3);
+            assert!(res.x.sub(&normal).norm_inf() < 1e-10);
+            let r = b.sub(&mat_a.dot_mul(&res.x)).norm2();
+            assert!((res.residual_norm[0] - r).abs() < 1e-12);
+        }
+    }
+
+    // the third column is the sum of the first two
+    let deficient = DenseMatrix::<f64>::from(4, 3, &[1.0, 0.0, 1.0,
+                                                     0.0, 1.0, 1.0,
+                                                     1.0, 1.0, 2.0,
+                                                     2.0, 1.0, 3.0]);
+    let qr = QR::pivoted(&deficient);
+    assert_eq!(qr.rank(), 2);
+    let b = DenseMatrix::<f64>::from(4, 1, &[1.0, 2.0, 3.0, 4.0]);
+    let res = qr.lstsq(&b);
+    assert_eq!(res.rank, 2);
+    assert_eq!(res.x.get_v(qr.perm()[2], 0), 0.0);
+    // the residual is orthogonal to the columns of A
+    let r = b.sub(&deficient.dot_mul(&res.x));
+    assert!(deficient.t().dot_mul(&r).norm_inf() < 1e-12);
+    assert!((res.residual_norm[0] - r.norm2()).abs() < 1e-12);
+
+    // without pivoting the repeated column leaves r_11 = 0 in front of the
+    // independent third column: only the first column is used, and the result
+    // stays finite
+    let repeated = DenseMatrix::<f64>::from(4, 3, &[1.0, 1.0, 0.0,
+                                                    0.0, 0.0, 1.0,
+                                                    0.0, 0.0, 1.0,
+                                                    0.0, 0.0, 0.0]);
+    let qr = QR::new(&repeated);
+    assert_eq!(qr.rank(), 2);
+    let res = qr.lstsq(&b);
+    assert_eq!(res.rank, 1);
+    assert!(res.x.as_slice().iter().all(|v| v.is_finite()));
+    assert_eq!((res.x.get_v(0, 0), res.x.get_v(1, 0), res.x.get_v(2, 0)), (1.0, 0.0, 0.0));
+    assert!((res.residual_norm[0] - 29.0f64.sqrt()).abs() < 1e-12);
+    let pivoted = QR::pivoted(&repeated).lstsq(&b);
+    assert_eq!(pivoted.rank, 2);
+    assert!((pivoted.residual_norm[0] - 16.5f64.sqrt()).abs() < 1e-12);
+}
@@ -0,0 +1,254 @@
+extern crate num;
+
+use super::super::matrix::dense::{DenseMatrix, Layout};
+use super::super::scalar::{self, Scalar};
+use super::super::super::parallel::Element;
+use super::super::super::error::{self, AlgebraError, AlgebraResult};
+use num::{Float, One, Zero};
+use num::complex::Complex;
+use std::fmt::Display;
+
+// the QR steps allowed per eigenvalue before giving up
+const MAX_SWEEPS: usize = 60;
+
+// The complex Schur form A = Q T Q^H of a real or complex A: Q is unitary and
+// T upper triangular with the eigenvalues of A on its diagonal. Both are kept
+// row-major.
+#[derive(Clone)]
+pub struct Schur<T>
+where T: Float + Display + Element
+{
+    size: usize,
+    q: Vec<Complex<T>>,
+    t: Vec<Complex<T>>,
+}
+
+impl<T> Schur<T>
+where T: Float + Display + Element
+{
+    // Householder reduction to Hessenberg form, then single-shift QR steps
+    // with Wilkinson shifts on the unreduced blocks
+    pub fn new<S>(mat: &DenseMatrix<S>) -> AlgebraResult<Schur<T>>
+    where S: Scalar<Real = T>
+    {
+        error::check_square(mat.shape())?;
+        let n = mat.row();
+        let mut t: Vec<Complex<T>> = mat.to_layout(Layout::RowMajor).into_vec().into_iter().map(S::to_complex).collect();
+        let mut q = vec![Complex::zero(); n * n];
+        for i in 0 .. n {
+            q[i * n + i] = Complex::one();
+        }
+        hessenberg(n, &mut t, &mut q);
+        qr_steps(n, &mut t, &mut q)?;
+        Ok(Schur {
+            size: n,
+            q,
+            t,
+        })
+    }
+
+    pub fn size(&self) -> usize
+    {
+        self.size
+    }
+
+    pub fn q(&self) -> DenseMatrix<Complex<T>>
+    {
+        DenseMatrix::from_vec(self.size, self.size, Layout::RowMajor, self.q.clone())
+    }
+
+    pub fn t(&self) -> DenseMatrix<Complex<T>>
+    {
+        DenseMatrix::from_vec(self.size, self.size, Layout::RowMajor, self.t.clone())
+    }
+
+    // the diagonal of T, in the order the QR steps left them
+    pub fn values(&self) -> Vec<Complex<T>>
+    {
+        (0 .. self.size).map(|i| self.t[i * self.size + i]).collect()
+    }
+
+    // Q F Q^H, e.g. f(A) for F = f(T)
+    pub fn unitary_similarity(&self, mat_f: &DenseMatrix<Complex<T>>) -> DenseMatrix<Complex<T>>
+    {
+        let q = self.q();
+        q.dot_mul(mat_f).dot_mul(&q.conj_t())
+    }
+}
+
+// overwrites a with the upper Hessenberg H^H a H and q with q H
+fn hessenberg<T>(n: usize, a: &mut [Complex<T>], q: &mut [Complex<T>])
+where T: Float + Display + Element
+{
+    for k in 0 .. n.saturating_sub(2) {
+        let mut v: Vec<Complex<T>> = (k + 1 .. n).map(|i| a[i * n + k]).collect();
+        let norm = scalar::norm(&v);
+        if norm == T::zero() {
+            continue;
+        }
+        // alpha = -e^(i arg x_0) ||x||, so that v_0 = x_0 - alpha does not cancel
+        let head = v[0].modulus();
+        let phase = if head == T::zero() { Complex::one() } else { v[0] / Complex::from_real(head) };
+        v[0] = v[0] + phase * Complex::from_real(norm);
+        let tau = Complex::from_real((T::one() + T::one()) / v.iter().fold(T::zero(), |sum, x| sum + x.modulus_sqr()));
+        // I - tau v v^H from the left, then from the right on a and q
+        for j in k .. n {
+            let s = v.iter().enumerate().fold(Complex::<T>::zero(), |sum, (i, x)| sum + x.conj() * a[(k + 1 + i) * n + j]) * tau;
+            for (i, &x) in v.iter().enumerate() {
+                a[(k + 1 + i) * n + j] = a[(k + 1 + i) * n + j] - s * x;
+            }
+        }
+        for mat in [&mut *a, &mut *q].iter_mut() {
+            for r in 0 .. n {
+                let s = v.iter().enumerate().fold(Complex::<T>::zero(), |sum, (j, &x)| sum + mat[r * n + k + 1 + j] * x) * tau;
+                for (j, x) in v.iter().enumerate() {
+                    mat[r * n + k + 1 + j] = mat[r * n + k + 1 + j] - s * x.conj();
+                }
+            }
+        }
+        for i in k + 2 .. n {
+            a[i * n + k] = Complex::zero();
+        }
+    }
+}
+
+// (c, s) with c real such that [c s; -conj(s) c] [x; y] = [r; 0]
+fn givens<T>(x: Complex<T>, y: Complex<T>) -> (T, Complex<T>)
+where T: Float + Display + Element
+{
+    let (x_abs, y_abs) = (x.modulus(), y.modulus());
+    if y_abs == T::zero() {
+        return (T::one(), Complex::zero());
+    }
+    if x_abs == T::zero() {
+        return (T::zero(), Complex::one());
+    }
+    let rho = x_abs.hypot(y_abs);
+    (x_abs / rho, x / Complex::from_real(x_abs) * y.conj() / Complex::from_real(rho))
+}
+
+// the eigenvalue of [a b; c d] nearer to d
+fn wilkinson_shift<T>(a: Complex<T>, b: Complex<T>, c: Complex<T>, d: Complex<T>) -> Complex<T>
+where T: Float + Display + Element
+{
+    let p = (a - d) / Complex::from_real(T::one() + T::one());
+    let disc = (p * p + b * c).sqrt();
+    let den = if (p + disc).modulus() >= (p - disc).modulus() { p + disc } else { p - disc };
+    if den == Complex::zero() {
+        d
+    }
+    else {
+        d - b * c / den
+    }
+}
+
+// turns the upper Hessenberg a into the triangular factor, rotating q along
+fn qr_steps<T>(n: usize, a: &mut [Complex<T>], q: &mut [Complex<T>]) -> AlgebraResult<()>
+where T: Float + Display + Element
+{
+    let at = |i: usize, j: usize| i * n + j;
+    let anorm = a.iter().fold(T::zero(), |sum, v| sum + v.modulus());
+    // hi is one past the last row of the active block
+    let mut hi = n;
+    let mut its = 0;
+    while hi > 1 {
+        let u = hi - 1;
+        let mut l = u;
+        while l > 0 {
+            let mut s = a[at(l - 1, l - 1)].modulus() + a[at(l, l)].modulus();
+            if s == T::zero() {
+                s = anorm;
+            }
+            if a[at(l, l - 1)].modulus() <= T::epsilon() * s {
+                a[at(l, l - 1)] = Complex::zero();
+                break;
+            }
+            l -= 1;
+        }
+        if l == u {
+            hi -= 1;
+            its = 0;
+            continue;
+        }
+        if its == MAX_SWEEPS {
+            return Err(AlgebraError::NotConverged { iterations: its, residual: a[at(u, u - 1)].modulus().to_f64().unwrap_or(f64::NAN) });
+        }
+        its += 1;
+        // an exceptional shift now and then breaks cycles
+        let mu = if its % 10 == 0 {
+            a[at(u, u)] + Complex::from_real(T::from(0.75).unwrap() * a[at(u, u - 1)].modulus())
+        }
+        else {
+            wilkinson_shift(a[at(u - 1, u - 1)], a[at(u - 1, u)], a[at(u, u - 1)], a[at(u, u)])
+        };
+        // one explicit QR step on the block l ..= u: A - mu I = Q R, then R Q + mu I
+        for i in l ..= u {
+            a[at(i, i)] = a[at(i, i)] - mu;
+        }
+        let mut rotations = Vec::with_capacity(u - l);
+        for k in l .. u {
+            let (c, s) = givens(a[at(k, k)], a[at(k + 1, k)]);
+            let c = Complex::from_real(c);
+            for j in k .. n {
+                let (x, y) = (a[at(k, j)], a[at(k + 1, j)]);
+                a[at(k, j)] = c * x + s * y;
+                a[at(k + 1, j)] = c * y - s.conj() * x;
+            }
+            a[at(k + 1, k)] = Complex::zero();
+            rotations.push((c, s));
+        }
+        for (k, &(c, s)) in (l .. u).zip(rotations.iter()) {
+            for r in 0 ..= k + 1 {
+                let (x, y) = (a[at(r, k)], a[at(r, k + 1)]);
+                a[at(r, k)] = x * c + y * s.conj();
+                a[at(r, k + 1)] = y * c - x * s;
+            }
+            for r in 0 .. n {
+                let (x, y) = (q[at(r, k)], q[at(r, k + 1)]);
+                q[at(r, k)] = x * c + y * s.conj();
+                q[at(r, k + 1)] = y * c - x * s;
+            }
+        }
+        for i in l ..= u {
+            a[at(i, i)] = a[at(i, i)] + mu;
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn schur_test() {
+    use super::eigen::Eigen;
+
+    let real = DenseMatrix::<f64>::from(5, 5, &[4.0, -2.0, 1.0, 0.5, 3.0,
+                                               1.0, 0.0, -3.0, 2.0, 1.0,
+                                               2.0, 1.0, 1.0, -1.0, 0.0,
+                                               0.0, 3.0, 2.0, 5.0, -2.0,
+                                               1.0, -1.0, 0.0, 2.0, 1.0]);
+    let c = |re, im| Complex::new(re, im);
+    let complex = DenseMatrix::from(3, 3, &[c(1.0, 2.0), c(0.0, -1.0), c(3.0, 0.0),
+                                            c(2.0, 0.0), c(4.0, 1.0), c(0.0, 1.0),
+                                            c(0.0, -3.0), c(1.0, 1.0), c(2.0, -2.0)]);
+    let complex_schur = Schur::new(&complex).unwrap();
+    let real_schur = Schur::new(&real).unwrap();
+    let as_complex = DenseMatrix::from_vec(5, 5, Layout::RowMajor, real.as_slice().iter().map(|&v| c(v, 0.0)).collect());
+    for (schur, mat) in &[(real_schur.clone(), as_complex), (complex_schur, complex.clone())] {
+        let n = schur.size();
+        let (q, t) = (schur.q(), schur.t());
+        assert!(q.conj_t().dot_mul(&q).sub(&DenseMatrix::eye(n)).norm2() < 1e-12);
+        assert!(schur.unitary_similarity(&t).sub(mat).norm2() < 1e-12 * mat.norm2());
+        for i in 0 .. n {
+            for j in 0 .. i {
+                assert_eq!(t.get_v(i, j), c(0.0, 0.0));
+            }
+        }
+    }
+    // the same eigenvalues as the real Francis iteration
+    let mut values = real_schur.values();
+    let mut expected = Eigen::new(&real, false).unwrap().values;
+    let key = |v: &Complex<f64>| (v.re * 1e6).round() as i64 * 1000 + (v.im * 1e3).round() as i64;
+    values.sort_by_key(key);
+    expected.sort_by_key(key);
+    assert!(values.iter().zip(expected.iter()).all(|(a, b)| (a - b).norm() < 1e-10));
+    assert_eq!(Schur::new(&DenseMatrix::<f64>::new(2, 3)).err(), Some(AlgebraError::NotSquare { shape: (2, 3) }));
+}
@@ -0,0 +1,515 @@
+extern crate num;
+
+use super::qr::QR;
+use super::super::matrix::dense::{DenseMatrix, Layout};
+use super::super::super::error::{self, AlgebraError, AlgebraResult};
+use super::super::super::parallel::Element;
+use num::Float;
+use std::cmp::Ordering;
+use std::fmt::Display;
+
+// the implicit QR sweeps allowed per singular value
+const MAX_SWEEPS: usize = 75;
+
+// The thin A = U diag(s) V^T of an m x n A with p = min(m, n): U is m x p,
+// V is n x p, both with orthonormal columns, and s is sorted descending.
+#[derive(Clone)]
+pub struct SVD<T>
+where T: Float + Display + Element
+{
+    u: DenseMatrix<T>,
+    s: Vec<T>,
+    v: DenseMatrix<T>,
+}
+
+impl<T> SVD<T>
+where T: Float + Display + Element
+{
+    // Householder bidiagonalisation (Golub-Kahan) and implicit shifted QR on
+    // the bidiagonal (Golub-Reinsch)
+    pub fn new(mat: &DenseMatrix<T>) -> AlgebraResult<SVD<T>>
+    {
+        error::check_finite(mat)?;
+        if mat.row() < mat.col() {
+            // A^T = U S V^T gives A = V S U^T
+            let svd = SVD::decompose(&mat.t())?;
+            return Ok(SVD {
+                u: svd.v,
+                s: svd.s,
+                v: svd.u,
+            });
+        }
+        SVD::decompose(mat)
+    }
+
+    // a finite A with at least as many rows as columns
+    fn decompose(mat: &DenseMatrix<T>) -> AlgebraResult<SVD<T>>
+    {
+        let (m, n) = (mat.row(), mat.col());
+        let mut a = mat.to_layout(Layout::RowMajor).into_vec();
+        let mut w = vec![T::zero(); n];
+        let mut v = vec![T::zero(); n * n];
+        golub_reinsch(m, n, &mut a, &mut w, &mut v)?;
+
+        let mut order: Vec<usize> = (0 .. n).collect();
+        order.sort_by(|&i, &j| w[j].partial_cmp(&w[i]).unwrap_or(Ordering::Equal));
+        let mut u_mat = DenseMatrix::new(m, n);
+        let mut v_mat = DenseMatrix::new(n, n);
+        for (j, &k) in order.iter().enumerate() {
+            for i in 0 .. m {
+                u_mat.set_v(i, j, a[i * n + k]);
+            }
+            for i in 0 .. n {
+                v_mat.set_v(i, j, v[i * n + k]);
+            }
+        }
+        Ok(SVD {
+            u: u_mat,
+            s: order.iter().map(|&k| w[k]).collect(),
+            v: v_mat,
+        })
+    }
+
+    pub fn u(&self) -> &DenseMatrix<T>
+    {
+        &self.u
+    }
+
+    pub fn singular_values(&self) -> &[T]
+    {
+        &self.s
+    }
+
+    pub fn v(&self) -> &DenseMatrix<T>
+    {
+        &self.v
+    }
+
+    // max(m, n) eps s_0, below which singular values count as zero
+    pub fn default_tol(&self) -> T
+    {
+        let size = self.u.row().max(self.v.row());
+        match self.s.first() {
+            Some(&s_0) => T::from(size).unwrap() * T::epsilon() * s_0,
+            None => T::zero(),
+        }
+    }
+
+    pub fn rank(&self, tol: T) -> usize
+    {
+        self.s.iter().filter(|&&s| s > tol).count()
+    }
+
+    // the 2-norm condition number s_max / s_min, infinite for a singular A
+    pub fn cond2(&self) -> T
+    {
+        match (self.s.first(), self.s.last()) {
+            (Some(&s_max), Some(&s_min)) => {
+                if s_min == T::zero() {
+                    T::infinity()
+                }
+                else {
+                    s_max / s_min
+                }
+            },
+            _ => T::zero(),
+        }
+    }
+
+    // V diag(1 / s) U^T over the singular values above tol
+    pub fn pinv(&self, tol: T) -> DenseMatrix<T>
+    {
+        let (m, n) = (self.u.row(), self.v.row());
+        let mut res = DenseMatrix::new(n, m);
+        for (k, &s) in self.s.iter().enumerate() {
+            if s <= tol {
+                continue;
+            }
+            for i in 0 .. n {
+                let v_ik = self.v.get_v(i, k) / s;
+                if v_ik == T::zero() {
+                    continue;
+                }
+                for j in 0 .. m {
+                    res.set_v(i, j, res.get_v(i, j) + v_ik * self.u.get_v(j, k));
+                }
+            }
+        }
+        res
+    }
+
+    // an orthonormal basis of the range of A, as columns
+    pub fn range(&self, tol: T) -> DenseMatrix<T>
+    {
+        let r = self.rank(tol);
+        first_cols(&self.u, r)
+    }
+
+    // an orthonormal basis of the null space of A, as columns: the complement
+    // of the row space, which also covers the n - m directions a thin V of a
+    // wide A leaves out
+    pub fn null_space(&self, tol: T) -> DenseMatrix<T>
+    {
+        let n = self.v.row();
+        let r = self.rank(tol);
+        if r == 0 {
+            return DenseMatrix::eye(n);
+        }
+        if r == n {
+            return DenseMatrix::new(n, 0);
+        }
+        QR::new(&first_cols(&self.v, r)).q().slice(0, n - 1, r, n - 1)
+    }
+
+    // the best rank-k approximation in the 2- and Frobenius norms
+    pub fn low_rank(&self, k: usize) -> DenseMatrix<T>
+    {
+        let (m, n) = (self.u.row(), self.v.row());
+        let mut res = DenseMatrix::new(m, n);
+        for (l, &s) in self.s.iter().enumerate().take(k) {
+            for i in 0 .. m {
+                let u_il = self.u.get_v(i, l) * s;
+                if u_il == T::zero() {
+                    continue;
+                }
+                for j in 0 .. n {
+                    res.set_v(i, j, res.get_v(i, j) + u_il * self.v.get_v(j, l));
+                }
+            }
+        }
+        res
+    }
+
+    // the factors truncated to the k largest singular values
+    pub fn truncate(&self, k: usize) -> SVD<T>
+    {
+        let k = k.min(self.s.len());
+        SVD {
+            u: first_cols(&self.u, k),
+            s: self.s[.. k].to_vec(),
+            v: first_cols(&self.v, k),
+        }
+    }
+}
+
+// the first k columns, allowing k = 0
+fn first_cols<T>(mat: &DenseMatrix<T>, k: usize) -> DenseMatrix<T>
+where T: Float + Display + Element
+{
+    if k == 0 || mat.row() == 0 {
+        DenseMatrix::new(mat.row(), k)
+    }
+    else {
+        mat.slice(0, mat.row() - 1, 0, k - 1)
+    }
+}
+
+fn sign<T: Float>(a: T, b: T) -> T
+{
+    if b >= T::zero() {
+        a.abs()
+    }
+    else {
+        -a.abs()
+    }
+}
+
+// m >= n: a (m x n, row-major) is replaced by U, w gets the unsorted singular
+// values and v (n x n) the right singular vectors
+fn golub_reinsch<T>(m: usize, n: usize, a: &mut [T], w: &mut [T], v: &mut [T]) -> AlgebraResult<()>
+where T: Float
+{
+    let at = |i: usize, j: usize| i * n + j;
+    let mut rv1 = vec![T::zero(); n];
+    let (mut g, mut scale, mut anorm) = (T::zero(), T::zero(), T::zero());
+    let mut l = 0;
+    // bidiagonalisation: w gets the diagonal and rv1 the superdiagonal
+    for i in 0 .. n {
+        l = i + 1;
+        rv1[i] = scale * g;
+        g = T::zero();
+        scale = T::zero();
+        let mut s = T::zero();
+        for k in i .. m {
+            scale = scale + a[at(k, i)].abs();
+        }
+        if scale != T::zero() {
+            for k in i .. m {
+                a[at(k, i)] = a[at(k, i)] / scale;
+                s = s + a[at(k, i)] * a[at(k, i)];
+            }
+            let f = a[at(i, i)];
+            g = -sign(s.sqrt(), f);
+            let h = f * g - s;
+            a[at(i, i)] = f - g;
+            for j in l .. n {
+                let s = (i .. m).fold(T::zero(), |sum, k| sum + a[at(k, i)] * a[at(k, j)]);
+                let f = s / h;
+                for k in i .. m {
+                    a[at(k, j)] = a[at(k, j)] + f * a[at(k, i)];
+                }
+            }
+            for k in i .. m {
+                a[at(k, i)] = a[at(k, i)] * scale;
+            }
+        }
+        w[i] = scale * g;
+        g = T::zero();
+        scale = T::zero();
+        s = T::zero();
+        if i + 1 != n {
+            for k in l .. n {
+                scale = scale + a[at(i, k)].abs();
+            }
+            if scale != T::zero() {
+                for k in l .. n {
+                    a[at(i, k)] = a[at(i, k)] / scale;
+                    s = s + a[at(i, k)] * a[at(i, k)];
+                }
+                let f = a[at(i, l)];
+                g = -sign(s.sqrt(), f);
+                let h = f * g - s;
+                a[at(i, l)] = f - g;
+                for k in l .. n {
+                    rv1[k] = a[at(i, k)] / h;
+                }
+                for j in l .. m {
+                    let s = (l .. n).fold(T::zero(), |sum, k| sum + a[at(j, k)] * a[at(i, k)]);
+                    for k in l .. n {
+                        a[at(j, k)] = a[at(j, k)] + s * rv1[k];
+                    }
+                }
+                for k in l .. n {
+                    a[at(i, k)] = a[at(i, k)] * scale;
+                }
+            }
+        }
+        anorm = anorm.max(w[i].abs() + rv1[i].abs());
+    }
+    // the right-hand transformations
+    for i in (0 .. n).rev() {
+        if i + 1 < n {
+            if g != T::zero() {
+                for j in l .. n {
+                    // two divisions to avoid an underflow
+                    v[at(j, i)] = (a[at(i, j)] / a[at(i, l)]) / g;
+                }
+                for j in l .. n {
+                    let s = (l .. n).fold(T::zero(), |sum, k| sum + a[at(i, k)] * v[at(k, j)]);
+                    for k in l .. n {
+                        v[at(k, j)] = v[at(k, j)] + s * v[at(k, i)];
+                    }
+                }
+            }
+            for j in l .. n {
+                v[at(i, j)] = T::zero();
+                v[at(j, i)] = T::zero();
+            }
+        }
+        v[at(i, i)] = T::one();
+        g = rv1[i];
+        l = i;
+    }
+    // the left-hand transformations
+    for i in (0 .. n).rev() {
+        let l = i + 1;
+        let mut g = w[i];
+        for j in l .. n {
+            a[at(i, j)] = T::zero();
+        }
+        if g != T::zero() {
+            g = T::one() / g;
+            for j in l .. n {
+                let s = (l .. m).fold(T::zero(), |sum, k| sum + a[at(k, i)] * a[at(k, j)]);
+                let f = (s / a[at(i, i)]) * g;
+                for k in i .. m {
+                    a[at(k, j)] = a[at(k, j)] + f * a[at(k, i)];
+                }
+            }
+            for j in i .. m {
+                a[at(j, i)] = a[at(j, i)] * g;
+            }
+        }
+        else {
+            for j in i .. m {
+                a[at(j, i)] = T::zero();
+            }
+        }
+        a[at(i, i)] = a[at(i, i)] + T::one();
+    }
+    // diagonalise the bidiagonal, one singular value k at a time
+    for k in (0 .. n).rev() {
+        let mut its = 0;
+        loop {
+            // split off a block l ..= k; rv1[0] is always zero
+            let mut l = k;
+            let mut cancel = true;
+            loop {
+                if rv1[l].abs() + anorm == anorm {
+                    cancel = false;
+                    break;
+                }
+                if w[l - 1].abs() + anorm == anorm {
+                    break;
+                }
+                l -= 1;
+            }
+            if cancel {
+                // w[l - 1] is negligible: chase rv1[l] out with rotations
+                let nm = l - 1;
+                let (mut c, mut s) = (T::zero(), T::one());
+                for i in l ..= k {
+                    let f = s * rv1[i];
+                    rv1[i] = c * rv1[i];
+                    if f.abs() + anorm == anorm {
+                        break;
+                    }
+                    let g = w[i];
+                    let h = f.hypot(g);
+                    w[i] = h;
+                    c = g / h;
+                    s = -f / h;
+                    for j in 0 .. m {
+                        let (y, z) = (a[at(j, nm)], a[at(j, i)]);
+                        a[at(j, nm)] = y * c + z * s;
+                        a[at(j, i)] = z * c - y * s;
+                    }
+                }
+            }
+            let z = w[k];
+            if l == k {
+                if z < T::zero() {
+                    w[k] = -z;
+                    for j in 0 .. n {
+                        v[at(j, k)] = -v[at(j, k)];
+                    }
+                }
+                break;
+            }
+            if its == MAX_SWEEPS {
+                return Err(AlgebraError::NotConverged { iterations: its, residual: rv1[k].abs().to_f64().unwrap_or(f64::NAN) });
+            }
+            its += 1;
+            // the shift from the trailing 2x2 minor
+            let two = T::one() + T::one();
+            let mut x = w[l];
+            let nm = k - 1;
+            let mut y = w[nm];
+            let mut g = rv1[nm];
+            let mut h = rv1[k];
+            let mut f = ((y - z) * (y + z) + (g - h) * (g + h)) / (two * h * y);
+            g = f.hypot(T::one());
+            f = ((x - z) * (x + z) + h * ((y / (f + sign(g, f))) - h)) / x;
+            let (mut c, mut s) = (T::one(), T::one());
+            for j in l ..= nm {
+                let i = j + 1;
+                g = rv1[i];
+                y = w[i];
+                h = s * g;
+                g = c * g;
+                let mut z = f.hypot(h);
+                rv1[j] = z;
+                c = f / z;
+                s = h / z;
+                f = x * c + g * s;
+                g = g * c - x * s;
+                h = y * s;
+                y = y * c;
+                for jj in 0 .. n {
+                    let (x, z) = (v[at(jj, j)], v[at(jj, i)]);
+                    v[at(jj, j)] = x * c + z * s;
+                    v[at(jj, i)] = z * c - x * s;
+                }
+                z = f.hypot(h);
+                w[j] = z;
+                if z != T::zero() {
+                    c = f / z;
+                    s = h / z;
+                }
+                f = c * g + s * y;
+                x = c * y - s * g;
+                for jj in 0 .. m {
+                    let (y, z) = (a[at(jj, j)], a[at(jj, i)]);
+                    a[at(jj, j)] = y * c + z * s;
+                    a[at(jj, i)] = z * c - y * s;
+                }
+            }
+            rv1[l] = T::zero();
+            rv1[k] = f;
+            w[k] = x;
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn svd_test() {
+    let mat = DenseMatrix::<f64>::from(4, 3, &[2.0, 0.0, 1.0,
+                                              -1.0, 3.0, 0.5,
+                                              0.0, 1.0, 4.0,
+                                              1.0, -2.0, 1.0]);
+    for a in &[mat.clone(), mat.t()] {
+        let svd = SVD::new(a).unwrap();
+        let s = svd.singular_values();
+        assert!(s.windows(2).all(|w| w[0] >= w[1]));
+        let p = s.len();
+        let mut sigma = DenseMatrix::new(p, p);
+        for (i, &s_i) in s.iter().enumerate() {
+            sigma.set_v(i, i, s_i);
+        }
+        assert!(svd.u().dot_mul(&sigma).dot_mul(&svd.v().t()).sub(a).norm_inf() < 1e-12);
+        assert!(svd.u().t().dot_mul(svd.u()).sub(&DenseMatrix::eye(p)).norm_inf() < 1e-12);
+        assert!(svd.v().t().dot_mul(svd.v()).sub(&DenseMatrix::eye(p)).norm_inf() < 1e-12);
+        // the squared singular values are the eigenvalues of A^T A
+        let ata = a.t().dot_mul(a);
+        let eig = ata.symmetric_eigen(false).unwrap().values;
+        let top = eig[eig.len() - 1].sqrt();
+        assert!((s[0] - top).abs() < 1e-12);
+        assert!((svd.cond2() - s[0] / s[p - 1]).abs() < 1e-12);
+        let pinv = svd.pinv(svd.default_tol());
+        assert!(a.dot_mul(&pinv).dot_mul(a).sub(a).norm_inf() < 1e-12);
+        assert!(pinv.dot_mul(a).dot_mul(&pinv).sub(&pinv).norm_inf() < 1e-12);
+    }
+
+    // rank 2: the third column is the sum of the first two
+    let deficient = DenseMatrix::<f64>::from(4, 3, &[1.0, 0.0, 1.0,
+                                                     0.0, 1.0, 1.0,
+                                                     1.0, 1.0, 2.0,
+                                                     2.0, 1.0, 3.0]);
+    let svd = SVD::new(&deficient).unwrap();
+    let tol = svd.default_tol();
+    assert_eq!(svd.rank(tol), 2);
+    assert!(svd.cond2() > 1e12);
+    let null = svd.null_space(tol);
+    assert_eq!(null.col(), 1);
+    assert!(deficient.dot_mul(&null).norm_inf() < 1e-12);
+    let range = svd.range(tol);
+    assert_eq!(range.col(), 2);
+    // the columns of A lie in the range
+    let proj = range.dot_mul(&range.t()).dot_mul(&deficient);
+    assert!(proj.sub(&deficient).norm_inf() < 1e-12);
+    assert!(svd.low_rank(2).sub(&deficient).norm_inf() < 1e-12);
+    assert!(deficient.pinv().unwrap().sub(&svd.pinv(tol)).norm_inf() < 1e-12);
+    assert_eq!(deficient.rank().unwrap(), 2);
+
+    // NaN is rejected before the bidiagonal QR, and the wide case reports the
+    // index in A rather than in A^T
+    let mut nan = deficient.t();
+    nan.set_v(0, 3, f64::NAN);
+    assert_eq!(SVD::new(&nan).err(), Some(AlgebraError::NotFinite { index: (0, 3) }));
+
+    // a wide matrix has at least n - m null directions
+    let wide = deficient.t();
+    let svd = SVD::new(&wide).unwrap();
+    let null = svd.null_space(svd.default_tol());
+    assert_eq!(null.col(), 2);
+    assert!(wide.dot_mul(&null).norm_inf() < 1e-12);
+
+    let svd = SVD::new(&mat).unwrap();
+    let s = svd.singular_values().to_vec();
+    let err = svd.low_rank(1).sub(&mat).norm2();
+    assert!((err - (s[1] * s[1] + s[2] * s[2]).sqrt()).abs() < 1e-12);
+    let svd1 = svd.truncate(1);
+    assert_eq!(svd1.singular_values().len(), 1);
+    assert!(svd1.low_rank(1).sub(&svd.low_rank(1)).norm_inf() < 1e-14);
+}
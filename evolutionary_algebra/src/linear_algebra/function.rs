@@ -0,0 +1,415 @@
+extern crate num;
+
+use super::decomposition::Schur;
+use super::matrix::dense::{DenseMatrix, Layout};
+use super::operator::LinearOperator;
+use super::scalar::{self, Scalar};
+use super::super::error::{self, AlgebraError, AlgebraResult};
+use super::super::parallel::Element;
+use num::{Float, NumCast, One, ToPrimitive, Zero};
+use num::complex::Complex;
+use std::fmt::Display;
+
+// Functions of square matrices. expm works on A itself; logm, sqrtm and powf
+// on the triangular factor of the complex Schur form. For a real A these are
+// the real principal values, so an eigenvalue on the negative real axis is an
+// error there while a complex A just gets the principal branch.
+
+// (theta_m, b_0 ..= b_m) of the [m/m] Pade approximants of exp, theta_m being
+// the largest ||A||_1 each one is accurate for in double precision (Higham 2005)
+const PADE: [(f64, &[f64]); 5] = [
+    (1.495585217958292e-2, &[120.0, 60.0, 12.0, 1.0]),
+    (2.53939833006323e-1, &[30240.0, 15120.0, 3360.0, 420.0, 30.0, 1.0]),
+    (9.504178996162932e-1, &[17297280.0, 8648640.0, 1995840.0, 277200.0, 25200.0, 1512.0, 56.0, 1.0]),
+    (2.097847961257068, &[17643225600.0, 8821612800.0, 2075673600.0, 302702400.0, 30270240.0, 2162160.0, 110880.0,
+                          3960.0, 90.0, 1.0]),
+    (5.371920351148152, &[64764752532480000.0, 32382376266240000.0, 7771770303897600.0, 1187353796428800.0,
+                          129060195264000.0, 10559470521600.0, 670442572800.0, 33522128640.0, 1323241920.0,
+                          40840800.0, 960960.0, 16380.0, 182.0, 1.0]),
+];
+
+// the 7-point Gauss-Legendre rule on [-1, 1] as (node, weight)
+const GAUSS_LEGENDRE: [(f64, f64); 7] = [
+    (-0.9491079123427585, 0.1294849661688697),
+    (-0.7415311855993945, 0.2797053914892767),
+    (-0.4058451513773972, 0.3818300505051189),
+    (0.0, 0.4179591836734694),
+    (0.4058451513773972, 0.3818300505051189),
+    (0.7415311855993945, 0.2797053914892767),
+    (0.9491079123427585, 0.1294849661688697),
+];
+
+// ||T - I||_1 below which log(I + X) is left to the quadrature
+const LOG_THETA: f64 = 0.25;
+// square roots taken in logm before giving up
+const MAX_ROOTS: usize = 64;
+// the step halvings allowed in one step of expm_multiply
+const MAX_HALVINGS: usize = 60;
+
+fn real<T>(v: f64) -> T
+where T: Scalar
+{
+    T::from_real(<T::Real as NumCast>::from(v).unwrap())
+}
+
+// exp(A) by scaling and squaring: the Pade approximant of the lowest degree
+// accurate for A / 2^s, then s squarings
+pub fn expm<T>(mat: &DenseMatrix<T>) -> AlgebraResult<DenseMatrix<T>>
+where T: Scalar
+{
+    error::check_square(mat.shape())?;
+    let n = mat.row();
+    let norm = mat.norm_1().to_f64().unwrap_or(f64::NAN);
+    let (theta, coeffs, s) = match PADE.iter().find(|&&(theta, _)| norm <= theta) {
+        Some(&(theta, coeffs)) => (theta, coeffs, 0),
+        None => {
+            let (theta, coeffs) = PADE[PADE.len() - 1];
+            (theta, coeffs, (norm / theta).log2().ceil().to_i32().unwrap_or(0).max(0))
+        }
+    };
+    debug_assert!(norm.is_nan() || norm / 2f64.powi(s) <= theta);
+    let a = mat.scalar_mul(real(0.5f64.powi(s)));
+    let a2 = a.dot_mul(&a);
+    // V = b_0 I + b_2 A^2 + ..., U = A (b_1 I + b_3 A^2 + ...)
+    let (mut u, mut v) = (DenseMatrix::zeros(n), DenseMatrix::zeros(n));
+    let mut power = DenseMatrix::eye(n);
+    for (k, pair) in coeffs.chunks(2).enumerate() {
+        if k > 0 {
+            power = power.dot_mul(&a2);
+        }
+        v = v.add(&power.scalar_mul(real(pair[0])));
+        u = u.add(&power.scalar_mul(real(pair[1])));
+    }
+    let u = a.dot_mul(&u);
+    // (V - U) R = V + U
+    let mut res = v.sub(&u).lu()?.solve(&v.add(&u));
+    for _ in 0 .. s {
+        res = res.dot_mul(&res);
+    }
+    Ok(res)
+}
+
+// the principal square root, Bjorck and Hammarling's recurrence on the Schur
+// factor; Err(Singular) when a zero eigenvalue makes it not exist
+pub fn sqrtm<T>(mat: &DenseMatrix<T>) -> AlgebraResult<DenseMatrix<T>>
+where T: Scalar
+{
+    let schur = Schur::new(mat)?;
+    check_branch::<T>(&schur, true)?;
+    Ok(from_schur(&schur, &sqrt_triangular(&schur.t())?))
+}
+
+// the principal logarithm by inverse scaling and squaring
+pub fn logm<T>(mat: &DenseMatrix<T>) -> AlgebraResult<DenseMatrix<T>>
+where T: Scalar
+{
+    let schur = Schur::new(mat)?;
+    check_branch::<T>(&schur, false)?;
+    Ok(from_schur(&schur, &log_triangular(schur.t())?))
+}
+
+// A^k by repeated squaring, A^-1 first for negative k
+pub fn powi<T>(mat: &DenseMatrix<T>, k: i32) -> AlgebraResult<DenseMatrix<T>>
+where T: Scalar
+{
+    error::check_square(mat.shape())?;
+    let mut base = if k < 0 { mat.lu()?.inverse() } else { mat.clone() };
+    let mut e = k.unsigned_abs();
+    let mut res = DenseMatrix::eye(mat.row());
+    while e > 0 {
+        if e & 1 == 1 {
+            res = res.dot_mul(&base);
+        }
+        e >>= 1;
+        if e > 0 {
+            base = base.dot_mul(&base);
+        }
+    }
+    Ok(res)
+}
+
+// the principal power exp(p log(A)); integer p goes to powi
+pub fn powf<T>(mat: &DenseMatrix<T>, p: T::Real) -> AlgebraResult<DenseMatrix<T>>
+where T: Scalar
+{
+    if p == p.trunc() {
+        if let Some(k) = p.to_i32() {
+            return powi(mat, k);
+        }
+    }
+    let schur = Schur::new(mat)?;
+    check_branch::<T>(&schur, false)?;
+    let log = log_triangular(schur.t())?;
+    Ok(from_schur(&schur, &expm(&log.scalar_mul(Complex::from_real(p)))?))
+}
+
+// Err(Singular) for a zero eigenvalue unless allowed, and for a real matrix
+// Err(NegativeEigenvalue) for one on the negative real axis up to rounding
+fn check_branch<T>(schur: &Schur<T::Real>, allow_zero: bool) -> AlgebraResult<()>
+where T: Scalar
+{
+    let values = schur.values();
+    let scale = values.iter().fold(T::Real::zero(), |m, v| m.max(v.modulus()));
+    let tol = <T::Real as NumCast>::from(10 * values.len()).unwrap() * T::Real::epsilon() * scale;
+    for v in values {
+        if v == Complex::zero() {
+            if !allow_zero {
+                return Err(AlgebraError::Singular);
+            }
+        }
+        else if !T::COMPLEX && v.re < T::Real::zero() && v.im.abs() <= tol {
+            return Err(AlgebraError::NegativeEigenvalue);
+        }
+    }
+    Ok(())
+}
+
+// Q F Q^H back in the element type of A
+fn from_schur<T>(schur: &Schur<T::Real>, mat_f: &DenseMatrix<Complex<T::Real>>) -> DenseMatrix<T>
+where T: Scalar
+{
+    let n = schur.size();
+    let elements = schur.unitary_similarity(mat_f).to_layout(Layout::RowMajor).into_vec();
+    DenseMatrix::from_vec(n, n, Layout::RowMajor, elements.into_iter().map(T::from_complex).collect())
+}
+
+// the principal square root of an upper triangular matrix, column by column
+fn sqrt_triangular<T>(mat_t: &DenseMatrix<Complex<T>>) -> AlgebraResult<DenseMatrix<Complex<T>>>
+where T: Float + Display + Element
+{
+    let n = mat_t.row();
+    let mut root = DenseMatrix::new(n, n);
+    for j in 0 .. n {
+        root.set_v(j, j, mat_t.get_v(j, j).sqrt());
+        for i in (0 .. j).rev() {
+            let mut s = mat_t.get_v(i, j);
+            for k in i + 1 .. j {
+                s = s - root.get_v(i, k) * root.get_v(k, j);
+            }
+            let d = root.get_v(i, i) + root.get_v(j, j);
+            if d == Complex::zero() {
+                if s != Complex::zero() {
+                    return Err(AlgebraError::Singular);
+                }
+            }
+            else {
+                root.set_v(i, j, s / d);
+            }
+        }
+    }
+    Ok(root)
+}
+
+// log(T) = 2^s log(T^(1/2^s)) with s square roots bringing T near I, and
+// log(I + X) = int_0^1 X (I + t X)^-1 dt by Gauss-Legendre quadrature
+fn log_triangular<T>(mat_t: DenseMatrix<Complex<T>>) -> AlgebraResult<DenseMatrix<Complex<T>>>
+where T: Float + Display + Element
+{
+    let n = mat_t.row();
+    let eye = DenseMatrix::eye(n);
+    let theta = T::from(LOG_THETA).unwrap();
+    let mut mat_t = mat_t;
+    let mut s = 0;
+    loop {
+        let dist = mat_t.sub(&eye).norm_1();
+        if dist <= theta {
+            break;
+        }
+        if s == MAX_ROOTS || dist.is_nan() {
+            return Err(AlgebraError::NotConverged { iterations: s, residual: dist.to_f64().unwrap_or(f64::NAN) });
+        }
+        mat_t = sqrt_triangular(&mat_t)?;
+        s += 1;
+    }
+    let x = mat_t.sub(&eye);
+    let mut log = DenseMatrix::new(n, n);
+    let half = T::from(0.5).unwrap();
+    for &(node, weight) in GAUSS_LEGENDRE.iter() {
+        let node = Complex::from_real(half * (T::one() + T::from(node).unwrap()));
+        let weight = Complex::from_real(half * T::from(weight).unwrap());
+        // X and I + t X commute, so (I + t X)^-1 X will do
+        let term = eye.add(&x.scalar_mul(node)).lu()?.solve(&x);
+        log = log.add(&term.scalar_mul(weight));
+    }
+    Ok(log.scalar_mul(Complex::from_real(T::from(2f64.powi(s as i32)).unwrap())))
+}
+
+// exp(t A) v for a square operator known only through products (Saad 1992,
+// Sidje's Expokit): Arnoldi on a Krylov space of dimension m, the dense expm
+// of the small Hessenberg matrix, and steps of t short enough for the error
+// estimate of each to stay below tol ||w|| for the current iterate w
+pub fn expm_multiply<A, T>(op: &A, t: T::Real, v: &[T], m: usize, tol: T::Real) -> AlgebraResult<Vec<T>>
+where A: LinearOperator<T> + ?Sized, T: Scalar
+{
+    error::check_square(op.shape())?;
+    let n = op.shape().0;
+    assert_eq!(v.len(), n, "the length of the vector does not match the operator!");
+    assert!(m > 0, "the dimension of the Krylov space must be positive!");
+    let m = m.min(n);
+    let zero = T::Real::zero();
+    let (sign, end) = (T::from_real(t.signum()), t.abs());
+    let mut w = v.to_vec();
+    let (mut done, mut tau) = (zero, end);
+    while done < end {
+        let beta = scalar::norm(&w);
+        if beta == zero {
+            break;
+        }
+        let mut basis = vec![w.iter().map(|&x| x / T::from_real(beta)).collect::<Vec<T>>()];
+        let mut mat_h = DenseMatrix::<T>::new(m, m);
+        // h_{k+1,k} of the last column; zero after a happy breakdown
+        let mut h_next = zero;
+        let mut k = m;
+        for j in 0 .. m {
+            let mut p = op.apply(&basis[j]);
+            let p_norm = scalar::norm(&p);
+            for (i, b) in basis.iter().enumerate() {
+                let h = scalar::dot(b, &p);
+                mat_h.set_v(i, j, h);
+                for (pl, &bl) in p.iter_mut().zip(b.iter()) {
+                    *pl = *pl - h * bl;
+                }
+            }
+            h_next = scalar::norm(&p);
+            // the space is invariant: the approximation is exact
+            if h_next <= <T::Real as NumCast>::from(10 * (j + 2)).unwrap() * T::Real::epsilon() * p_norm {
+                k = j + 1;
+                h_next = zero;
+                break;
+            }
+            if j + 1 < m {
+                mat_h.set_v(j + 1, j, T::from_real(h_next));
+                basis.push(p.iter().map(|&x| x / T::from_real(h_next)).collect());
+            }
+        }
+        let mat_h = mat_h.slice(0, k - 1, 0, k - 1);
+        // the longest step up to the remaining time with a small enough error
+        let mut halvings = 0;
+        let f = loop {
+            tau = tau.min(end - done);
+            let f = expm(&mat_h.scalar_mul(sign * T::from_real(tau)))?;
+            let err = beta * tau * h_next * f.get_v(k - 1, 0).modulus();
+            if err <= tol * beta {
+                break f;
+            }
+            if halvings == MAX_HALVINGS || err.is_nan() {
+                return Err(AlgebraError::NotConverged { iterations: halvings, residual: (err / beta).to_f64().unwrap_or(f64::NAN) });
+            }
+            tau = tau / (T::Real::one() + T::Real::one());
+            halvings += 1;
+        };
+        w = vec![T::zero(); n];
+        for (i, b) in basis.iter().take(k).enumerate() {
+            let c = T::from_real(beta) * f.get_v(i, 0);
+            for (wl, &bl) in w.iter_mut().zip(b.iter()) {
+                *wl = *wl + c * bl;
+            }
+        }
+        done = done + tau;
+        // try a longer step next time if this one went through at once
+        if halvings == 0 {
+            tau = tau + tau;
+        }
+    }
+    Ok(w)
+}
+
+#[test]
+fn expm_test() {
+    let theta = 0.7f64;
+    let rotation = expm(&DenseMatrix::from(2, 2, &[0.0, theta, -theta, 0.0])).unwrap();
+    let expected = DenseMatrix::from(2, 2, &[theta.cos(), theta.sin(), -theta.sin(), theta.cos()]);
+    assert!(rotation.sub(&expected).norm2() < 1e-14);
+    let nilpotent = DenseMatrix::from(3, 3, &[0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0]);
+    let expected = DenseMatrix::from(3, 3, &[1.0, 1.0, 0.5, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0]);
+    assert!(expm(&nilpotent).unwrap().sub(&expected).norm2() < 1e-15);
+    // a large norm takes squarings: exp(A) exp(-A) = I, exp(2A) = exp(A)^2
+    let a = DenseMatrix::<f64>::from(3, 3, &[1.0, 4.0, -2.0, 3.0, -1.0, 5.0, 0.5, 2.0, 2.0]);
+    let (ea, e_neg) = (expm(&a).unwrap(), expm(&a.neg()).unwrap());
+    assert!(ea.dot_mul(&e_neg).sub(&DenseMatrix::eye(3)).norm2() < 1e-10);
+    let e2a = expm(&a.scalar_mul(2.0)).unwrap();
+    assert!(e2a.sub(&ea.dot_mul(&ea)).norm2() < 1e-12 * e2a.norm2());
+    // exp(i phi I) = e^(i phi) I
+    let phase = expm(&DenseMatrix::<Complex<f64>>::eye(2).scalar_mul(Complex::new(0.0, 2.0))).unwrap();
+    assert!(phase.sub(&DenseMatrix::eye(2).scalar_mul(Complex::new(0.0, 2.0).exp())).norm2() < 1e-14);
+    assert_eq!(expm(&DenseMatrix::<f64>::new(2, 3)).err(), Some(AlgebraError::NotSquare { shape: (2, 3) }));
+}
+
+#[test]
+fn logm_sqrtm_test() {
+    let a = DenseMatrix::<f64>::from(4, 4, &[4.0, 1.0, 0.0, 0.5,
+                                            -1.0, 3.0, 1.0, 0.0,
+                                            0.5, 0.0, 5.0, 1.0,
+                                            0.0, 0.5, -1.0, 2.0]);
+    let log = logm(&a).unwrap();
+    assert!(expm(&log).unwrap().sub(&a).norm2() < 1e-12 * a.norm2());
+    let small = a.scalar_mul(0.2);
+    assert!(logm(&expm(&small).unwrap()).unwrap().sub(&small).norm2() < 1e-12);
+    let root = sqrtm(&a).unwrap();
+    assert!(root.dot_mul(&root).sub(&a).norm2() < 1e-12 * a.norm2());
+    // powf agrees with powi, sqrtm and the inverse
+    assert!(powf(&a, 3.0).unwrap().sub(&a.dot_mul(&a).dot_mul(&a)).norm2() < 1e-12 * powi(&a, 3).unwrap().norm2());
+    assert!(powf(&a, 0.5).unwrap().sub(&root).norm2() < 1e-12);
+    assert!(powf(&a, -0.5).unwrap().dot_mul(&root).sub(&DenseMatrix::eye(4)).norm2() < 1e-12);
+    assert!(powi(&a, -2).unwrap().dot_mul(&a.dot_mul(&a)).sub(&DenseMatrix::eye(4)).norm2() < 1e-12);
+    assert!(powi(&a, 0).unwrap() == DenseMatrix::eye(4));
+
+    let c = |re, im| Complex::new(re, im);
+    let z = DenseMatrix::from(2, 2, &[c(2.0, 1.0), c(0.0, 1.0), c(1.0, -1.0), c(3.0, 0.0)]);
+    let z_root = sqrtm(&z).unwrap();
+    assert!(z_root.dot_mul(&z_root).sub(&z).norm2() < 1e-13);
+    assert!(expm(&logm(&z).unwrap()).unwrap().sub(&z).norm2() < 1e-13);
+    // the principal branch for complex matrices, an error for real ones
+    let minus_eye = DenseMatrix::<Complex<f64>>::eye(2).neg();
+    assert!(sqrtm(&minus_eye).unwrap().sub(&DenseMatrix::eye(2).scalar_mul(c(0.0, 1.0))).norm2() < 1e-15);
+    let negative = DenseMatrix::<f64>::from(2, 2, &[-1.0, 0.0, 0.0, 1.0]);
+    assert_eq!(logm(&negative).err(), Some(AlgebraError::NegativeEigenvalue));
+    assert_eq!(sqrtm(&negative).err(), Some(AlgebraError::NegativeEigenvalue));
+    assert_eq!(powf(&negative, 0.5).err(), Some(AlgebraError::NegativeEigenvalue));
+    // a real matrix with complex eigenvalues still has a real logarithm
+    let rotation = DenseMatrix::<f64>::from(2, 2, &[0.0, 1.0, -1.0, 0.0]);
+    let expected = rotation.scalar_mul(std::f64::consts::FRAC_PI_2);
+    assert!(logm(&rotation).unwrap().sub(&expected).norm2() < 1e-13);
+    let singular = DenseMatrix::<f64>::from(2, 2, &[1.0, 2.0, 2.0, 4.0]);
+    assert_eq!(logm(&singular).err(), Some(AlgebraError::Singular));
+    assert!(sqrtm(&DenseMatrix::<f64>::from(2, 2, &[0.0, 1.0, 0.0, 0.0])).is_err());
+}
+
+#[test]
+fn expm_multiply_test() {
+    use super::matrix::MatOps;
+    use super::matrix::sparse::SparseMatrix;
+
+    // the heat equation u' = A u with the 1D Laplacian
+    let n = 60;
+    let h = 1.0 / (n + 1) as f64;
+    let mut mat = SparseMatrix::<f64>::new(n, n);
+    for i in 0 .. n {
+        mat.set_v(i, i, -2.0 / (h * h));
+        if i > 0 {
+            mat.set_v(i, i - 1, 1.0 / (h * h));
+            mat.set_v(i - 1, i, 1.0 / (h * h));
+        }
+    }
+    let v: Vec<f64> = (0 .. n).map(|i| ((i + 1) as f64 * h * 3.0).sin() + (i as f64 * h)).collect();
+    let t = 0.01;
+    let expected = expm(&mat.to_dense().scalar_mul(t)).unwrap().mat_vec(&v);
+    let res = expm_multiply(&mat, t, &v, 20, 1e-12).unwrap();
+    let err = res.iter().zip(expected.iter()).map(|(a, b)| (a - b).abs()).fold(0.0, f64::max);
+    assert!(err < 1e-8 * scalar::norm(&expected));
+    // the Schrodinger equation i u' = -A u keeps the norm
+    let schrodinger = SparseMatrix::<Complex<f64>>::from_dense(&DenseMatrix::from_vec(n, n, Layout::RowMajor,
+        mat.to_dense().to_layout(Layout::RowMajor).into_vec().into_iter().map(|x| Complex::new(0.0, x * h * h)).collect()));
+    let psi: Vec<Complex<f64>> = v.iter().map(|&x| Complex::new(x, 0.0)).collect();
+    let phi = expm_multiply(&schrodinger, 50.0, &psi, 30, 1e-12).unwrap();
+    assert!((scalar::norm(&phi) - scalar::norm(&psi)).abs() < 1e-9);
+    // and runs backwards in time
+    let back = expm_multiply(&schrodinger, -50.0, &phi, 30, 1e-12).unwrap();
+    assert!(back.iter().zip(psi.iter()).all(|(a, b)| (a - b).norm() < 1e-8));
+    // an invariant subspace gives the exact answer
+    let mut e0 = vec![0.0; n];
+    e0[0] = 1.0;
+    let diag = SparseMatrix::from_dense(&DenseMatrix::<f64>::eye(n).scalar_mul(2.0));
+    let res = expm_multiply(&diag, 1.5, &e0, 10, 1e-12).unwrap();
+    assert!((res[0] - 3f64.exp()).abs() < 1e-12 && res[1 ..].iter().all(|&x| x == 0.0));
+}
@@ -0,0 +1,306 @@
+extern crate num;
+
+use super::super::matrix::dense::{DenseMatrix, Layout};
+use super::super::matrix::sparse::SparseMatrix;
+use super::super::scalar::Scalar;
+use super::super::super::error::{AlgebraError, AlgebraResult};
+use num::{Complex, Float, NumCast};
+use std::convert::TryFrom;
+use std::io::{Read, Write};
+use std::mem;
+
+// A compact little-endian format that keeps every bit of the values:
+//
+//     0 .. 4    magic "EVMX"
+//     4         version
+//     5         kind: 0 dense, 1 sparse
+//     6         entries: 1 f32, 2 f64, 3 complex f32, 4 complex f64
+//     7         layout of a dense matrix: 0 row-major, 1 col-major
+//     8 .. 24   rows, cols as u64
+//
+// then the values of a dense matrix in storage order, or the number of
+// stored entries of a sparse one as u64 followed by (row u64, col u64, value)
+// in row-major order. A complex value is its real and imaginary parts.
+// Wrap files in a BufReader / BufWriter, the values go one at a time.
+const MAGIC: &[u8; 4] = b"EVMX";
+const VERSION: u8 = 1;
+const DENSE: u8 = 0;
+const SPARSE: u8 = 1;
+
+struct Header {
+    kind: u8,
+    layout: Layout,
+    row_num: usize,
+    col_num: usize,
+}
+
+fn element_code<T>() -> AlgebraResult<u8>
+where T: Scalar
+{
+    match (T::COMPLEX, mem::size_of::<T::Real>()) {
+        (false, 4) => Ok(1),
+        (false, 8) => Ok(2),
+        (true, 4) => Ok(3),
+        (true, 8) => Ok(4),
+        (_, size) => Err(AlgebraError::UnsupportedFormat { format: format!("{}-byte floats", size) }),
+    }
+}
+
+fn element_name(code: u8) -> String
+{
+    match code {
+        1 => "f32".to_string(),
+        2 => "f64".to_string(),
+        3 => "complex f32".to_string(),
+        4 => "complex f64".to_string(),
+        _ => format!("element type {}", code),
+    }
+}
+
+fn write_header<T, W>(writer: &mut W, kind: u8, layout: Layout, row_num: usize, col_num: usize) -> AlgebraResult<()>
+where T: Scalar, W: Write
+{
+    let layout = match layout {
+        Layout::RowMajor => 0,
+        Layout::ColMajor => 1,
+    };
+    writer.write_all(MAGIC)?;
+    writer.write_all(&[VERSION, kind, element_code::<T>()?, layout])?;
+    write_u64(writer, row_num)?;
+    write_u64(writer, col_num)?;
+    Ok(())
+}
+
+fn read_header<T, R>(reader: &mut R) -> AlgebraResult<Header>
+where T: Scalar, R: Read
+{
+    let mut head = [0u8; 8];
+    reader.read_exact(&mut head)?;
+    if &head[.. 4] != MAGIC {
+        return Err(AlgebraError::UnsupportedFormat { format: "not a binary matrix file".to_string() });
+    }
+    if head[4] != VERSION {
+        return Err(AlgebraError::UnsupportedFormat { format: format!("binary version {}", head[4]) });
+    }
+    let kind = head[5];
+    if kind != DENSE && kind != SPARSE {
+        return Err(AlgebraError::UnsupportedFormat { format: format!("matrix kind {}", kind) });
+    }
+    let expected = element_code::<T>()?;
+    if head[6] != expected {
+        return Err(AlgebraError::UnsupportedFormat {
+            format: format!("{} entries read as {}", element_name(head[6]), element_name(expected)),
+        });
+    }
+    let layout = match head[7] {
+        0 => Layout::RowMajor,
+        1 => Layout::ColMajor,
+        code => return Err(AlgebraError::UnsupportedFormat { format: format!("layout {}", code) }),
+    };
+    let row_num = read_u64(reader)?;
+    let col_num = read_u64(reader)?;
+    Ok(Header { kind, layout, row_num, col_num })
+}
+
+fn write_u64<W>(writer: &mut W, n: usize) -> AlgebraResult<()>
+where W: Write
+{
+    writer.write_all(&(n as u64).to_le_bytes())?;
+    Ok(())
+}
+
+fn read_u64<R>(reader: &mut R) -> AlgebraResult<usize>
+where R: Read
+{
+    let mut bytes = [0u8; 8];
+    reader.read_exact(&mut bytes)?;
+    let n = u64::from_le_bytes(bytes);
+    usize::try_from(n).map_err(|_| AlgebraError::UnsupportedFormat { format: format!("a size of {}", n) })
+}
+
+fn write_real<R, W>(writer: &mut W, x: R) -> AlgebraResult<()>
+where R: Float, W: Write
+{
+    let x = x.to_f64().unwrap();
+    if mem::size_of::<R>() == 4 {
+        writer.write_all(&(x as f32).to_bits().to_le_bytes())?;
+    }
+    else {
+        writer.write_all(&x.to_bits().to_le_bytes())?;
+    }
+    Ok(())
+}
+
+// the casts below are from a float of the same width, so they are exact
+fn read_real<R, Rd>(reader: &mut Rd) -> AlgebraResult<R>
+where R: Float, Rd: Read
+{
+    if mem::size_of::<R>() == 4 {
+        let mut bytes = [0u8; 4];
+        reader.read_exact(&mut bytes)?;
+        Ok(<R as NumCast>::from(f32::from_bits(u32::from_le_bytes(bytes))).unwrap())
+    }
+    else {
+        let mut bytes = [0u8; 8];
+        reader.read_exact(&mut bytes)?;
+        Ok(<R as NumCast>::from(f64::from_bits(u64::from_le_bytes(bytes))).unwrap())
+    }
+}
+
+fn write_value<T, W>(writer: &mut W, v: T) -> AlgebraResult<()>
+where T: Scalar, W: Write
+{
+    write_real(writer, v.re())?;
+    if T::COMPLEX {
+        write_real(writer, v.im())?;
+    }
+    Ok(())
+}
+
+fn read_value<T, R>(reader: &mut R) -> AlgebraResult<T>
+where T: Scalar, R: Read
+{
+    let re = read_real(reader)?;
+    if T::COMPLEX {
+        let im = read_real(reader)?;
+        Ok(T::from_complex(Complex::new(re, im)))
+    }
+    else {
+        Ok(T::from_real(re))
+    }
+}
+
+// the sizes come from the file, so nothing is reserved up front beyond this
+const MAX_RESERVE: usize = 1 << 20;
+
+fn dense_len(header: &Header) -> AlgebraResult<usize>
+{
+    header.row_num.checked_mul(header.col_num).ok_or_else(|| AlgebraError::UnsupportedFormat {
+        format: format!("a {}x{} matrix", header.row_num, header.col_num),
+    })
+}
+
+fn read_dense_payload<T, R>(reader: &mut R, header: &Header) -> AlgebraResult<DenseMatrix<T>>
+where T: Scalar, R: Read
+{
+    let len = dense_len(header)?;
+    let mut elements = Vec::with_capacity(len.min(MAX_RESERVE));
+    for _ in 0 .. len {
+        elements.push(read_value(reader)?);
+    }
+    Ok(DenseMatrix::from_vec(header.row_num, header.col_num, header.layout, elements))
+}
+
+fn read_sparse_payload<T, R>(reader: &mut R, header: &Header) -> AlgebraResult<SparseMatrix<T>>
+where T: Scalar, R: Read
+{
+    let shape = (header.row_num, header.col_num);
+    let mut mat = SparseMatrix::new(header.row_num, header.col_num);
+    let nnz = read_u64(reader)?;
+    for _ in 0 .. nnz {
+        let i = read_u64(reader)?;
+        let j = read_u64(reader)?;
+        if i >= shape.0 || j >= shape.1 {
+            return Err(AlgebraError::IndexOutOfBounds { index: (i, j), shape });
+        }
+        mat.set_v(i, j, read_value(reader)?);
+    }
+    Ok(mat)
+}
+
+pub fn write_binary_dense<T, W>(mut writer: W, mat: &DenseMatrix<T>) -> AlgebraResult<()>
+where T: Scalar, W: Write
+{
+    write_header::<T, W>(&mut writer, DENSE, mat.layout(), mat.row(), mat.col())?;
+    for &v in mat.as_slice() {
+        write_value(&mut writer, v)?;
+    }
+    Ok(())
+}
+
+pub fn write_binary_sparse<T, W>(mut writer: W, mat: &SparseMatrix<T>) -> AlgebraResult<()>
+where T: Scalar, W: Write
+{
+    write_header::<T, W>(&mut writer, SPARSE, Layout::RowMajor, mat.row(), mat.col())?;
+    write_u64(&mut writer, mat.nnz())?;
+    for (i, j, v) in mat.iter() {
+        write_u64(&mut writer, i)?;
+        write_u64(&mut writer, j)?;
+        write_value(&mut writer, v)?;
+    }
+    Ok(())
+}
+
+// either kind of file, a sparse one is expanded
+pub fn read_binary_dense<T, R>(mut reader: R) -> AlgebraResult<DenseMatrix<T>>
+where T: Scalar, R: Read
+{
+    let header = read_header::<T, R>(&mut reader)?;
+    if header.kind == SPARSE {
+        let len = dense_len(&header)?;
+        let mat = read_sparse_payload(&mut reader, &header)?;
+        // a dense file holds every entry it claims, a sparse one must hold
+        // at least one in MAX_RESERVE before it is expanded
+        if len > MAX_RESERVE && len / MAX_RESERVE > mat.nnz() {
+            return Err(AlgebraError::UnsupportedFormat {
+                format: format!("a {}x{} matrix with {} nonzeros", header.row_num, header.col_num, mat.nnz()),
+            });
+        }
+        Ok(mat.to_dense())
+    }
+    else {
+        read_dense_payload(&mut reader, &header)
+    }
+}
+
+// either kind of file, the zeros of a dense one are dropped
+pub fn read_binary_sparse<T, R>(mut reader: R) -> AlgebraResult<SparseMatrix<T>>
+where T: Scalar, R: Read
+{
+    let header = read_header::<T, R>(&mut reader)?;
+    if header.kind == DENSE {
+        Ok(SparseMatrix::from_dense(&read_dense_payload(&mut reader, &header)?))
+    }
+    else {
+        read_sparse_payload(&mut reader, &header)
+    }
+}
+
+#[test]
+fn binary_test() {
+    let mut mat = DenseMatrix::<f64>::with_layout(2, 3, Layout::ColMajor);
+    mat.set_v(0, 2, 0.1);
+    mat.set_v(1, 0, -1e-300);
+    mat.set_v(1, 1, f64::NAN);
+    let mut out = Vec::new();
+    write_binary_dense(&mut out, &mat).unwrap();
+    assert_eq!(out.len(), 24 + 6 * 8);
+    let back = read_binary_dense::<f64, _>(&out[..]).unwrap();
+    assert_eq!(back.layout(), Layout::ColMajor);
+    assert!(back.as_slice().iter().zip(mat.as_slice()).all(|(a, b)| a.to_bits() == b.to_bits()));
+    assert_eq!(read_binary_sparse::<f64, _>(&out[..]).unwrap().nnz(), 3);
+    assert_eq!(read_binary_dense::<f32, _>(&out[..]).err(),
+               Some(AlgebraError::UnsupportedFormat { format: "f64 entries read as f32".to_string() }));
+    assert!(read_binary_dense::<f64, _>(&out[.. 40]).is_err());
+
+    let mut sparse = SparseMatrix::<Complex<f32>>::new(4, 5);
+    sparse.set_v(3, 4, Complex::new(1.5, -0.25));
+    sparse.set_v(0, 1, Complex::new(0.0, 1.0 / 3.0));
+    let mut out = Vec::new();
+    write_binary_sparse(&mut out, &sparse).unwrap();
+    assert!(read_binary_sparse::<Complex<f32>, _>(&out[..]).unwrap().to_dense() == sparse.to_dense());
+    assert!(read_binary_dense::<Complex<f32>, _>(&out[..]).unwrap() == sparse.to_dense());
+    out[4] = 9;
+    assert!(read_binary_sparse::<Complex<f32>, _>(&out[..]).is_err());
+
+    // a short sparse file can not claim a shape too large to expand
+    for &(n, message) in &[(1 << 20, "a 1048576x1048576 matrix with 0 nonzeros"),
+                           (1 << 40, "a 1099511627776x1099511627776 matrix")] {
+        let mut out = Vec::new();
+        write_header::<f64, _>(&mut out, SPARSE, Layout::RowMajor, n, n).unwrap();
+        write_u64(&mut out, 0).unwrap();
+        assert_eq!(read_binary_sparse::<f64, _>(&out[..]).unwrap().shape(), (n, n));
+        assert_eq!(read_binary_dense::<f64, _>(&out[..]).err(),
+                   Some(AlgebraError::UnsupportedFormat { format: message.to_string() }));
+    }
+}
@@ -0,0 +1,68 @@
+extern crate num;
+
+use super::super::matrix::dense::{DenseMatrix, Layout};
+use super::super::super::error::{AlgebraError, AlgebraResult};
+use num::Num;
+use std::fmt::Display;
+use std::io::{BufRead, Write};
+use std::str::FromStr;
+
+// One matrix row per line, the values split by the delimiter. Blank lines are
+// skipped and there is no header row or quoting: the fields are numbers.
+pub fn read_csv<T, R>(reader: R, delimiter: char) -> AlgebraResult<DenseMatrix<T>>
+where T: Num + Clone + Copy + Display + FromStr, R: BufRead
+{
+    let (mut row_num, mut col_num) = (0, 0);
+    let mut elements = Vec::new();
+    for (k, text) in reader.lines().enumerate() {
+        let text = text?;
+        if text.trim().is_empty() {
+            continue;
+        }
+        let before = elements.len();
+        for field in text.split(delimiter).map(|f| f.trim()) {
+            let v = field.parse::<T>().map_err(|_| AlgebraError::Parse { line: k + 1, message: format!("invalid value \"{}\"", field) })?;
+            elements.push(v);
+        }
+        let len = elements.len() - before;
+        if row_num == 0 {
+            col_num = len;
+        }
+        else if len != col_num {
+            return Err(AlgebraError::Parse { line: k + 1, message: format!("expected {} values, found {}", col_num, len) });
+        }
+        row_num += 1;
+    }
+    Ok(DenseMatrix::from_vec(row_num, col_num, Layout::RowMajor, elements))
+}
+
+// the values are written with Display, which reads back exactly for floats
+pub fn write_csv<T, W>(mut writer: W, mat: &DenseMatrix<T>, delimiter: char) -> AlgebraResult<()>
+where T: Num + Clone + Copy + Display, W: Write
+{
+    for i in 0 .. mat.row() {
+        for j in 0 .. mat.col() {
+            if j > 0 {
+                write!(writer, "{}", delimiter)?;
+            }
+            write!(writer, "{}", mat.get_v(i, j))?;
+        }
+        writeln!(writer)?;
+    }
+    Ok(())
+}
+
+#[test]
+fn csv_test() {
+    let mat = read_csv::<f64, _>("1, 2.5, -3\n\n4e-3,0.1,6\r\n".as_bytes(), ',').unwrap();
+    assert!(mat == DenseMatrix::from(2, 3, &[1.0, 2.5, -3.0, 4e-3, 0.1, 6.0]));
+    let mut out = Vec::new();
+    write_csv(&mut out, &mat.t(), ';').unwrap();
+    assert_eq!(String::from_utf8(out.clone()).unwrap(), "1;0.004\n2.5;0.1\n-3;6\n");
+    assert!(read_csv::<f64, _>(&out[..], ';').unwrap() == mat.t());
+    assert!(read_csv::<i32, _>("1 2\n3 4\n".as_bytes(), ' ').unwrap() == DenseMatrix::from(2, 2, &[1, 2, 3, 4]));
+
+    assert_eq!(read_csv::<f64, _>("1,2\n3\n".as_bytes(), ',').err(),
+               Some(AlgebraError::Parse { line: 2, message: "expected 2 values, found 1".to_string() }));
+    assert!(read_csv::<f64, _>("1,x\n".as_bytes(), ',').is_err());
+}
@@ -0,0 +1,394 @@
+extern crate num;
+
+use super::super::matrix::MatIndex;
+use super::super::matrix::dense::DenseMatrix;
+use super::super::matrix::sparse::{CooMatrix, SparseMatrix};
+use super::super::scalar::Scalar;
+use super::super::super::error::{self, AlgebraError, AlgebraResult};
+use num::{Complex, Float, NumCast, ToPrimitive};
+use std::io::{BufRead, Lines, Write};
+use std::str::SplitWhitespace;
+
+// The Matrix Market exchange format (.mtx): a `%%MatrixMarket` banner, `%`
+// comment lines, a size line and the entries with 1-based indices. Coordinate
+// files list (row, col, value) triples, array files every value column by
+// column. The symmetric kinds only store the lower triangle; the reader
+// mirrors it and the writers check the matrix really has the symmetry.
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MarketFormat {
+    Coordinate,
+    Array,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MarketField {
+    Real,
+    Integer,
+    Complex,
+    // positions only, every stored value is one
+    Pattern,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MarketSymmetry {
+    General,
+    Symmetric,
+    SkewSymmetric,
+    Hermitian,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct MarketHeader {
+    pub format: MarketFormat,
+    pub field: MarketField,
+    pub symmetry: MarketSymmetry,
+}
+
+impl MarketHeader {
+    pub fn banner(&self) -> String
+    {
+        let format = match self.format {
+            MarketFormat::Coordinate => "coordinate",
+            MarketFormat::Array => "array",
+        };
+        let field = match self.field {
+            MarketField::Real => "real",
+            MarketField::Integer => "integer",
+            MarketField::Complex => "complex",
+            MarketField::Pattern => "pattern",
+        };
+        let symmetry = match self.symmetry {
+            MarketSymmetry::General => "general",
+            MarketSymmetry::Symmetric => "symmetric",
+            MarketSymmetry::SkewSymmetric => "skew-symmetric",
+            MarketSymmetry::Hermitian => "hermitian",
+        };
+        format!("%%MatrixMarket matrix {} {} {}", format, field, symmetry)
+    }
+
+    fn parse(banner: &str) -> AlgebraResult<MarketHeader>
+    {
+        let tokens: Vec<String> = banner.split_whitespace().map(|t| t.to_lowercase()).collect();
+        if tokens.is_empty() || tokens[0] != "%%matrixmarket" {
+            return Err(parse_error(1, "the %%MatrixMarket banner is missing".to_string()));
+        }
+        let unsupported = || AlgebraError::UnsupportedFormat { format: banner.trim().to_string() };
+        if tokens.len() != 5 || tokens[1] != "matrix" {
+            return Err(unsupported());
+        }
+        let format = match tokens[2].as_str() {
+            "coordinate" => MarketFormat::Coordinate,
+            "array" => MarketFormat::Array,
+            _ => return Err(unsupported()),
+        };
+        let field = match tokens[3].as_str() {
+            "real" | "double" => MarketField::Real,
+            "integer" => MarketField::Integer,
+            "complex" => MarketField::Complex,
+            "pattern" => MarketField::Pattern,
+            _ => return Err(unsupported()),
+        };
+        let symmetry = match tokens[4].as_str() {
+            "general" => MarketSymmetry::General,
+            "symmetric" => MarketSymmetry::Symmetric,
+            "skew-symmetric" => MarketSymmetry::SkewSymmetric,
+            "hermitian" => MarketSymmetry::Hermitian,
+            _ => return Err(unsupported()),
+        };
+        let header = MarketHeader { format, field, symmetry };
+        header.check()?;
+        Ok(header)
+    }
+
+    // the combinations the format allows
+    fn check(&self) -> AlgebraResult<()>
+    {
+        let invalid = matches!((self.format, self.field, self.symmetry),
+            (MarketFormat::Array, MarketField::Pattern, _) |
+            (_, MarketField::Pattern, MarketSymmetry::SkewSymmetric) |
+            (_, MarketField::Pattern, MarketSymmetry::Hermitian) |
+            (_, MarketField::Real, MarketSymmetry::Hermitian) |
+            (_, MarketField::Integer, MarketSymmetry::Hermitian));
+        if invalid {
+            Err(AlgebraError::UnsupportedFormat { format: self.banner() })
+        }
+        else {
+            Ok(())
+        }
+    }
+}
+
+fn parse_error(line: usize, message: String) -> AlgebraError
+{
+    AlgebraError::Parse { line, message }
+}
+
+// the lines holding data with their numbers, past the comments and blank lines
+struct DataLines<R> {
+    lines: Lines<R>,
+    line: usize,
+}
+
+impl<R> DataLines<R>
+where R: BufRead
+{
+    fn next_line(&mut self) -> AlgebraResult<Option<(usize, String)>>
+    {
+        for text in &mut self.lines {
+            let text = text?;
+            self.line += 1;
+            let text = text.trim();
+            if !text.is_empty() && !text.starts_with('%') {
+                return Ok(Some((self.line, text.to_string())));
+            }
+        }
+        Ok(None)
+    }
+}
+
+fn parse_index(token: Option<&str>, line: usize) -> AlgebraResult<usize>
+{
+    let token = token.ok_or_else(|| parse_error(line, "an index is missing".to_string()))?;
+    token.parse::<usize>().map_err(|_| parse_error(line, format!("invalid index \"{}\"", token)))
+}
+
+fn parse_real<R>(token: Option<&str>, line: usize) -> AlgebraResult<R>
+where R: Float
+{
+    let token = token.ok_or_else(|| parse_error(line, "a value is missing".to_string()))?;
+    token.parse::<f64>().ok().and_then(<R as NumCast>::from)
+        .ok_or_else(|| parse_error(line, format!("invalid value \"{}\"", token)))
+}
+
+fn parse_value<T>(tokens: &mut SplitWhitespace, field: MarketField, line: usize) -> AlgebraResult<T>
+where T: Scalar
+{
+    match field {
+        MarketField::Pattern => Ok(T::one()),
+        MarketField::Real | MarketField::Integer => Ok(T::from_real(parse_real(tokens.next(), line)?)),
+        MarketField::Complex => {
+            let re = parse_real(tokens.next(), line)?;
+            let im = parse_real(tokens.next(), line)?;
+            Ok(T::from_complex(Complex::new(re, im)))
+        }
+    }
+}
+
+// the entry at (j, i) implied by the one at (i, j)
+fn mirror<T>(symmetry: MarketSymmetry, v: T) -> T
+where T: Scalar
+{
+    match symmetry {
+        MarketSymmetry::General | MarketSymmetry::Symmetric => v,
+        MarketSymmetry::SkewSymmetric => -v,
+        MarketSymmetry::Hermitian => v.conj(),
+    }
+}
+
+fn push_entry<T>(coo: &mut CooMatrix<T>, symmetry: MarketSymmetry, i: usize, j: usize, v: T)
+where T: Scalar
+{
+    coo.push(i, j, v);
+    if i != j && symmetry != MarketSymmetry::General {
+        coo.push(j, i, mirror(symmetry, v));
+    }
+}
+
+// the entries a symmetric kind stores
+fn in_triangle(symmetry: MarketSymmetry, i: usize, j: usize) -> bool
+{
+    match symmetry {
+        MarketSymmetry::General => true,
+        MarketSymmetry::SkewSymmetric => i > j,
+        MarketSymmetry::Symmetric | MarketSymmetry::Hermitian => i >= j,
+    }
+}
+
+// reads a coordinate or array file; the symmetric kinds come back with both
+// triangles and duplicated positions add up once the matrix is converted
+pub fn read_matrix_market<T, R>(reader: R) -> AlgebraResult<CooMatrix<T>>
+where T: Scalar, R: BufRead
+{
+    let mut lines = reader.lines();
+    let banner = match lines.next() {
+        Some(text) => text?,
+        None => return Err(parse_error(1, "the file is empty".to_string())),
+    };
+    let header = MarketHeader::parse(&banner)?;
+    if header.field == MarketField::Complex && !T::COMPLEX {
+        return Err(AlgebraError::UnsupportedFormat { format: "complex entries in a real matrix".to_string() });
+    }
+
+    let mut data = DataLines { lines, line: 1 };
+    let (line, size) = match data.next_line()? {
+        Some(size) => size,
+        None => return Err(parse_error(data.line, "the size line is missing".to_string())),
+    };
+    let mut tokens = size.split_whitespace();
+    let row_num = parse_index(tokens.next(), line)?;
+    let col_num = parse_index(tokens.next(), line)?;
+    if header.symmetry != MarketSymmetry::General && row_num != col_num {
+        return Err(parse_error(line, format!("a {}x{} matrix can not be symmetric", row_num, col_num)));
+    }
+
+    let mut coo = CooMatrix::new(row_num, col_num);
+    match header.format {
+        MarketFormat::Coordinate => {
+            let entries = parse_index(tokens.next(), line)?;
+            for k in 0 .. entries {
+                let (line, text) = match data.next_line()? {
+                    Some(entry) => entry,
+                    None => return Err(parse_error(data.line, format!("expected {} entries, found {}", entries, k))),
+                };
+                let mut tokens = text.split_whitespace();
+                let i = parse_index(tokens.next(), line)?;
+                let j = parse_index(tokens.next(), line)?;
+                if i == 0 || j == 0 || i > row_num || j > col_num {
+                    return Err(parse_error(line, format!("entry ({}, {}) is outside the {}x{} matrix", i, j, row_num, col_num)));
+                }
+                let v = parse_value(&mut tokens, header.field, line)?;
+                push_entry(&mut coo, header.symmetry, i - 1, j - 1, v);
+            }
+        }
+        MarketFormat::Array => {
+            for j in 0 .. col_num {
+                for i in 0 .. row_num {
+                    if !in_triangle(header.symmetry, i, j) {
+                        continue;
+                    }
+                    let (line, text) = match data.next_line()? {
+                        Some(entry) => entry,
+                        None => return Err(parse_error(data.line, format!("the value at ({}, {}) is missing", i + 1, j + 1))),
+                    };
+                    let v: T = parse_value(&mut text.split_whitespace(), header.field, line)?;
+                    if v != T::zero() {
+                        push_entry(&mut coo, header.symmetry, i, j, v);
+                    }
+                }
+            }
+        }
+    }
+    match data.next_line()? {
+        Some((line, _)) => Err(parse_error(line, "more entries than the size line declares".to_string())),
+        None => Ok(coo),
+    }
+}
+
+// the field for the entries of T, unless the caller asked for a pattern
+fn check_field<T>(field: MarketField) -> AlgebraResult<()>
+where T: Scalar
+{
+    match field {
+        MarketField::Integer => Err(AlgebraError::UnsupportedFormat { format: "writing integer entries".to_string() }),
+        MarketField::Real if T::COMPLEX => Err(AlgebraError::UnsupportedFormat { format: "complex entries as real".to_string() }),
+        _ => Ok(()),
+    }
+}
+
+fn check_symmetry<T, I, F>(shape: MatIndex, symmetry: MarketSymmetry, entries: I, get: F) -> AlgebraResult<()>
+where T: Scalar, I: Iterator<Item = (usize, usize, T)>, F: Fn(usize, usize) -> T
+{
+    if symmetry == MarketSymmetry::General {
+        return Ok(());
+    }
+    error::check_square(shape)?;
+    for (i, j, v) in entries {
+        if get(j, i) != mirror(symmetry, v) {
+            return Err(AlgebraError::NotSymmetric);
+        }
+    }
+    Ok(())
+}
+
+fn write_value<T, W>(writer: &mut W, field: MarketField, v: T) -> AlgebraResult<()>
+where T: Scalar, W: Write
+{
+    // the shortest exponent form that reads back to the same number
+    match field {
+        MarketField::Pattern => {}
+        MarketField::Complex => write!(writer, " {:e} {:e}", v.re().to_f64().unwrap(), v.im().to_f64().unwrap())?,
+        _ => write!(writer, " {:e}", v.re().to_f64().unwrap())?,
+    }
+    writeln!(writer)?;
+    Ok(())
+}
+
+// writes the stored entries in coordinate format; field is Real or Complex
+// for the values, or Pattern for the positions alone
+pub fn write_matrix_market<T, W>(mut writer: W, mat: &SparseMatrix<T>, field: MarketField, symmetry: MarketSymmetry) -> AlgebraResult<()>
+where T: Scalar, W: Write
+{
+    let header = MarketHeader { format: MarketFormat::Coordinate, field, symmetry };
+    header.check()?;
+    check_field::<T>(field)?;
+    check_symmetry(mat.shape(), symmetry, mat.iter(), |i, j| mat.get_v(i, j))?;
+
+    let entries: Vec<(usize, usize, T)> = mat.iter().filter(|&(i, j, _)| in_triangle(symmetry, i, j)).collect();
+    writeln!(writer, "{}", header.banner())?;
+    writeln!(writer, "{} {} {}", mat.row(), mat.col(), entries.len())?;
+    for (i, j, v) in entries {
+        write!(writer, "{} {}", i + 1, j + 1)?;
+        write_value(&mut writer, field, v)?;
+    }
+    Ok(())
+}
+
+// writes every value in array format, real or complex after T
+pub fn write_matrix_market_dense<T, W>(mut writer: W, mat: &DenseMatrix<T>, symmetry: MarketSymmetry) -> AlgebraResult<()>
+where T: Scalar, W: Write
+{
+    let field = if T::COMPLEX { MarketField::Complex } else { MarketField::Real };
+    let header = MarketHeader { format: MarketFormat::Array, field, symmetry };
+    header.check()?;
+    let (row_num, col_num) = mat.shape();
+    let entries = (0 .. row_num).flat_map(|i| (0 .. col_num).map(move |j| (i, j, mat.get_v(i, j))));
+    check_symmetry(mat.shape(), symmetry, entries, |i, j| mat.get_v(i, j))?;
+
+    writeln!(writer, "{}", header.banner())?;
+    writeln!(writer, "{} {}", row_num, col_num)?;
+    for j in 0 .. col_num {
+        for i in 0 .. row_num {
+            if in_triangle(symmetry, i, j) {
+                write_value(&mut writer, field, mat.get_v(i, j))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn matrix_market_test() {
+    let text = "%%MatrixMarket matrix coordinate real symmetric\n\
+                % the 1-D Laplacian\n\
+                \n\
+                3 3 5\n\
+                1 1 2.0\n2 1 -1\n2 2 2\n3 2 -1e0\n3 3 2.0\n";
+    let lap = read_matrix_market::<f64, _>(text.as_bytes()).unwrap().to_sparse();
+    assert_eq!(lap.nnz(), 7);
+    assert_eq!(lap.get_v(0, 1), -1.0);
+    assert_eq!(lap.get_v(1, 2), -1.0);
+
+    let mut out = Vec::new();
+    write_matrix_market(&mut out, &lap, MarketField::Real, MarketSymmetry::Symmetric).unwrap();
+    assert!(String::from_utf8(out.clone()).unwrap().starts_with("%%MatrixMarket matrix coordinate real symmetric\n3 3 5\n"));
+    assert!(read_matrix_market::<f64, _>(&out[..]).unwrap().to_sparse().to_dense() == lap.to_dense());
+    let mut out = Vec::new();
+    write_matrix_market(&mut out, &lap, MarketField::Pattern, MarketSymmetry::General).unwrap();
+    assert_eq!(read_matrix_market::<f32, _>(&out[..]).unwrap().to_sparse().get_v(2, 1), 1.0);
+    assert_eq!(write_matrix_market(Vec::new(), &lap, MarketField::Real, MarketSymmetry::SkewSymmetric), Err(AlgebraError::NotSymmetric));
+
+    let text = "%%MatrixMarket matrix array complex hermitian\n2 2\n1 0\n3 4\n5 0\n";
+    let herm = read_matrix_market::<Complex<f64>, _>(text.as_bytes()).unwrap().to_dense();
+    assert!(herm.get_v(0, 1) == Complex::new(3.0, -4.0) && herm.get_v(1, 1) == Complex::new(5.0, 0.0));
+    let mut out = Vec::new();
+    write_matrix_market_dense(&mut out, &herm, MarketSymmetry::Hermitian).unwrap();
+    assert!(read_matrix_market::<Complex<f64>, _>(&out[..]).unwrap().to_dense() == herm);
+    assert!(read_matrix_market::<f64, _>(text.as_bytes()).is_err());
+
+    let bad = "%%MatrixMarket matrix coordinate real general\n2 2 2\n1 1 1.0\n3 1 1.0\n";
+    assert_eq!(read_matrix_market::<f64, _>(bad.as_bytes()).err(),
+               Some(AlgebraError::Parse { line: 4, message: "entry (3, 1) is outside the 2x2 matrix".to_string() }));
+    let short = "%%MatrixMarket matrix array real general\n2 1\n1.5\n";
+    assert!(read_matrix_market::<f64, _>(short.as_bytes()).is_err());
+}
@@ -0,0 +1,10 @@
+pub mod matrix_market;
+pub mod csv;
+pub mod binary;
+#[cfg(feature = "serde")]
+mod serde_impl;
+
+pub use self::matrix_market::{MarketFormat, MarketField, MarketSymmetry, MarketHeader};
+pub use self::matrix_market::{read_matrix_market, write_matrix_market, write_matrix_market_dense};
+pub use self::csv::{read_csv, write_csv};
+pub use self::binary::{read_binary_dense, read_binary_sparse, write_binary_dense, write_binary_sparse};
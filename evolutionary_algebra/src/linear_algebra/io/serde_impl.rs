@@ -0,0 +1,121 @@
+extern crate num;
+
+use super::super::matrix::dense::{DenseMatrix, Layout};
+use super::super::matrix::sparse::SparseMatrix;
+use num::Num;
+use serde::de;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt::Display;
+
+// serde for the assembly formats: a dense matrix as its shape, layout and
+// elements in storage order, a sparse one as its shape and (row, col, value)
+// entries. The shapes are checked when deserialising. num's Complex has no
+// serde 1 support, so the entries have to be real.
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "Layout")]
+enum LayoutDef {
+    RowMajor,
+    ColMajor,
+}
+
+#[derive(Serialize)]
+#[serde(rename = "DenseMatrix")]
+struct DenseRef<'a, T> {
+    rows: usize,
+    cols: usize,
+    #[serde(with = "LayoutDef")]
+    layout: Layout,
+    elements: &'a [T],
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "DenseMatrix")]
+struct DenseOwned<T> {
+    rows: usize,
+    cols: usize,
+    #[serde(with = "LayoutDef")]
+    layout: Layout,
+    elements: Vec<T>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "SparseMatrix")]
+struct SparseRepr<T> {
+    rows: usize,
+    cols: usize,
+    entries: Vec<(usize, usize, T)>,
+}
+
+impl<T> Serialize for DenseMatrix<T>
+where T: Num + Clone + Copy + Display + Serialize
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: Serializer
+    {
+        let repr = DenseRef { rows: self.row(), cols: self.col(), layout: self.layout(), elements: self.as_slice() };
+        repr.serialize(serializer)
+    }
+}
+
+impl<'de, T> Deserialize<'de> for DenseMatrix<T>
+where T: Num + Clone + Copy + Display + Deserialize<'de>
+{
+    fn deserialize<D>(deserializer: D) -> Result<DenseMatrix<T>, D::Error>
+    where D: Deserializer<'de>
+    {
+        let repr = DenseOwned::<T>::deserialize(deserializer)?;
+        if repr.rows.checked_mul(repr.cols) != Some(repr.elements.len()) {
+            return Err(de::Error::custom(format!("{} elements do not fill a {}x{} matrix",
+                                                 repr.elements.len(), repr.rows, repr.cols)));
+        }
+        Ok(DenseMatrix::from_vec(repr.rows, repr.cols, repr.layout, repr.elements))
+    }
+}
+
+impl<T> Serialize for SparseMatrix<T>
+where T: Num + Clone + Copy + Display + Serialize
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: Serializer
+    {
+        let repr = SparseRepr { rows: self.row(), cols: self.col(), entries: self.iter().collect() };
+        repr.serialize(serializer)
+    }
+}
+
+impl<'de, T> Deserialize<'de> for SparseMatrix<T>
+where T: Num + Clone + Copy + Display + Deserialize<'de>
+{
+    fn deserialize<D>(deserializer: D) -> Result<SparseMatrix<T>, D::Error>
+    where D: Deserializer<'de>
+    {
+        let repr = SparseRepr::<T>::deserialize(deserializer)?;
+        let mut mat = SparseMatrix::new(repr.rows, repr.cols);
+        for (i, j, v) in repr.entries {
+            if i >= repr.rows || j >= repr.cols {
+                return Err(de::Error::custom(format!("entry ({}, {}) is outside the {}x{} matrix", i, j, repr.rows, repr.cols)));
+            }
+            mat.set_v(i, j, v);
+        }
+        Ok(mat)
+    }
+}
+
+#[test]
+fn serde_test() {
+    let mut mat = DenseMatrix::<f64>::with_layout(2, 2, Layout::ColMajor);
+    mat.set_v(0, 1, 0.1);
+    mat.set_v(1, 1, -2.0);
+    let json = serde_json::to_string(&mat).unwrap();
+    assert_eq!(json, r#"{"rows":2,"cols":2,"layout":"ColMajor","elements":[0.0,0.0,0.1,-2.0]}"#);
+    let back: DenseMatrix<f64> = serde_json::from_str(&json).unwrap();
+    assert!(back == mat && back.layout() == Layout::ColMajor);
+    assert!(serde_json::from_str::<DenseMatrix<f64>>(r#"{"rows":2,"cols":2,"layout":"RowMajor","elements":[1.0]}"#).is_err());
+
+    let sparse = SparseMatrix::from_dense(&mat);
+    let json = serde_json::to_string(&sparse).unwrap();
+    assert_eq!(json, r#"{"rows":2,"cols":2,"entries":[[0,1,0.1],[1,1,-2.0]]}"#);
+    assert!(serde_json::from_str::<SparseMatrix<f64>>(&json).unwrap().to_dense() == mat);
+    assert!(serde_json::from_str::<SparseMatrix<f64>>(r#"{"rows":1,"cols":1,"entries":[[0,1,1.0]]}"#).is_err());
+}
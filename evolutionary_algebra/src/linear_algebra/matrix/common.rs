@@ -1,12 +1,283 @@
+use super::super::scalar::{self, Scalar};
+use num::{Num, Float, NumCast, One, Zero};
+use std::ops::Range;
 
 pub type MatIndex = (usize, usize);
-pub trait MatOps<E> {
-    type Output;
-    fn t(&self) -> Self::Output;
+
+// The storage-independent view of a matrix. Every algorithm in
+// `linear_algebra::solver` is written against this trait only, so a new
+// storage format gets them all by implementing the required methods; the
+// provided ones can be overridden when the format allows something faster.
+pub trait MatOps<E>: Sized
+where E: Num + Copy
+{
+    fn new(row: usize, col: usize) -> Self;
+    fn t(&self) -> Self;
     //pub fn t_mut(&mut self);
     fn set_v(&mut self, row: usize, col: usize, v: E);
     fn get_v(&self, row: usize, col: usize) -> E;
-    //fn slice(&self, row_from: usize, row_to: usize, col_from: usize, col_to: usize)-> Self::Output;
+    fn slice(&self, row_from: usize, row_to: usize, col_from: usize, col_to: usize) -> Self;
     fn row(&self) -> usize;
     fn col(&self) -> usize;
-}
\ No newline at end of file
+    fn dot_mul(&self, mat: &Self) -> Self;
+
+    fn shape(&self) -> MatIndex
+    {
+        (self.row(), self.col())
+    }
+
+    fn eye(num: usize) -> Self
+    {
+        let mut mat = Self::new(num, num);
+        for i in 0 .. num {
+            mat.set_v(i, i, E::one());
+        }
+        mat
+    }
+
+    // the (column, value) pairs of a row that may be nonzero, in column order
+    fn row_entries(&self, row: usize) -> Vec<(usize, E)>
+    {
+        (0 .. self.col()).map(|j| (j, self.get_v(row, j))).filter(|&(_, v)| v != E::zero()).collect()
+    }
+
+    fn swap_rows(&mut self, row_i: usize, row_j: usize)
+    {
+        for col in 0 .. self.col() {
+            let tmp = self.get_v(row_i, col);
+            self.set_v(row_i, col, self.get_v(row_j, col));
+            self.set_v(row_j, col, tmp);
+        }
+    }
+
+    // row_i -= (a_i,col / divisor) * pivot_row for every row i in rows, the
+    // pivot row given by its nonzero (column, value) pairs
+    fn eliminate_rows(&mut self, rows: Range<usize>, col: usize, divisor: E, pivot_row: &[(usize, E)])
+    {
+        for i in rows {
+            let factor = self.get_v(i, col) / divisor;
+            if factor != E::zero() {
+                for &(j, v) in pivot_row {
+                    self.set_v(i, j, self.get_v(i, j) - factor * v);
+                }
+            }
+        }
+    }
+
+    fn concat(&self, mat: &Self) -> Self
+    {
+        let mut res = Self::new(self.row(), self.col() + mat.col());
+        for i in 0 .. self.row() {
+            for (j, v) in self.row_entries(i) {
+                res.set_v(i, j, v);
+            }
+            for (j, v) in mat.row_entries(i) {
+                res.set_v(i, j + self.col(), v);
+            }
+        }
+        res
+    }
+
+    fn scalar_mul(&self, k: E) -> Self
+    {
+        let mut res = Self::new(self.row(), self.col());
+        for i in 0 .. self.row() {
+            for (j, v) in self.row_entries(i) {
+                res.set_v(i, j, k * v);
+            }
+        }
+        res
+    }
+
+    fn add(&self, mat: &Self) -> Self
+    {
+        let mut res = self.scalar_mul(E::one());
+        for i in 0 .. mat.row() {
+            for (j, v) in mat.row_entries(i) {
+                res.set_v(i, j, res.get_v(i, j) + v);
+            }
+        }
+        res
+    }
+
+    fn sub(&self, mat: &Self) -> Self
+    {
+        let mut res = self.scalar_mul(E::one());
+        for i in 0 .. mat.row() {
+            for (j, v) in mat.row_entries(i) {
+                res.set_v(i, j, res.get_v(i, j) - v);
+            }
+        }
+        res
+    }
+
+    fn mat_vec(&self, x: &[E]) -> Vec<E>
+    {
+        assert_eq!(x.len(), self.col(), "the length of the vector does not match the matrix!");
+        (0 .. self.row()).map(|i| {
+            self.row_entries(i).into_iter().fold(E::zero(), |sum, (j, v)| sum + v * x[j])
+        }).collect()
+    }
+
+    fn get_col_vec(&self, col: usize) -> Vec<E>
+    {
+        (0 .. self.row()).map(|i| self.get_v(i, col)).collect()
+    }
+
+    // |a_ij - a_ji| <= tol for every stored a_ij
+    fn is_symmetric(&self, tol: E) -> bool
+    where E: Float
+    {
+        if self.row() != self.col() {
+            return false;
+        }
+        (0 .. self.row()).all(|i| {
+            self.row_entries(i).into_iter().all(|(j, v)| (v - self.get_v(j, i)).abs() <= tol)
+        })
+    }
+
+    // the conjugate transpose A^H, the transpose for real matrices
+    fn conj_t(&self) -> Self
+    where E: Scalar
+    {
+        let mut res = Self::new(self.col(), self.row());
+        for i in 0 .. self.row() {
+            for (j, v) in self.row_entries(i) {
+                res.set_v(j, i, v.conj());
+            }
+        }
+        res
+    }
+
+    // |a_ij - conj(a_ji)| <= tol for every stored a_ij, so the diagonal has
+    // to be real up to tol as well
+    fn is_hermitian(&self, tol: E::Real) -> bool
+    where E: Scalar
+    {
+        if self.row() != self.col() {
+            return false;
+        }
+        (0 .. self.row()).all(|i| {
+            self.row_entries(i).into_iter().all(|(j, v)| (v - self.get_v(j, i).conj()).modulus() <= tol)
+        })
+    }
+
+    // maximum absolute column sum
+    fn norm_1(&self) -> E
+    where E: Float
+    {
+        let mut sums = vec![E::zero(); self.col()];
+        for i in 0 .. self.row() {
+            for (j, v) in self.row_entries(i) {
+                sums[j] = sums[j] + v.abs();
+            }
+        }
+        sums.into_iter().fold(E::zero(), E::max)
+    }
+
+    // maximum absolute row sum
+    fn norm_inf(&self) -> E
+    where E: Float
+    {
+        (0 .. self.row()).map(|i| {
+            self.row_entries(i).into_iter().fold(E::zero(), |sum, (_, v)| sum + v.abs())
+        }).fold(E::zero(), E::max)
+    }
+
+    // the Frobenius norm, sqrt(trace(A^T A))
+    fn norm2(&self) -> E
+    where E: Float
+    {
+        (0 .. self.row()).map(|i| {
+            self.row_entries(i).into_iter().fold(E::zero(), |sum, (_, v)| sum + v * v)
+        }).fold(E::zero(), |sum, v| sum + v).sqrt()
+    }
+}
+
+// The norms above in the modulus of the entries, for complex matrices as well;
+// the inherent norms of the matrix types use these.
+pub(crate) fn modulus_norm_1<M, E>(mat: &M) -> E::Real
+where M: MatOps<E>, E: Scalar
+{
+    let mut sums = vec![E::Real::zero(); mat.col()];
+    for i in 0 .. mat.row() {
+        for (j, v) in mat.row_entries(i) {
+            sums[j] = sums[j] + v.modulus();
+        }
+    }
+    sums.into_iter().fold(E::Real::zero(), E::Real::max)
+}
+
+pub(crate) fn modulus_norm_inf<M, E>(mat: &M) -> E::Real
+where M: MatOps<E>, E: Scalar
+{
+    (0 .. mat.row()).map(|i| {
+        mat.row_entries(i).into_iter().fold(E::Real::zero(), |sum, (_, v)| sum + v.modulus())
+    }).fold(E::Real::zero(), E::Real::max)
+}
+
+// sqrt(trace(A^H A))
+pub(crate) fn modulus_norm_fro<M, E>(mat: &M) -> E::Real
+where M: MatOps<E>, E: Scalar
+{
+    (0 .. mat.row()).map(|i| {
+        mat.row_entries(i).into_iter().fold(E::Real::zero(), |sum, (_, v)| sum + v.modulus_sqr())
+    }).fold(E::Real::zero(), |sum, v| sum + v).sqrt()
+}
+
+// the largest modulus of an entry
+pub(crate) fn modulus_norm_max<M, E>(mat: &M) -> E::Real
+where M: MatOps<E>, E: Scalar
+{
+    (0 .. mat.row()).map(|i| {
+        mat.row_entries(i).into_iter().fold(E::Real::zero(), |m, (_, v)| m.max(v.modulus()))
+    }).fold(E::Real::zero(), E::Real::max)
+}
+
+// The spectral norm, the largest singular value, by power iteration on A^H A
+// from the unit vector of the column of largest norm. ||A x|| for a unit x
+// only grows towards it, so the iteration stops once a step gains at most
+// 1000 eps relatively, or after 1000 steps; singular values that are close
+// together slow it down.
+pub(crate) fn modulus_norm_spectral<M, E>(mat: &M) -> E::Real
+where M: MatOps<E>, E: Scalar
+{
+    let tol = <E::Real as NumCast>::from(1000).unwrap() * E::Real::epsilon();
+    let mut col_sqr = vec![E::Real::zero(); mat.col()];
+    for i in 0 .. mat.row() {
+        for (j, v) in mat.row_entries(i) {
+            col_sqr[j] = col_sqr[j] + v.modulus_sqr();
+        }
+    }
+    let mut start = 0;
+    for j in 1 .. mat.col() {
+        if col_sqr[j] > col_sqr[start] {
+            start = j;
+        }
+    }
+    if col_sqr.get(start).map_or(true, |&s| s == E::Real::zero()) {
+        return E::Real::zero();
+    }
+
+    let mut x = vec![E::zero(); mat.col()];
+    x[start] = E::one();
+    let mut sigma = E::Real::zero();
+    for _ in 0 .. 1000 {
+        let y = mat.mat_vec(&x);
+        let next = scalar::norm(&y);
+        if next - sigma <= tol * next {
+            return sigma.max(next);
+        }
+        sigma = next;
+        // x = A^H y / ||A^H y||
+        let mut z = vec![E::zero(); mat.col()];
+        for (i, &y_i) in y.iter().enumerate() {
+            for (j, v) in mat.row_entries(i) {
+                z[j] = z[j] + v.conj() * y_i;
+            }
+        }
+        let scale = E::from_real(E::Real::one() / scalar::norm(&z));
+        x = z.into_iter().map(|v| v * scale).collect();
+    }
+    sigma
+}
@@ -1,129 +1,235 @@
 extern crate num;
 
-use super::super::common::MatIndex;
-use num::{Num, Float};
-use std::collections::HashMap;
+use super::super::common::{self, MatIndex, MatOps};
+use super::super::format::{self, FormatOptions};
+use super::super::super::scalar::Scalar;
+use super::super::super::solver::direct;
+use super::super::super::solver::rhs::RightHandSide;
+use super::super::super::decomposition::{LU, Cholesky, LDLT, LeastSquares, QR, Eigen, SymmetricEigen, SVD, Schur};
+use super::super::super::function;
+use super::super::super::super::error::{self, AlgebraResult};
+use super::super::super::super::parallel::{self, Element};
+use num::{Num, Float, Zero};
+use std::borrow::Cow;
 use std::fmt::Display;
+use std::ops::Range;
 
-#[allow(dead_code)]
-pub struct DenseMatrix<T> 
+// the edge of the square tiles used by the blocked matrix product
+const BLOCK_SIZE: usize = 64;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Layout {
+    RowMajor,
+    ColMajor,
+}
+
+#[derive(Clone)]
+pub struct DenseMatrix<T>
 where T: Num + Clone + Copy + Display
 {
     row_num: usize,
     col_num: usize,
-    elements: HashMap<MatIndex, T>,
+    layout: Layout,
+    elements: Vec<T>,
 }
 
 macro_rules! build_dense_matrix_v {
     ($row_num:expr , $col_num:expr , $insert_item:expr) => {
         {
-            let mut elements = HashMap::<MatIndex, T>::with_capacity($row_num * $col_num);
-            for i in 0 .. $row_num {
-                for j in 0 .. $col_num {
-                    elements.insert((i,j), $insert_item);
-                }
-            }
+            let row_num = $row_num;
+            let col_num = $col_num;
             DenseMatrix {
-                row_num: $row_num,
-                col_num: $col_num,
-                elements: elements,
+                row_num,
+                col_num,
+                layout: Layout::RowMajor,
+                elements: vec![$insert_item; row_num * col_num],
             }
-        } 
+        }
     };
 }
 
 macro_rules! build_dense_matrix_f {
     ($row_num:expr , $col_num:expr , $insert_fun:expr) => {
         {
-            let mut elements = HashMap::<MatIndex, T>::with_capacity($row_num * $col_num);
-            for i in 0 .. $row_num {
-                for j in 0 .. $col_num {
-                    elements.insert((i,j), $insert_fun(i, j));
+            let row_num = $row_num;
+            let col_num = $col_num;
+            let mut elements = Vec::<T>::with_capacity(row_num * col_num);
+            for i in 0 .. row_num {
+                for j in 0 .. col_num {
+                    elements.push($insert_fun(i, j));
                 }
             }
             DenseMatrix {
-                row_num: $row_num,
-                col_num: $col_num,
-                elements: elements,
+                row_num,
+                col_num,
+                layout: Layout::RowMajor,
+                elements,
             }
-        } 
+        }
     };
 }
 
-impl<T> DenseMatrix<T> 
+impl<T> DenseMatrix<T>
 where T: Num + Clone + Copy + Display
 {
     pub fn new(row_num: usize, col_num: usize) -> DenseMatrix<T>
+    {
+        Self::with_layout(row_num, col_num, Layout::RowMajor)
+    }
+
+    pub fn with_layout(row_num: usize, col_num: usize, layout: Layout) -> DenseMatrix<T>
     {
         DenseMatrix {
-            row_num: row_num,
-            col_num: col_num,
-            elements: HashMap::with_capacity(row_num * col_num),
+            row_num,
+            col_num,
+            layout,
+            elements: vec![T::zero(); row_num * col_num],
         }
     }
 
     pub fn from(row_num: usize, col_num: usize, slice: &[T]) -> DenseMatrix<T>
     {
-        build_dense_matrix_f!(row_num , col_num , |i, j|{slice[i * col_num + j]})
+        Self::from_vec(row_num, col_num, Layout::RowMajor, slice[.. row_num * col_num].to_vec())
     }
 
-    pub fn t(&self) -> DenseMatrix<T>
+    pub fn from_vec(row_num: usize, col_num: usize, layout: Layout, elements: Vec<T>) -> DenseMatrix<T>
+    {
+        assert_eq!(elements.len(), row_num * col_num, "the length of elements does not match the shape!");
+        DenseMatrix {
+            row_num,
+            col_num,
+            layout,
+            elements,
+        }
+    }
+
+    #[inline]
+    pub fn layout(&self) -> Layout
+    {
+        self.layout
+    }
+
+    // (row stride, col stride) of the flat storage
+    #[inline]
+    pub fn strides(&self) -> (usize, usize)
+    {
+        match self.layout {
+            Layout::RowMajor => (self.col_num, 1),
+            Layout::ColMajor => (1, self.row_num),
+        }
+    }
+
+    #[inline]
+    fn offset(&self, row: usize, col: usize) -> usize
+    {
+        let (row_stride, col_stride) = self.strides();
+        row * row_stride + col * col_stride
+    }
+
+    pub fn as_slice(&self) -> &[T]
+    {
+        &self.elements
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T]
+    {
+        &mut self.elements
+    }
+
+    pub fn into_vec(self) -> Vec<T>
     {
-        let row_num = self.row();
-        let col_num = self.col();
-        let mut elements = HashMap::with_capacity(row_num * col_num);
-        for i in 0 .. row_num {
-            for j in 0 .. col_num {
-                elements.insert((j, i), self.get_v(i, j));
+        self.elements
+    }
+
+    pub fn to_layout(&self, layout: Layout) -> DenseMatrix<T>
+    {
+        if layout == self.layout {
+            return self.clone();
+        }
+        let mut mat = Self::with_layout(self.row_num, self.col_num, layout);
+        for i in 0 .. self.row_num {
+            for j in 0 .. self.col_num {
+                let off = mat.offset(i, j);
+                mat.elements[off] = self.get_v(i, j);
             }
         }
+        mat
+    }
+
+    fn row_major_elements(&self) -> Cow<'_, [T]>
+    {
+        match self.layout {
+            Layout::RowMajor => Cow::Borrowed(&self.elements),
+            Layout::ColMajor => Cow::Owned(self.to_layout(Layout::RowMajor).elements),
+        }
+    }
+
+    // the transpose of a row-major matrix is the same storage read column-major
+    pub fn t(&self) -> DenseMatrix<T>
+    {
+        let layout = match self.layout {
+            Layout::RowMajor => Layout::ColMajor,
+            Layout::ColMajor => Layout::RowMajor,
+        };
         DenseMatrix {
-            row_num: col_num,
-            col_num: row_num,
-            elements: elements,
-        }      
+            row_num: self.col_num,
+            col_num: self.row_num,
+            layout,
+            elements: self.elements.clone(),
+        }
     }
-    
+
     #[inline]
     pub fn set_v(&mut self, row: usize, col: usize, v: T)
     {
         if row < self.row_num && col < self.col_num {
-            let elm = self.elements.entry((row, col)).or_insert(v);
-            *elm = v;
+            let off = self.offset(row, col);
+            self.elements[off] = v;
         }
     }
 
     #[inline]
     pub fn get_v(&self, row: usize, col: usize) -> T
     {
-        T::clone(self.elements.get(&(row, col)).expect("not find key!"))
+        if row >= self.row_num || col >= self.col_num {
+            panic!("index ({}, {}) is out of bounds!", row, col);
+        }
+        self.elements[self.offset(row, col)]
+    }
+
+    pub fn get_ref(&self, row: usize, col: usize) -> &T
+    {
+        if row >= self.row_num || col >= self.col_num {
+            panic!("index ({}, {}) is out of bounds!", row, col);
+        }
+        &self.elements[self.offset(row, col)]
+    }
+
+    pub fn get_mut(&mut self, row: usize, col: usize) -> &mut T
+    {
+        if row >= self.row_num || col >= self.col_num {
+            panic!("index ({}, {}) is out of bounds!", row, col);
+        }
+        let off = self.offset(row, col);
+        &mut self.elements[off]
     }
 
     pub fn slice(&self, row_from: usize, row_to: usize, col_from: usize, col_to: usize) -> DenseMatrix<T>
     {
         let row_num = row_to - row_from + 1;
         let col_num = col_to - col_from + 1;
-        let mut elements = HashMap::with_capacity(row_num * col_num);
-        for i in row_from .. row_to + 1 {
-            for j in col_from .. col_to + 1 {
-                elements.insert((i - row_from, j - col_from), self.get_v(i, j));
-            }
-        } 
-        DenseMatrix {
-            row_num: row_num,
-            col_num: col_num,
-            elements: elements,
-        }
+        build_dense_matrix_f!(row_num , col_num , |i, j|{ self.get_v(i + row_from, j + col_from) })
+    }
+
+    pub fn try_slice(&self, row_from: usize, row_to: usize, col_from: usize, col_to: usize) -> AlgebraResult<DenseMatrix<T>>
+    {
+        error::check_slice(self.shape(), row_from, row_to, col_from, col_to)?;
+        Ok(self.slice(row_from, row_to, col_from, col_to))
     }
 
     pub fn safe_slice(&self, row_from: usize, row_to: usize, col_from: usize, col_to: usize) -> Option<DenseMatrix<T>>
     {
-        if row_to < self.row_num && col_to < self.col_num {
-            Some(self.slice(row_from, row_to, col_from, col_to))
-        }
-        else {
-            None
-        }
+        self.try_slice(row_from, row_to, col_from, col_to).ok()
     }
 
     #[inline]
@@ -138,8 +244,43 @@ where T: Num + Clone + Copy + Display
         self.col_num
     }
 
+    #[inline]
+    pub fn shape(&self) -> MatIndex
+    {
+        (self.row_num, self.col_num)
+    }
+
+    fn map<F>(&self, f: F) -> DenseMatrix<T>
+    where F: Fn(T) -> T
+    {
+        DenseMatrix {
+            row_num: self.row_num,
+            col_num: self.col_num,
+            layout: self.layout,
+            elements: self.elements.iter().map(|&v| f(v)).collect(),
+        }
+    }
+
+    fn zip_with<F>(&self, mat: &DenseMatrix<T>, f: F) -> DenseMatrix<T>
+    where F: Fn(T, T) -> T
+    {
+        assert!(self.row_num == mat.row_num && self.col_num == mat.col_num,
+                "the shapes of the matrices do not match!");
+        if self.layout == mat.layout {
+            DenseMatrix {
+                row_num: self.row_num,
+                col_num: self.col_num,
+                layout: self.layout,
+                elements: self.elements.iter().zip(mat.elements.iter()).map(|(&a, &b)| f(a, b)).collect(),
+            }
+        }
+        else {
+            build_dense_matrix_f!(self.row_num , self.col_num , |i, j|{ f(self.get_v(i, j), mat.get_v(i, j)) })
+        }
+    }
+
     pub fn scalar_mul(&self, k: T) -> DenseMatrix<T> {
-        build_dense_matrix_f!(self.row_num , self.col_num , |i, j|{ k * self.get_v(i, j) })
+        self.map(|v| k * v)
     }
 
     pub fn concat(&self, mat: &DenseMatrix<T>) -> DenseMatrix<T> {
@@ -156,83 +297,132 @@ where T: Num + Clone + Copy + Display
         build_dense_matrix_f!(row_num , col_num , calc_elm)
     }
 
-    pub fn dot_mul(&self, mat: &DenseMatrix<T>) -> DenseMatrix<T> {
-        let row_num = self.row();
-        let col_num = mat.col();
-        let calc_elm = |i, j| {
-            let mut sum = T::zero();
-            for k in 0 .. self.col() {
-                sum = sum + self.get_v(i, k) * mat.get_v(k, j);
+    pub fn try_concat(&self, mat: &DenseMatrix<T>) -> AlgebraResult<DenseMatrix<T>> {
+        if self.row_num != mat.row_num {
+            return Err(error::AlgebraError::DimensionMismatch { left: self.shape(), right: mat.shape() });
+        }
+        Ok(self.concat(mat))
+    }
+
+    pub fn dot_mul(&self, mat: &DenseMatrix<T>) -> DenseMatrix<T>
+    where T: Element
+    {
+        assert_eq!(self.col_num, mat.row_num, "the inner dimensions of the matrices do not match!");
+        let (m, n, p) = (self.row_num, self.col_num, mat.col_num);
+        let a = self.row_major_elements();
+        let b = mat.row_major_elements();
+        let mut c = vec![T::zero(); m * p];
+        // tiles of A, B and C stay in cache while they are reused, and the
+        // innermost loop runs over contiguous rows of B and C; every band of
+        // BLOCK_SIZE rows of C is one parallel task
+        parallel::for_each_row(&mut c, BLOCK_SIZE * p, m * n * p, |band, c_band| {
+            let ii = band * BLOCK_SIZE;
+            let i_end = (ii + BLOCK_SIZE).min(m);
+            for kk in (0 .. n).step_by(BLOCK_SIZE) {
+                let k_end = (kk + BLOCK_SIZE).min(n);
+                for jj in (0 .. p).step_by(BLOCK_SIZE) {
+                    let j_end = (jj + BLOCK_SIZE).min(p);
+                    for i in ii .. i_end {
+                        let c_row = &mut c_band[(i - ii) * p + jj .. (i - ii) * p + j_end];
+                        for k in kk .. k_end {
+                            let a_ik = a[i * n + k];
+                            let b_row = &b[k * p + jj .. k * p + j_end];
+                            for (c_ij, &b_kj) in c_row.iter_mut().zip(b_row.iter()) {
+                                *c_ij = *c_ij + a_ik * b_kj;
+                            }
+                        }
+                    }
+                }
             }
-            sum
-        };
-        build_dense_matrix_f!(row_num , col_num , calc_elm)
+        });
+        Self::from_vec(m, p, Layout::RowMajor, c)
     }
 
+    // the elementwise product
+    pub fn hadamard(&self, mat: &DenseMatrix<T>) -> DenseMatrix<T> {
+        self.zip_with(mat, |a, b| a * b)
+    }
+
+    #[deprecated(note = "`mul` is the elementwise product, use `hadamard` (or `*` for the matrix product)")]
     pub fn mul(&self, mat: &DenseMatrix<T>) -> DenseMatrix<T> {
-        let calc_elm = |i, j| {
-            self.get_v(i, j) * mat.get_v(i, j)
-        };
-        build_dense_matrix_f!(self.row_num , self.col_num , calc_elm)
+        self.hadamard(mat)
+    }
+
+    pub fn try_hadamard(&self, mat: &DenseMatrix<T>) -> AlgebraResult<DenseMatrix<T>> {
+        error::check_same_shape(self.shape(), mat.shape())?;
+        Ok(self.hadamard(mat))
+    }
+
+    pub fn try_dot_mul(&self, mat: &DenseMatrix<T>) -> AlgebraResult<DenseMatrix<T>>
+    where T: Element
+    {
+        error::check_product(self.shape(), mat.shape())?;
+        Ok(self.dot_mul(mat))
     }
 
     pub fn safe_dot_mul(&self, mat: &DenseMatrix<T>) -> Option<DenseMatrix<T>>
+    where T: Element
     {
-        if self.col_num == mat.row_num {
-            Some(self.dot_mul(mat))
-        }
-        else {
-            None
-        }
+        self.try_dot_mul(mat).ok()
     }
 
     pub fn neg(&self) -> DenseMatrix<T> {
         let zero = T::zero();
-        build_dense_matrix_f!(self.row_num , self.col_num , |i, j|{ zero - self.get_v(i, j) })
+        self.map(|v| zero - v)
     }
 
     pub fn add(&self, mat: &DenseMatrix<T>) -> DenseMatrix<T> {
-        build_dense_matrix_f!(self.row_num , self.col_num , |i, j|{ self.get_v(i, j) + mat.get_v(i, j) })
+        self.zip_with(mat, |a, b| a + b)
+    }
+
+    pub fn try_add(&self, mat: &DenseMatrix<T>) -> AlgebraResult<DenseMatrix<T>>
+    {
+        error::check_same_shape(self.shape(), mat.shape())?;
+        Ok(self.add(mat))
     }
 
     pub fn safe_add(&self, mat: &DenseMatrix<T>) -> Option<DenseMatrix<T>>
     {
-        if self.row_num == mat.row_num && self.col_num == mat.col_num {
-            Some(self.add(mat))
-        }
-        else {
-            None
-        }
+        self.try_add(mat).ok()
     }
 
     pub fn sub(&self, mat: &DenseMatrix<T>) -> DenseMatrix<T> {
-        build_dense_matrix_f!(self.row_num , self.col_num , |i, j|{ self.get_v(i, j) - mat.get_v(i, j) })
+        self.zip_with(mat, |a, b| a - b)
+    }
+
+    pub fn try_sub(&self, mat: &DenseMatrix<T>) -> AlgebraResult<DenseMatrix<T>>
+    {
+        error::check_same_shape(self.shape(), mat.shape())?;
+        Ok(self.sub(mat))
     }
 
     pub fn safe_sub(&self, mat: &DenseMatrix<T>) -> Option<DenseMatrix<T>>
     {
-        if self.row_num == mat.row_num && self.col_num == mat.col_num {
-            Some(self.sub(mat))
-        }
-        else {
-            None
-        }
+        self.try_sub(mat).ok()
     }
 
     pub fn show(&self) {
-        if self.row() * self.col() == self.elements.len() {
-            for i in 0 .. self.row_num {
-                for j in 0 .. self.col_num {
-                    print!("{} ", self.get_v(i, j));
-                }
-                println!("");
-            }
-        }
+        println!("{}", format::render(self.shape(), &FormatOptions::new(), |i, j| Some(self.get_v(i, j)), |v| v.to_string()));
     }
 
 }
 
-impl<T> DenseMatrix<T> 
+impl<T> PartialEq for DenseMatrix<T>
+where T: Num + Clone + Copy + Display
+{
+    fn eq(&self, other: &DenseMatrix<T>) -> bool
+    {
+        if self.row_num != other.row_num || self.col_num != other.col_num {
+            return false;
+        }
+        if self.layout == other.layout {
+            return self.elements == other.elements;
+        }
+        (0 .. self.row_num).all(|i| (0 .. self.col_num).all(|j| self.get_v(i, j) == other.get_v(i, j)))
+    }
+}
+
+impl<T> DenseMatrix<T>
 where T: Num + Clone + Copy + Display
 {
     pub fn ones(num: usize) -> DenseMatrix<T> {
@@ -300,162 +490,457 @@ where T: Num + Clone + Copy + Display
         sum
     }
 
-    
+
 }
 
-impl<T> DenseMatrix<T> 
-where T: Float + Clone + Copy + Display
+impl<T> DenseMatrix<T>
+where T: Scalar
 {
     pub fn cofactors(&self, row: usize, col: usize) -> DenseMatrix<T>
     {
-        let row_num = self.row_num - 1;
-        let col_num = self.col_num - 1;
-        let calc_elm = |i, j| {
-            let ri = if i < row {
-                i
-            } else {
-                i + 1
-            };
-            let cj = if j < col {
-                j
-            } else {
-                j + 1
-            };
-            self.get_v(ri, cj)
-        };
-        build_dense_matrix_f!(row_num , col_num , calc_elm)
+        direct::cofactors(self, row, col)
     }
 
     pub fn adjoint(&self) -> DenseMatrix<T>
     {
-        let scalar = -T::one();
-        let calc_elm = |i, j| {
-            scalar.powi((i + j) as i32) * self.cofactors(i, j).det_adj()
-        };
-        build_dense_matrix_f!(self.row_num , self.col_num , calc_elm).t()
+        direct::adjoint(self)
     }
 
     pub fn det_adj(&self) -> T
     {
-        if self.row_num == self.col_num {
-            match self.row_num {
-                1 => self.get_v(0, 0),
-                2 => self.get_v(0, 0) * self.get_v(1, 1) - self.get_v(0, 1) * self.get_v(1, 0),
-                _ => {
-                    let mut sum = T::zero();
-                    let scalar = -T::one();
-                    for i in 0 .. self.row_num {
-                        sum = sum + scalar.powi(i as i32) * self.get_v(i, 0) * self.cofactors(i, 0).det_adj();
-                    }
-                    sum
-                }
-            }
-        }
-        else {
-            T::zero()
-        }
+        direct::det_adj(self)
     }
 
     pub fn inv_adj(&self) -> Option<DenseMatrix<T>>
     {
-        let det = self.det_adj();
-        if det.abs() == T::zero() {
-            None
-        }
-        else {
-            Some(
-                self.adjoint().scalar_mul(T::one() / det)
-            )
-        }
+        direct::inv_adj(self)
     }
 
-    pub fn norm2(&self) -> T {
-        self.t().dot_mul(&self).trace().sqrt()
+    pub fn try_inv_adj(&self) -> AlgebraResult<DenseMatrix<T>>
+    {
+        direct::try_inv_adj(self)
     }
 
-    #[allow(dead_code)]
-    fn permute_rows(&mut self, row_i: usize, row_j: usize) {
-        for col in 0 .. self.col_num {
-            let tmp = self.get_v(row_i, col);
-            self.set_v(row_i, col, self.get_v(row_j, col));
-            self.set_v(row_j, col, tmp);
-        }
+    // the conjugate transpose A^H
+    pub fn conj_t(&self) -> DenseMatrix<T>
+    {
+        self.t().map(T::conj)
     }
 
-    #[allow(dead_code)]
-    fn pivoting(&mut self, site: usize) -> i32 {
-        let mut tmp = self.get_v(site, site).abs();
-        let mut index = site;
-        for row in (site + 1) .. self.row_num {
-            if self.get_v(row, site).abs() > tmp {
-                tmp = self.get_v(row, site).abs();
-                index = row;
-            }
+    pub fn is_hermitian(&self, tol: T::Real) -> bool
+    {
+        MatOps::is_hermitian(self, tol)
+    }
+
+    pub fn norm_1(&self) -> T::Real
+    {
+        common::modulus_norm_1(self)
+    }
+
+    pub fn norm_inf(&self) -> T::Real
+    {
+        common::modulus_norm_inf(self)
+    }
+
+    // the Frobenius norm, sqrt(trace(A^H A))
+    pub fn norm_fro(&self) -> T::Real
+    {
+        self.elements.iter().fold(T::Real::zero(), |sum, &v| sum + v.modulus_sqr()).sqrt()
+    }
+
+    // also the Frobenius norm, not the spectral one
+    pub fn norm2(&self) -> T::Real {
+        self.norm_fro()
+    }
+
+    pub fn norm_max(&self) -> T::Real
+    {
+        common::modulus_norm_max(self)
+    }
+
+    // the 2-norm ||A||_2, the largest singular value
+    pub fn norm_spectral(&self) -> T::Real
+    {
+        common::modulus_norm_spectral(self)
+    }
+
+    pub fn solve_ge<B>(mat_a: &DenseMatrix<T>, mat_b: &B) -> Option<B>
+    where B: RightHandSide<T>
+    {
+        direct::solve_ge(mat_a, mat_b)
+    }
+
+    pub fn try_solve_ge<B>(mat_a: &DenseMatrix<T>, mat_b: &B) -> AlgebraResult<B>
+    where B: RightHandSide<T>
+    {
+        direct::try_solve_ge(mat_a, mat_b)
+    }
+
+    pub fn inv_ge(&self) -> Option<DenseMatrix<T>>
+    {
+        direct::inv_ge(self)
+    }
+
+    pub fn try_inv_ge(&self) -> AlgebraResult<DenseMatrix<T>>
+    {
+        direct::try_inv_ge(self)
+    }
+
+    pub fn det_ge(&self) -> T {
+        direct::det_ge(self)
+    }
+
+    pub fn try_det_ge(&self) -> AlgebraResult<T> {
+        direct::try_det_ge(self)
+    }
+
+    // factorise once, then solve as often as needed
+    pub fn lu(&self) -> AlgebraResult<LU<T>>
+    {
+        LU::new(self)
+    }
+
+    // an estimate of ||A||_1 ||A^-1||_1 from an LU factorisation, infinite
+    // for a singular A; with an existing factorisation call LU::cond_est
+    pub fn cond_1_est(&self) -> AlgebraResult<T::Real>
+    {
+        match LU::new(self) {
+            Ok(lu) => Ok(lu.cond_est()),
+            Err(error::AlgebraError::SingularPivot { .. }) => Ok(T::Real::infinity()),
+            Err(err) => Err(err),
         }
-        if index != site {
-            self.permute_rows(index, site);
-            return 1;
+    }
+
+    // A = Q T Q^H with complex Q and T, for real matrices as well
+    pub fn schur(&self) -> AlgebraResult<Schur<T::Real>>
+    {
+        Schur::new(self)
+    }
+
+    pub fn expm(&self) -> AlgebraResult<DenseMatrix<T>>
+    {
+        function::expm(self)
+    }
+
+    pub fn logm(&self) -> AlgebraResult<DenseMatrix<T>>
+    {
+        function::logm(self)
+    }
+
+    pub fn sqrtm(&self) -> AlgebraResult<DenseMatrix<T>>
+    {
+        function::sqrtm(self)
+    }
+
+    pub fn powi(&self, k: i32) -> AlgebraResult<DenseMatrix<T>>
+    {
+        function::powi(self, k)
+    }
+
+    pub fn powf(&self, p: T::Real) -> AlgebraResult<DenseMatrix<T>>
+    {
+        function::powf(self, p)
+    }
+}
+
+impl<T> DenseMatrix<T>
+where T: Float + Clone + Copy + Display + Element
+{
+    pub fn cholesky(&self) -> AlgebraResult<Cholesky<T>>
+    {
+        Cholesky::new(self)
+    }
+
+    pub fn ldlt(&self) -> AlgebraResult<LDLT<T>>
+    {
+        LDLT::new(self)
+    }
+
+    pub fn is_symmetric(&self, tol: T) -> bool
+    {
+        MatOps::is_symmetric(self, tol)
+    }
+
+    pub fn qr(&self) -> QR<T>
+    {
+        QR::new(self)
+    }
+
+    // min ||A x - b|| through a column-pivoted QR, so rank-deficient A are fine
+    pub fn lstsq(&self, mat_b: &DenseMatrix<T>) -> AlgebraResult<LeastSquares<T>>
+    {
+        if self.row_num != mat_b.row_num {
+            return Err(error::AlgebraError::DimensionMismatch { left: self.shape(), right: mat_b.shape() });
         }
-        return 0;
+        Ok(QR::pivoted(self).lstsq(mat_b))
     }
 
-    pub fn solve_ge(mat_a: &DenseMatrix<T>, mat_b: &DenseMatrix<T>) -> Option<DenseMatrix<T>>
+    pub fn symmetric_eigen(&self, vectors: bool) -> AlgebraResult<SymmetricEigen<T>>
     {
-        let mut mat = mat_a.concat(&mat_b);
-        for r in 0 .. mat.row_num {
-            mat.pivoting(r);
-            let head = mat.get_v(r, r);
-            if head == T::zero() {
-                return None;
-            }    
-            for c in r .. mat.col_num {
-                mat.set_v(r, c, mat.get_v(r, c) / head);
-            }
-            for nr in r + 1 .. mat.row_num {
-                let row_head = mat.get_v(nr, r);
-                for c in r .. mat.col_num {
-                    mat.set_v(nr, c, mat.get_v(nr, c) - row_head * mat.get_v(r, c));
-                }
-            }  
+        SymmetricEigen::new(self, vectors)
+    }
+
+    pub fn eigen(&self, vectors: bool) -> AlgebraResult<Eigen<T>>
+    {
+        Eigen::new(self, vectors)
+    }
+
+    pub fn svd(&self) -> AlgebraResult<SVD<T>>
+    {
+        SVD::new(self)
+    }
+
+    // the Moore-Penrose inverse, defined for singular and rectangular A too
+    pub fn pinv(&self) -> AlgebraResult<DenseMatrix<T>>
+    {
+        let svd = SVD::new(self)?;
+        Ok(svd.pinv(svd.default_tol()))
+    }
+
+    pub fn rank(&self) -> AlgebraResult<usize>
+    {
+        let svd = SVD::new(self)?;
+        Ok(svd.rank(svd.default_tol()))
+    }
+
+    pub fn cond2(&self) -> AlgebraResult<T>
+    {
+        Ok(SVD::new(self)?.cond2())
+    }
+}
+
+impl<T> MatOps<T> for DenseMatrix<T>
+where T: Num + Clone + Copy + Display + Element
+{
+    fn new(row: usize, col: usize) -> DenseMatrix<T>
+    {
+        DenseMatrix::new(row, col)
+    }
+
+    fn t(&self) -> DenseMatrix<T>
+    {
+        DenseMatrix::t(self)
+    }
+
+    fn set_v(&mut self, row: usize, col: usize, v: T)
+    {
+        DenseMatrix::set_v(self, row, col, v)
+    }
+
+    fn get_v(&self, row: usize, col: usize) -> T
+    {
+        DenseMatrix::get_v(self, row, col)
+    }
+
+    fn slice(&self, row_from: usize, row_to: usize, col_from: usize, col_to: usize) -> DenseMatrix<T>
+    {
+        DenseMatrix::slice(self, row_from, row_to, col_from, col_to)
+    }
+
+    fn row(&self) -> usize
+    {
+        self.row_num
+    }
+
+    fn col(&self) -> usize
+    {
+        self.col_num
+    }
+
+    fn dot_mul(&self, mat: &DenseMatrix<T>) -> DenseMatrix<T>
+    {
+        DenseMatrix::dot_mul(self, mat)
+    }
+
+    fn eye(num: usize) -> DenseMatrix<T>
+    {
+        DenseMatrix::eye(num)
+    }
+
+    fn swap_rows(&mut self, row_i: usize, row_j: usize)
+    {
+        for col in 0 .. self.col_num {
+            let off_i = self.offset(row_i, col);
+            let off_j = self.offset(row_j, col);
+            self.elements.swap(off_i, off_j);
         }
+    }
 
-        for r in (0 .. mat.row_num).rev() {
-            for nr in (0 .. r).rev() {
-                let row_head = mat.get_v(nr, r);
-                for c in r .. mat.col_num {
-                    mat.set_v(nr, c, mat.get_v(nr, c) - row_head * mat.get_v(r, c));
+    // the rows are independent of each other, so with row-major storage every
+    // one of them is a task of its own
+    fn eliminate_rows(&mut self, rows: Range<usize>, col: usize, divisor: T, pivot_row: &[(usize, T)])
+    {
+        let update = |row: &mut [T], stride: usize| {
+            let factor = row[col * stride] / divisor;
+            if factor != T::zero() {
+                for &(j, v) in pivot_row {
+                    row[j * stride] = row[j * stride] - factor * v;
                 }
             }
+        };
+        match self.layout {
+            Layout::RowMajor => {
+                let n = self.col_num;
+                let work = rows.len() * pivot_row.len();
+                parallel::for_each_row(&mut self.elements[rows.start * n .. rows.end * n], n, work, |_, row| update(row, 1));
+            },
+            Layout::ColMajor => {
+                let m = self.row_num;
+                for i in rows {
+                    update(&mut self.elements[i ..], m);
+                }
+            },
         }
+    }
 
-        Some(mat.slice(0, mat.row_num - 1, mat_a.col_num, mat.col_num - 1))
+    fn concat(&self, mat: &DenseMatrix<T>) -> DenseMatrix<T>
+    {
+        DenseMatrix::concat(self, mat)
     }
 
-    pub fn inv_ge(&self) -> Option<DenseMatrix<T>>
+    fn scalar_mul(&self, k: T) -> DenseMatrix<T>
     {
-        Self::solve_ge(&self, &Self::eye(self.row_num))
+        DenseMatrix::scalar_mul(self, k)
     }
 
-    pub fn det_ge(&self) -> T {
-        let mut mat = self.scalar_mul(T::one());
-        let mut count = 0;
-        for r in 0 .. mat.row_num {
-            count += mat.pivoting(r);
-            let head = mat.get_v(r, r);
-            if head == T::zero() {
-                return T::zero();
-            }    
-            for nr in r + 1 .. mat.row_num {
-                let row_head = mat.get_v(nr, r) / head;
-                for c in r .. mat.col_num {
-                    mat.set_v(nr, c, mat.get_v(nr, c) - row_head * mat.get_v(r, c));
-                }
-            }  
+    fn add(&self, mat: &DenseMatrix<T>) -> DenseMatrix<T>
+    {
+        DenseMatrix::add(self, mat)
+    }
+
+    fn sub(&self, mat: &DenseMatrix<T>) -> DenseMatrix<T>
+    {
+        DenseMatrix::sub(self, mat)
+    }
+
+    fn mat_vec(&self, x: &[T]) -> Vec<T>
+    {
+        assert_eq!(x.len(), self.col_num, "the length of the vector does not match the matrix!");
+        parallel::map_range(self.row_num, self.row_num * self.col_num, |i| {
+            let mut sum = T::zero();
+            for (j, &x_j) in x.iter().enumerate() {
+                sum = sum + self.elements[self.offset(i, j)] * x_j;
+            }
+            sum
+        })
+    }
+}
+
+#[test]
+fn dense_matrix_layout_test() {
+    let mat = DenseMatrix::<f64>::from(2, 3, &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+    assert_eq!(mat.layout(), Layout::RowMajor);
+    assert_eq!(mat.strides(), (3, 1));
+    let mat_t = mat.t();
+    assert_eq!(mat_t.layout(), Layout::ColMajor);
+    assert_eq!(mat_t.strides(), (1, 3));
+    assert_eq!(mat_t.row(), 3);
+    assert_eq!(mat_t.get_v(2, 1), 6.0);
+    assert!(mat_t.to_layout(Layout::RowMajor) == mat_t);
+    assert_eq!(mat_t.to_layout(Layout::RowMajor).as_slice(), &[1.0, 4.0, 2.0, 5.0, 3.0, 6.0]);
+    assert_eq!(mat.slice(0, 1, 1, 2).as_slice(), &[2.0, 3.0, 5.0, 6.0]);
+}
+
+#[test]
+fn dense_matrix_dot_mul_test() {
+    // larger than one tile so that the edges of the blocked loop are exercised
+    let (m, n, p) = (70, 130, 65);
+    let mat_a = DenseMatrix::<f64>::from_vec(m, n, Layout::RowMajor, (0 .. m * n).map(|v| (v % 7) as f64).collect());
+    let mat_b = DenseMatrix::<f64>::from_vec(n, p, Layout::ColMajor, (0 .. n * p).map(|v| (v % 5) as f64 - 2.0).collect());
+    let mat_c = mat_a.dot_mul(&mat_b);
+    for i in 0 .. m {
+        for j in 0 .. p {
+            let mut sum = 0.0;
+            for k in 0 .. n {
+                sum += mat_a.get_v(i, k) * mat_b.get_v(k, j);
+            }
+            assert_eq!(mat_c.get_v(i, j), sum);
         }
-        let mut product = T::one();
-        for i in 0 .. self.row_num {
-            product = product * mat.get_v(i, i);
+    }
+    assert!(mat_b.t().dot_mul(&mat_a.t()) == mat_c.t());
+}
+
+#[test]
+fn dense_matrix_solve_ge_test() {
+    let mat_a = DenseMatrix::<f64>::from(3, 3, &[2.0, 1.0, 1.0, 4.0, -6.0, 0.0, -2.0, 7.0, 2.0]);
+    let mat_x = DenseMatrix::<f64>::from(3, 1, &[1.0, 2.0, 3.0]);
+    let mat_b = mat_a.dot_mul(&mat_x);
+    let x = DenseMatrix::solve_ge(&mat_a, &mat_b).unwrap();
+    assert!(x.sub(&mat_x).norm2() < 1e-12);
+    assert!((mat_a.det_ge() - mat_a.det_adj()).abs() < 1e-12);
+    assert!(mat_a.inv_ge().unwrap().dot_mul(&mat_a).sub(&DenseMatrix::eye(3)).norm2() < 1e-12);
+}
+
+#[test]
+fn dense_matrix_norm_test() {
+    use super::super::sparse::SparseMatrix;
+    use num::Complex;
+
+    let mat = DenseMatrix::<f64>::from(3, 2, &[1.0, -2.0, 0.0, 3.0, -4.0, 1.0]);
+    assert_eq!((mat.norm_1(), mat.norm_inf(), mat.norm_max()), (6.0, 5.0, 4.0));
+    assert_eq!(mat.norm_fro(), 31.0f64.sqrt());
+    let s_0 = mat.svd().unwrap().singular_values()[0];
+    assert!((mat.norm_spectral() - s_0).abs() < 1e-10 * s_0);
+    let sparse = SparseMatrix::from_dense(&mat);
+    assert_eq!((sparse.norm_max(), sparse.norm_fro()), (4.0, mat.norm_fro()));
+    assert!((sparse.norm_spectral() - s_0).abs() < 1e-10 * s_0);
+    assert_eq!(DenseMatrix::<f64>::new(2, 2).norm_spectral(), 0.0);
+    // a rotation scaled by 2 keeps every singular value at 2
+    let rot = DenseMatrix::from(2, 2, &[Complex::new(0.0, 2.0), Complex::new(0.0, 0.0), Complex::new(0.0, 0.0), Complex::new(2.0, 0.0)]);
+    assert!((rot.norm_spectral() - 2.0).abs() < 1e-12);
+
+    let hilbert = DenseMatrix::from_vec(4, 4, Layout::RowMajor, (0 .. 16).map(|k| 1.0 / (k / 4 + k % 4 + 1) as f64).collect());
+    let cond = hilbert.norm_1() * hilbert.inv_ge().unwrap().norm_1();
+    let est = hilbert.cond_1_est().unwrap();
+    assert!(est <= cond * (1.0 + 1e-8) && est > 0.5 * cond);
+    assert!((SparseMatrix::from_dense(&hilbert).cond_1_est().unwrap() - est).abs() < 1e-6 * cond);
+    assert_eq!(DenseMatrix::<f64>::ones(2).cond_1_est(), Ok(f64::INFINITY));
+    assert!(mat.cond_1_est().is_err());
+}
+
+#[test]
+fn dense_matrix_try_test() {
+    use super::super::super::super::error::AlgebraError;
+
+    let mat_a = DenseMatrix::<f64>::from(2, 3, &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+    let mat_b = DenseMatrix::<f64>::from(2, 2, &[1.0, 2.0, 3.0, 4.0]);
+    assert_eq!(mat_a.try_add(&mat_b).err(), Some(AlgebraError::DimensionMismatch { left: (2, 3), right: (2, 2) }));
+    assert_eq!(mat_a.try_dot_mul(&mat_b).err(), Some(AlgebraError::DimensionMismatch { left: (2, 3), right: (2, 2) }));
+    assert!(mat_b.try_dot_mul(&mat_a).is_ok());
+    assert!(mat_a.try_concat(&mat_b).is_ok());
+    assert_eq!(mat_a.try_concat(&mat_a.t()).err(), Some(AlgebraError::DimensionMismatch { left: (2, 3), right: (3, 2) }));
+    assert_eq!(mat_a.try_slice(0, 2, 0, 0).err(), Some(AlgebraError::IndexOutOfBounds { index: (2, 0), shape: (2, 3) }));
+    assert!(mat_a.safe_slice(0, 1, 1, 2).is_some());
+    assert_eq!(mat_a.try_inv_ge().err(), Some(AlgebraError::NotSquare { shape: (2, 3) }));
+    assert_eq!(DenseMatrix::<f64>::ones(2).try_inv_ge().err(), Some(AlgebraError::SingularPivot { index: 1 }));
+}
+
+#[test]
+fn dense_matrix_parallel_test() {
+    // big enough to be split over the threads with the `parallel` feature,
+    // which has to give the very same bits as the plain loops
+    let (m, n, p) = (150, 120, 90);
+    let entry = |i: usize, j: usize| ((i * 31 + j * 17) % 23) as f64 / 7.0 - 1.5;
+    let mat_a = DenseMatrix::from_vec(m, n, Layout::RowMajor, (0 .. m * n).map(|k| entry(k / n, k % n)).collect());
+    let mat_b = DenseMatrix::from_vec(n, p, Layout::ColMajor, (0 .. n * p).map(|k| entry(k, k / 3)).collect());
+    let prod = mat_a.dot_mul(&mat_b);
+    for i in 0 .. m {
+        for j in 0 .. p {
+            let sum = (0 .. n).fold(0.0, |s, k| s + mat_a.get_v(i, k) * mat_b.get_v(k, j));
+            assert_eq!(prod.get_v(i, j).to_bits(), sum.to_bits());
         }
-        (-T::one()).powi(count) * product
     }
-}
\ No newline at end of file
+    let x: Vec<f64> = (0 .. n).map(|j| entry(j, 3)).collect();
+    let y = MatOps::mat_vec(&mat_a, &x);
+    assert!((0 .. m).all(|i| y[i] == (0 .. n).fold(0.0, |s, j| s + mat_a.get_v(i, j) * x[j])));
+
+    // a diagonally dominant system, wide enough for the row updates to be split
+    let size = 200;
+    let mut mat_s = DenseMatrix::from_vec(size, size, Layout::RowMajor, (0 .. size * size).map(|k| entry(k / size, k % size)).collect());
+    for i in 0 .. size {
+        mat_s.set_v(i, i, 40.0);
+    }
+    let mat_x = DenseMatrix::from_vec(size, 2, Layout::RowMajor, (0 .. 2 * size).map(|k| entry(k, 1)).collect());
+    let x = DenseMatrix::solve_ge(&mat_s, &mat_s.dot_mul(&mat_x)).unwrap();
+    assert!(x.sub(&mat_x).norm_inf() < 1e-12);
+}
@@ -1,2 +1,2 @@
 pub mod dense_matrix;
-pub use self::dense_matrix::{DenseMatrix};
\ No newline at end of file
+pub use self::dense_matrix::{DenseMatrix, Layout};
\ No newline at end of file
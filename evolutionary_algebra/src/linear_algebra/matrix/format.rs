@@ -0,0 +1,220 @@
+extern crate num;
+
+use super::common::MatIndex;
+use super::dense::DenseMatrix;
+use super::sparse::SparseMatrix;
+use num::Num;
+use std::fmt::{self, Debug, Display, LowerExp};
+
+// How the matrices print. Display ("{}", "{:.3}") and LowerExp ("{:e}",
+// "{:.2e}") lay the values out in right-aligned columns; a matrix larger than
+// max_rows x max_cols shows its first and last rows and columns around "...",
+// unless the alternate flag ("{:#}") asks for everything. The positions a
+// sparse matrix does not store print as ".". Debug is the plain field dump.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct FormatOptions {
+    precision: Option<usize>,
+    exponent: bool,
+    align: bool,
+    max_rows: usize,
+    max_cols: usize,
+}
+
+impl FormatOptions {
+    pub fn new() -> FormatOptions
+    {
+        FormatOptions {
+            precision: None,
+            exponent: false,
+            align: true,
+            max_rows: 20,
+            max_cols: 20,
+        }
+    }
+
+    // digits after the point; without it the shortest exact form
+    pub fn precision(mut self, precision: usize) -> FormatOptions
+    {
+        self.precision = Some(precision);
+        self
+    }
+
+    pub fn exponent(mut self, exponent: bool) -> FormatOptions
+    {
+        self.exponent = exponent;
+        self
+    }
+
+    pub fn align(mut self, align: bool) -> FormatOptions
+    {
+        self.align = align;
+        self
+    }
+
+    // usize::MAX never elides
+    pub fn max_rows(mut self, max_rows: usize) -> FormatOptions
+    {
+        self.max_rows = max_rows;
+        self
+    }
+
+    pub fn max_cols(mut self, max_cols: usize) -> FormatOptions
+    {
+        self.max_cols = max_cols;
+        self
+    }
+
+    fn from_formatter(f: &fmt::Formatter, exponent: bool) -> FormatOptions
+    {
+        let mut options = FormatOptions::new().exponent(exponent);
+        options.precision = f.precision();
+        if f.alternate() {
+            options = options.max_rows(usize::MAX).max_cols(usize::MAX);
+        }
+        options
+    }
+
+    pub(crate) fn cell<T>(&self, v: T) -> String
+    where T: Display + LowerExp
+    {
+        match (self.exponent, self.precision) {
+            (false, None) => format!("{}", v),
+            (false, Some(p)) => format!("{:.*}", p, v),
+            (true, None) => format!("{:e}", v),
+            (true, Some(p)) => format!("{:.*e}", p, v),
+        }
+    }
+}
+
+impl Default for FormatOptions {
+    fn default() -> FormatOptions
+    {
+        FormatOptions::new()
+    }
+}
+
+// the indices shown out of 0 .. n, None where the "..." goes
+fn visible(n: usize, max: usize) -> Vec<Option<usize>>
+{
+    if n <= max {
+        return (0 .. n).map(Some).collect();
+    }
+    let (head, tail) = (max.div_ceil(2), max / 2);
+    (0 .. head).map(Some).chain(Some(None)).chain((n - tail .. n).map(Some)).collect()
+}
+
+// the grid of a row_num x col_num matrix; get gives None for an unstored entry
+pub(crate) fn render<T, G, C>(shape: MatIndex, options: &FormatOptions, get: G, cell: C) -> String
+where G: Fn(usize, usize) -> Option<T>, C: Fn(T) -> String
+{
+    let rows = visible(shape.0, options.max_rows);
+    let cols = visible(shape.1, options.max_cols);
+    let cells: Vec<Vec<String>> = rows.iter().map(|&i| cols.iter().map(|&j| match (i, j) {
+        (Some(i), Some(j)) => get(i, j).map_or_else(|| ".".to_string(), &cell),
+        _ => "...".to_string(),
+    }).collect()).collect();
+
+    let mut widths = vec![0; cols.len()];
+    if options.align {
+        for row in cells.iter() {
+            for (w, c) in widths.iter_mut().zip(row.iter()) {
+                *w = (*w).max(c.chars().count());
+            }
+        }
+    }
+    let lines: Vec<String> = cells.iter().map(|row| {
+        let padded: Vec<String> = row.iter().zip(widths.iter()).map(|(c, &w)| format!("{:>1$}", c, w)).collect();
+        padded.join("  ")
+    }).collect();
+    lines.join("\n")
+}
+
+impl<T> DenseMatrix<T>
+where T: Num + Clone + Copy + Display + LowerExp
+{
+    pub fn to_string_with(&self, options: &FormatOptions) -> String
+    {
+        render(self.shape(), options, |i, j| Some(self.get_v(i, j)), |v| options.cell(v))
+    }
+}
+
+impl<T> SparseMatrix<T>
+where T: Num + Clone + Copy + Display + LowerExp
+{
+    pub fn to_string_with(&self, options: &FormatOptions) -> String
+    {
+        render(self.shape(), options, |i, j| self.get(i, j), |v| options.cell(v))
+    }
+}
+
+macro_rules! impl_mat_fmt {
+    ($mat:ident) => {
+        impl<T> fmt::Display for $mat<T>
+        where T: Num + Clone + Copy + Display + LowerExp
+        {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str(&self.to_string_with(&FormatOptions::from_formatter(f, false)))
+            }
+        }
+
+        impl<T> fmt::LowerExp for $mat<T>
+        where T: Num + Clone + Copy + Display + LowerExp
+        {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str(&self.to_string_with(&FormatOptions::from_formatter(f, true)))
+            }
+        }
+    };
+}
+
+impl_mat_fmt!(DenseMatrix);
+impl_mat_fmt!(SparseMatrix);
+
+impl<T> fmt::Debug for DenseMatrix<T>
+where T: Num + Clone + Copy + Display + Debug
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("DenseMatrix")
+            .field("rows", &self.row())
+            .field("cols", &self.col())
+            .field("layout", &self.layout())
+            .field("elements", &self.as_slice())
+            .finish()
+    }
+}
+
+impl<T> fmt::Debug for SparseMatrix<T>
+where T: Num + Clone + Copy + Display + Debug
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SparseMatrix")
+            .field("rows", &self.row())
+            .field("cols", &self.col())
+            .field("entries", &self.iter().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+#[test]
+fn format_test() {
+    let mat = DenseMatrix::<f64>::from(2, 3, &[1.0, -2.5, 0.125, 10.0, 0.0, 3.0]);
+    assert_eq!(format!("{}", mat), " 1  -2.5  0.125\n10     0      3");
+    assert_eq!(format!("{:.1}", mat), " 1.0  -2.5  0.1\n10.0   0.0  3.0");
+    assert_eq!(format!("{:.1e}", mat), "1.0e0  -2.5e0  1.2e-1\n1.0e1   0.0e0   3.0e0");
+    assert_eq!(mat.to_string_with(&FormatOptions::new().align(false).max_cols(2)), "1  ...  0.125\n10  ...  3");
+
+    let big = DenseMatrix::<i32>::ones(30);
+    let text = format!("{}", big);
+    assert_eq!(text.lines().count(), 21);
+    assert_eq!(text.lines().nth(10), Some(vec!["..."; 21].join("  ").as_str()));
+    assert_eq!(format!("{:#}", big).lines().count(), 30);
+
+    let mut sparse = SparseMatrix::<f64>::new(2, 2);
+    sparse.set_v(1, 0, 4.0);
+    assert_eq!(format!("{}", sparse), ".  .\n4  .");
+    assert_eq!(format!("{:?}", sparse), "SparseMatrix { rows: 2, cols: 2, entries: [(1, 0, 4.0)] }");
+    let lower = SparseMatrix::from_dense(&DenseMatrix::<f64>::ones(6).tri_l());
+    assert_eq!(lower.spy(6, 6), "*.....\n**....\n***...\n****..\n*****.\n******");
+    assert_eq!(lower.spy(3, 3), "*..\n**.\n***");
+    assert_eq!(format!("{:?}", DenseMatrix::<f32>::eye(1)), "DenseMatrix { rows: 1, cols: 1, layout: RowMajor, elements: [1.0] }");
+}
@@ -1,6 +1,9 @@
 pub mod dense;
 pub mod sparse;
+pub mod format;
 mod common;
+mod ops;
 
 pub use self::common::{MatIndex, MatOps};
+pub use self::format::FormatOptions;
 
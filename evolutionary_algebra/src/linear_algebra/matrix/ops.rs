@@ -0,0 +1,269 @@
+// std::ops for the matrix types. `*` between two matrices is the matrix
+// product (dot_mul); the elementwise product is spelled `hadamard`. Only the
+// dense matrix indexes: a `&mut T` into a sparse one could leave a stored zero
+// behind, and a `&T` to an absent entry would need a stored zero to point to,
+// so it is read through get_v and written through set_v or update.
+use super::dense::DenseMatrix;
+use super::sparse::SparseMatrix;
+use super::super::super::parallel::Element;
+use num::Num;
+use std::fmt::Display;
+use std::ops::{Add, AddAssign, Index, IndexMut, Mul, MulAssign, Neg, Sub, SubAssign};
+
+// implements a binary operator for every owned/borrowed combination of operands
+macro_rules! impl_mat_binop {
+    ($op_trait:ident, $op_fn:ident, $lhs:ident, $rhs:ident, $out:ident, $method:path) => {
+        impl<'a, 'b, T> $op_trait<&'b $rhs<T>> for &'a $lhs<T>
+        where T: Num + Clone + Copy + Display + Element
+        {
+            type Output = $out<T>;
+            fn $op_fn(self, mat: &'b $rhs<T>) -> $out<T> {
+                $method(self, mat)
+            }
+        }
+
+        impl<'b, T> $op_trait<&'b $rhs<T>> for $lhs<T>
+        where T: Num + Clone + Copy + Display + Element
+        {
+            type Output = $out<T>;
+            fn $op_fn(self, mat: &'b $rhs<T>) -> $out<T> {
+                $method(&self, mat)
+            }
+        }
+
+        impl<'a, T> $op_trait<$rhs<T>> for &'a $lhs<T>
+        where T: Num + Clone + Copy + Display + Element
+        {
+            type Output = $out<T>;
+            fn $op_fn(self, mat: $rhs<T>) -> $out<T> {
+                $method(self, &mat)
+            }
+        }
+
+        impl<T> $op_trait<$rhs<T>> for $lhs<T>
+        where T: Num + Clone + Copy + Display + Element
+        {
+            type Output = $out<T>;
+            fn $op_fn(self, mat: $rhs<T>) -> $out<T> {
+                $method(&self, &mat)
+            }
+        }
+    };
+}
+
+macro_rules! impl_mat_assign_op {
+    ($op_trait:ident, $op_fn:ident, $lhs:ident, $rhs:ident, $method:path) => {
+        impl<'b, T> $op_trait<&'b $rhs<T>> for $lhs<T>
+        where T: Num + Clone + Copy + Display
+        {
+            fn $op_fn(&mut self, mat: &'b $rhs<T>) {
+                *self = $method(self, mat);
+            }
+        }
+
+        impl<T> $op_trait<$rhs<T>> for $lhs<T>
+        where T: Num + Clone + Copy + Display
+        {
+            fn $op_fn(&mut self, mat: $rhs<T>) {
+                *self = $method(self, &mat);
+            }
+        }
+    };
+}
+
+macro_rules! impl_mat_unop_and_scalar {
+    ($mat:ident) => {
+        impl<'a, T> Neg for &'a $mat<T>
+        where T: Num + Clone + Copy + Display
+        {
+            type Output = $mat<T>;
+            fn neg(self) -> $mat<T> {
+                $mat::neg(self)
+            }
+        }
+
+        impl<T> Neg for $mat<T>
+        where T: Num + Clone + Copy + Display
+        {
+            type Output = $mat<T>;
+            fn neg(self) -> $mat<T> {
+                $mat::neg(&self)
+            }
+        }
+
+        impl<'a, T> Mul<T> for &'a $mat<T>
+        where T: Num + Clone + Copy + Display
+        {
+            type Output = $mat<T>;
+            fn mul(self, k: T) -> $mat<T> {
+                self.scalar_mul(k)
+            }
+        }
+
+        impl<T> Mul<T> for $mat<T>
+        where T: Num + Clone + Copy + Display
+        {
+            type Output = $mat<T>;
+            fn mul(self, k: T) -> $mat<T> {
+                self.scalar_mul(k)
+            }
+        }
+
+        impl<T> MulAssign<T> for $mat<T>
+        where T: Num + Clone + Copy + Display
+        {
+            fn mul_assign(&mut self, k: T) {
+                *self = self.scalar_mul(k);
+            }
+        }
+
+        // the scalar on the left needs one impl per concrete scalar type
+        impl_scalar_lhs_mul!($mat, f32);
+        impl_scalar_lhs_mul!($mat, f64);
+    };
+}
+
+macro_rules! impl_scalar_lhs_mul {
+    ($mat:ident, $scalar:ty) => {
+        impl<'a> Mul<&'a $mat<$scalar>> for $scalar {
+            type Output = $mat<$scalar>;
+            fn mul(self, mat: &'a $mat<$scalar>) -> $mat<$scalar> {
+                mat.scalar_mul(self)
+            }
+        }
+
+        impl Mul<$mat<$scalar>> for $scalar {
+            type Output = $mat<$scalar>;
+            fn mul(self, mat: $mat<$scalar>) -> $mat<$scalar> {
+                mat.scalar_mul(self)
+            }
+        }
+    };
+}
+
+fn dense_plus_sparse<T>(dense: &DenseMatrix<T>, sparse: &SparseMatrix<T>) -> DenseMatrix<T>
+where T: Num + Clone + Copy + Display
+{
+    assert!(dense.row() == sparse.row() && dense.col() == sparse.col(), "the shapes of the matrices do not match!");
+    let mut res = dense.clone();
+    for (i, j, v) in sparse.iter() {
+        res.set_v(i, j, res.get_v(i, j) + v);
+    }
+    res
+}
+
+fn dense_minus_sparse<T>(dense: &DenseMatrix<T>, sparse: &SparseMatrix<T>) -> DenseMatrix<T>
+where T: Num + Clone + Copy + Display
+{
+    assert!(dense.row() == sparse.row() && dense.col() == sparse.col(), "the shapes of the matrices do not match!");
+    let mut res = dense.clone();
+    for (i, j, v) in sparse.iter() {
+        res.set_v(i, j, res.get_v(i, j) - v);
+    }
+    res
+}
+
+fn sparse_plus_dense<T>(sparse: &SparseMatrix<T>, dense: &DenseMatrix<T>) -> DenseMatrix<T>
+where T: Num + Clone + Copy + Display
+{
+    dense_plus_sparse(dense, sparse)
+}
+
+fn sparse_minus_dense<T>(sparse: &SparseMatrix<T>, dense: &DenseMatrix<T>) -> DenseMatrix<T>
+where T: Num + Clone + Copy + Display
+{
+    dense_plus_sparse(&dense.neg(), sparse)
+}
+
+fn dense_times_sparse<T>(dense: &DenseMatrix<T>, sparse: &SparseMatrix<T>) -> DenseMatrix<T>
+where T: Num + Clone + Copy + Display
+{
+    // (D S) = (S^T D^T)^T
+    sparse.to_csr().t().dot_mul_dense(&dense.t()).t()
+}
+
+fn sparse_times_dense<T>(sparse: &SparseMatrix<T>, dense: &DenseMatrix<T>) -> DenseMatrix<T>
+where T: Num + Clone + Copy + Display
+{
+    sparse.to_csr().dot_mul_dense(dense)
+}
+
+impl_mat_binop!(Add, add, DenseMatrix, DenseMatrix, DenseMatrix, DenseMatrix::add);
+impl_mat_binop!(Sub, sub, DenseMatrix, DenseMatrix, DenseMatrix, DenseMatrix::sub);
+impl_mat_binop!(Mul, mul, DenseMatrix, DenseMatrix, DenseMatrix, DenseMatrix::dot_mul);
+impl_mat_binop!(Add, add, SparseMatrix, SparseMatrix, SparseMatrix, SparseMatrix::add);
+impl_mat_binop!(Sub, sub, SparseMatrix, SparseMatrix, SparseMatrix, SparseMatrix::sub);
+impl_mat_binop!(Mul, mul, SparseMatrix, SparseMatrix, SparseMatrix, SparseMatrix::dot_mul);
+impl_mat_binop!(Add, add, DenseMatrix, SparseMatrix, DenseMatrix, dense_plus_sparse);
+impl_mat_binop!(Sub, sub, DenseMatrix, SparseMatrix, DenseMatrix, dense_minus_sparse);
+impl_mat_binop!(Mul, mul, DenseMatrix, SparseMatrix, DenseMatrix, dense_times_sparse);
+impl_mat_binop!(Add, add, SparseMatrix, DenseMatrix, DenseMatrix, sparse_plus_dense);
+impl_mat_binop!(Sub, sub, SparseMatrix, DenseMatrix, DenseMatrix, sparse_minus_dense);
+impl_mat_binop!(Mul, mul, SparseMatrix, DenseMatrix, DenseMatrix, sparse_times_dense);
+
+impl_mat_assign_op!(AddAssign, add_assign, DenseMatrix, DenseMatrix, DenseMatrix::add);
+impl_mat_assign_op!(SubAssign, sub_assign, DenseMatrix, DenseMatrix, DenseMatrix::sub);
+impl_mat_assign_op!(AddAssign, add_assign, SparseMatrix, SparseMatrix, SparseMatrix::add);
+impl_mat_assign_op!(SubAssign, sub_assign, SparseMatrix, SparseMatrix, SparseMatrix::sub);
+impl_mat_assign_op!(AddAssign, add_assign, DenseMatrix, SparseMatrix, dense_plus_sparse);
+impl_mat_assign_op!(SubAssign, sub_assign, DenseMatrix, SparseMatrix, dense_minus_sparse);
+
+impl_mat_unop_and_scalar!(DenseMatrix);
+impl_mat_unop_and_scalar!(SparseMatrix);
+
+impl<T> Index<(usize, usize)> for DenseMatrix<T>
+where T: Num + Clone + Copy + Display
+{
+    type Output = T;
+    fn index(&self, (row, col): (usize, usize)) -> &T {
+        self.get_ref(row, col)
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for DenseMatrix<T>
+where T: Num + Clone + Copy + Display
+{
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut T {
+        self.get_mut(row, col)
+    }
+}
+
+#[test]
+fn matrix_ops_test() {
+    let a = DenseMatrix::<f64>::from(2, 2, &[1.0, 2.0, 3.0, 4.0]);
+    let b = DenseMatrix::<f64>::from(2, 2, &[0.0, 1.0, 1.0, 0.0]);
+    let mut s = SparseMatrix::<f64>::new(2, 2);
+    s.set_v(0, 1, 1.0);
+    s.update(1, 0, |v| v + 1.0);
+    s.update(1, 1, |v| v * 2.0);
+    assert_eq!(s.nnz(), 2);
+    assert_eq!(s.get_v(1, 1), 0.0);
+
+    assert!(&a + &b == DenseMatrix::from(2, 2, &[1.0, 3.0, 4.0, 4.0]));
+    assert!(&a - &b == DenseMatrix::from(2, 2, &[1.0, 1.0, 2.0, 4.0]));
+    assert!(&a * &b == DenseMatrix::from(2, 2, &[2.0, 1.0, 4.0, 3.0]));
+    assert!(&b * &a == DenseMatrix::from(2, 2, &[3.0, 4.0, 1.0, 2.0]));
+    assert!(-&a == a.scalar_mul(-1.0));
+    assert!(2.0 * &a == &a * 2.0);
+    assert!(a.hadamard(&b) == DenseMatrix::from(2, 2, &[0.0, 2.0, 3.0, 0.0]));
+
+    assert!(&a + &s == &a + &b);
+    assert!(&s + &a == &a + &b);
+    assert!(&a - &s == &a - &b);
+    assert!(&s - &a == &b - &a);
+    assert!(&a * &s == &a * &b);
+    assert!(&s * &a == &b * &a);
+    assert!((&s * &s).to_dense() == DenseMatrix::eye(2));
+
+    let mut c = a.clone();
+    c += &b;
+    c -= s.clone();
+    c *= 3.0;
+    c[(0, 0)] = 0.0;
+    assert!(c == DenseMatrix::from(2, 2, &[0.0, 6.0, 9.0, 12.0]));
+    assert_eq!(c[(1, 0)], 9.0);
+
+    let mut d = s.clone() + s.clone() * 2.0;
+    d -= &s;
+    assert!(d.to_dense() == b.scalar_mul(2.0));
+}
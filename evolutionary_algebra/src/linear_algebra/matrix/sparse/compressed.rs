@@ -0,0 +1,154 @@
+// Helpers shared by the compressed row (CSR) and compressed column (CSC)
+// formats. Both store, for every "major" line (a row for CSR, a column for
+// CSC), the sorted "minor" indices and values of its nonzeros; the routines
+// below only ever talk about major/minor so that they serve both formats.
+use super::super::super::super::parallel::{self, Element};
+use num::Num;
+
+pub(crate) struct Compressed<T> {
+    pub indptr: Vec<usize>,
+    pub indices: Vec<usize>,
+    pub data: Vec<T>,
+}
+
+// builds the compressed arrays from unordered triplets, summing duplicates
+pub(crate) fn compress<T>(major_num: usize, majors: &[usize], minors: &[usize], values: &[T]) -> Compressed<T>
+where T: Num + Copy
+{
+    let mut indptr = vec![0; major_num + 1];
+    for &major in majors {
+        indptr[major + 1] += 1;
+    }
+    for i in 0 .. major_num {
+        indptr[i + 1] += indptr[i];
+    }
+    let mut next = indptr.clone();
+    let mut pairs = vec![(0, T::zero()); values.len()];
+    for ((&major, &minor), &v) in majors.iter().zip(minors.iter()).zip(values.iter()) {
+        pairs[next[major]] = (minor, v);
+        next[major] += 1;
+    }
+
+    let mut indices = Vec::with_capacity(values.len());
+    let mut data = Vec::with_capacity(values.len());
+    let mut new_indptr = Vec::with_capacity(major_num + 1);
+    new_indptr.push(0);
+    for i in 0 .. major_num {
+        let line = &mut pairs[indptr[i] .. indptr[i + 1]];
+        line.sort_by_key(|&(minor, _)| minor);
+        for &(minor, v) in line.iter() {
+            if indices.len() > new_indptr[i] && indices[indices.len() - 1] == minor {
+                let last = data.len() - 1;
+                data[last] = data[last] + v;
+            }
+            else {
+                indices.push(minor);
+                data.push(v);
+            }
+        }
+        new_indptr.push(indices.len());
+    }
+    Compressed { indptr: new_indptr, indices, data }
+}
+
+// swaps the roles of major and minor, e.g. CSR -> CSC of the same matrix
+pub(crate) fn transpose<T>(minor_num: usize, indptr: &[usize], indices: &[usize], data: &[T]) -> Compressed<T>
+where T: Num + Copy
+{
+    let major_num = indptr.len() - 1;
+    let mut new_indptr = vec![0; minor_num + 1];
+    for &minor in indices {
+        new_indptr[minor + 1] += 1;
+    }
+    for i in 0 .. minor_num {
+        new_indptr[i + 1] += new_indptr[i];
+    }
+    let mut next = new_indptr.clone();
+    let mut new_indices = vec![0; indices.len()];
+    let mut new_data = vec![T::zero(); data.len()];
+    // walking the old majors in order keeps the new minor indices sorted
+    for major in 0 .. major_num {
+        for p in indptr[major] .. indptr[major + 1] {
+            let q = next[indices[p]];
+            new_indices[q] = major;
+            new_data[q] = data[p];
+            next[indices[p]] += 1;
+        }
+    }
+    Compressed { indptr: new_indptr, indices: new_indices, data: new_data }
+}
+
+// Gustavson's product: every output line is the combination of the `lookup`
+// lines selected by the entries of the matching `driver` line
+pub(crate) fn product<T>(minor_num: usize,
+                         driver: (&[usize], &[usize], &[T]),
+                         lookup: (&[usize], &[usize], &[T])) -> Compressed<T>
+where T: Num + Copy
+{
+    let (d_ptr, d_idx, d_val) = driver;
+    let (l_ptr, l_idx, l_val) = lookup;
+    let major_num = d_ptr.len() - 1;
+    let mut marker = vec![usize::MAX; minor_num];
+    let mut acc = vec![T::zero(); minor_num];
+    let mut indptr = Vec::with_capacity(major_num + 1);
+    let mut indices = Vec::new();
+    let mut data = Vec::new();
+    indptr.push(0);
+    for i in 0 .. major_num {
+        let start = indices.len();
+        for p in d_ptr[i] .. d_ptr[i + 1] {
+            let k = d_idx[p];
+            for q in l_ptr[k] .. l_ptr[k + 1] {
+                let j = l_idx[q];
+                if marker[j] != i {
+                    marker[j] = i;
+                    acc[j] = d_val[p] * l_val[q];
+                    indices.push(j);
+                }
+                else {
+                    acc[j] = acc[j] + d_val[p] * l_val[q];
+                }
+            }
+        }
+        indices[start ..].sort_unstable();
+        for &j in &indices[start ..] {
+            data.push(acc[j]);
+        }
+        indptr.push(indices.len());
+    }
+    Compressed { indptr, indices, data }
+}
+
+// y = M x when the major lines are rows
+pub(crate) fn rows_mat_vec<T>(indptr: &[usize], indices: &[usize], data: &[T], x: &[T]) -> Vec<T>
+where T: Num + Copy + Element
+{
+    parallel::map_range(indptr.len() - 1, data.len(), |i| {
+        let mut sum = T::zero();
+        for p in indptr[i] .. indptr[i + 1] {
+            sum = sum + data[p] * x[indices[p]];
+        }
+        sum
+    })
+}
+
+// y = M x when the major lines are columns
+pub(crate) fn cols_mat_vec<T>(row_num: usize, indptr: &[usize], indices: &[usize], data: &[T], x: &[T]) -> Vec<T>
+where T: Num + Copy
+{
+    let mut y = vec![T::zero(); row_num];
+    for (j, &x_j) in x.iter().enumerate().take(indptr.len() - 1) {
+        for p in indptr[j] .. indptr[j + 1] {
+            y[indices[p]] = y[indices[p]] + data[p] * x_j;
+        }
+    }
+    y
+}
+
+// position of `minor` inside one sorted major line
+pub(crate) fn find<T>(indptr: &[usize], indices: &[usize], data: &[T], major: usize, minor: usize) -> Option<T>
+where T: Copy
+{
+    let (start, end) = (indptr[major], indptr[major + 1]);
+    indices[start .. end].binary_search(&minor).ok().map(|p| data[start + p])
+}
@@ -0,0 +1,136 @@
+extern crate num;
+
+use super::compressed;
+use super::csc_matrix::CscMatrix;
+use super::csr_matrix::CsrMatrix;
+use super::sparse_matrix::SparseMatrix;
+use super::super::dense::DenseMatrix;
+use num::Num;
+use std::fmt::Display;
+
+// Coordinate (triplet) format, meant for assembly: entries are appended in
+// any order and duplicated positions are summed when compressing.
+#[derive(Clone)]
+pub struct CooMatrix<T>
+where T: Num + Clone + Copy + Display
+{
+    row_num: usize,
+    col_num: usize,
+    rows: Vec<usize>,
+    cols: Vec<usize>,
+    values: Vec<T>,
+}
+
+impl<T> CooMatrix<T>
+where T: Num + Clone + Copy + Display
+{
+    pub fn new(row_num: usize, col_num: usize) -> CooMatrix<T>
+    {
+        Self::with_capacity(row_num, col_num, 0)
+    }
+
+    pub fn with_capacity(row_num: usize, col_num: usize, capacity: usize) -> CooMatrix<T>
+    {
+        CooMatrix {
+            row_num,
+            col_num,
+            rows: Vec::with_capacity(capacity),
+            cols: Vec::with_capacity(capacity),
+            values: Vec::with_capacity(capacity),
+        }
+    }
+
+    pub fn push(&mut self, row: usize, col: usize, v: T)
+    {
+        assert!(row < self.row_num && col < self.col_num, "index ({}, {}) is out of bounds!", row, col);
+        self.rows.push(row);
+        self.cols.push(col);
+        self.values.push(v);
+    }
+
+    #[inline]
+    pub fn row(&self) -> usize
+    {
+        self.row_num
+    }
+
+    #[inline]
+    pub fn col(&self) -> usize
+    {
+        self.col_num
+    }
+
+    // the number of stored triplets, duplicates included
+    pub fn nnz(&self) -> usize
+    {
+        self.values.len()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (usize, usize, T)> + '_
+    {
+        self.rows.iter().zip(self.cols.iter()).zip(self.values.iter()).map(|((&i, &j), &v)| (i, j, v))
+    }
+
+    pub fn to_csr(&self) -> CsrMatrix<T>
+    {
+        let c = compressed::compress(self.row_num, &self.rows, &self.cols, &self.values);
+        CsrMatrix::from_raw(self.row_num, self.col_num, c.indptr, c.indices, c.data)
+    }
+
+    pub fn to_csc(&self) -> CscMatrix<T>
+    {
+        let c = compressed::compress(self.col_num, &self.cols, &self.rows, &self.values);
+        CscMatrix::from_raw(self.row_num, self.col_num, c.indptr, c.indices, c.data)
+    }
+
+    pub fn to_sparse(&self) -> SparseMatrix<T>
+    {
+        let mut mat = SparseMatrix::new(self.row_num, self.col_num);
+        for (i, j, v) in self.iter() {
+            mat.set_v(i, j, mat.get_v(i, j) + v);
+        }
+        mat
+    }
+
+    pub fn to_dense(&self) -> DenseMatrix<T>
+    {
+        let mut mat = DenseMatrix::new(self.row_num, self.col_num);
+        for (i, j, v) in self.iter() {
+            mat.set_v(i, j, mat.get_v(i, j) + v);
+        }
+        mat
+    }
+
+    pub fn from_dense(mat: &DenseMatrix<T>) -> CooMatrix<T>
+    {
+        let mut coo = Self::new(mat.row(), mat.col());
+        for i in 0 .. mat.row() {
+            for j in 0 .. mat.col() {
+                let v = mat.get_v(i, j);
+                if v != T::zero() {
+                    coo.push(i, j, v);
+                }
+            }
+        }
+        coo
+    }
+}
+
+#[test]
+fn coo_matrix_test() {
+    let mut coo = CooMatrix::<f64>::new(3, 4);
+    coo.push(2, 1, 1.0);
+    coo.push(0, 3, 2.0);
+    coo.push(2, 1, 4.0);
+    coo.push(1, 0, -1.0);
+    assert_eq!(coo.nnz(), 4);
+    let csr = coo.to_csr();
+    assert_eq!(csr.nnz(), 3);
+    assert_eq!(csr.get_v(2, 1), 5.0);
+    assert_eq!(csr.pattern(), vec![(0, 3), (1, 0), (2, 1)]);
+    let csc = coo.to_csc();
+    assert_eq!(csc.nnz(), 3);
+    assert_eq!(csc.get_v(0, 3), 2.0);
+    assert!(coo.to_dense() == csc.to_dense());
+    assert_eq!(coo.to_sparse().get_v(2, 1), 5.0);
+}
@@ -0,0 +1,227 @@
+extern crate num;
+
+use super::super::common::MatIndex;
+use super::compressed;
+use super::coo_matrix::CooMatrix;
+use super::csr_matrix::CsrMatrix;
+use super::sparse_matrix::SparseMatrix;
+use super::super::dense::DenseMatrix;
+use super::super::super::super::parallel::Element;
+use num::Num;
+use std::fmt::Display;
+
+// Compressed sparse column format: the row indices and values of column j are
+// indices[indptr[j] .. indptr[j + 1]] and data[indptr[j] .. indptr[j + 1]],
+// with the row indices sorted inside every column.
+#[derive(Clone)]
+pub struct CscMatrix<T>
+where T: Num + Clone + Copy + Display
+{
+    row_num: usize,
+    col_num: usize,
+    indptr: Vec<usize>,
+    indices: Vec<usize>,
+    data: Vec<T>,
+}
+
+impl<T> CscMatrix<T>
+where T: Num + Clone + Copy + Display
+{
+    pub fn new(row_num: usize, col_num: usize) -> CscMatrix<T>
+    {
+        CscMatrix {
+            row_num,
+            col_num,
+            indptr: vec![0; col_num + 1],
+            indices: Vec::new(),
+            data: Vec::new(),
+        }
+    }
+
+    pub fn from_raw(row_num: usize, col_num: usize,
+                    indptr: Vec<usize>, indices: Vec<usize>, data: Vec<T>) -> CscMatrix<T>
+    {
+        assert_eq!(indptr.len(), col_num + 1, "indptr must have col_num + 1 entries!");
+        assert_eq!(indices.len(), data.len(), "indices and data must have the same length!");
+        assert_eq!(indptr[col_num], data.len(), "indptr does not match the number of nonzeros!");
+        for j in 0 .. col_num {
+            let col = &indices[indptr[j] .. indptr[j + 1]];
+            assert!(col.windows(2).all(|w| w[0] < w[1]), "row indices must be sorted and unique!");
+            assert!(col.iter().all(|&i| i < row_num), "row index is out of bounds!");
+        }
+        CscMatrix {
+            row_num,
+            col_num,
+            indptr,
+            indices,
+            data,
+        }
+    }
+
+    #[inline]
+    pub fn row(&self) -> usize
+    {
+        self.row_num
+    }
+
+    #[inline]
+    pub fn col(&self) -> usize
+    {
+        self.col_num
+    }
+
+    pub fn nnz(&self) -> usize
+    {
+        self.data.len()
+    }
+
+    pub fn indptr(&self) -> &[usize]
+    {
+        &self.indptr
+    }
+
+    pub fn indices(&self) -> &[usize]
+    {
+        &self.indices
+    }
+
+    pub fn data(&self) -> &[T]
+    {
+        &self.data
+    }
+
+    pub fn data_mut(&mut self) -> &mut [T]
+    {
+        &mut self.data
+    }
+
+    pub fn col_nnz(&self, col: usize) -> usize
+    {
+        self.indptr[col + 1] - self.indptr[col]
+    }
+
+    pub fn col_indices(&self, col: usize) -> &[usize]
+    {
+        &self.indices[self.indptr[col] .. self.indptr[col + 1]]
+    }
+
+    pub fn col_values(&self, col: usize) -> &[T]
+    {
+        &self.data[self.indptr[col] .. self.indptr[col + 1]]
+    }
+
+    #[inline]
+    pub fn get_v(&self, row: usize, col: usize) -> T
+    {
+        compressed::find(&self.indptr, &self.indices, &self.data, col, row).unwrap_or_else(T::zero)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (usize, usize, T)> + '_
+    {
+        (0 .. self.col_num).flat_map(move |j| {
+            self.col_indices(j).iter().zip(self.col_values(j).iter()).map(move |(&i, &v)| (i, j, v))
+        })
+    }
+
+    // the stored positions in column-major order
+    pub fn pattern(&self) -> Vec<MatIndex>
+    {
+        self.iter().map(|(i, j, _)| (i, j)).collect()
+    }
+
+    pub fn mat_vec(&self, x: &[T]) -> Vec<T>
+    {
+        assert_eq!(x.len(), self.col_num, "the length of the vector does not match the matrix!");
+        compressed::cols_mat_vec(self.row_num, &self.indptr, &self.indices, &self.data, x)
+    }
+
+    // y = A^T x without forming the transpose
+    pub fn t_mat_vec(&self, x: &[T]) -> Vec<T>
+    where T: Element
+    {
+        assert_eq!(x.len(), self.row_num, "the length of the vector does not match the matrix!");
+        compressed::rows_mat_vec(&self.indptr, &self.indices, &self.data, x)
+    }
+
+    pub fn dot_mul(&self, mat: &CscMatrix<T>) -> CscMatrix<T>
+    {
+        assert_eq!(self.col_num, mat.row_num, "the inner dimensions of the matrices do not match!");
+        // column j of the product combines the columns of self picked by column j of mat
+        let c = compressed::product(self.row_num,
+                                    (&mat.indptr, &mat.indices, &mat.data),
+                                    (&self.indptr, &self.indices, &self.data));
+        CscMatrix::from_raw(self.row_num, mat.col_num, c.indptr, c.indices, c.data)
+    }
+
+    pub fn scalar_mul(&self, k: T) -> CscMatrix<T>
+    {
+        let mut mat = self.clone();
+        for v in mat.data.iter_mut() {
+            *v = k * *v;
+        }
+        mat
+    }
+
+    pub fn t(&self) -> CscMatrix<T>
+    {
+        let c = compressed::transpose(self.row_num, &self.indptr, &self.indices, &self.data);
+        CscMatrix::from_raw(self.col_num, self.row_num, c.indptr, c.indices, c.data)
+    }
+
+    pub fn to_csr(&self) -> CsrMatrix<T>
+    {
+        let c = compressed::transpose(self.row_num, &self.indptr, &self.indices, &self.data);
+        CsrMatrix::from_raw(self.row_num, self.col_num, c.indptr, c.indices, c.data)
+    }
+
+    pub fn to_coo(&self) -> CooMatrix<T>
+    {
+        let mut coo = CooMatrix::with_capacity(self.row_num, self.col_num, self.nnz());
+        for (i, j, v) in self.iter() {
+            coo.push(i, j, v);
+        }
+        coo
+    }
+
+    pub fn to_sparse(&self) -> SparseMatrix<T>
+    {
+        let mut mat = SparseMatrix::new(self.row_num, self.col_num);
+        for (i, j, v) in self.iter() {
+            mat.set_v(i, j, v);
+        }
+        mat
+    }
+
+    pub fn to_dense(&self) -> DenseMatrix<T>
+    {
+        let mut mat = DenseMatrix::new(self.row_num, self.col_num);
+        for (i, j, v) in self.iter() {
+            mat.set_v(i, j, v);
+        }
+        mat
+    }
+
+    pub fn from_dense(mat: &DenseMatrix<T>) -> CscMatrix<T>
+    {
+        CooMatrix::from_dense(mat).to_csc()
+    }
+}
+
+#[test]
+fn csc_matrix_test() {
+    // [1 0 2]
+    // [0 0 3]
+    // [4 5 0]
+    let mat = CscMatrix::<f64>::from_raw(3, 3, vec![0, 2, 3, 5], vec![0, 2, 2, 0, 1], vec![1.0, 4.0, 5.0, 2.0, 3.0]);
+    assert_eq!(mat.col_nnz(0), 2);
+    assert_eq!(mat.get_v(1, 2), 3.0);
+    assert_eq!(mat.get_v(1, 1), 0.0);
+    assert_eq!(mat.pattern(), vec![(0, 0), (2, 0), (2, 1), (0, 2), (1, 2)]);
+    assert_eq!(mat.mat_vec(&[1.0, 1.0, 1.0]), vec![3.0, 3.0, 9.0]);
+    assert_eq!(mat.t_mat_vec(&[1.0, 1.0, 1.0]), vec![5.0, 5.0, 5.0]);
+    let dense = mat.to_dense();
+    assert!(mat.dot_mul(&mat).to_dense() == dense.dot_mul(&dense));
+    assert!(mat.t().to_dense() == dense.t());
+    assert!(mat.to_csr().to_csc().pattern() == mat.pattern());
+    assert!(mat.to_coo().to_dense() == dense);
+}
@@ -0,0 +1,241 @@
+extern crate num;
+
+use super::super::common::MatIndex;
+use super::compressed;
+use super::coo_matrix::CooMatrix;
+use super::csc_matrix::CscMatrix;
+use super::sparse_matrix::SparseMatrix;
+use super::super::dense::DenseMatrix;
+use super::super::super::super::parallel::Element;
+use num::Num;
+use std::fmt::Display;
+
+// Compressed sparse row format: the column indices and values of row i are
+// indices[indptr[i] .. indptr[i + 1]] and data[indptr[i] .. indptr[i + 1]],
+// with the column indices sorted inside every row.
+#[derive(Clone)]
+pub struct CsrMatrix<T>
+where T: Num + Clone + Copy + Display
+{
+    row_num: usize,
+    col_num: usize,
+    indptr: Vec<usize>,
+    indices: Vec<usize>,
+    data: Vec<T>,
+}
+
+impl<T> CsrMatrix<T>
+where T: Num + Clone + Copy + Display
+{
+    pub fn new(row_num: usize, col_num: usize) -> CsrMatrix<T>
+    {
+        CsrMatrix {
+            row_num,
+            col_num,
+            indptr: vec![0; row_num + 1],
+            indices: Vec::new(),
+            data: Vec::new(),
+        }
+    }
+
+    pub fn from_raw(row_num: usize, col_num: usize,
+                    indptr: Vec<usize>, indices: Vec<usize>, data: Vec<T>) -> CsrMatrix<T>
+    {
+        assert_eq!(indptr.len(), row_num + 1, "indptr must have row_num + 1 entries!");
+        assert_eq!(indices.len(), data.len(), "indices and data must have the same length!");
+        assert_eq!(indptr[row_num], data.len(), "indptr does not match the number of nonzeros!");
+        for i in 0 .. row_num {
+            let row = &indices[indptr[i] .. indptr[i + 1]];
+            assert!(row.windows(2).all(|w| w[0] < w[1]), "column indices must be sorted and unique!");
+            assert!(row.iter().all(|&j| j < col_num), "column index is out of bounds!");
+        }
+        CsrMatrix {
+            row_num,
+            col_num,
+            indptr,
+            indices,
+            data,
+        }
+    }
+
+    #[inline]
+    pub fn row(&self) -> usize
+    {
+        self.row_num
+    }
+
+    #[inline]
+    pub fn col(&self) -> usize
+    {
+        self.col_num
+    }
+
+    pub fn nnz(&self) -> usize
+    {
+        self.data.len()
+    }
+
+    pub fn indptr(&self) -> &[usize]
+    {
+        &self.indptr
+    }
+
+    pub fn indices(&self) -> &[usize]
+    {
+        &self.indices
+    }
+
+    pub fn data(&self) -> &[T]
+    {
+        &self.data
+    }
+
+    pub fn data_mut(&mut self) -> &mut [T]
+    {
+        &mut self.data
+    }
+
+    pub fn row_nnz(&self, row: usize) -> usize
+    {
+        self.indptr[row + 1] - self.indptr[row]
+    }
+
+    pub fn row_indices(&self, row: usize) -> &[usize]
+    {
+        &self.indices[self.indptr[row] .. self.indptr[row + 1]]
+    }
+
+    pub fn row_values(&self, row: usize) -> &[T]
+    {
+        &self.data[self.indptr[row] .. self.indptr[row + 1]]
+    }
+
+    #[inline]
+    pub fn get_v(&self, row: usize, col: usize) -> T
+    {
+        compressed::find(&self.indptr, &self.indices, &self.data, row, col).unwrap_or_else(T::zero)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (usize, usize, T)> + '_
+    {
+        (0 .. self.row_num).flat_map(move |i| {
+            self.row_indices(i).iter().zip(self.row_values(i).iter()).map(move |(&j, &v)| (i, j, v))
+        })
+    }
+
+    // the stored positions in row-major order
+    pub fn pattern(&self) -> Vec<MatIndex>
+    {
+        self.iter().map(|(i, j, _)| (i, j)).collect()
+    }
+
+    pub fn mat_vec(&self, x: &[T]) -> Vec<T>
+    where T: Element
+    {
+        assert_eq!(x.len(), self.col_num, "the length of the vector does not match the matrix!");
+        compressed::rows_mat_vec(&self.indptr, &self.indices, &self.data, x)
+    }
+
+    // y = A^T x without forming the transpose
+    pub fn t_mat_vec(&self, x: &[T]) -> Vec<T>
+    {
+        assert_eq!(x.len(), self.row_num, "the length of the vector does not match the matrix!");
+        compressed::cols_mat_vec(self.col_num, &self.indptr, &self.indices, &self.data, x)
+    }
+
+    pub fn dot_mul(&self, mat: &CsrMatrix<T>) -> CsrMatrix<T>
+    {
+        assert_eq!(self.col_num, mat.row_num, "the inner dimensions of the matrices do not match!");
+        let c = compressed::product(mat.col_num,
+                                    (&self.indptr, &self.indices, &self.data),
+                                    (&mat.indptr, &mat.indices, &mat.data));
+        CsrMatrix::from_raw(self.row_num, mat.col_num, c.indptr, c.indices, c.data)
+    }
+
+    pub fn dot_mul_dense(&self, mat: &DenseMatrix<T>) -> DenseMatrix<T>
+    {
+        assert_eq!(self.col_num, mat.row(), "the inner dimensions of the matrices do not match!");
+        let mut res = DenseMatrix::new(self.row_num, mat.col());
+        for i in 0 .. self.row_num {
+            for (&k, &a) in self.row_indices(i).iter().zip(self.row_values(i).iter()) {
+                for j in 0 .. mat.col() {
+                    res.set_v(i, j, res.get_v(i, j) + a * mat.get_v(k, j));
+                }
+            }
+        }
+        res
+    }
+
+    pub fn scalar_mul(&self, k: T) -> CsrMatrix<T>
+    {
+        let mut mat = self.clone();
+        for v in mat.data.iter_mut() {
+            *v = k * *v;
+        }
+        mat
+    }
+
+    pub fn t(&self) -> CsrMatrix<T>
+    {
+        let c = compressed::transpose(self.col_num, &self.indptr, &self.indices, &self.data);
+        CsrMatrix::from_raw(self.col_num, self.row_num, c.indptr, c.indices, c.data)
+    }
+
+    pub fn to_csc(&self) -> CscMatrix<T>
+    {
+        let c = compressed::transpose(self.col_num, &self.indptr, &self.indices, &self.data);
+        CscMatrix::from_raw(self.row_num, self.col_num, c.indptr, c.indices, c.data)
+    }
+
+    pub fn to_coo(&self) -> CooMatrix<T>
+    {
+        let mut coo = CooMatrix::with_capacity(self.row_num, self.col_num, self.nnz());
+        for (i, j, v) in self.iter() {
+            coo.push(i, j, v);
+        }
+        coo
+    }
+
+    pub fn to_sparse(&self) -> SparseMatrix<T>
+    {
+        let mut mat = SparseMatrix::new(self.row_num, self.col_num);
+        for (i, j, v) in self.iter() {
+            mat.set_v(i, j, v);
+        }
+        mat
+    }
+
+    pub fn to_dense(&self) -> DenseMatrix<T>
+    {
+        let mut mat = DenseMatrix::new(self.row_num, self.col_num);
+        for (i, j, v) in self.iter() {
+            mat.set_v(i, j, v);
+        }
+        mat
+    }
+
+    pub fn from_dense(mat: &DenseMatrix<T>) -> CsrMatrix<T>
+    {
+        CooMatrix::from_dense(mat).to_csr()
+    }
+}
+
+#[test]
+fn csr_matrix_test() {
+    // [1 0 2]
+    // [0 0 3]
+    // [4 5 0]
+    let mat = CsrMatrix::<f64>::from_raw(3, 3, vec![0, 2, 3, 5], vec![0, 2, 2, 0, 1], vec![1.0, 2.0, 3.0, 4.0, 5.0]);
+    assert_eq!(mat.nnz(), 5);
+    assert_eq!(mat.row_nnz(1), 1);
+    assert_eq!(mat.get_v(2, 1), 5.0);
+    assert_eq!(mat.get_v(1, 1), 0.0);
+    assert_eq!(mat.mat_vec(&[1.0, 1.0, 1.0]), vec![3.0, 3.0, 9.0]);
+    assert_eq!(mat.t_mat_vec(&[1.0, 1.0, 1.0]), vec![5.0, 5.0, 5.0]);
+    let dense = mat.to_dense();
+    assert!(mat.dot_mul(&mat).to_dense() == dense.dot_mul(&dense));
+    assert!(mat.dot_mul_dense(&dense) == dense.dot_mul(&dense));
+    assert!(mat.t().to_dense() == dense.t());
+    assert!(CsrMatrix::from_dense(&dense).pattern() == mat.pattern());
+    assert!(mat.to_csc().to_csr().to_dense() == dense);
+}
@@ -1,2 +1,10 @@
 pub mod sparse_matrix;
-pub use self::sparse_matrix::{SparseMatrix};
\ No newline at end of file
+pub mod coo_matrix;
+pub mod csr_matrix;
+pub mod csc_matrix;
+mod compressed;
+
+pub use self::sparse_matrix::{SparseMatrix};
+pub use self::coo_matrix::CooMatrix;
+pub use self::csr_matrix::CsrMatrix;
+pub use self::csc_matrix::CscMatrix;
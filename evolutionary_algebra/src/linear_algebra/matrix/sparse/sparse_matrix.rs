@@ -1,38 +1,68 @@
 extern crate num;
 
 use super::super::common::MatIndex;
+use super::coo_matrix::CooMatrix;
+use super::csc_matrix::CscMatrix;
+use super::csr_matrix::CsrMatrix;
+use super::super::common::{self, MatOps};
+use super::super::format::{self, FormatOptions};
+use super::super::super::scalar::Scalar;
+use super::super::dense::DenseMatrix;
+use super::super::super::solver::{direct, iterative};
+use super::super::super::solver::rhs::RightHandSide;
+use super::super::super::solver::preconditioner::{PrecondSide, Preconditioner};
+use super::super::super::decomposition::{SparseLU, SparseCholesky, SparseLDLT};
+use super::super::super::super::error::{self, AlgebraError, AlgebraResult};
+use super::super::super::super::parallel::{self, Element};
 use num::{Num, Float};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::fmt::Display;
 
-#[allow(dead_code)]
-pub struct SparseMatrix<T> 
+// Dictionary-of-keys storage, the format for assembling a matrix entry by
+// entry. Keys are ordered row-major, so a row is a contiguous range of the map
+// and all the operations below only visit the stored nonzeros.
+#[derive(Clone)]
+pub struct SparseMatrix<T>
 where T: Num + Clone + Copy + Display
 {
     row_num: usize,
     col_num: usize,
-    elements: HashMap<MatIndex, T>,
+    elements: BTreeMap<MatIndex, T>,
 }
 
 
-impl<T> SparseMatrix<T> 
+impl<T> SparseMatrix<T>
 where T: Num + Clone + Copy + Display
 {
     pub fn new(row_num: usize, col_num: usize) -> SparseMatrix<T>
     {
         SparseMatrix {
-            row_num: row_num,
-            col_num: col_num,
-            elements: HashMap::new(),
+            row_num,
+            col_num,
+            elements: BTreeMap::new(),
         }
     }
-    
+
+    fn from_entries<I>(row_num: usize, col_num: usize, entries: I) -> SparseMatrix<T>
+    where I: Iterator<Item = (MatIndex, T)>
+    {
+        SparseMatrix {
+            row_num,
+            col_num,
+            elements: entries.filter(|&(_, v)| v != T::zero()).collect(),
+        }
+    }
+
     #[inline]
     pub fn set_v(&mut self, row: usize, col: usize, v: T)
     {
-        if row < self.row_num && col < self.col_num && v != T::zero() {
-            let elm = self.elements.entry((row, col)).or_insert(v);
-            *elm = v;
+        if row < self.row_num && col < self.col_num {
+            if v != T::zero() {
+                self.elements.insert((row, col), v);
+            }
+            else {
+                self.elements.remove(&(row, col));
+            }
         }
     }
 
@@ -45,6 +75,24 @@ where T: Num + Clone + Copy + Display
         }
     }
 
+    // None where the position is not stored
+    #[inline]
+    pub fn get(&self, row: usize, col: usize) -> Option<T>
+    {
+        self.elements.get(&(row, col)).cloned()
+    }
+
+    // a_ij = f(a_ij), as set_v: a zero result is not stored
+    pub fn update<F>(&mut self, row: usize, col: usize, f: F)
+    where F: FnOnce(T) -> T
+    {
+        if row >= self.row_num || col >= self.col_num {
+            panic!("index ({}, {}) is out of bounds!", row, col);
+        }
+        let v = f(self.get_v(row, col));
+        self.set_v(row, col, v);
+    }
+
     #[inline]
     pub fn row(&self) -> usize
     {
@@ -57,6 +105,74 @@ where T: Num + Clone + Copy + Display
         self.col_num
     }
 
+    #[inline]
+    pub fn shape(&self) -> MatIndex
+    {
+        (self.row_num, self.col_num)
+    }
+
+    pub fn nnz(&self) -> usize
+    {
+        self.elements.len()
+    }
+
+    // the stored positions in row-major order
+    pub fn pattern(&self) -> Vec<MatIndex>
+    {
+        self.elements.keys().cloned().collect()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (usize, usize, T)> + '_
+    {
+        self.elements.iter().map(|(&(i, j), &v)| (i, j, v))
+    }
+
+    pub fn to_coo(&self) -> CooMatrix<T>
+    {
+        let mut coo = CooMatrix::with_capacity(self.row_num, self.col_num, self.nnz());
+        for (i, j, v) in self.iter() {
+            coo.push(i, j, v);
+        }
+        coo
+    }
+
+    pub fn to_csr(&self) -> CsrMatrix<T>
+    {
+        let mut indptr = vec![0; self.row_num + 1];
+        let mut indices = Vec::with_capacity(self.nnz());
+        let mut data = Vec::with_capacity(self.nnz());
+        // the keys are already sorted row-major
+        for (i, j, v) in self.iter() {
+            indptr[i + 1] += 1;
+            indices.push(j);
+            data.push(v);
+        }
+        for i in 0 .. self.row_num {
+            indptr[i + 1] += indptr[i];
+        }
+        CsrMatrix::from_raw(self.row_num, self.col_num, indptr, indices, data)
+    }
+
+    pub fn to_csc(&self) -> CscMatrix<T>
+    {
+        self.to_csr().to_csc()
+    }
+
+    pub fn to_dense(&self) -> DenseMatrix<T>
+    {
+        let mut mat = DenseMatrix::new(self.row_num, self.col_num);
+        for (i, j, v) in self.iter() {
+            mat.set_v(i, j, v);
+        }
+        mat
+    }
+
+    pub fn from_dense(mat: &DenseMatrix<T>) -> SparseMatrix<T>
+    {
+        let entries = (0 .. mat.row()).flat_map(|i| (0 .. mat.col()).map(move |j| ((i, j), mat.get_v(i, j))));
+        Self::from_entries(mat.row(), mat.col(), entries)
+    }
+
 }
 
 macro_rules! build_sparse_matrix_f {
@@ -69,25 +185,17 @@ macro_rules! build_sparse_matrix_f {
                 }
             }
             matrix
-        } 
+        }
     };
 }
 
 
-impl<T> SparseMatrix<T> 
+impl<T> SparseMatrix<T>
 where T: Num + Clone + Copy + Display
 {
     pub fn t(&self) -> SparseMatrix<T>
     {
-        let row_num = self.row();
-        let col_num = self.col();
-        let mut matrix = SparseMatrix::<T>::new(row_num, col_num);
-        for i in 0 .. row_num {
-                for j in 0 .. col_num {
-                    matrix.set_v(j, i, self.get_v(i, j));
-                }
-        }
-        matrix
+        Self::from_entries(self.col_num, self.row_num, self.iter().map(|(i, j, v)| ((j, i), v)))
     }
 
 
@@ -95,78 +203,118 @@ where T: Num + Clone + Copy + Display
     {
         let row_num = row_to - row_from + 1;
         let col_num = col_to - col_from + 1;
-        let mut matrix = SparseMatrix::<T>::new(row_num, col_num);
-        for i in row_from .. row_to + 1 {
-            for j in col_from .. col_to + 1 {
-                matrix.set_v( i - row_from, j - col_from, self.get_v(i, j) );
-            }
-        } 
-        matrix
+        let entries = (row_from .. row_to + 1).flat_map(|i| {
+            self.elements.range((i, col_from) ..= (i, col_to)).map(move |(&(_, j), &v)| ((i - row_from, j - col_from), v))
+        });
+        Self::from_entries(row_num, col_num, entries)
+    }
+
+    pub fn try_slice(&self, row_from: usize, row_to: usize, col_from: usize, col_to: usize) -> AlgebraResult<SparseMatrix<T>>
+    {
+        error::check_slice(self.shape(), row_from, row_to, col_from, col_to)?;
+        Ok(self.slice(row_from, row_to, col_from, col_to))
     }
 
     pub fn scalar_mul(&self, k: T) -> SparseMatrix<T> {
-        build_sparse_matrix_f!(self.row_num , self.col_num , |i, j|{ k * self.get_v(i, j) })
+        Self::from_entries(self.row_num, self.col_num, self.iter().map(|(i, j, v)| ((i, j), k * v)))
     }
 
     pub fn concat(&self, mat: &SparseMatrix<T>) -> SparseMatrix<T> {
         let row_num = self.row_num;
         let col_num = self.col_num + mat.col_num;
-        let calc_elm = |i, j| {
-            if j < self.col_num {
-                self.get_v(i, j)
-            }
-            else {
-                mat.get_v(i, j - self.col_num)
-            }
-        };
-        build_sparse_matrix_f!(row_num , col_num , calc_elm)
+        let shift = self.col_num;
+        let entries = self.iter().map(|(i, j, v)| ((i, j), v))
+            .chain(mat.iter().map(|(i, j, v)| ((i, j + shift), v)));
+        Self::from_entries(row_num, col_num, entries)
+    }
+
+    pub fn try_concat(&self, mat: &SparseMatrix<T>) -> AlgebraResult<SparseMatrix<T>> {
+        if self.row_num != mat.row_num {
+            return Err(AlgebraError::DimensionMismatch { left: self.shape(), right: mat.shape() });
+        }
+        Ok(self.concat(mat))
     }
 
     pub fn dot_mul(&self, mat: &SparseMatrix<T>) -> SparseMatrix<T> {
-        let row_num = self.row();
-        let col_num = mat.col();
-        let calc_elm = |i, j| {
-            let mut sum = T::zero();
-            for k in 0 .. self.col() {
-                sum = sum + self.get_v(i, k) * mat.get_v(k, j);
-            }
-            sum
-        };
-        build_sparse_matrix_f!(row_num , col_num , calc_elm)
+        self.to_csr().dot_mul(&mat.to_csr()).to_sparse()
+    }
+
+    pub fn try_dot_mul(&self, mat: &SparseMatrix<T>) -> AlgebraResult<SparseMatrix<T>> {
+        error::check_product(self.shape(), mat.shape())?;
+        Ok(self.dot_mul(mat))
     }
 
+    // the elementwise product
+    pub fn hadamard(&self, mat: &SparseMatrix<T>) -> SparseMatrix<T> {
+        Self::from_entries(self.row_num, self.col_num, self.iter().map(|(i, j, v)| ((i, j), v * mat.get_v(i, j))))
+    }
+
+    pub fn try_hadamard(&self, mat: &SparseMatrix<T>) -> AlgebraResult<SparseMatrix<T>> {
+        error::check_same_shape(self.shape(), mat.shape())?;
+        Ok(self.hadamard(mat))
+    }
+
+    #[deprecated(note = "`mul` is the elementwise product, use `hadamard` (or `*` for the matrix product)")]
     pub fn mul(&self, mat: &SparseMatrix<T>) -> SparseMatrix<T> {
-        let calc_elm = |i, j| {
-            self.get_v(i, j) * mat.get_v(i, j)
-        };
-        build_sparse_matrix_f!(self.row_num , self.col_num , calc_elm)
+        self.hadamard(mat)
     }
 
     pub fn neg(&self) -> SparseMatrix<T> {
         let zero = T::zero();
-        build_sparse_matrix_f!(self.row_num , self.col_num , |i, j|{ zero - self.get_v(i, j) })
+        Self::from_entries(self.row_num, self.col_num, self.iter().map(|(i, j, v)| ((i, j), zero - v)))
+    }
+
+    fn merge<F>(&self, mat: &SparseMatrix<T>, f: F) -> SparseMatrix<T>
+    where F: Fn(T, T) -> T
+    {
+        let mut matrix = self.clone();
+        for (i, j, v) in mat.iter() {
+            matrix.set_v(i, j, f(self.get_v(i, j), v));
+        }
+        matrix
     }
 
     pub fn add(&self, mat: &SparseMatrix<T>) -> SparseMatrix<T> {
-        build_sparse_matrix_f!(self.row_num , self.col_num , |i, j|{ self.get_v(i, j) + mat.get_v(i, j) })
+        self.merge(mat, |a, b| a + b)
     }
 
     pub fn sub(&self, mat: &SparseMatrix<T>) -> SparseMatrix<T> {
-        build_sparse_matrix_f!(self.row_num , self.col_num , |i, j|{ self.get_v(i, j) - mat.get_v(i, j) })
+        self.merge(mat, |a, b| a - b)
+    }
+
+    pub fn try_add(&self, mat: &SparseMatrix<T>) -> AlgebraResult<SparseMatrix<T>> {
+        error::check_same_shape(self.shape(), mat.shape())?;
+        Ok(self.add(mat))
+    }
+
+    pub fn try_sub(&self, mat: &SparseMatrix<T>) -> AlgebraResult<SparseMatrix<T>> {
+        error::check_same_shape(self.shape(), mat.shape())?;
+        Ok(self.sub(mat))
     }
 
     pub fn show(&self) {
-        for i in 0 .. self.row_num {
-            for j in 0 .. self.col_num {
-                print!("{} ", self.get_v(i, j));
-            }
-            println!("");
+        println!("{}", format::render(self.shape(), &FormatOptions::new(), |i, j| self.get(i, j), |v| v.to_string()));
+    }
+
+    // the sparsity pattern, '*' for a stored position and '.' for the others;
+    // each character covers a block of the matrix when it is larger than
+    // max_rows x max_cols
+    pub fn spy(&self, max_rows: usize, max_cols: usize) -> String
+    {
+        let block_rows = self.row_num.div_ceil(max_rows.max(1)).max(1);
+        let block_cols = self.col_num.div_ceil(max_cols.max(1)).max(1);
+        let (rows, cols) = (self.row_num.div_ceil(block_rows), self.col_num.div_ceil(block_cols));
+        let mut grid = vec![vec!['.'; cols]; rows];
+        for &(i, j) in self.elements.keys() {
+            grid[i / block_rows][j / block_cols] = '*';
         }
+        let lines: Vec<String> = grid.into_iter().map(|row| row.into_iter().collect()).collect();
+        lines.join("\n")
     }
 
 }
 
-impl<T> SparseMatrix<T> 
+impl<T> SparseMatrix<T>
 where T: Num + Clone + Copy + Display
 {
     pub fn ones(num: usize) -> SparseMatrix<T> {
@@ -174,83 +322,37 @@ where T: Num + Clone + Copy + Display
     }
 
     pub fn zeros(num: usize) -> SparseMatrix<T> {
-        SparseMatrix {
-            row_num: num,
-            col_num: num,
-            elements: HashMap::new(),
-        }
+        Self::new(num, num)
     }
 
     pub fn eye(num: usize) -> SparseMatrix<T> {
-        let calc_elm = |i, j| {
-            if i == j {
-                T::one()
-            }
-            else {
-                T::zero()
-            }
-        };
-        build_sparse_matrix_f!(num , num , calc_elm)
+        Self::from_entries(num, num, (0 .. num).map(|i| ((i, i), T::one())))
+    }
+
+    fn filter<F>(&self, keep: F) -> SparseMatrix<T>
+    where F: Fn(usize, usize) -> bool
+    {
+        Self::from_entries(self.row_num, self.col_num, self.iter().filter(|&(i, j, _)| keep(i, j)).map(|(i, j, v)| ((i, j), v)))
     }
 
     pub fn diag(&self) -> SparseMatrix<T> {
-        let calc_elm = |i, j| {
-            if i == j {
-                self.get_v(i, j)
-            }
-            else {
-                T::zero()
-            }
-        };
-        build_sparse_matrix_f!(self.row_num , self.col_num , calc_elm)
+        self.filter(|i, j| i == j)
     }
 
     pub fn tri_u(&self) -> SparseMatrix<T> {
-        let calc_elm = |i, j| {
-            if i <= j {
-                self.get_v(i, j)
-            }
-            else {
-                T::zero()
-            }
-        };
-        build_sparse_matrix_f!(self.row_num , self.col_num , calc_elm)
+        self.filter(|i, j| i <= j)
     }
 
     pub fn tri_l(&self) -> SparseMatrix<T> {
-        let calc_elm = |i, j| {
-            if i >= j {
-                self.get_v(i, j)
-            }
-            else {
-                T::zero()
-            }
-        };
-        build_sparse_matrix_f!(self.row_num , self.col_num , calc_elm)
+        self.filter(|i, j| i >= j)
     }
 
     pub fn tri_strict_u(&self) -> SparseMatrix<T> {
-        let calc_elm = |i, j| {
-            if i < j {
-                self.get_v(i, j)
-            }
-            else {
-                T::zero()
-            }
-        };
-        build_sparse_matrix_f!(self.row_num , self.col_num , calc_elm)
+        self.filter(|i, j| i < j)
     }
 
     pub fn tri_strict_l(&self) -> SparseMatrix<T> {
-        let calc_elm = |i, j| {
-            if i > j {
-                self.get_v(i, j)
-            }
-            else {
-                T::zero()
-            }
-        };
-        build_sparse_matrix_f!(self.row_num , self.col_num , calc_elm)
+        self.filter(|i, j| i > j)
     }
 
     pub fn trace(&self) -> T {
@@ -262,255 +364,396 @@ where T: Num + Clone + Copy + Display
         sum
     }
 
-    
+
 }
 
-impl<T> SparseMatrix<T> 
-where T: Float + Clone + Copy + Display
+impl<T> SparseMatrix<T>
+where T: Scalar
 {
     pub fn cofactors(&self, row: usize, col: usize) -> SparseMatrix<T>
     {
-        let row_num = self.row_num - 1;
-        let col_num = self.col_num - 1;
-        let calc_elm = |i, j| {
-            let ri = if i < row {
-                i
-            } else {
-                i + 1
-            };
-            let cj = if j < col {
-                j
-            } else {
-                j + 1
-            };
-            self.get_v(ri, cj)
-        };
-        build_sparse_matrix_f!(row_num , col_num , calc_elm)
+        direct::cofactors(self, row, col)
     }
 
     pub fn adjoint(&self) -> SparseMatrix<T>
     {
-        let scalar = -T::one();
-        let calc_elm = |i, j| {
-            scalar.powi((i + j) as i32) * self.cofactors(i, j).det_adj()
-        };
-        build_sparse_matrix_f!(self.row_num , self.col_num , calc_elm).t()
+        direct::adjoint(self)
     }
 
     pub fn det_adj(&self) -> T
     {
-        if self.row_num == self.col_num {
-            match self.row_num {
-                1 => self.get_v(0, 0),
-                2 => self.get_v(0, 0) * self.get_v(1, 1) - self.get_v(0, 1) * self.get_v(1, 0),
-                _ => {
-                    let mut sum = T::zero();
-                    let scalar = -T::one();
-                    for i in 0 .. self.row_num {
-                        sum = sum + scalar.powi(i as i32) * self.get_v(i, 0) * self.cofactors(i, 0).det_adj();
-                    }
-                    sum
-                }
-            }
-        }
-        else {
-            T::zero()
-        }
+        direct::det_adj(self)
     }
 
     pub fn inv_adj(&self) -> Option<SparseMatrix<T>>
     {
-        let det = self.det_adj();
-        if det.abs() == T::zero() {
-            None
-        }
-        else {
-            Some(
-                self.adjoint().scalar_mul(T::one() / det)
-            )
-        }
+        direct::inv_adj(self)
+    }
+
+    pub fn try_inv_adj(&self) -> AlgebraResult<SparseMatrix<T>>
+    {
+        direct::try_inv_adj(self)
+    }
+
+    // the conjugate transpose A^H
+    pub fn conj_t(&self) -> SparseMatrix<T>
+    {
+        MatOps::conj_t(self)
+    }
+
+    pub fn is_hermitian(&self, tol: T::Real) -> bool
+    {
+        MatOps::is_hermitian(self, tol)
+    }
+
+    pub fn norm_1(&self) -> T::Real
+    {
+        common::modulus_norm_1(self)
+    }
+
+    pub fn norm_inf(&self) -> T::Real
+    {
+        common::modulus_norm_inf(self)
+    }
+
+    // the Frobenius norm, sqrt(trace(A^H A))
+    pub fn norm_fro(&self) -> T::Real
+    {
+        common::modulus_norm_fro(self)
+    }
+
+    // also the Frobenius norm, not the spectral one
+    pub fn norm2(&self) -> T::Real {
+        self.norm_fro()
+    }
+
+    pub fn norm_max(&self) -> T::Real
+    {
+        common::modulus_norm_max(self)
+    }
+
+    // the 2-norm ||A||_2, the largest singular value
+    pub fn norm_spectral(&self) -> T::Real
+    {
+        common::modulus_norm_spectral(self)
+    }
+
+    pub fn solve_ge<B>(mat_a: &SparseMatrix<T>, mat_b: &B) -> Option<B>
+    where B: RightHandSide<T>
+    {
+        direct::solve_ge(mat_a, mat_b)
+    }
+
+    pub fn try_solve_ge<B>(mat_a: &SparseMatrix<T>, mat_b: &B) -> AlgebraResult<B>
+    where B: RightHandSide<T>
+    {
+        direct::try_solve_ge(mat_a, mat_b)
+    }
+
+    pub fn inv_ge(&self) -> Option<SparseMatrix<T>>
+    {
+        direct::inv_ge(self)
     }
 
-    pub fn norm2(&self) -> T {
-        self.t().dot_mul(&self).trace().sqrt()
+    pub fn try_inv_ge(&self) -> AlgebraResult<SparseMatrix<T>>
+    {
+        direct::try_inv_ge(self)
     }
 
+    pub fn det_ge(&self) -> T {
+        direct::det_ge(self)
+    }
+
+    pub fn try_det_ge(&self) -> AlgebraResult<T> {
+        direct::try_det_ge(self)
+    }
+}
+
+impl<T> SparseMatrix<T>
+where T: Float + Clone + Copy + Display + Element
+{
     pub fn get_col(&self, col_id: usize) -> SparseMatrix<T>
     {
+        // a slice needs at least one row
+        if self.row_num == 0 {
+            return Self::new(0, 1);
+        }
         self.slice(0, self.row_num - 1, col_id, col_id)
     }
 
     pub fn get_row(&self, row_id: usize) -> SparseMatrix<T>
     {
+        if self.col_num == 0 {
+            return Self::new(1, 0);
+        }
         self.slice(row_id, row_id, 0, self.col_num - 1)
     }
 
-    #[allow(dead_code)]
-    fn permute_rows(&mut self, row_i: usize, row_j: usize) {
-        for col in 0 .. self.col_num {
-            let tmp = self.get_v(row_i, col);
-            self.set_v(row_i, col, self.get_v(row_j, col));
-            self.set_v(row_j, col, tmp);
-        }
+    pub fn solve_sor<B>(mat_a: &SparseMatrix<T>, mat_b: &B, w_param: T, max_it: usize) -> B
+    where B: RightHandSide<T>
+    {
+        iterative::solve_sor(mat_a, mat_b, w_param, max_it)
     }
 
-    #[allow(dead_code)]
-    fn pivoting(&mut self, site: usize) -> i32 {
-        let mut tmp = self.get_v(site, site).abs();
-        let mut index = site;
-        for row in (site + 1) .. self.row_num {
-            if self.get_v(row, site).abs() > tmp {
-                tmp = self.get_v(row, site).abs();
-                index = row;
-            }
-        }
-        if index != site {
-            self.permute_rows(index, site);
-            return 1;
-        }
-        return 0;
+    pub fn try_solve_sor<B>(mat_a: &SparseMatrix<T>, mat_b: &B, w_param: T, max_it: usize, tol: T) -> AlgebraResult<B>
+    where B: RightHandSide<T>
+    {
+        iterative::try_solve_sor(mat_a, mat_b, w_param, max_it, tol)
     }
 
-    pub fn solve_ge(mat_a: &SparseMatrix<T>, mat_b: &SparseMatrix<T>) -> Option<SparseMatrix<T>>
+    pub fn solve_gmres<B>(mat_a: &SparseMatrix<T>, mat_b: &B, m:usize, max_it: usize) -> B
+    where B: RightHandSide<T>, T: Scalar<Real = T>
     {
-        let mut mat = mat_a.concat(&mat_b);
-        for r in 0 .. mat.row_num {
-            mat.pivoting(r);
-            let head = mat.get_v(r, r);
-            if head == T::zero() {
-                return None;
-            }    
-            for c in r .. mat.col_num {
-                mat.set_v(r, c, mat.get_v(r, c) / head);
-            }
-            for nr in r + 1 .. mat.row_num {
-                let row_head = mat.get_v(nr, r);
-                for c in r .. mat.col_num {
-                    mat.set_v(nr, c, mat.get_v(nr, c) - row_head * mat.get_v(r, c));
-                }
-            }  
-        }
+        iterative::solve_gmres(mat_a, mat_b, m, max_it)
+    }
 
-        for r in (0 .. mat.row_num).rev() {
-            for nr in (0 .. r).rev() {
-                let row_head = mat.get_v(nr, r);
-                for c in r .. mat.col_num {
-                    mat.set_v(nr, c, mat.get_v(nr, c) - row_head * mat.get_v(r, c));
-                }
-            }
-        }
+    pub fn try_solve_gmres<B>(mat_a: &SparseMatrix<T>, mat_b: &B, m:usize, max_it: usize, tol: T) -> AlgebraResult<B>
+    where B: RightHandSide<T>, T: Scalar<Real = T>
+    {
+        iterative::try_solve_gmres(mat_a, mat_b, m, max_it, tol)
+    }
 
-        Some(mat.slice(0, mat.row_num - 1, mat_a.col_num, mat.col_num - 1))
+    pub fn try_solve_cg<B>(mat_a: &SparseMatrix<T>, mat_b: &B, max_it: usize, tol: T) -> AlgebraResult<B>
+    where B: RightHandSide<T>
+    {
+        iterative::try_solve_cg(mat_a, mat_b, max_it, tol)
     }
 
-    #[allow(dead_code)]
-    fn mul_scalar_f(&self, k: T) -> SparseMatrix<T> {
-        build_sparse_matrix_f!(self.row_num , self.col_num , |i, j|{ k * self.get_v(i, j) })
+    pub fn try_solve_bicgstab<B>(mat_a: &SparseMatrix<T>, mat_b: &B, max_it: usize, tol: T) -> AlgebraResult<B>
+    where B: RightHandSide<T>
+    {
+        iterative::try_solve_bicgstab(mat_a, mat_b, max_it, tol)
     }
 
-    #[allow(dead_code)]
-    fn normal(&self) -> SparseMatrix<T> {
-        let scalar = self.norm2();
-        self.mul_scalar_f(T::one() / (scalar * scalar))
+    pub fn try_solve_cgs<B>(mat_a: &SparseMatrix<T>, mat_b: &B, max_it: usize, tol: T) -> AlgebraResult<B>
+    where B: RightHandSide<T>
+    {
+        iterative::try_solve_cgs(mat_a, mat_b, max_it, tol)
     }
 
-    #[allow(dead_code)]
-    fn inner_product(vec1: &SparseMatrix<T>, vec2: &SparseMatrix<T>) -> T {
-        vec1.dot_mul(&vec2).get_v(0, 0)
+    pub fn try_solve_minres<B>(mat_a: &SparseMatrix<T>, mat_b: &B, max_it: usize, tol: T) -> AlgebraResult<B>
+    where B: RightHandSide<T>
+    {
+        iterative::try_solve_minres(mat_a, mat_b, max_it, tol)
     }
 
-    #[allow(dead_code)]
-    fn arnoldi_proc(m: usize, mat_a: &SparseMatrix<T>,
-                    mat_v: &mut SparseMatrix<T>, mat_h: &mut SparseMatrix<T>) {
-        for j in 0 .. m {
-            let mut v_j_plus = mat_a.dot_mul(&mat_v.get_col(j));
-            for i in 0 .. j {
-                let h_ij = Self::inner_product(&mat_v.get_col(i).t(), &mat_a.dot_mul(&mat_v.get_col(j)));
-                mat_h.set_v(i, j, h_ij);
-                v_j_plus = v_j_plus.sub(&mat_v.get_col(i).mul_scalar_f(h_ij));
-            }
-            let h_j_plus = v_j_plus.norm2();
-            mat_h.set_v(j+1, j, h_j_plus);
-            v_j_plus = v_j_plus.mul_scalar_f(T::one() / h_j_plus);
-            for i in 0 .. v_j_plus.row_num {
-                mat_v.set_v(i, j+1, v_j_plus.get_v(i, 0));
-            }
-        }
+    pub fn try_solve_gmres_precond<B, P>(mat_a: &SparseMatrix<T>, mat_b: &B, m: usize, max_it: usize, tol: T,
+                                      precond: &P, side: PrecondSide) -> AlgebraResult<B>
+    where B: RightHandSide<T>, T: Scalar<Real = T>, P: Preconditioner<T> + ?Sized
+    {
+        iterative::try_solve_gmres_precond(mat_a, mat_b, m, max_it, tol, precond, side)
+    }
+
+    pub fn try_solve_cg_precond<B, P>(mat_a: &SparseMatrix<T>, mat_b: &B, max_it: usize, tol: T, precond: &P) -> AlgebraResult<B>
+    where B: RightHandSide<T>, P: Preconditioner<T> + ?Sized
+    {
+        iterative::try_solve_cg_precond(mat_a, mat_b, max_it, tol, precond)
+    }
+
+    pub fn try_solve_bicgstab_precond<B, P>(mat_a: &SparseMatrix<T>, mat_b: &B, max_it: usize, tol: T, precond: &P) -> AlgebraResult<B>
+    where B: RightHandSide<T>, P: Preconditioner<T> + ?Sized
+    {
+        iterative::try_solve_bicgstab_precond(mat_a, mat_b, max_it, tol, precond)
     }
 
-    pub fn solve_sor(mat_a: &SparseMatrix<T>, mat_b: &SparseMatrix<T>, w_param: T, max_it: usize) -> SparseMatrix<T>
-    {
-        // Note that 0 < w < 2 !!!
-        let mut xmat = Self::new(mat_a.row_num, mat_b.col_num);
-        let mat_a_ = mat_a.t().dot_mul(&mat_a);
-        let mat_b_ = mat_a.t().dot_mul(&mat_b);
-        let mat_d = mat_a_.diag();
-        let mat_e = mat_a_.tri_strict_l().scalar_mul(-T::one());
-        let mat_f = mat_a_.tri_strict_u().scalar_mul(-T::one());
-        let mat_m = mat_d.sub(&mat_e.mul_scalar_f(w_param)).inv_ge().unwrap();
-        let b = mat_m.dot_mul(&mat_d.mul_scalar_f(T::one() - w_param).add(&mat_f.mul_scalar_f(w_param)));
-        let c = mat_m.dot_mul(&mat_b_.mul_scalar_f(w_param));
-        for _ in 0 .. max_it {
-            xmat = b.dot_mul(&xmat).add(&c);
+    pub fn try_solve_cgs_precond<B, P>(mat_a: &SparseMatrix<T>, mat_b: &B, max_it: usize, tol: T, precond: &P) -> AlgebraResult<B>
+    where B: RightHandSide<T>, P: Preconditioner<T> + ?Sized
+    {
+        iterative::try_solve_cgs_precond(mat_a, mat_b, max_it, tol, precond)
+    }
+
+    // factorise once, then solve as often as needed
+    pub fn lu(&self) -> AlgebraResult<SparseLU<T>>
+    {
+        SparseLU::new(self)
+    }
+
+    // as DenseMatrix::cond_1_est, through a SparseLU; real matrices only, as
+    // SparseLU has no complex counterpart of the dense LU
+    pub fn cond_1_est(&self) -> AlgebraResult<T>
+    where T: Scalar<Real = T>
+    {
+        match SparseLU::new(self) {
+            Ok(lu) => Ok(lu.cond_est()),
+            Err(AlgebraError::SingularPivot { .. }) => Ok(T::infinity()),
+            Err(err) => Err(err),
         }
-        xmat
     }
 
-    pub fn solve_gmres(mat_a: &SparseMatrix<T>, mat_b: &SparseMatrix<T>, m:usize, max_it: usize) -> SparseMatrix<T>
+    pub fn cholesky(&self) -> AlgebraResult<SparseCholesky<T>>
     {
-        let mut xmat = Self::new(mat_a.row_num, mat_b.col_num);
-        let mut r = mat_b.sub(&mat_a.dot_mul(&xmat));
-        let mut beta = r.norm2();
-        let mut v1 = r.normal();
-        for _ in 0 .. max_it {
-            let mut mat_v = Self::new(v1.row_num, m+1);
-            for i in 0 .. v1.row_num {
-                mat_v.set_v(i, 0, v1.get_v(i, 0));
-            }
-            let mut mat_h = Self::new(m+1, m);
-            //Self::arnoldi_proc(m, &mat_a.t().dot_mul(&mat_a), &mut mat_v, &mut mat_h);
-            Self::arnoldi_proc(m, &mat_a, &mut mat_v, &mut mat_h);
-            let mut e = Self::new(m, 1);
-            e.set_v(0, 0, beta);
-            match Self::solve_ge(&mat_h.slice(0, m-1, 0, m-1), &e) {
-                Some(y) => xmat = xmat.add(&mat_v.slice(0, mat_v.row() - 1, 0, m-1).dot_mul(&y)),
-                None => break,
-            }
-            r = mat_b.sub(&mat_a.dot_mul(&xmat));
-            beta = r.norm2();
-            if beta == T::zero() {
-                break;
-            }
-            v1 = r.normal();
+        SparseCholesky::new(self)
+    }
+
+    pub fn ldlt(&self) -> AlgebraResult<SparseLDLT<T>>
+    {
+        SparseLDLT::new(self)
+    }
+
+    pub fn is_symmetric(&self, tol: T) -> bool
+    {
+        MatOps::is_symmetric(self, tol)
+    }
+}
+
+impl<T> SparseMatrix<T>
+where T: Num + Clone + Copy + Display + Element
+{
+    // y = A x a row at a time, the rows shared out among the threads; the
+    // sums are those of mat_vec
+    pub(crate) fn row_wise_mat_vec(&self, x: &[T]) -> Vec<T>
+    {
+        assert_eq!(x.len(), self.col_num, "the length of the vector does not match the matrix!");
+        parallel::map_range(self.row_num, self.elements.len(), |i| {
+            self.elements.range((i, 0) .. (i + 1, 0)).fold(T::zero(), |sum, (&(_, j), &v)| sum + v * x[j])
+        })
+    }
+}
+
+impl<T> MatOps<T> for SparseMatrix<T>
+where T: Num + Clone + Copy + Display
+{
+    fn new(row: usize, col: usize) -> SparseMatrix<T>
+    {
+        SparseMatrix::new(row, col)
+    }
+
+    fn t(&self) -> SparseMatrix<T>
+    {
+        SparseMatrix::t(self)
+    }
+
+    fn set_v(&mut self, row: usize, col: usize, v: T)
+    {
+        SparseMatrix::set_v(self, row, col, v)
+    }
+
+    fn get_v(&self, row: usize, col: usize) -> T
+    {
+        SparseMatrix::get_v(self, row, col)
+    }
+
+    fn slice(&self, row_from: usize, row_to: usize, col_from: usize, col_to: usize) -> SparseMatrix<T>
+    {
+        SparseMatrix::slice(self, row_from, row_to, col_from, col_to)
+    }
+
+    fn row(&self) -> usize
+    {
+        self.row_num
+    }
+
+    fn col(&self) -> usize
+    {
+        self.col_num
+    }
+
+    fn dot_mul(&self, mat: &SparseMatrix<T>) -> SparseMatrix<T>
+    {
+        SparseMatrix::dot_mul(self, mat)
+    }
+
+    fn eye(num: usize) -> SparseMatrix<T>
+    {
+        SparseMatrix::eye(num)
+    }
+
+    fn row_entries(&self, row: usize) -> Vec<(usize, T)>
+    {
+        self.elements.range((row, 0) .. (row + 1, 0)).map(|(&(_, j), &v)| (j, v)).collect()
+    }
+
+    fn swap_rows(&mut self, row_i: usize, row_j: usize)
+    {
+        let entries_i = self.row_entries(row_i);
+        let entries_j = self.row_entries(row_j);
+        for &(col, _) in entries_i.iter().chain(entries_j.iter()) {
+            self.elements.remove(&(row_i, col));
+            self.elements.remove(&(row_j, col));
+        }
+        for (col, v) in entries_i {
+            self.elements.insert((row_j, col), v);
+        }
+        for (col, v) in entries_j {
+            self.elements.insert((row_i, col), v);
         }
-        xmat
     }
 
-    pub fn inv_ge(&self) -> Option<SparseMatrix<T>>
+    fn concat(&self, mat: &SparseMatrix<T>) -> SparseMatrix<T>
     {
-        Self::solve_ge(&self, &Self::eye(self.row_num))
+        SparseMatrix::concat(self, mat)
     }
 
-    pub fn det_ge(&self) -> T {
-        let mut mat = self.scalar_mul(T::one());
-        let mut count = 0;
-        for r in 0 .. mat.row_num {
-            count += mat.pivoting(r);
-            let head = mat.get_v(r, r);
-            if head == T::zero() {
-                return T::zero();
-            }    
-            for nr in r + 1 .. mat.row_num {
-                let row_head = mat.get_v(nr, r) / head;
-                for c in r .. mat.col_num {
-                    mat.set_v(nr, c, mat.get_v(nr, c) - row_head * mat.get_v(r, c));
-                }
-            }  
+    fn scalar_mul(&self, k: T) -> SparseMatrix<T>
+    {
+        SparseMatrix::scalar_mul(self, k)
+    }
+
+    fn add(&self, mat: &SparseMatrix<T>) -> SparseMatrix<T>
+    {
+        SparseMatrix::add(self, mat)
+    }
+
+    fn sub(&self, mat: &SparseMatrix<T>) -> SparseMatrix<T>
+    {
+        SparseMatrix::sub(self, mat)
+    }
+
+    fn mat_vec(&self, x: &[T]) -> Vec<T>
+    {
+        assert_eq!(x.len(), self.col_num, "the length of the vector does not match the matrix!");
+        let mut y = vec![T::zero(); self.row_num];
+        for (i, j, v) in self.iter() {
+            y[i] = y[i] + v * x[j];
         }
-        let mut product = T::one();
-        for i in 0 .. self.row_num {
-            product = product * mat.get_v(i, i);
+        y
+    }
+}
+
+#[test]
+fn sparse_matrix_test() {
+    // a 1D Poisson matrix is large enough that anything dense would be hopeless
+    let n = 100_000;
+    let mut mat = SparseMatrix::<f64>::new(n, n);
+    for i in 0 .. n {
+        mat.set_v(i, i, 2.0);
+        if i > 0 {
+            mat.set_v(i, i - 1, -1.0);
+        }
+        if i + 1 < n {
+            mat.set_v(i, i + 1, -1.0);
         }
-        (-T::one()).powi(count) * product
     }
+    assert_eq!(mat.nnz(), 3 * n - 2);
+    assert_eq!(mat.tri_strict_l().nnz(), n - 1);
+    assert_eq!(mat.t().sub(&mat).nnz(), 0);
+    assert_eq!(mat.dot_mul(&mat).nnz(), 5 * n - 6);
+    let csr = mat.to_csr();
+    let y = csr.mat_vec(&vec![1.0; n]);
+    assert_eq!(y[0], 1.0);
+    assert_eq!(y[n / 2], 0.0);
+    mat.set_v(0, 1, 0.0);
+    assert_eq!(mat.nnz(), 3 * n - 3);
+
+    let small = SparseMatrix::from_dense(&DenseMatrix::<f64>::from(3, 3, &[2.0, 1.0, 0.0, 1.0, 3.0, 1.0, 0.0, 1.0, 4.0]));
+    let mut b = SparseMatrix::<f64>::new(3, 1);
+    b.set_v(0, 0, 3.0);
+    b.set_v(1, 0, 5.0);
+    b.set_v(2, 0, 5.0);
+    let x = SparseMatrix::solve_ge(&small, &b).unwrap();
+    for i in 0 .. 3 {
+        assert!((x.get_v(i, 0) - 1.0).abs() < 1e-12);
+    }
+    assert!((small.det_ge() - small.det_adj()).abs() < 1e-12);
+    assert_eq!(small.try_add(&b).err(), Some(AlgebraError::DimensionMismatch { left: (3, 3), right: (3, 1) }));
+    assert!(small.try_dot_mul(&b).is_ok());
+    assert!(b.try_dot_mul(&small).is_err());
+    assert!(small.try_slice(1, 2, 0, 3).is_err());
+    assert_eq!(SparseMatrix::try_solve_ge(&b, &b).err(), Some(AlgebraError::NotSquare { shape: (3, 1) }));
+    assert_eq!(SparseMatrix::try_solve_sor(&b, &b, 1.0, 10, 1e-8).err(), Some(AlgebraError::NotSquare { shape: (3, 1) }));
+
+    // the columns of a matrix without rows and the rows of one without columns are empty
+    let empty = SparseMatrix::<f64>::new(0, 3);
+    assert_eq!(empty.get_col(2).shape(), (0, 1));
+    assert_eq!(empty.t().get_row(2).shape(), (1, 0));
 }
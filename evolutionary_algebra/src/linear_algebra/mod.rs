@@ -1 +1,9 @@
 pub mod matrix;
+pub mod vector;
+pub mod solver;
+pub mod decomposition;
+pub mod operator;
+pub mod scalar;
+pub mod function;
+pub mod tensor;
+pub mod io;
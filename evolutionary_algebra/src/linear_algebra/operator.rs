@@ -0,0 +1,369 @@
+extern crate num;
+
+use super::matrix::{MatIndex, MatOps};
+use super::matrix::dense::DenseMatrix;
+use super::matrix::sparse::{CscMatrix, CsrMatrix, SparseMatrix};
+use super::super::parallel::Element;
+use num::Num;
+use std::fmt::Display;
+
+// A linear map known only through its action on vectors. The Krylov solvers
+// are written against this trait, so they run on assembled matrices and on
+// matrix-free operators (e.g. the Jacobian of a Newton step) alike.
+pub trait LinearOperator<T> {
+    // (rows, cols): apply takes cols values and returns rows of them
+    fn shape(&self) -> MatIndex;
+    // A x
+    fn apply(&self, x: &[T]) -> Vec<T>;
+    // A^T x
+    fn apply_transpose(&self, x: &[T]) -> Vec<T>;
+}
+
+impl<T, A> LinearOperator<T> for &A
+where A: LinearOperator<T> + ?Sized
+{
+    fn shape(&self) -> MatIndex
+    {
+        (**self).shape()
+    }
+
+    fn apply(&self, x: &[T]) -> Vec<T>
+    {
+        (**self).apply(x)
+    }
+
+    fn apply_transpose(&self, x: &[T]) -> Vec<T>
+    {
+        (**self).apply_transpose(x)
+    }
+}
+
+// A^T x through the rows of A
+fn rows_t_mat_vec<M, T>(mat: &M, x: &[T]) -> Vec<T>
+where M: MatOps<T>, T: Num + Copy
+{
+    assert_eq!(x.len(), mat.row(), "the length of the vector does not match the matrix!");
+    let mut y = vec![T::zero(); mat.col()];
+    for (i, &x_i) in x.iter().enumerate() {
+        for (j, v) in mat.row_entries(i) {
+            y[j] = y[j] + v * x_i;
+        }
+    }
+    y
+}
+
+impl<T> LinearOperator<T> for DenseMatrix<T>
+where T: Num + Clone + Copy + Display + Element
+{
+    fn shape(&self) -> MatIndex
+    {
+        (self.row(), self.col())
+    }
+
+    fn apply(&self, x: &[T]) -> Vec<T>
+    {
+        self.mat_vec(x)
+    }
+
+    fn apply_transpose(&self, x: &[T]) -> Vec<T>
+    {
+        rows_t_mat_vec(self, x)
+    }
+}
+
+impl<T> LinearOperator<T> for SparseMatrix<T>
+where T: Num + Clone + Copy + Display + Element
+{
+    fn shape(&self) -> MatIndex
+    {
+        (self.row(), self.col())
+    }
+
+    fn apply(&self, x: &[T]) -> Vec<T>
+    {
+        self.row_wise_mat_vec(x)
+    }
+
+    fn apply_transpose(&self, x: &[T]) -> Vec<T>
+    {
+        rows_t_mat_vec(self, x)
+    }
+}
+
+impl<T> LinearOperator<T> for CsrMatrix<T>
+where T: Num + Clone + Copy + Display + Element
+{
+    fn shape(&self) -> MatIndex
+    {
+        (self.row(), self.col())
+    }
+
+    fn apply(&self, x: &[T]) -> Vec<T>
+    {
+        self.mat_vec(x)
+    }
+
+    fn apply_transpose(&self, x: &[T]) -> Vec<T>
+    {
+        self.t_mat_vec(x)
+    }
+}
+
+impl<T> LinearOperator<T> for CscMatrix<T>
+where T: Num + Clone + Copy + Display + Element
+{
+    fn shape(&self) -> MatIndex
+    {
+        (self.row(), self.col())
+    }
+
+    fn apply(&self, x: &[T]) -> Vec<T>
+    {
+        self.mat_vec(x)
+    }
+
+    fn apply_transpose(&self, x: &[T]) -> Vec<T>
+    {
+        self.t_mat_vec(x)
+    }
+}
+
+type Action<'a, T> = Box<dyn Fn(&[T]) -> Vec<T> + 'a>;
+
+// an operator given by closures; the transpose is optional and applying it
+// without one panics
+pub struct FnOperator<'a, T> {
+    shape: MatIndex,
+    apply: Action<'a, T>,
+    apply_transpose: Option<Action<'a, T>>,
+}
+
+impl<'a, T> FnOperator<'a, T>
+{
+    pub fn new<F>(row: usize, col: usize, apply: F) -> FnOperator<'a, T>
+    where F: Fn(&[T]) -> Vec<T> + 'a
+    {
+        FnOperator {
+            shape: (row, col),
+            apply: Box::new(apply),
+            apply_transpose: None,
+        }
+    }
+
+    pub fn with_transpose<G>(mut self, apply_transpose: G) -> FnOperator<'a, T>
+    where G: Fn(&[T]) -> Vec<T> + 'a
+    {
+        self.apply_transpose = Some(Box::new(apply_transpose));
+        self
+    }
+}
+
+impl<'a, T> LinearOperator<T> for FnOperator<'a, T>
+{
+    fn shape(&self) -> MatIndex
+    {
+        self.shape
+    }
+
+    fn apply(&self, x: &[T]) -> Vec<T>
+    {
+        assert_eq!(x.len(), self.shape.1, "the length of the vector does not match the operator!");
+        (self.apply)(x)
+    }
+
+    fn apply_transpose(&self, x: &[T]) -> Vec<T>
+    {
+        assert_eq!(x.len(), self.shape.0, "the length of the vector does not match the operator!");
+        match self.apply_transpose {
+            Some(ref apply_transpose) => apply_transpose(x),
+            None => panic!("the operator has no transpose!"),
+        }
+    }
+}
+
+// A B
+pub struct Product<A, B> {
+    left: A,
+    right: B,
+}
+
+impl<A, B> Product<A, B>
+{
+    pub fn new<T>(left: A, right: B) -> Product<A, B>
+    where A: LinearOperator<T>, B: LinearOperator<T>
+    {
+        assert_eq!(left.shape().1, right.shape().0, "the operators cannot be composed!");
+        Product { left, right }
+    }
+}
+
+impl<T, A, B> LinearOperator<T> for Product<A, B>
+where A: LinearOperator<T>, B: LinearOperator<T>
+{
+    fn shape(&self) -> MatIndex
+    {
+        (self.left.shape().0, self.right.shape().1)
+    }
+
+    fn apply(&self, x: &[T]) -> Vec<T>
+    {
+        self.left.apply(&self.right.apply(x))
+    }
+
+    fn apply_transpose(&self, x: &[T]) -> Vec<T>
+    {
+        self.right.apply_transpose(&self.left.apply_transpose(x))
+    }
+}
+
+// A + B
+pub struct Sum<A, B> {
+    left: A,
+    right: B,
+}
+
+impl<A, B> Sum<A, B>
+{
+    pub fn new<T>(left: A, right: B) -> Sum<A, B>
+    where A: LinearOperator<T>, B: LinearOperator<T>
+    {
+        assert_eq!(left.shape(), right.shape(), "the operators have different shapes!");
+        Sum { left, right }
+    }
+}
+
+impl<T, A, B> LinearOperator<T> for Sum<A, B>
+where A: LinearOperator<T>, B: LinearOperator<T>, T: Num + Copy
+{
+    fn shape(&self) -> MatIndex
+    {
+        self.left.shape()
+    }
+
+    fn apply(&self, x: &[T]) -> Vec<T>
+    {
+        self.left.apply(x).into_iter().zip(self.right.apply(x)).map(|(a, b)| a + b).collect()
+    }
+
+    fn apply_transpose(&self, x: &[T]) -> Vec<T>
+    {
+        self.left.apply_transpose(x).into_iter().zip(self.right.apply_transpose(x)).map(|(a, b)| a + b).collect()
+    }
+}
+
+// k A, e.g. with Sum for the shifted operator A - s I
+pub struct Scaled<T, A> {
+    k: T,
+    op: A,
+}
+
+impl<T, A> Scaled<T, A>
+where A: LinearOperator<T>
+{
+    pub fn new(k: T, op: A) -> Scaled<T, A>
+    {
+        Scaled { k, op }
+    }
+}
+
+impl<T, A> LinearOperator<T> for Scaled<T, A>
+where A: LinearOperator<T>, T: Num + Copy
+{
+    fn shape(&self) -> MatIndex
+    {
+        self.op.shape()
+    }
+
+    fn apply(&self, x: &[T]) -> Vec<T>
+    {
+        self.op.apply(x).into_iter().map(|v| self.k * v).collect()
+    }
+
+    fn apply_transpose(&self, x: &[T]) -> Vec<T>
+    {
+        self.op.apply_transpose(x).into_iter().map(|v| self.k * v).collect()
+    }
+}
+
+// A^T, e.g. with Product for the normal equations A^T A
+pub struct Transpose<A> {
+    op: A,
+}
+
+impl<A> Transpose<A>
+{
+    pub fn new(op: A) -> Transpose<A>
+    {
+        Transpose { op }
+    }
+}
+
+impl<T, A> LinearOperator<T> for Transpose<A>
+where A: LinearOperator<T>
+{
+    fn shape(&self) -> MatIndex
+    {
+        let (row, col) = self.op.shape();
+        (col, row)
+    }
+
+    fn apply(&self, x: &[T]) -> Vec<T>
+    {
+        self.op.apply_transpose(x)
+    }
+
+    fn apply_transpose(&self, x: &[T]) -> Vec<T>
+    {
+        self.op.apply(x)
+    }
+}
+
+#[test]
+fn operator_test() {
+    use super::solver::{cg, gmres, minres, SolverOptions};
+
+    let mat = DenseMatrix::<f64>::from(2, 3, &[1.0, 2.0, 3.0,
+                                              4.0, 5.0, 6.0]);
+    let sparse = SparseMatrix::from_dense(&mat);
+    let x = [1.0, -1.0, 2.0];
+    let y = [1.0, 2.0];
+    for op in &[&mat as &dyn LinearOperator<f64>, &sparse, &sparse.to_csr(), &sparse.to_csc()] {
+        assert_eq!(op.shape(), (2, 3));
+        assert_eq!(op.apply(&x), vec![5.0, 11.0]);
+        assert_eq!(op.apply_transpose(&y), vec![9.0, 12.0, 15.0]);
+    }
+    let normal = Product::new(Transpose::new(&mat), &mat);
+    assert_eq!(normal.shape(), (3, 3));
+    assert_eq!(normal.apply(&x), mat.t().dot_mul(&mat).mat_vec(&x));
+    let shifted = Sum::new(&normal, Scaled::new(-2.0, DenseMatrix::<f64>::eye(3)));
+    assert_eq!(shifted.apply(&x), vec![47.0, 67.0, 77.0]);
+    assert_eq!(shifted.apply_transpose(&x), shifted.apply(&x));
+
+    // the 1D Laplacian with Dirichlet ends, never assembled
+    let n = 50;
+    let laplace = |v: &[f64]| (0 .. v.len()).map(|i| {
+        2.0 * v[i] - if i > 0 { v[i - 1] } else { 0.0 } - if i + 1 < v.len() { v[i + 1] } else { 0.0 }
+    }).collect::<Vec<f64>>();
+    let op = FnOperator::new(n, n, laplace).with_transpose(laplace);
+    let exact: Vec<f64> = (0 .. n).map(|i| (i as f64 * 0.3).sin()).collect();
+    let b = op.apply(&exact);
+    for report in (0 .. 3).map(|k| {
+        let mut x = vec![0.0; n];
+        let mut options = SolverOptions::new().rel_tol(1e-12);
+        let report = match k {
+            0 => cg(&op, &b, &mut x, &mut options),
+            1 => minres(&op, &b, &mut x, &mut options),
+            _ => gmres(&op, &b, &mut x, n, &mut options),
+        };
+        assert!(x.iter().zip(exact.iter()).all(|(a, b)| (a - b).abs() < 1e-8));
+        report
+    }) {
+        assert!(report.converged());
+    }
+    // a shift through Sum: (L + I) x = b
+    let shifted = Sum::new(&op, FnOperator::new(n, n, |v: &[f64]| v.to_vec()));
+    let b = shifted.apply(&exact);
+    let mut x = vec![0.0; n];
+    assert!(cg(&shifted, &b, &mut x, &mut SolverOptions::new().rel_tol(1e-12)).converged());
+    assert!(x.iter().zip(exact.iter()).all(|(a, b)| (a - b).abs() < 1e-8));
+}
@@ -0,0 +1,205 @@
+extern crate num;
+
+use num::{Float, Num, Zero};
+use num::complex::Complex;
+use std::fmt::Display;
+use std::ops::Neg;
+use super::super::parallel::{self, Element};
+
+// The element types the complex-capable algorithms (Gaussian elimination,
+// LU, GMRES) are written for: the real floats and the complex numbers over
+// them. `Real` is the type of moduli, norms and tolerances, the type itself
+// for f32 and f64.
+pub trait Scalar: Num + Copy + Display + Neg<Output = Self> + Element
+{
+    type Real: Float + Display + Element;
+
+    const COMPLEX: bool;
+
+    fn from_real(re: Self::Real) -> Self;
+    // the real part for the real types
+    fn from_complex(z: Complex<Self::Real>) -> Self;
+    fn to_complex(self) -> Complex<Self::Real>;
+    fn re(self) -> Self::Real;
+    fn im(self) -> Self::Real;
+    fn conj(self) -> Self;
+    // |z|, without overflow for large parts
+    fn modulus(self) -> Self::Real;
+
+    // |z|^2
+    fn modulus_sqr(self) -> Self::Real
+    {
+        let m = self.modulus();
+        m * m
+    }
+
+    fn is_nan(self) -> bool
+    {
+        self.re().is_nan() || self.im().is_nan()
+    }
+}
+
+macro_rules! impl_real_scalar {
+    ($t:ty) => {
+        impl Scalar for $t {
+            type Real = $t;
+
+            const COMPLEX: bool = false;
+
+            #[inline]
+            fn from_real(re: $t) -> $t
+            {
+                re
+            }
+
+            #[inline]
+            fn from_complex(z: Complex<$t>) -> $t
+            {
+                z.re
+            }
+
+            #[inline]
+            fn to_complex(self) -> Complex<$t>
+            {
+                Complex::new(self, 0.0)
+            }
+
+            #[inline]
+            fn re(self) -> $t
+            {
+                self
+            }
+
+            #[inline]
+            fn im(self) -> $t
+            {
+                0.0
+            }
+
+            #[inline]
+            fn conj(self) -> $t
+            {
+                self
+            }
+
+            #[inline]
+            fn modulus(self) -> $t
+            {
+                self.abs()
+            }
+        }
+    };
+}
+
+impl<R> Scalar for Complex<R>
+where R: Float + Display + Element
+{
+    type Real = R;
+
+    const COMPLEX: bool = true;
+
+    #[inline]
+    fn from_real(re: R) -> Complex<R>
+    {
+        Complex::new(re, R::zero())
+    }
+
+    #[inline]
+    fn from_complex(z: Complex<R>) -> Complex<R>
+    {
+        z
+    }
+
+    #[inline]
+    fn to_complex(self) -> Complex<R>
+    {
+        self
+    }
+
+    #[inline]
+    fn re(self) -> R
+    {
+        self.re
+    }
+
+    #[inline]
+    fn im(self) -> R
+    {
+        self.im
+    }
+
+    #[inline]
+    fn conj(self) -> Complex<R>
+    {
+        Complex::conj(&self)
+    }
+
+    #[inline]
+    fn modulus(self) -> R
+    {
+        self.norm()
+    }
+
+    #[inline]
+    fn modulus_sqr(self) -> R
+    {
+        self.norm_sqr()
+    }
+}
+
+impl_real_scalar!(f32);
+impl_real_scalar!(f64);
+
+// sum conj(x_i) y_i, linear in y
+pub(crate) fn dot<T>(x: &[T], y: &[T]) -> T
+where T: Scalar
+{
+    dot_with(x, y, false)
+}
+
+pub(crate) fn norm<T>(x: &[T]) -> T::Real
+where T: Scalar
+{
+    norm_with(x, false)
+}
+
+// as dot and norm, deterministic as in parallel::sum
+pub(crate) fn dot_with<T>(x: &[T], y: &[T], deterministic: bool) -> T
+where T: Scalar
+{
+    let len = x.len().min(y.len());
+    parallel::sum(len, T::zero(), deterministic, |r| {
+        x[r.clone()].iter().zip(y[r].iter()).fold(T::zero(), |sum, (&a, &b)| sum + a.conj() * b)
+    })
+}
+
+pub(crate) fn norm_with<T>(x: &[T], deterministic: bool) -> T::Real
+where T: Scalar
+{
+    parallel::sum(x.len(), T::Real::zero(), deterministic, |r| x[r].iter().fold(T::Real::zero(), |sum, &v| sum + v.modulus_sqr())).sqrt()
+}
+
+#[test]
+fn scalar_test() {
+    let z = Complex::new(3.0, -4.0);
+    assert_eq!(z.modulus(), 5.0);
+    assert_eq!(z.modulus_sqr(), 25.0);
+    assert_eq!(Scalar::conj(z), Complex::new(3.0, 4.0));
+    assert_eq!((z.re(), z.im()), (3.0, -4.0));
+    assert_eq!(Complex::<f32>::from_real(2.0), Complex::new(2.0, 0.0));
+    assert_eq!((-2.5f64).modulus(), 2.5);
+    assert_eq!(Scalar::conj(-2.5f64), -2.5);
+    assert!(Scalar::is_nan(Complex::new(1.0, f64::NAN)));
+    assert!(!Scalar::is_nan(1.0f32));
+    assert_eq!(f64::from_complex(Complex::new(1.5, 2.0)), 1.5);
+    assert_eq!((-1.5f32).to_complex(), Complex::new(-1.5, 0.0));
+    assert_eq!((Complex::<f64>::COMPLEX, f64::COMPLEX), (true, false));
+
+    let x = [Complex::new(1.0f64, 1.0), Complex::new(0.0, 2.0)];
+    let y = [Complex::new(2.0, 0.0), Complex::new(1.0, -1.0)];
+    // <x, y> = (1 - i) 2 + (-2i) (1 - i) = 2 - 2i - 2i - 2
+    assert_eq!(dot(&x, &y), Complex::new(0.0, -4.0));
+    assert_eq!(dot(&x, &x), Complex::new(6.0, 0.0));
+    assert!((norm(&x) - 6f64.sqrt()).abs() < 1e-15);
+    assert_eq!(norm(&[3.0, 4.0]), 5.0);
+}
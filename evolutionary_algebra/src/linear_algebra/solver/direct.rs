@@ -0,0 +1,237 @@
+extern crate num;
+
+use super::super::matrix::MatOps;
+use super::super::scalar::Scalar;
+use super::rhs::RightHandSide;
+use super::super::super::error::{self, AlgebraError, AlgebraResult};
+
+fn pivoting<M, T>(mat: &mut M, site: usize) -> i32
+where M: MatOps<T>, T: Scalar
+{
+    // the largest modulus, so complex entries are compared by size as well
+    let mut tmp = mat.get_v(site, site).modulus();
+    let mut index = site;
+    for row in (site + 1) .. mat.row() {
+        if mat.get_v(row, site).modulus() > tmp {
+            tmp = mat.get_v(row, site).modulus();
+            index = row;
+        }
+    }
+    if index != site {
+        mat.swap_rows(index, site);
+        return 1;
+    }
+    0
+}
+
+pub fn solve_ge<M, B, T>(mat_a: &M, mat_b: &B) -> Option<B>
+where M: MatOps<T>, B: RightHandSide<T>, T: Scalar
+{
+    try_solve_ge(mat_a, mat_b).ok()
+}
+
+// b is copied into a matrix of A's type, which the elimination runs on
+pub fn try_solve_ge<M, B, T>(mat_a: &M, mat_b: &B) -> AlgebraResult<B>
+where M: MatOps<T>, B: RightHandSide<T>, T: Scalar
+{
+    error::check_system(mat_a.shape(), mat_b.rhs_shape())?;
+    let col_num = mat_b.rhs_shape().1;
+    let mut rhs = M::new(mat_a.row(), col_num);
+    for k in 0 .. col_num {
+        for (i, v) in mat_b.rhs_col(k).into_iter().enumerate() {
+            rhs.set_v(i, k, v);
+        }
+    }
+    let xmat = eliminate(mat_a, &rhs)?;
+    Ok(B::from_rhs_cols(mat_a.col(), (0 .. col_num).map(|k| xmat.get_col_vec(k)).collect()))
+}
+
+// Gauss-Jordan on [A | B]
+fn eliminate<M, T>(mat_a: &M, mat_b: &M) -> AlgebraResult<M>
+where M: MatOps<T>, T: Scalar
+{
+    error::check_system(mat_a.shape(), mat_b.shape())?;
+    let mut mat = mat_a.concat(mat_b);
+    let row_num = mat.row();
+    for r in 0 .. row_num {
+        pivoting(&mut mat, r);
+        let head = mat.get_v(r, r);
+        if head == T::zero() {
+            return Err(AlgebraError::SingularPivot { index: r });
+        }
+        for (c, v) in mat.row_entries(r) {
+            if c >= r {
+                mat.set_v(r, c, v / head);
+            }
+        }
+        let pivot_row = mat.row_entries(r);
+        mat.eliminate_rows(r + 1 .. row_num, r, T::one(), &pivot_row);
+    }
+
+    for r in (0 .. row_num).rev() {
+        let pivot_row = mat.row_entries(r);
+        mat.eliminate_rows(0 .. r, r, T::one(), &pivot_row);
+    }
+
+    Ok(mat.slice(0, row_num - 1, mat_a.col(), mat.col() - 1))
+}
+
+pub fn inv_ge<M, T>(mat: &M) -> Option<M>
+where M: MatOps<T>, T: Scalar
+{
+    try_inv_ge(mat).ok()
+}
+
+pub fn try_inv_ge<M, T>(mat: &M) -> AlgebraResult<M>
+where M: MatOps<T>, T: Scalar
+{
+    error::check_square(mat.shape())?;
+    eliminate(mat, &M::eye(mat.row()))
+}
+
+pub fn det_ge<M, T>(mat: &M) -> T
+where M: MatOps<T>, T: Scalar
+{
+    try_det_ge(mat).unwrap_or_else(|_| T::zero())
+}
+
+pub fn try_det_ge<M, T>(mat: &M) -> AlgebraResult<T>
+where M: MatOps<T>, T: Scalar
+{
+    error::check_square(mat.shape())?;
+    let mut mat = mat.scalar_mul(T::one());
+    let mut count = 0;
+    for r in 0 .. mat.row() {
+        count += pivoting(&mut mat, r);
+        let head = mat.get_v(r, r);
+        if head == T::zero() {
+            return Ok(T::zero());
+        }
+        let pivot_row = mat.row_entries(r);
+        let row_num = mat.row();
+        mat.eliminate_rows(r + 1 .. row_num, r, head, &pivot_row);
+    }
+    let mut product = T::one();
+    for i in 0 .. mat.row() {
+        product = product * mat.get_v(i, i);
+    }
+    Ok(if count % 2 == 0 { product } else { -product })
+}
+
+pub fn cofactors<M, T>(mat: &M, row: usize, col: usize) -> M
+where M: MatOps<T>, T: Scalar
+{
+    let mut res = M::new(mat.row() - 1, mat.col() - 1);
+    for i in (0 .. mat.row()).filter(|&i| i != row) {
+        let ri = if i < row {
+            i
+        } else {
+            i - 1
+        };
+        for (j, v) in mat.row_entries(i).into_iter().filter(|&(j, _)| j != col) {
+            let cj = if j < col {
+                j
+            } else {
+                j - 1
+            };
+            res.set_v(ri, cj, v);
+        }
+    }
+    res
+}
+
+pub fn adjoint<M, T>(mat: &M) -> M
+where M: MatOps<T>, T: Scalar
+{
+    let mut res = M::new(mat.col(), mat.row());
+    for i in 0 .. mat.row() {
+        for j in 0 .. mat.col() {
+            let minor = det_adj(&cofactors(mat, i, j));
+            res.set_v(j, i, if (i + j) % 2 == 0 { minor } else { -minor });
+        }
+    }
+    res
+}
+
+pub fn det_adj<M, T>(mat: &M) -> T
+where M: MatOps<T>, T: Scalar
+{
+    if mat.row() == mat.col() {
+        match mat.row() {
+            1 => mat.get_v(0, 0),
+            2 => mat.get_v(0, 0) * mat.get_v(1, 1) - mat.get_v(0, 1) * mat.get_v(1, 0),
+            _ => {
+                let mut sum = T::zero();
+                for i in 0 .. mat.row() {
+                    let head = mat.get_v(i, 0);
+                    if head != T::zero() {
+                        let term = head * det_adj(&cofactors(mat, i, 0));
+                        sum = if i % 2 == 0 { sum + term } else { sum - term };
+                    }
+                }
+                sum
+            }
+        }
+    }
+    else {
+        T::zero()
+    }
+}
+
+pub fn inv_adj<M, T>(mat: &M) -> Option<M>
+where M: MatOps<T>, T: Scalar
+{
+    try_inv_adj(mat).ok()
+}
+
+pub fn try_inv_adj<M, T>(mat: &M) -> AlgebraResult<M>
+where M: MatOps<T>, T: Scalar
+{
+    error::check_square(mat.shape())?;
+    let det = det_adj(mat);
+    if det == T::zero() {
+        Err(AlgebraError::Singular)
+    }
+    else {
+        Ok(
+            adjoint(mat).scalar_mul(T::one() / det)
+        )
+    }
+}
+
+#[test]
+fn direct_solver_test() {
+    use super::super::matrix::dense::DenseMatrix;
+    use super::super::matrix::sparse::SparseMatrix;
+
+    let values = [4.0, -2.0, 1.0, 0.0,
+                  3.0, 6.0, -4.0, 2.0,
+                  2.0, 1.0, 8.0, -5.0,
+                  0.0, 1.0, 1.0, 3.0];
+    let dense = DenseMatrix::<f64>::from(4, 4, &values);
+    let sparse = SparseMatrix::from_dense(&dense);
+    let dense_b = DenseMatrix::<f64>::from(4, 1, &[1.0, 2.0, 3.0, 4.0]);
+    let sparse_b = SparseMatrix::from_dense(&dense_b);
+
+    let dense_x = solve_ge(&dense, &dense_b).unwrap();
+    let sparse_x = solve_ge(&sparse, &sparse_b).unwrap();
+    for i in 0 .. 4 {
+        assert!((dense_x.get_v(i, 0) - sparse_x.get_v(i, 0)).abs() < 1e-12);
+    }
+    let residual = dense.mat_vec(&dense_x.get_col_vec(0));
+    for (i, &r_i) in residual.iter().enumerate() {
+        assert!((r_i - dense_b.get_v(i, 0)).abs() < 1e-12);
+    }
+    assert!((det_ge(&dense) - det_adj(&sparse)).abs() < 1e-10);
+    let dense_inv = inv_ge(&dense).unwrap();
+    let sparse_inv = inv_adj(&sparse).unwrap().to_dense();
+    assert!(dense_inv.sub(&sparse_inv).norm_inf() < 1e-12);
+    assert!(cofactors(&sparse, 1, 2).to_dense() == cofactors(&dense, 1, 2));
+
+    let singular = DenseMatrix::<f64>::from(3, 3, &[1.0, 2.0, 3.0, 2.0, 4.0, 6.0, 1.0, 0.0, 1.0]);
+    assert_eq!(try_inv_ge(&singular).err(), Some(AlgebraError::SingularPivot { index: 2 }));
+    assert_eq!(try_inv_adj(&singular).err(), Some(AlgebraError::Singular));
+    assert_eq!(try_det_ge(&singular), Ok(0.0));
+    assert_eq!(try_solve_ge(&dense, &singular).err(), Some(AlgebraError::DimensionMismatch { left: (4, 4), right: (3, 3) }));
+    assert_eq!(try_det_ge(&dense_b).err(), Some(AlgebraError::NotSquare { shape: (4, 1) }));
+}
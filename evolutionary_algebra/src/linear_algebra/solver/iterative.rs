@@ -0,0 +1,883 @@
+extern crate num;
+
+use super::super::matrix::MatOps;
+use super::super::operator::LinearOperator;
+use super::super::scalar::{self, Scalar};
+use super::monitor::{Monitor, SolveReport, SolverOptions, StopReason};
+use super::preconditioner::{Identity, PrecondSide, Preconditioner};
+use super::rhs::RightHandSide;
+use super::super::super::error::{self, AlgebraError, AlgebraResult};
+use super::super::super::parallel::{self, Element};
+use num::{Float, Num, NumCast, Zero};
+
+// deterministic as in parallel::sum
+fn inner_product<T>(vec1: &[T], vec2: &[T], deterministic: bool) -> T
+where T: Float + Element
+{
+    let len = vec1.len().min(vec2.len());
+    parallel::sum(len, T::zero(), deterministic, |r| {
+        vec1[r.clone()].iter().zip(vec2[r].iter()).fold(T::zero(), |sum, (&a, &b)| sum + a * b)
+    })
+}
+
+fn norm<T>(vec: &[T], deterministic: bool) -> T
+where T: Float + Element
+{
+    inner_product(vec, vec, deterministic).sqrt()
+}
+
+fn axpy<T>(alpha: T, x: &[T], y: &mut [T])
+where T: Num + Copy
+{
+    for (y_i, &x_i) in y.iter_mut().zip(x.iter()) {
+        *y_i = *y_i + alpha * x_i;
+    }
+}
+
+fn residual<A, T>(op: &A, b: &[T], x: &[T]) -> Vec<T>
+where A: LinearOperator<T> + ?Sized, T: Num + Copy
+{
+    op.apply(x).iter().zip(b.iter()).map(|(&ax, &b)| b - ax).collect()
+}
+
+fn check_lengths<A, T>(op: &A, b: &[T], x: &[T])
+where A: LinearOperator<T> + ?Sized
+{
+    let (row, col) = op.shape();
+    assert_eq!(b.len(), row, "the length of the vector does not match the operator!");
+    assert_eq!(x.len(), col, "the length of the initial guess does not match the operator!");
+}
+
+// one solve per column of b from a zero guess, the iterates become the columns of x
+fn solve_columns<M, B, T, F>(mat_a: &M, mat_b: &B, mut solve: F) -> (B, Vec<SolveReport<T>>)
+where M: MatOps<T>, B: RightHandSide<T>, T: Float + Element, F: FnMut(&[T], &mut [T]) -> SolveReport<T>
+{
+    let n = mat_a.col();
+    let col_num = mat_b.rhs_shape().1;
+    let mut cols = Vec::with_capacity(col_num);
+    let mut reports = Vec::with_capacity(col_num);
+    for k in 0 .. col_num {
+        let b = mat_b.rhs_col(k);
+        let mut x = vec![T::zero(); n];
+        reports.push(solve(&b, &mut x));
+        cols.push(x);
+    }
+    (B::from_rhs_cols(n, cols), reports)
+}
+
+// Err(NotConverged) with the worst relative residual unless every column converged
+fn check_converged<M, T>(xmat: M, reports: &[SolveReport<T>], b_norms: &[T]) -> AlgebraResult<M>
+where T: Float + Element
+{
+    let mut failed = None;
+    let mut worst = T::zero();
+    for (report, &b_norm) in reports.iter().zip(b_norms.iter()) {
+        if !report.converged() {
+            failed = Some(report.iterations);
+            let res = if b_norm == T::zero() { report.residual() } else { report.residual() / b_norm };
+            worst = worst.max(res);
+        }
+    }
+    match failed {
+        None => Ok(xmat),
+        Some(iterations) => Err(AlgebraError::NotConverged { iterations, residual: worst.to_f64().unwrap_or(f64::NAN) }),
+    }
+}
+
+fn col_norms<B, T, N>(mat_b: &B, b_norm: N) -> Vec<T>
+where B: RightHandSide<T>, T: Float + Element, N: Fn(&[T]) -> T
+{
+    (0 .. mat_b.rhs_shape().1).map(|k| b_norm(&mat_b.rhs_col(k))).collect()
+}
+
+// SOR sweeps on the normal equations A^T A x = A^T b, starting from x; every
+// sweep is one iteration and is judged by the residual ||b - A x||. Unlike
+// the Krylov solvers below SOR needs the entries of A, not just products.
+pub fn sor<M, T>(mat_a: &M, b: &[T], x: &mut [T], w_param: T, options: &mut SolverOptions<T>) -> SolveReport<T>
+where M: MatOps<T>, T: Float + Element
+{
+    // Note that 0 < w < 2 !!!
+    let n = mat_a.col();
+    assert_eq!(b.len(), mat_a.row(), "the length of the vector does not match the matrix!");
+    assert_eq!(x.len(), n, "the length of the initial guess does not match the matrix!");
+    let mat_at = mat_a.t();
+    let mat_n = mat_at.dot_mul(mat_a);
+    let c = mat_at.mat_vec(b);
+    let rows: Vec<Vec<(usize, T)>> = (0 .. n).map(|i| mat_n.row_entries(i)).collect();
+    let det = options.is_deterministic();
+    let mut monitor = Monitor::new(options, norm(b, det));
+    let r_norm_of = |x: &[T]| norm(&mat_a.mat_vec(x).iter().zip(b.iter()).map(|(&ax, &b)| b - ax).collect::<Vec<T>>(), det);
+    let mut r_norm = r_norm_of(x);
+    loop {
+        if let Some(reason) = monitor.check(x, r_norm) {
+            return monitor.report(reason);
+        }
+        for i in 0 .. n {
+            let mut sigma = T::zero();
+            let mut diag = T::zero();
+            for &(j, v) in &rows[i] {
+                if j == i {
+                    diag = v;
+                }
+                else {
+                    sigma = sigma + v * x[j];
+                }
+            }
+            x[i] = (T::one() - w_param) * x[i] + w_param * (c[i] - sigma) / diag;
+        }
+        r_norm = r_norm_of(x);
+    }
+}
+
+// x += D y with R y = g, R upper triangular and given by its columns, for the
+// first columns of R that there are
+fn update_solution<T>(mat_r: &[Vec<T>], g: &[T], directions: &[Vec<T>], x: &mut [T])
+where T: Num + Copy
+{
+    let k = mat_r.len();
+    let mut y = vec![T::zero(); k];
+    for i in (0 .. k).rev() {
+        let mut sum = g[i];
+        for l in i + 1 .. k {
+            sum = sum - mat_r[l][i] * y[l];
+        }
+        y[i] = sum / mat_r[i][i];
+    }
+    for (y_i, d_i) in y.into_iter().zip(directions.iter()) {
+        axpy(y_i, d_i, x);
+    }
+}
+
+// One restarted GMRES(m) on op, optionally preconditioned from the left by
+// `left` or from the right by `right`. A right preconditioner may change from
+// one step to the next (flexible GMRES), as the preconditioned directions are
+// kept. Every Arnoldi step is one iteration, judged by the residual estimate
+// the Givens QR of the Hessenberg matrix gives for free; it is ||b - A x||
+// (||M^-1 (b - A x)|| with left preconditioning) in exact arithmetic. The
+// iterate is only formed at the end of a cycle, so inside one the callbacks
+// see the x of the last restart. Complex systems are solved with the
+// Hermitian inner product and complex rotations.
+fn gmres_cycles<A, T, L, R>(op: &A, b: &[T], x: &mut [T], m: usize, left: Option<L>, mut right: Option<R>,
+                            options: &mut SolverOptions<T::Real, T>) -> SolveReport<T::Real>
+where A: LinearOperator<T> + ?Sized, T: Scalar, L: Fn(&[T]) -> Vec<T>, R: FnMut(&[T]) -> Vec<T>
+{
+    check_lengths(op, b, x);
+    assert!(m > 0, "the restart length must be positive!");
+    let reorthogonalize = options.reorthogonalizes();
+    let det = options.is_deterministic();
+    let precondition = |v: Vec<T>| match left {
+        Some(ref left) => left(&v),
+        None => v,
+    };
+    let mut monitor = Monitor::new(options, scalar::norm_with(&precondition(b.to_vec()), det));
+    let mut r = precondition(residual(op, b, x));
+    let mut beta = scalar::norm_with(&r, det);
+    if let Some(reason) = monitor.check(x, beta) {
+        return monitor.report(reason);
+    }
+    // the largest ||A v|| seen, a lower bound of ||A||
+    let mut op_norm = T::Real::zero();
+    loop {
+        let mut mat_v = vec![r.iter().map(|&v| v / T::from_real(beta)).collect::<Vec<T>>()];
+        // the preconditioned directions M_j^-1 v_j, only kept for a right preconditioner
+        let mut mat_z: Vec<Vec<T>> = Vec::new();
+        // the columns of the Hessenberg matrix, turned into R by the rotations
+        let mut mat_h: Vec<Vec<T>> = Vec::with_capacity(m);
+        // (c, s) with c real
+        let mut rotations: Vec<(T::Real, T)> = Vec::with_capacity(m);
+        let mut g = vec![T::from_real(beta)];
+        let mut breakdown = false;
+        for j in 0 .. m {
+            let mut w = match right {
+                Some(ref mut right) => {
+                    mat_z.push(right(&mat_v[j]));
+                    op.apply(&mat_z[j])
+                },
+                None => op.apply(&mat_v[j]),
+            };
+            w = precondition(w);
+            let w_norm = scalar::norm_with(&w, det);
+            // modified Gram-Schmidt, twice if asked for
+            let mut h = vec![T::zero(); j + 2];
+            for _ in 0 .. if reorthogonalize { 2 } else { 1 } {
+                for (i, v_i) in mat_v.iter().enumerate() {
+                    let h_ij = scalar::dot_with(v_i, &w, det);
+                    h[i] = h[i] + h_ij;
+                    axpy(-h_ij, v_i, &mut w);
+                }
+            }
+            let h_next = scalar::norm_with(&w, det);
+            h[j + 1] = T::from_real(h_next);
+            // below this Gram-Schmidt leaves nothing but rounding errors
+            let noise = <T::Real as NumCast>::from(10 * (j + 2)).unwrap() * T::Real::epsilon();
+            op_norm = op_norm.max(w_norm);
+            // the new vector is (numerically) in the Krylov space already: the
+            // exact solution of this space is at hand
+            let happy = h_next <= noise * w_norm;
+            for (i, &(c, s)) in rotations.iter().enumerate() {
+                let (u, v) = (h[i], h[i + 1]);
+                h[i] = T::from_real(c) * u + s * v;
+                h[i + 1] = T::from_real(c) * v - s.conj() * u;
+            }
+            let (a, b) = (h[j], h[j + 1]);
+            let rho = a.modulus().hypot(b.modulus());
+            if rho <= noise * op_norm || rho.is_nan() {
+                // H is singular: keep the first j directions
+                breakdown = true;
+                break;
+            }
+            // [c s; -conj(s) c] [a; b] = [r; 0], r keeping the phase of a
+            let (c, s, r_jj) = if a == T::zero() {
+                (T::Real::zero(), T::one(), b)
+            }
+            else {
+                let phase = a / T::from_real(a.modulus());
+                (a.modulus() / rho, phase * b.conj() / T::from_real(rho), phase * T::from_real(rho))
+            };
+            h[j] = r_jj;
+            h[j + 1] = T::zero();
+            rotations.push((c, s));
+            g.push(-s.conj() * g[j]);
+            g[j] = T::from_real(c) * g[j];
+            mat_h.push(h);
+            if !happy {
+                mat_v.push(w.into_iter().map(|v| v / T::from_real(h_next)).collect());
+            }
+            // the iterate is formed in time for the callbacks when the cycle ends
+            let last = happy || j + 1 == m;
+            if last {
+                update_solution(&mat_h, &g, if right.is_some() { &mat_z } else { &mat_v }, x);
+            }
+            if let Some(reason) = monitor.check(x, g[j + 1].modulus()) {
+                if !last {
+                    update_solution(&mat_h, &g, if right.is_some() { &mat_z } else { &mat_v }, x);
+                }
+                return monitor.report(reason);
+            }
+            if happy {
+                return monitor.report(StopReason::Breakdown);
+            }
+        }
+        if breakdown {
+            update_solution(&mat_h, &g, if right.is_some() { &mat_z } else { &mat_v }, x);
+            return monitor.report(StopReason::Breakdown);
+        }
+        r = precondition(residual(op, b, x));
+        beta = scalar::norm_with(&r, det);
+    }
+}
+
+// restarted GMRES(m) starting from x; every Arnoldi step is one iteration
+pub fn gmres<A, T>(op: &A, b: &[T], x: &mut [T], m: usize, options: &mut SolverOptions<T::Real, T>) -> SolveReport<T::Real>
+where A: LinearOperator<T> + ?Sized, T: Scalar
+{
+    gmres_cycles(op, b, x, m, None::<fn(&[T]) -> Vec<T>>, None::<fn(&[T]) -> Vec<T>>, options)
+}
+
+// GMRES(m) on M^-1 A (left) or A M^-1 (right); with left preconditioning the
+// monitored residual is ||M^-1 (b - A x)|| relative to ||M^-1 b||
+pub fn gmres_precond<A, T, P>(op: &A, b: &[T], x: &mut [T], m: usize, precond: &P, side: PrecondSide,
+                              options: &mut SolverOptions<T::Real, T>) -> SolveReport<T::Real>
+where A: LinearOperator<T> + ?Sized, T: Scalar, P: Preconditioner<T> + ?Sized
+{
+    let apply = |v: &[T]| precond.apply(v);
+    match side {
+        PrecondSide::Left => gmres_cycles(op, b, x, m, Some(apply), None::<fn(&[T]) -> Vec<T>>, options),
+        PrecondSide::Right => gmres_cycles(op, b, x, m, None::<fn(&[T]) -> Vec<T>>, Some(apply), options),
+    }
+}
+
+// flexible GMRES(m): right preconditioned by z = precond(v), which may be a
+// different operator at every step, e.g. a few iterations of another solver
+pub fn fgmres<A, T, F>(op: &A, b: &[T], x: &mut [T], m: usize, precond: F, options: &mut SolverOptions<T::Real, T>) -> SolveReport<T::Real>
+where A: LinearOperator<T> + ?Sized, T: Scalar, F: FnMut(&[T]) -> Vec<T>
+{
+    gmres_cycles(op, b, x, m, None::<fn(&[T]) -> Vec<T>>, Some(precond), options)
+}
+
+// conjugate gradients for a symmetric positive definite A; a direction with
+// p^T A p <= 0 shows that A is not, and stops with Breakdown
+pub fn cg<A, T>(op: &A, b: &[T], x: &mut [T], options: &mut SolverOptions<T>) -> SolveReport<T>
+where A: LinearOperator<T> + ?Sized, T: Float + Element
+{
+    cg_precond(op, b, x, &Identity, options)
+}
+
+// preconditioned CG; M has to be symmetric positive definite as well
+pub fn cg_precond<A, T, P>(op: &A, b: &[T], x: &mut [T], precond: &P, options: &mut SolverOptions<T>) -> SolveReport<T>
+where A: LinearOperator<T> + ?Sized, T: Float + Element, P: Preconditioner<T> + ?Sized
+{
+    check_lengths(op, b, x);
+    let det = options.is_deterministic();
+    let mut monitor = Monitor::new(options, norm(b, det));
+    let mut r = residual(op, b, x);
+    let mut z = precond.apply(&r);
+    let mut p = z.clone();
+    let mut rz = inner_product(&r, &z, det);
+    loop {
+        if let Some(reason) = monitor.check(x, norm(&r, det)) {
+            return monitor.report(reason);
+        }
+        let ap = op.apply(&p);
+        let pap = inner_product(&p, &ap, det);
+        if pap <= T::zero() || pap.is_nan() {
+            return monitor.report(StopReason::Breakdown);
+        }
+        let alpha = rz / pap;
+        axpy(alpha, &p, x);
+        axpy(-alpha, &ap, &mut r);
+        z = precond.apply(&r);
+        let rz_new = inner_product(&r, &z, det);
+        let beta = rz_new / rz;
+        for (p_i, &z_i) in p.iter_mut().zip(z.iter()) {
+            *p_i = z_i + beta * *p_i;
+        }
+        rz = rz_new;
+    }
+}
+
+// BiCGSTAB for a general square A; the shadow residual is the initial one
+pub fn bicgstab<A, T>(op: &A, b: &[T], x: &mut [T], options: &mut SolverOptions<T>) -> SolveReport<T>
+where A: LinearOperator<T> + ?Sized, T: Float + Element
+{
+    bicgstab_precond(op, b, x, &Identity, options)
+}
+
+// right preconditioned BiCGSTAB, so the monitored residual stays ||b - A x||
+pub fn bicgstab_precond<A, T, P>(op: &A, b: &[T], x: &mut [T], precond: &P, options: &mut SolverOptions<T>) -> SolveReport<T>
+where A: LinearOperator<T> + ?Sized, T: Float + Element, P: Preconditioner<T> + ?Sized
+{
+    check_lengths(op, b, x);
+    let det = options.is_deterministic();
+    let mut monitor = Monitor::new(options, norm(b, det));
+    let mut r = residual(op, b, x);
+    let r_hat = r.clone();
+    let mut p = vec![T::zero(); r.len()];
+    let mut v = vec![T::zero(); r.len()];
+    let (mut rho, mut alpha, mut omega) = (T::one(), T::one(), T::one());
+    loop {
+        if let Some(reason) = monitor.check(x, norm(&r, det)) {
+            return monitor.report(reason);
+        }
+        let rho_new = inner_product(&r_hat, &r, det);
+        if rho_new == T::zero() || omega == T::zero() {
+            return monitor.report(StopReason::Breakdown);
+        }
+        let beta = (rho_new / rho) * (alpha / omega);
+        for ((p_i, &r_i), &v_i) in p.iter_mut().zip(r.iter()).zip(v.iter()) {
+            *p_i = r_i + beta * (*p_i - omega * v_i);
+        }
+        let p_hat = precond.apply(&p);
+        v = op.apply(&p_hat);
+        let r_hat_v = inner_product(&r_hat, &v, det);
+        if r_hat_v == T::zero() {
+            return monitor.report(StopReason::Breakdown);
+        }
+        alpha = rho_new / r_hat_v;
+        // s overwrites r
+        axpy(-alpha, &v, &mut r);
+        let s_hat = precond.apply(&r);
+        let t = op.apply(&s_hat);
+        let tt = inner_product(&t, &t, det);
+        omega = if tt == T::zero() { T::zero() } else { inner_product(&t, &r, det) / tt };
+        axpy(alpha, &p_hat, x);
+        axpy(omega, &s_hat, x);
+        axpy(-omega, &t, &mut r);
+        rho = rho_new;
+    }
+}
+
+// conjugate gradients squared for a general square A
+pub fn cgs<A, T>(op: &A, b: &[T], x: &mut [T], options: &mut SolverOptions<T>) -> SolveReport<T>
+where A: LinearOperator<T> + ?Sized, T: Float + Element
+{
+    cgs_precond(op, b, x, &Identity, options)
+}
+
+// right preconditioned CGS
+pub fn cgs_precond<A, T, P>(op: &A, b: &[T], x: &mut [T], precond: &P, options: &mut SolverOptions<T>) -> SolveReport<T>
+where A: LinearOperator<T> + ?Sized, T: Float + Element, P: Preconditioner<T> + ?Sized
+{
+    check_lengths(op, b, x);
+    let det = options.is_deterministic();
+    let mut monitor = Monitor::new(options, norm(b, det));
+    let mut r = residual(op, b, x);
+    let r_hat = r.clone();
+    let mut p = vec![T::zero(); r.len()];
+    let mut q = vec![T::zero(); r.len()];
+    let mut rho = T::one();
+    loop {
+        if let Some(reason) = monitor.check(x, norm(&r, det)) {
+            return monitor.report(reason);
+        }
+        let rho_new = inner_product(&r_hat, &r, det);
+        if rho_new == T::zero() {
+            return monitor.report(StopReason::Breakdown);
+        }
+        let beta = rho_new / rho;
+        let u: Vec<T> = r.iter().zip(q.iter()).map(|(&r_i, &q_i)| r_i + beta * q_i).collect();
+        for ((p_i, &u_i), &q_i) in p.iter_mut().zip(u.iter()).zip(q.iter()) {
+            *p_i = u_i + beta * (q_i + beta * *p_i);
+        }
+        let v = op.apply(&precond.apply(&p));
+        let sigma = inner_product(&r_hat, &v, det);
+        if sigma == T::zero() {
+            return monitor.report(StopReason::Breakdown);
+        }
+        let alpha = rho_new / sigma;
+        q = u.iter().zip(v.iter()).map(|(&u_i, &v_i)| u_i - alpha * v_i).collect();
+        let w = precond.apply(&u.iter().zip(q.iter()).map(|(&u_i, &q_i)| u_i + q_i).collect::<Vec<T>>());
+        axpy(alpha, &w, x);
+        axpy(-alpha, &op.apply(&w), &mut r);
+        rho = rho_new;
+    }
+}
+
+// MINRES (Paige and Saunders) for a symmetric, possibly indefinite A. The
+// residual it monitors is the one of the Lanczos recurrence, which equals
+// ||b - A x|| in exact arithmetic.
+pub fn minres<A, T>(op: &A, b: &[T], x: &mut [T], options: &mut SolverOptions<T>) -> SolveReport<T>
+where A: LinearOperator<T> + ?Sized, T: Float + Element
+{
+    check_lengths(op, b, x);
+    let n = x.len();
+    let det = options.is_deterministic();
+    let mut monitor = Monitor::new(options, norm(b, det));
+    let mut r1 = residual(op, b, x);
+    let mut r2 = r1.clone();
+    let mut beta = norm(&r1, det);
+    let mut old_beta = T::zero();
+    let mut phi_bar = beta;
+    let (mut d_bar, mut epsilon) = (T::zero(), T::zero());
+    let (mut cs, mut sn) = (-T::one(), T::zero());
+    let mut w = vec![T::zero(); n];
+    let mut w2 = vec![T::zero(); n];
+    loop {
+        if let Some(reason) = monitor.check(x, phi_bar) {
+            return monitor.report(reason);
+        }
+        // an invariant Krylov space that does not contain the solution
+        if beta == T::zero() {
+            return monitor.report(StopReason::Breakdown);
+        }
+        // Lanczos: v_k = r2 / beta_k and beta_{k+1} v_{k+1} = A v_k - alpha_k v_k - beta_k v_{k-1}
+        let v: Vec<T> = r2.iter().map(|&r| r / beta).collect();
+        let mut y = op.apply(&v);
+        if old_beta != T::zero() {
+            axpy(-beta / old_beta, &r1, &mut y);
+        }
+        let alpha = inner_product(&v, &y, det);
+        axpy(-alpha / beta, &r2, &mut y);
+        r1 = r2;
+        r2 = y;
+        old_beta = beta;
+        beta = norm(&r2, det);
+        // the previous rotations applied to the new column of the tridiagonal matrix
+        let old_epsilon = epsilon;
+        let delta = cs * d_bar + sn * alpha;
+        let gamma_bar = sn * d_bar - cs * alpha;
+        epsilon = sn * beta;
+        d_bar = -cs * beta;
+        let gamma = gamma_bar.hypot(beta).max(T::epsilon());
+        cs = gamma_bar / gamma;
+        sn = beta / gamma;
+        let phi = cs * phi_bar;
+        phi_bar = sn * phi_bar;
+        let w1 = w2;
+        w2 = w;
+        w = (0 .. n).map(|i| (v[i] - old_epsilon * w1[i] - delta * w2[i]) / gamma).collect();
+        axpy(phi, &w, x);
+    }
+}
+
+// runs `solve` from a zero guess on every column of b with at most max_it
+// iterations, and fails unless ||b - A x|| <= tol * ||b|| was reached for all;
+// a rectangular A is NotSquare and a b of other height DimensionMismatch
+fn try_solve_columns<M, B, T, F>(mat_a: &M, mat_b: &B, max_it: usize, tol: T, solve: F) -> AlgebraResult<B>
+where M: MatOps<T>, B: RightHandSide<T>, T: Float + Element, F: FnMut(&[T], &mut [T], &mut SolverOptions<T>) -> SolveReport<T>
+{
+    try_solve_columns_scaled(mat_a, mat_b, max_it, tol, |b| norm(b, false), solve)
+}
+
+// as try_solve_columns for a solver whose reported residual is not
+// ||b - A x|| but the norm of some transform of it; b_norm(b) is the norm of
+// the same transform of b
+fn try_solve_columns_scaled<M, B, T, N, F>(mat_a: &M, mat_b: &B, max_it: usize, tol: T, b_norm: N, mut solve: F) -> AlgebraResult<B>
+where M: MatOps<T>, B: RightHandSide<T>, T: Float + Element, N: Fn(&[T]) -> T,
+      F: FnMut(&[T], &mut [T], &mut SolverOptions<T>) -> SolveReport<T>
+{
+    error::check_system(MatOps::shape(mat_a), mat_b.rhs_shape())?;
+    let mut options = SolverOptions::new().max_it(max_it).rel_tol(tol);
+    let (xmat, reports) = solve_columns(mat_a, mat_b, |b, x| solve(b, x, &mut options));
+    check_converged(xmat, &reports, &col_norms(mat_b, b_norm))
+}
+
+// exactly max_it SOR sweeps, column by column of b
+pub fn solve_sor<M, B, T>(mat_a: &M, mat_b: &B, w_param: T, max_it: usize) -> B
+where M: MatOps<T>, B: RightHandSide<T>, T: Float + Element
+{
+    let mut options = SolverOptions::new().max_it(max_it).rel_tol(T::zero());
+    solve_columns(mat_a, mat_b, |b, x| sor(mat_a, b, x, w_param, &mut options)).0
+}
+
+// as solve_sor, but stops once ||b - A x|| <= tol * ||b|| and fails if that
+// is not reached for every column. Unlike sor it wants a square A: the least
+// squares solution of a tall system generally leaves ||b - A x|| far above
+// any tolerance, so the test above could never pass
+pub fn try_solve_sor<M, B, T>(mat_a: &M, mat_b: &B, w_param: T, max_it: usize, tol: T) -> AlgebraResult<B>
+where M: MatOps<T>, B: RightHandSide<T>, T: Float + Element
+{
+    try_solve_columns(mat_a, mat_b, max_it, tol, |b, x, options| sor(mat_a, b, x, w_param, options))
+}
+
+// at most max_it restarts of GMRES(m), column by column of b
+pub fn solve_gmres<M, B, T>(mat_a: &M, mat_b: &B, m: usize, max_it: usize) -> B
+where M: MatOps<T> + LinearOperator<T>, B: RightHandSide<T>, T: Float + Scalar<Real = T>
+{
+    let mut options = SolverOptions::new().max_it(max_it * m).rel_tol(T::zero());
+    solve_columns(mat_a, mat_b, |b, x| gmres(mat_a, b, x, m, &mut options)).0
+}
+
+// as solve_gmres, but stops once ||b - A x|| <= tol * ||b|| and fails if that
+// is not reached for every column
+pub fn try_solve_gmres<M, B, T>(mat_a: &M, mat_b: &B, m: usize, max_it: usize, tol: T) -> AlgebraResult<B>
+where M: MatOps<T> + LinearOperator<T>, B: RightHandSide<T>, T: Float + Scalar<Real = T>
+{
+    try_solve_columns(mat_a, mat_b, max_it * m, tol, |b, x, options| gmres(mat_a, b, x, m, options))
+}
+
+// the Krylov solvers above on every column of b, with the semantics of try_solve_gmres
+pub fn try_solve_cg<M, B, T>(mat_a: &M, mat_b: &B, max_it: usize, tol: T) -> AlgebraResult<B>
+where M: MatOps<T> + LinearOperator<T>, B: RightHandSide<T>, T: Float + Element
+{
+    try_solve_columns(mat_a, mat_b, max_it, tol, |b, x, options| cg(mat_a, b, x, options))
+}
+
+pub fn try_solve_bicgstab<M, B, T>(mat_a: &M, mat_b: &B, max_it: usize, tol: T) -> AlgebraResult<B>
+where M: MatOps<T> + LinearOperator<T>, B: RightHandSide<T>, T: Float + Element
+{
+    try_solve_columns(mat_a, mat_b, max_it, tol, |b, x, options| bicgstab(mat_a, b, x, options))
+}
+
+pub fn try_solve_cgs<M, B, T>(mat_a: &M, mat_b: &B, max_it: usize, tol: T) -> AlgebraResult<B>
+where M: MatOps<T> + LinearOperator<T>, B: RightHandSide<T>, T: Float + Element
+{
+    try_solve_columns(mat_a, mat_b, max_it, tol, |b, x, options| cgs(mat_a, b, x, options))
+}
+
+// with PrecondSide::Left the test is the one GMRES runs on M^-1 A x = M^-1 b,
+// ||M^-1 (b - A x)|| <= tol * ||M^-1 b||, and NotConverged reports that
+// ratio; with PrecondSide::Right it is ||b - A x|| <= tol * ||b||
+pub fn try_solve_gmres_precond<M, B, T, P>(mat_a: &M, mat_b: &B, m: usize, max_it: usize, tol: T, precond: &P, side: PrecondSide) -> AlgebraResult<B>
+where M: MatOps<T> + LinearOperator<T>, B: RightHandSide<T>, T: Float + Scalar<Real = T>, P: Preconditioner<T> + ?Sized
+{
+    let b_norm = |b: &[T]| match side {
+        PrecondSide::Left => norm(&precond.apply(b), false),
+        PrecondSide::Right => norm(b, false),
+    };
+    try_solve_columns_scaled(mat_a, mat_b, max_it * m, tol, b_norm, |b, x, options| gmres_precond(mat_a, b, x, m, precond, side, options))
+}
+
+pub fn try_solve_cg_precond<M, B, T, P>(mat_a: &M, mat_b: &B, max_it: usize, tol: T, precond: &P) -> AlgebraResult<B>
+where M: MatOps<T> + LinearOperator<T>, B: RightHandSide<T>, T: Float + Element, P: Preconditioner<T> + ?Sized
+{
+    try_solve_columns(mat_a, mat_b, max_it, tol, |b, x, options| cg_precond(mat_a, b, x, precond, options))
+}
+
+pub fn try_solve_bicgstab_precond<M, B, T, P>(mat_a: &M, mat_b: &B, max_it: usize, tol: T, precond: &P) -> AlgebraResult<B>
+where M: MatOps<T> + LinearOperator<T>, B: RightHandSide<T>, T: Float + Element, P: Preconditioner<T> + ?Sized
+{
+    try_solve_columns(mat_a, mat_b, max_it, tol, |b, x, options| bicgstab_precond(mat_a, b, x, precond, options))
+}
+
+pub fn try_solve_cgs_precond<M, B, T, P>(mat_a: &M, mat_b: &B, max_it: usize, tol: T, precond: &P) -> AlgebraResult<B>
+where M: MatOps<T> + LinearOperator<T>, B: RightHandSide<T>, T: Float + Element, P: Preconditioner<T> + ?Sized
+{
+    try_solve_columns(mat_a, mat_b, max_it, tol, |b, x, options| cgs_precond(mat_a, b, x, precond, options))
+}
+
+pub fn try_solve_minres<M, B, T>(mat_a: &M, mat_b: &B, max_it: usize, tol: T) -> AlgebraResult<B>
+where M: MatOps<T> + LinearOperator<T>, B: RightHandSide<T>, T: Float + Element
+{
+    try_solve_columns(mat_a, mat_b, max_it, tol, |b, x, options| minres(mat_a, b, x, options))
+}
+
+#[test]
+fn iterative_solver_test() {
+    use super::super::matrix::sparse::SparseMatrix;
+
+    let n = 20;
+    let mut mat_a = SparseMatrix::<f64>::new(n, n);
+    let mut mat_x = SparseMatrix::<f64>::new(n, 1);
+    for i in 0 .. n {
+        mat_a.set_v(i, i, 4.0);
+        if i > 0 {
+            mat_a.set_v(i, i - 1, -1.0);
+        }
+        if i + 1 < n {
+            mat_a.set_v(i, i + 1, -2.0);
+        }
+        mat_x.set_v(i, 0, (i + 1) as f64);
+    }
+    let mat_b = mat_a.dot_mul(&mat_x);
+    let sor_x = solve_sor(&mat_a, &mat_b, 1.2, 200);
+    let gmres_x = solve_gmres(&mat_a, &mat_b, 10, 20);
+    assert!(sor_x.sub(&mat_x).norm2() < 1e-6);
+    assert!(gmres_x.sub(&mat_x).norm2() < 1e-8);
+    let dense_x = solve_gmres(&mat_a.to_dense(), &mat_b.to_dense(), 10, 20);
+    assert!(dense_x.sub(&gmres_x.to_dense()).norm2() < 1e-8);
+
+    assert!(try_solve_gmres(&mat_a, &mat_b, 10, 20, 1e-10).is_ok());
+    match try_solve_sor(&mat_a, &mat_b, 1.2, 2, 1e-10) {
+        Err(AlgebraError::NotConverged { iterations, residual }) => {
+            assert_eq!(iterations, 2);
+            assert!(residual > 1e-10);
+        },
+        _ => panic!("two SOR sweeps cannot reach 1e-10"),
+    }
+    assert!(try_solve_sor(&mat_a, &mat_x.t(), 1.2, 2, 1e-10).is_err());
+    // plain SOR runs on the normal equations, but the try_ solvers want a square A
+    let tall = mat_a.slice(0, 3, 0, 1);
+    assert_eq!(try_solve_sor(&tall, &mat_b.slice(0, 3, 0, 0), 1.2, 2, 1e-10).err(), Some(AlgebraError::NotSquare { shape: (4, 2) }));
+
+    let b = mat_b.get_col_vec(0);
+    let exact = mat_x.get_col_vec(0);
+    let mut history = Vec::new();
+    let mut x = vec![0.0; n];
+    let report = {
+        let mut options = SolverOptions::new().rel_tol(1e-10).callback(|info| history.push(info.residual));
+        gmres(&mat_a, &b, &mut x, 10, &mut options)
+    };
+    assert!(report.converged());
+    assert_eq!(history, report.residual_history);
+    assert_eq!(report.iterations + 1, history.len());
+    assert!(report.residual() <= 1e-10 * norm(&b, false));
+    assert!(x.iter().zip(exact.iter()).all(|(a, b)| (a - b).abs() < 1e-8));
+    // warm start from the solution: converged before the first iteration
+    let report = gmres(&mat_a, &b, &mut x, 10, &mut SolverOptions::new());
+    assert_eq!((report.iterations, report.reason), (0, StopReason::Converged));
+
+    let mut x = vec![0.0; n];
+    let report = sor(&mat_a, &b, &mut x, 1.2, &mut SolverOptions::new().max_it(5));
+    assert_eq!((report.iterations, report.reason), (5, StopReason::MaxIterations));
+    assert!(report.residual_history.windows(2).all(|w| w[1] < w[0]));
+    let report = sor(&mat_a, &b, &mut x, 1.2, &mut SolverOptions::new().abs_tol(1e-6));
+    assert!(report.converged() && report.residual() <= 1e-6);
+}
+
+#[test]
+fn gmres_test() {
+    use super::super::matrix::sparse::SparseMatrix;
+
+    // three distinct eigenvalues: the Krylov space is exhausted after three steps
+    let n = 30;
+    let mut diag = SparseMatrix::<f64>::new(n, n);
+    for i in 0 .. n {
+        diag.set_v(i, i, [1.0, 2.0, 5.0][i % 3]);
+    }
+    let b: Vec<f64> = (0 .. n).map(|i| 1.0 + i as f64).collect();
+    let mut x = vec![0.0; n];
+    let report = gmres(&diag, &b, &mut x, 20, &mut SolverOptions::new().rel_tol(0.0).abs_tol(1e-12));
+    assert_eq!((report.iterations, report.reason), (3, StopReason::Converged));
+    assert!(norm(&residual(&diag, &b, &x), false) < 1e-12);
+
+    // a nonsymmetric tridiagonal matrix
+    let mut mat_a = SparseMatrix::<f64>::new(n, n);
+    for i in 0 .. n {
+        mat_a.set_v(i, i, 3.0);
+        if i + 1 < n {
+            mat_a.set_v(i, i + 1, -1.4);
+            mat_a.set_v(i + 1, i, -0.6);
+        }
+    }
+
+    // a single step from zero minimises ||b - t A b|| over t, which takes
+    // v_1 = b / ||b|| and h_11 = (v_1, A v_1) from the Arnoldi process
+    let mut x = vec![0.0; n];
+    gmres(&mat_a, &b, &mut x, 1, &mut SolverOptions::new().max_it(1));
+    let ab = mat_a.apply(&b);
+    let t = inner_product(&b, &ab, false) / inner_product(&ab, &ab, false);
+    assert!(x.iter().zip(b.iter()).all(|(&x_i, &b_i)| (x_i - t * b_i).abs() < 1e-12 * b_i.abs()));
+
+    // the residual estimate of every step matches the true residual
+    let mut x = vec![0.0; n];
+    let mut true_residuals = Vec::new();
+    let report = {
+        let mut options = SolverOptions::new().rel_tol(1e-10).callback(|info| {
+            if info.iteration % 5 == 0 {
+                true_residuals.push((info.iteration, info.residual, norm(&residual(&mat_a, &b, info.x), false)));
+            }
+        });
+        gmres(&mat_a, &b, &mut x, 5, &mut options)
+    };
+    assert!(report.converged());
+    assert!(report.residual_history.windows(2).all(|w| w[1] <= w[0]));
+    assert!((norm(&residual(&mat_a, &b, &x), false) - report.residual()).abs() < 1e-10 * norm(&b, false));
+    // at a restart the iterate handed to the callbacks is current
+    for (_, estimate, exact) in true_residuals.into_iter().skip(1) {
+        assert!((estimate - exact).abs() < 1e-10 * norm(&b, false));
+    }
+    let mut y = vec![0.0; n];
+    let again = gmres(&mat_a, &b, &mut y, 5, &mut SolverOptions::new().rel_tol(1e-10).reorthogonalize(true));
+    assert_eq!(again.iterations, report.iterations);
+    assert!(x.iter().zip(y.iter()).all(|(a, b)| (a - b).abs() < 1e-10));
+
+    // b has a component in the null space of a singular A: no NaNs, no convergence
+    let mut singular = SparseMatrix::<f64>::eye(n);
+    singular.set_v(n - 1, n - 1, 0.0);
+    let mut x = vec![0.0; n];
+    let report = gmres(&singular, &b, &mut x, 10, &mut SolverOptions::new().max_it(50));
+    assert!(!report.converged());
+    assert!(x.iter().all(|v| v.is_finite()));
+    assert!((report.residual() - b[n - 1]).abs() < 1e-10);
+
+    // flexible GMRES with a preconditioner that changes at every step: a
+    // growing number of Jacobi sweeps
+    let mut sweeps = 0;
+    let mut x = vec![0.0; n];
+    let report = fgmres(&mat_a, &b, &mut x, 10, |v: &[f64]| {
+        sweeps += 1;
+        let mut z = vec![0.0; v.len()];
+        for _ in 0 .. sweeps % 4 + 1 {
+            let az = mat_a.apply(&z);
+            for i in 0 .. z.len() {
+                z[i] += (v[i] - az[i]) / 3.0;
+            }
+        }
+        z
+    }, &mut SolverOptions::new().rel_tol(1e-10));
+    assert!(report.converged());
+    assert!(norm(&residual(&mat_a, &b, &x), false) < 1e-9 * norm(&b, false));
+    let mut x = vec![0.0; n];
+    assert!(report.iterations < gmres(&mat_a, &b, &mut x, 10, &mut SolverOptions::new().rel_tol(1e-10)).iterations);
+}
+
+#[test]
+fn krylov_solver_test() {
+    use super::super::matrix::sparse::SparseMatrix;
+
+    // the 1D Laplacian (SPD), a convection-diffusion matrix (nonsymmetric) and
+    // a symmetric matrix with eigenvalues of both signs
+    let n = 30;
+    let mut spd = SparseMatrix::<f64>::new(n, n);
+    let mut nonsym = SparseMatrix::<f64>::new(n, n);
+    let mut indef = SparseMatrix::<f64>::new(n, n);
+    let mut mat_x = SparseMatrix::<f64>::new(n, 2);
+    for i in 0 .. n {
+        spd.set_v(i, i, 2.0);
+        nonsym.set_v(i, i, 4.0);
+        indef.set_v(i, i, if i % 2 == 0 { 3.0 } else { -3.0 });
+        if i + 1 < n {
+            spd.set_v(i, i + 1, -1.0);
+            spd.set_v(i + 1, i, -1.0);
+            nonsym.set_v(i, i + 1, -2.0);
+            nonsym.set_v(i + 1, i, -1.0);
+            indef.set_v(i, i + 1, 1.0);
+            indef.set_v(i + 1, i, 1.0);
+        }
+        mat_x.set_v(i, 0, (i + 1) as f64);
+        mat_x.set_v(i, 1, (i as f64).sin());
+    }
+
+    let (b_spd, b_nonsym, b_indef) = (spd.dot_mul(&mat_x), nonsym.dot_mul(&mat_x), indef.dot_mul(&mat_x));
+    let check = |x: AlgebraResult<SparseMatrix<f64>>| assert!(x.unwrap().sub(&mat_x).norm2() < 1e-6 * mat_x.norm2());
+    check(try_solve_cg(&spd, &b_spd, 100, 1e-10));
+    check(try_solve_minres(&spd, &b_spd, 100, 1e-10));
+    check(try_solve_bicgstab(&nonsym, &b_nonsym, 100, 1e-10));
+    check(try_solve_cgs(&nonsym, &b_nonsym, 100, 1e-10));
+    check(try_solve_minres(&indef, &b_indef, 100, 1e-10));
+    check(try_solve_bicgstab(&indef, &b_indef, 200, 1e-10));
+    assert!(try_solve_cg(&spd, &mat_x.t(), 10, 1e-10).is_err());
+
+    // exact arithmetic needs at most n CG steps
+    let b = spd.mat_vec(&mat_x.get_col_vec(0));
+    let mut x = vec![0.0; n];
+    let report = cg(&spd, &b, &mut x, &mut SolverOptions::new().rel_tol(1e-12));
+    assert!(report.converged() && report.iterations <= n + 5);
+    assert!(norm(&residual(&spd, &b, &x), false) <= 1e-11 * norm(&b, false));
+    // the MINRES residual estimate is monotone and close to the true one
+    let b = indef.mat_vec(&mat_x.get_col_vec(1));
+    let mut x = vec![0.0; n];
+    let report = minres(&indef, &b, &mut x, &mut SolverOptions::new().rel_tol(1e-10));
+    assert!(report.converged());
+    assert!(report.residual_history.windows(2).all(|w| w[1] <= w[0] * (1.0 + 1e-12)));
+    assert!(norm(&residual(&indef, &b, &x), false) < 1e-8 * norm(&b, false));
+    // CG notices an indefinite matrix
+    let mut x = vec![0.0; n];
+    let report = cg(&indef, &b, &mut x, &mut SolverOptions::new().max_it(200));
+    assert_eq!(report.reason, StopReason::Breakdown);
+    assert!(!report.converged());
+    assert!(matches!(try_solve_cg(&indef, &b_indef, 200, 1e-10), Err(AlgebraError::NotConverged { .. })));
+
+    let mut x = vec![0.0; n];
+    let report = bicgstab(&nonsym, &b, &mut x, &mut SolverOptions::new().max_it(3).rel_tol(0.0));
+    assert_eq!((report.iterations, report.reason), (3, StopReason::MaxIterations));
+    assert_eq!(report.residual_history.len(), 4);
+}
+
+#[test]
+fn complex_gmres_test() {
+    use num::complex::Complex;
+    use super::super::matrix::dense::DenseMatrix;
+    use super::super::matrix::sparse::SparseMatrix;
+    use super::preconditioner::BlockJacobi;
+
+    // a damped 1D Helmholtz operator -u'' - k^2 u + i s u; the real part is indefinite
+    let n = 60;
+    let mut mat = SparseMatrix::new(n, n);
+    for i in 0 .. n {
+        mat.set_v(i, i, Complex::new(2.0 - 0.3, 0.2));
+        if i > 0 {
+            mat.set_v(i, i - 1, Complex::new(-1.0, 0.0));
+        }
+        if i + 1 < n {
+            mat.set_v(i, i + 1, Complex::new(-1.0, 0.0));
+        }
+    }
+    let exact: Vec<Complex<f64>> = (0 .. n).map(|i| Complex::new((i as f64 * 0.2).sin(), (i as f64 * 0.1).cos())).collect();
+    let b = mat.apply(&exact);
+    let mut x = vec![Complex::new(0.0, 0.0); n];
+    let report = gmres(&mat, &b, &mut x, 30, &mut SolverOptions::new().rel_tol(1e-10));
+    assert!(report.converged());
+    assert!(x.iter().zip(exact.iter()).all(|(a, e)| (a - e).norm() < 1e-7));
+    // the Givens estimate is the true residual
+    let r_norm = scalar::norm(&residual(&mat, &b, &x));
+    assert!((r_norm - report.residual()).abs() < 1e-8 * scalar::norm(&b));
+
+    let precond = BlockJacobi::new(&mat, 6).unwrap();
+    for &side in &[PrecondSide::Left, PrecondSide::Right] {
+        let mut y = vec![Complex::new(0.0, 0.0); n];
+        let precond_report = gmres_precond(&mat, &b, &mut y, 30, &precond, side, &mut SolverOptions::new().rel_tol(1e-10));
+        assert!(precond_report.converged() && precond_report.iterations < report.iterations);
+        assert!(y.iter().zip(exact.iter()).all(|(a, e)| (a - e).norm() < 1e-7));
+    }
+    let ge = SparseMatrix::solve_ge(&mat, &SparseMatrix::from_dense(&DenseMatrix::from(n, 1, &b))).unwrap();
+    assert!((0 .. n).all(|i| (ge.get_v(i, 0) - exact[i]).norm() < 1e-10));
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn deterministic_solver_test() {
+    use super::super::matrix::sparse::SparseMatrix;
+
+    // long enough for the inner products to be split over the threads
+    let n = 1 << 15;
+    let mut mat_a = SparseMatrix::<f64>::new(n, n);
+    for i in 0 .. n {
+        mat_a.set_v(i, i, 4.0);
+        if i + 1 < n {
+            mat_a.set_v(i, i + 1, -1.0);
+            mat_a.set_v(i + 1, i, -1.0);
+        }
+    }
+    let b: Vec<f64> = (0 .. n).map(|i| ((i * 7919) % 1000) as f64 / 997.0).collect();
+    let solve = |threads: usize| parallel::with_threads(threads, || {
+        let mut x = vec![0.0; n];
+        cg(&mat_a, &b, &mut x, &mut SolverOptions::new().max_it(10).deterministic(true));
+        x
+    });
+    let x = solve(1);
+    for &threads in &[2, 3, 8] {
+        assert!(solve(threads).iter().zip(x.iter()).all(|(a, b)| a.to_bits() == b.to_bits()));
+    }
+}
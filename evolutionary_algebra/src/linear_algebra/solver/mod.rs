@@ -0,0 +1,17 @@
+pub mod direct;
+pub mod iterative;
+pub mod monitor;
+pub mod preconditioner;
+pub mod multigrid;
+pub mod rhs;
+
+pub use self::direct::{solve_ge, inv_ge, det_ge, cofactors, adjoint, det_adj, inv_adj};
+pub use self::direct::{try_solve_ge, try_inv_ge, try_det_ge, try_inv_adj};
+pub use self::iterative::{solve_sor, solve_gmres, try_solve_sor, try_solve_gmres, sor, gmres};
+pub use self::iterative::{cg, bicgstab, cgs, minres, try_solve_cg, try_solve_bicgstab, try_solve_cgs, try_solve_minres};
+pub use self::iterative::{fgmres, gmres_precond, cg_precond, bicgstab_precond, cgs_precond};
+pub use self::iterative::{try_solve_gmres_precond, try_solve_cg_precond, try_solve_bicgstab_precond, try_solve_cgs_precond};
+pub use self::monitor::{SolverOptions, SolveReport, StopReason, IterationInfo, StoppingCriterion, Stagnation, Divergence};
+pub use self::preconditioner::{Preconditioner, PrecondSide, Identity, Jacobi, BlockJacobi, SSOR, ILU, IC0};
+pub use self::multigrid::{Multigrid, Cycle, Smoother};
+pub use self::rhs::RightHandSide;
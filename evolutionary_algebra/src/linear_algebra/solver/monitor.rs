@@ -0,0 +1,305 @@
+extern crate num;
+
+use num::Float;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StopReason {
+    // ||b - A x|| <= max(rel_tol ||b||, abs_tol)
+    Converged,
+    MaxIterations,
+    Stagnated,
+    Diverged,
+    // the method cannot continue, e.g. a zero denominator or a NaN residual
+    Breakdown,
+}
+
+// what a solve did: iterations is the number of iterations performed and
+// residual_history[k] the residual norm after k of them, so the history
+// starts with the residual of the initial guess
+#[derive(Debug, Clone)]
+pub struct SolveReport<T> {
+    pub iterations: usize,
+    pub residual_history: Vec<T>,
+    pub reason: StopReason,
+}
+
+impl<T> SolveReport<T>
+where T: Float
+{
+    pub fn converged(&self) -> bool
+    {
+        self.reason == StopReason::Converged
+    }
+
+    pub fn residual(&self) -> T
+    {
+        self.residual_history.last().cloned().unwrap_or_else(T::zero)
+    }
+}
+
+// handed to the callbacks after every iteration; X is the type of the
+// entries of x, complex for the complex solvers while the residuals are real
+pub struct IterationInfo<'x, T, X = T> {
+    pub iteration: usize,
+    pub residual: T,
+    pub relative_residual: T,
+    pub x: &'x [X],
+}
+
+// an extra reason to stop, checked after the tolerances
+pub trait StoppingCriterion<T> {
+    fn check(&mut self, history: &[T], b_norm: T) -> Option<StopReason>;
+}
+
+// stops once the last `window` iterations reduced the residual by less than
+// the factor `ratio`
+pub struct Stagnation<T> {
+    pub window: usize,
+    pub ratio: T,
+}
+
+impl<T> StoppingCriterion<T> for Stagnation<T>
+where T: Float
+{
+    fn check(&mut self, history: &[T], _b_norm: T) -> Option<StopReason>
+    {
+        if history.len() <= self.window {
+            return None;
+        }
+        let last = history[history.len() - 1];
+        let before = history[history.len() - 1 - self.window];
+        if last > self.ratio * before {
+            Some(StopReason::Stagnated)
+        }
+        else {
+            None
+        }
+    }
+}
+
+// stops once the residual grew beyond `factor` times the initial one
+pub struct Divergence<T> {
+    pub factor: T,
+}
+
+impl<T> StoppingCriterion<T> for Divergence<T>
+where T: Float
+{
+    fn check(&mut self, history: &[T], _b_norm: T) -> Option<StopReason>
+    {
+        match (history.first(), history.last()) {
+            (Some(&first), Some(&last)) if last > self.factor * first => Some(StopReason::Diverged),
+            _ => None,
+        }
+    }
+}
+
+type Callback<'a, T, X> = Box<dyn FnMut(&IterationInfo<T, X>) + 'a>;
+
+// T is the type of the tolerances and residuals, X the one of the unknowns
+pub struct SolverOptions<'a, T, X = T> {
+    max_it: usize,
+    rel_tol: T,
+    abs_tol: T,
+    reorthogonalize: bool,
+    deterministic: bool,
+    criteria: Vec<Box<dyn StoppingCriterion<T> + 'a>>,
+    callbacks: Vec<Callback<'a, T, X>>,
+}
+
+impl<'a, T, X> SolverOptions<'a, T, X>
+where T: Float
+{
+    // 1000 iterations and a relative tolerance of 1e-8
+    pub fn new() -> SolverOptions<'a, T, X>
+    {
+        SolverOptions {
+            max_it: 1000,
+            rel_tol: T::from(1e-8).unwrap(),
+            abs_tol: T::zero(),
+            reorthogonalize: false,
+            deterministic: false,
+            criteria: Vec::new(),
+            callbacks: Vec::new(),
+        }
+    }
+
+    pub fn max_it(mut self, max_it: usize) -> SolverOptions<'a, T, X>
+    {
+        self.max_it = max_it;
+        self
+    }
+
+    pub fn rel_tol(mut self, rel_tol: T) -> SolverOptions<'a, T, X>
+    {
+        self.rel_tol = rel_tol;
+        self
+    }
+
+    pub fn abs_tol(mut self, abs_tol: T) -> SolverOptions<'a, T, X>
+    {
+        self.abs_tol = abs_tol;
+        self
+    }
+
+    // a second Gram-Schmidt pass in the Arnoldi based solvers, for when the
+    // basis loses orthogonality
+    pub fn reorthogonalize(mut self, reorthogonalize: bool) -> SolverOptions<'a, T, X>
+    {
+        self.reorthogonalize = reorthogonalize;
+        self
+    }
+
+    // inner products and norms reduced in a fixed order (see parallel.rs), so
+    // that the iterates do not depend on the number of threads
+    pub fn deterministic(mut self, deterministic: bool) -> SolverOptions<'a, T, X>
+    {
+        self.deterministic = deterministic;
+        self
+    }
+
+    pub fn criterion<C>(mut self, criterion: C) -> SolverOptions<'a, T, X>
+    where C: StoppingCriterion<T> + 'a
+    {
+        self.criteria.push(Box::new(criterion));
+        self
+    }
+
+    pub fn callback<F>(mut self, callback: F) -> SolverOptions<'a, T, X>
+    where F: FnMut(&IterationInfo<T, X>) + 'a
+    {
+        self.callbacks.push(Box::new(callback));
+        self
+    }
+}
+
+impl<'a, T, X> SolverOptions<'a, T, X>
+{
+    pub(crate) fn reorthogonalizes(&self) -> bool
+    {
+        self.reorthogonalize
+    }
+
+    pub(crate) fn is_deterministic(&self) -> bool
+    {
+        self.deterministic
+    }
+}
+
+impl<'a, T, X> Default for SolverOptions<'a, T, X>
+where T: Float
+{
+    fn default() -> SolverOptions<'a, T, X>
+    {
+        SolverOptions::new()
+    }
+}
+
+// The bookkeeping shared by the iterative solvers: they report the residual
+// of every iterate and stop as soon as this says so.
+pub(crate) struct Monitor<'m, 'a, T, X = T> {
+    options: &'m mut SolverOptions<'a, T, X>,
+    b_norm: T,
+    history: Vec<T>,
+}
+
+impl<'m, 'a, T, X> Monitor<'m, 'a, T, X>
+where T: Float
+{
+    pub(crate) fn new(options: &'m mut SolverOptions<'a, T, X>, b_norm: T) -> Monitor<'m, 'a, T, X>
+    {
+        Monitor {
+            options,
+            b_norm,
+            history: Vec::new(),
+        }
+    }
+
+    pub(crate) fn iterations(&self) -> usize
+    {
+        self.history.len().saturating_sub(1)
+    }
+
+    // records the residual of the current iterate x; the initial guess counts
+    // as iteration 0
+    pub(crate) fn check(&mut self, x: &[X], residual: T) -> Option<StopReason>
+    {
+        self.history.push(residual);
+        let iteration = self.iterations();
+        let relative_residual = if self.b_norm == T::zero() { residual } else { residual / self.b_norm };
+        let info = IterationInfo {
+            iteration,
+            residual,
+            relative_residual,
+            x,
+        };
+        for callback in self.options.callbacks.iter_mut() {
+            callback(&info);
+        }
+        if residual.is_nan() {
+            return Some(StopReason::Breakdown);
+        }
+        if residual <= (self.options.rel_tol * self.b_norm).max(self.options.abs_tol) {
+            return Some(StopReason::Converged);
+        }
+        for criterion in self.options.criteria.iter_mut() {
+            if let Some(reason) = criterion.check(&self.history, self.b_norm) {
+                return Some(reason);
+            }
+        }
+        if iteration >= self.options.max_it {
+            return Some(StopReason::MaxIterations);
+        }
+        None
+    }
+
+    pub(crate) fn report(self, reason: StopReason) -> SolveReport<T>
+    {
+        SolveReport {
+            iterations: self.iterations(),
+            residual_history: self.history,
+            reason,
+        }
+    }
+}
+
+#[test]
+fn monitor_test() {
+    use std::cell::RefCell;
+
+    let seen = RefCell::new(Vec::new());
+    let mut options = SolverOptions::<f64>::new()
+        .max_it(3)
+        .rel_tol(1e-3)
+        .callback(|info| seen.borrow_mut().push((info.iteration, info.relative_residual)));
+    let mut monitor = Monitor::new(&mut options, 10.0);
+    assert_eq!(monitor.check(&[0.0], 10.0), None);
+    assert_eq!(monitor.check(&[0.0], 1.0), None);
+    assert_eq!(monitor.check(&[0.0], 1e-3), Some(StopReason::Converged));
+    let report = monitor.report(StopReason::Converged);
+    assert!(report.converged());
+    assert_eq!(report.iterations, 2);
+    assert_eq!(report.residual(), 1e-3);
+    drop(options);
+    assert_eq!(seen.into_inner(), vec![(0, 1.0), (1, 0.1), (2, 1e-4)]);
+
+    let mut options = SolverOptions::<f64>::new().max_it(2).rel_tol(0.0);
+    let mut monitor = Monitor::new(&mut options, 1.0);
+    assert_eq!(monitor.check(&[], 1.0), None);
+    assert_eq!(monitor.check(&[], 0.5), None);
+    assert_eq!(monitor.check(&[], 0.25), Some(StopReason::MaxIterations));
+
+    let mut options = SolverOptions::<f64>::new().rel_tol(0.0)
+        .criterion(Stagnation { window: 2, ratio: 0.9 })
+        .criterion(Divergence { factor: 100.0 });
+    let mut monitor = Monitor::new(&mut options, 1.0);
+    assert_eq!(monitor.check(&[], 1.0), None);
+    assert_eq!(monitor.check(&[], 0.5), None);
+    assert_eq!(monitor.check(&[], 0.48), None);
+    assert_eq!(monitor.check(&[], 0.47), Some(StopReason::Stagnated));
+    let mut options = SolverOptions::<f64>::new().criterion(Divergence { factor: 100.0 });
+    let mut monitor = Monitor::new(&mut options, 1.0);
+    assert_eq!(monitor.check(&[], 1.0), None);
+    assert_eq!(monitor.check(&[], 1e3), Some(StopReason::Diverged));
+    assert_eq!(monitor.check(&[], f64::NAN), Some(StopReason::Breakdown));
+}
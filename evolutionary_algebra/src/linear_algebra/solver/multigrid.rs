@@ -0,0 +1,490 @@
+extern crate num;
+
+use super::super::matrix::sparse::{CooMatrix, CsrMatrix, SparseMatrix};
+use super::super::decomposition::SparseLU;
+use super::monitor::{Monitor, SolveReport, SolverOptions};
+use super::preconditioner::Preconditioner;
+use super::super::super::error::{self, AlgebraError, AlgebraResult};
+use super::super::super::parallel::Element;
+use num::Float;
+use std::fmt::Display;
+
+// coarsening stops once a level has at most this many unknowns, which are
+// then solved by a sparse LU
+const MAX_COARSE: usize = 50;
+const MAX_LEVELS: usize = 25;
+// a_ij is a strong connection of i when |a_ij| >= theta sqrt(|a_ii a_jj|)
+const STRENGTH_THETA: f64 = 0.08;
+
+// how often a cycle visits the next coarser level: once (V), twice (W), or an
+// F-cycle there followed by a V-cycle (F)
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Cycle {
+    V,
+    W,
+    F,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Smoother<T> {
+    // damped Jacobi with this weight
+    Jacobi(T),
+    // forward sweeps before the coarse correction and backward ones after,
+    // which keeps the cycle symmetric for CG
+    GaussSeidel,
+}
+
+#[derive(Clone)]
+struct Level<T>
+where T: Float + Display
+{
+    mat_a: CsrMatrix<T>,
+    inv_diag: Vec<T>,
+    // to and from the next coarser level; R = P^T
+    prolong: CsrMatrix<T>,
+    restrict: CsrMatrix<T>,
+}
+
+// A multigrid hierarchy A_0 = A, A_{k+1} = P_k^T A_k P_k. The prolongations
+// P_k come from the grid for geometric multigrid and from the matrix alone
+// for smoothed aggregation; everything else is shared.
+#[derive(Clone)]
+pub struct Multigrid<T>
+where T: Float + Display
+{
+    levels: Vec<Level<T>>,
+    coarse_mat: CsrMatrix<T>,
+    coarse: SparseLU<T>,
+    sizes: Vec<usize>,
+    nnz: Vec<usize>,
+    cycle: Cycle,
+    smoother: Smoother<T>,
+    pre_sweeps: usize,
+    post_sweeps: usize,
+}
+
+// the coarse size and, for every fine point, its (coarse point, weight) pairs
+type Interpolation<T> = (usize, Vec<Vec<(usize, T)>>);
+
+// 1D linear interpolation from the odd points of a grid of n points, n / 2 of
+// them, with zero Dirichlet values beyond both ends
+fn interpolation_1d<T>(n: usize) -> Interpolation<T>
+where T: Float
+{
+    let half = T::from(0.5).unwrap();
+    let n_c = n / 2;
+    let weights = (0 .. n).map(|i| {
+        if i % 2 == 1 {
+            vec![((i - 1) / 2, T::one())]
+        }
+        else {
+            let mut w = Vec::new();
+            if i >= 2 {
+                w.push((i / 2 - 1, half));
+            }
+            if i / 2 < n_c {
+                w.push((i / 2, half));
+            }
+            w
+        }
+    }).collect();
+    (n_c, weights)
+}
+
+// the tensor product of the 1D interpolations of every dimension that still
+// has at least 3 points; None once none has
+fn grid_prolongation<T>(dims: &[usize]) -> Option<(Vec<usize>, CsrMatrix<T>)>
+where T: Float + Display
+{
+    if dims.iter().all(|&n| n < 3) {
+        return None;
+    }
+    let per_dim: Vec<Interpolation<T>> = dims.iter().map(|&n| {
+        if n < 3 { (n, (0 .. n).map(|i| vec![(i, T::one())]).collect()) } else { interpolation_1d(n) }
+    }).collect();
+    let coarse_dims: Vec<usize> = per_dim.iter().map(|&(n_c, _)| n_c).collect();
+    let n: usize = dims.iter().product();
+    let mut coo = CooMatrix::new(n, coarse_dims.iter().product());
+    for fine in 0 .. n {
+        // the (coarse index, weight) pairs of this point, one dimension at a time
+        let mut entries = vec![(0, T::one())];
+        let (mut rest, mut stride) = (fine, 1);
+        for (d, &(n_c, ref weights)) in per_dim.iter().enumerate() {
+            let i = rest % dims[d];
+            rest /= dims[d];
+            entries = entries.iter().flat_map(|&(c, w)| {
+                weights[i].iter().map(move |&(j, v)| (c + j * stride, w * v))
+            }).collect();
+            stride *= n_c;
+        }
+        for (c, w) in entries {
+            coo.push(fine, c, w);
+        }
+    }
+    Some((coarse_dims, coo.to_csr()))
+}
+
+// smoothed aggregation: aggregates of strongly connected unknowns give a
+// piecewise constant P_tent, smoothed by one damped Jacobi step
+fn aggregation_prolongation<T>(mat_a: &CsrMatrix<T>, inv_diag: &[T]) -> CsrMatrix<T>
+where T: Float + Display
+{
+    let n = mat_a.row();
+    let theta = T::from(STRENGTH_THETA).unwrap();
+    let strong: Vec<Vec<usize>> = (0 .. n).map(|i| {
+        mat_a.row_indices(i).iter().zip(mat_a.row_values(i).iter())
+            .filter(|&(&j, &v)| j != i && v.abs() >= theta * (T::one() / (inv_diag[i] * inv_diag[j])).abs().sqrt())
+            .map(|(&j, _)| j).collect()
+    }).collect();
+    let mut aggregate: Vec<Option<usize>> = vec![None; n];
+    let mut count = 0;
+    // whole neighbourhoods that are still free become aggregates
+    for i in 0 .. n {
+        if aggregate[i].is_none() && strong[i].iter().all(|&j| aggregate[j].is_none()) {
+            aggregate[i] = Some(count);
+            for &j in &strong[i] {
+                aggregate[j] = Some(count);
+            }
+            count += 1;
+        }
+    }
+    // the rest joins a neighbouring aggregate, or starts a new one
+    let first_pass = aggregate.clone();
+    for i in 0 .. n {
+        if aggregate[i].is_none() {
+            aggregate[i] = strong[i].iter().filter_map(|&j| first_pass[j]).next();
+        }
+    }
+    for i in 0 .. n {
+        if aggregate[i].is_none() {
+            aggregate[i] = Some(count);
+            for &j in &strong[i] {
+                if aggregate[j].is_none() {
+                    aggregate[j] = Some(count);
+                }
+            }
+            count += 1;
+        }
+    }
+    // P = (I - w D^-1 A) P_tent with w = 4 / (3 rho(D^-1 A)), rho bounded by
+    // the largest absolute row sum of D^-1 A
+    let rho = (0 .. n).map(|i| {
+        mat_a.row_values(i).iter().fold(T::zero(), |sum, &v| sum + (v * inv_diag[i]).abs())
+    }).fold(T::zero(), T::max);
+    let omega = T::from(4.0 / 3.0).unwrap() / rho;
+    let mut coo = CooMatrix::new(n, count);
+    for i in 0 .. n {
+        coo.push(i, aggregate[i].unwrap(), T::one());
+        for (&j, &v) in mat_a.row_indices(i).iter().zip(mat_a.row_values(i).iter()) {
+            coo.push(i, aggregate[j].unwrap(), -omega * inv_diag[i] * v);
+        }
+    }
+    coo.to_csr()
+}
+
+fn inverse_diagonal<T>(mat_a: &CsrMatrix<T>) -> AlgebraResult<Vec<T>>
+where T: Float + Display
+{
+    (0 .. mat_a.row()).map(|i| {
+        let d = mat_a.get_v(i, i);
+        if d == T::zero() { Err(AlgebraError::SingularPivot { index: i }) } else { Ok(T::one() / d) }
+    }).collect()
+}
+
+impl<T> Multigrid<T>
+where T: Float + Display + Element
+{
+    // coarsen returns the prolongation of a level, or None to make it the coarsest
+    fn build<F>(mat: &SparseMatrix<T>, mut coarsen: F) -> AlgebraResult<Multigrid<T>>
+    where F: FnMut(&CsrMatrix<T>, &[T]) -> Option<CsrMatrix<T>>
+    {
+        error::check_square(mat.shape())?;
+        let mut mat_a = mat.to_csr();
+        let mut levels = Vec::new();
+        let mut sizes = vec![mat_a.row()];
+        let mut nnz = vec![mat_a.nnz()];
+        while mat_a.row() > MAX_COARSE && levels.len() + 1 < MAX_LEVELS {
+            let inv_diag = inverse_diagonal(&mat_a)?;
+            let prolong = match coarsen(&mat_a, &inv_diag) {
+                Some(prolong) if prolong.col() < mat_a.row() && prolong.col() > 0 => prolong,
+                _ => break,
+            };
+            let restrict = prolong.t();
+            let coarse = restrict.dot_mul(&mat_a.dot_mul(&prolong));
+            sizes.push(coarse.row());
+            nnz.push(coarse.nnz());
+            levels.push(Level { mat_a, inv_diag, prolong, restrict });
+            mat_a = coarse;
+        }
+        Ok(Multigrid {
+            levels,
+            coarse: SparseLU::new(&mat_a.to_sparse())?,
+            coarse_mat: mat_a,
+            sizes,
+            nnz,
+            cycle: Cycle::V,
+            smoother: Smoother::GaussSeidel,
+            pre_sweeps: 1,
+            post_sweeps: 1,
+        })
+    }
+
+    // geometric multigrid for an operator discretised on a structured grid of
+    // dims[0] x dims[1] x ... points, the unknown of point (i, j, k) being
+    // number i + dims[0] (j + dims[1] k); linear interpolation from every
+    // second point and Galerkin coarse operators
+    pub fn geometric(mat: &SparseMatrix<T>, dims: &[usize]) -> AlgebraResult<Multigrid<T>>
+    {
+        let n: usize = dims.iter().product();
+        if n != mat.row() {
+            return Err(AlgebraError::DimensionMismatch { left: mat.shape(), right: (n, 1) });
+        }
+        let mut dims = dims.to_vec();
+        Multigrid::build(mat, |_, _| {
+            grid_prolongation(&dims).map(|(coarse_dims, prolong)| {
+                dims = coarse_dims;
+                prolong
+            })
+        })
+    }
+
+    // algebraic multigrid by smoothed aggregation, from the matrix alone
+    pub fn aggregation(mat: &SparseMatrix<T>) -> AlgebraResult<Multigrid<T>>
+    {
+        Multigrid::build(mat, |mat_a, inv_diag| Some(aggregation_prolongation(mat_a, inv_diag)))
+    }
+
+    pub fn cycle(mut self, cycle: Cycle) -> Multigrid<T>
+    {
+        self.cycle = cycle;
+        self
+    }
+
+    pub fn smoother(mut self, smoother: Smoother<T>) -> Multigrid<T>
+    {
+        self.smoother = smoother;
+        self
+    }
+
+    pub fn sweeps(mut self, pre: usize, post: usize) -> Multigrid<T>
+    {
+        self.pre_sweeps = pre;
+        self.post_sweeps = post;
+        self
+    }
+
+    pub fn levels(&self) -> usize
+    {
+        self.sizes.len()
+    }
+
+    // the number of unknowns on every level, finest first
+    pub fn sizes(&self) -> &[usize]
+    {
+        &self.sizes
+    }
+
+    // the nonzeros of all levels over those of A
+    pub fn operator_complexity(&self) -> T
+    {
+        // an empty A is its own only level
+        if self.nnz[0] == 0 {
+            return T::one();
+        }
+        T::from(self.nnz.iter().sum::<usize>()).unwrap() / T::from(self.nnz[0]).unwrap()
+    }
+
+    fn smooth(&self, level: &Level<T>, b: &[T], x: &mut [T], sweeps: usize, forward: bool)
+    {
+        let mat_a = &level.mat_a;
+        for _ in 0 .. sweeps {
+            match self.smoother {
+                Smoother::Jacobi(omega) => {
+                    let ax = mat_a.mat_vec(x);
+                    for i in 0 .. x.len() {
+                        x[i] = x[i] + omega * level.inv_diag[i] * (b[i] - ax[i]);
+                    }
+                },
+                Smoother::GaussSeidel => {
+                    let n = x.len();
+                    for k in 0 .. n {
+                        let i = if forward { k } else { n - 1 - k };
+                        let mut sigma = b[i];
+                        for (&j, &v) in mat_a.row_indices(i).iter().zip(mat_a.row_values(i).iter()) {
+                            if j != i {
+                                sigma = sigma - v * x[j];
+                            }
+                        }
+                        x[i] = sigma * level.inv_diag[i];
+                    }
+                },
+            }
+        }
+    }
+
+    fn visit(&self, k: usize, b: &[T], x: &mut [T], cycle: Cycle)
+    {
+        if k == self.levels.len() {
+            x.copy_from_slice(&self.coarse.solve_vec(b));
+            return;
+        }
+        let level = &self.levels[k];
+        self.smooth(level, b, x, self.pre_sweeps, true);
+        let ax = level.mat_a.mat_vec(x);
+        let r: Vec<T> = b.iter().zip(ax.iter()).map(|(&b, &ax)| b - ax).collect();
+        let r_c = level.restrict.mat_vec(&r);
+        let mut x_c = vec![T::zero(); r_c.len()];
+        match cycle {
+            Cycle::V => self.visit(k + 1, &r_c, &mut x_c, Cycle::V),
+            Cycle::W => {
+                self.visit(k + 1, &r_c, &mut x_c, Cycle::W);
+                self.visit(k + 1, &r_c, &mut x_c, Cycle::W);
+            },
+            Cycle::F => {
+                self.visit(k + 1, &r_c, &mut x_c, Cycle::F);
+                self.visit(k + 1, &r_c, &mut x_c, Cycle::V);
+            },
+        }
+        for (x_i, e_i) in x.iter_mut().zip(level.prolong.mat_vec(&x_c)) {
+            *x_i = *x_i + e_i;
+        }
+        self.smooth(level, b, x, self.post_sweeps, false);
+    }
+
+    // one cycle on A x = b, improving x in place
+    pub fn cycle_once(&self, b: &[T], x: &mut [T])
+    {
+        assert_eq!(b.len(), self.sizes[0], "the length of the vector does not match the matrix!");
+        assert_eq!(x.len(), self.sizes[0], "the length of the initial guess does not match the matrix!");
+        self.visit(0, b, x, self.cycle);
+    }
+
+    // multigrid as a standalone solver starting from x; every cycle is one
+    // iteration
+    pub fn solve(&self, b: &[T], x: &mut [T], options: &mut SolverOptions<T>) -> SolveReport<T>
+    {
+        let norm = |v: &[T]| v.iter().fold(T::zero(), |sum, &v| sum + v * v).sqrt();
+        let mut monitor = Monitor::new(options, norm(b));
+        let mat_a = self.levels.first().map_or(&self.coarse_mat, |level| &level.mat_a);
+        let residual_norm = |x: &[T]| {
+            norm(&mat_a.mat_vec(x).iter().zip(b.iter()).map(|(&ax, &b)| b - ax).collect::<Vec<T>>())
+        };
+        let mut r_norm = residual_norm(x);
+        loop {
+            if let Some(reason) = monitor.check(x, r_norm) {
+                return monitor.report(reason);
+            }
+            self.cycle_once(b, x);
+            r_norm = residual_norm(x);
+        }
+    }
+}
+
+// one cycle from a zero guess
+impl<T> Preconditioner<T> for Multigrid<T>
+where T: Float + Display + Element
+{
+    fn apply(&self, r: &[T]) -> Vec<T>
+    {
+        let mut z = vec![T::zero(); r.len()];
+        self.cycle_once(r, &mut z);
+        z
+    }
+}
+
+#[test]
+fn multigrid_test() {
+    use super::super::matrix::MatOps;
+    use super::iterative::{cg, cg_precond, gmres_precond};
+    use super::monitor::StopReason;
+    use super::preconditioner::PrecondSide;
+
+    // the 5-point Laplacian on an m x m grid, plus an optional convection term
+    let laplace_2d = |m: usize, convection: f64| {
+        let mut mat = SparseMatrix::<f64>::new(m * m, m * m);
+        for j in 0 .. m {
+            for i in 0 .. m {
+                let k = i + m * j;
+                mat.set_v(k, k, 4.0);
+                if i > 0 { mat.set_v(k, k - 1, -1.0 - convection); }
+                if i + 1 < m { mat.set_v(k, k + 1, -1.0 + convection); }
+                if j > 0 { mat.set_v(k, k - m, -1.0); }
+                if j + 1 < m { mat.set_v(k, k + m, -1.0); }
+            }
+        }
+        mat
+    };
+    let m = 31;
+    let mat_a = laplace_2d(m, 0.0);
+    let exact: Vec<f64> = (0 .. m * m).map(|k| ((k % m) as f64 * 0.2).sin() + (k / m) as f64 / m as f64).collect();
+    let b = mat_a.mat_vec(&exact);
+    let err = |x: &[f64]| x.iter().zip(exact.iter()).fold(0.0, |e: f64, (a, b)| e.max((a - b).abs()));
+
+    let geometric = Multigrid::geometric(&mat_a, &[m, m]).unwrap();
+    assert_eq!(geometric.sizes(), &[961, 225, 49]);
+    for &cycle in &[Cycle::V, Cycle::W, Cycle::F] {
+        let mg = geometric.clone().cycle(cycle);
+        let mut x = vec![0.0; m * m];
+        let report = mg.solve(&b, &mut x, &mut SolverOptions::new().rel_tol(1e-10));
+        assert!(report.converged() && report.iterations <= 16);
+        assert!(err(&x) < 1e-7);
+    }
+    let mg = geometric.clone().smoother(Smoother::Jacobi(0.8)).sweeps(2, 2);
+    let mut x = vec![0.0; m * m];
+    assert!(mg.solve(&b, &mut x, &mut SolverOptions::new().rel_tol(1e-10).max_it(30)).converged());
+
+    let amg = Multigrid::aggregation(&mat_a).unwrap();
+    assert!(amg.levels() >= 3 && amg.operator_complexity() < 2.0);
+    let mut x = vec![0.0; m * m];
+    let report = amg.solve(&b, &mut x, &mut SolverOptions::new().rel_tol(1e-10));
+    assert!(report.converged() && report.iterations <= 40);
+    assert!(err(&x) < 1e-7);
+
+    // as preconditioners
+    let mut x = vec![0.0; m * m];
+    let plain = cg(&mat_a, &b, &mut x, &mut SolverOptions::new().rel_tol(1e-10));
+    for mg in &[&geometric, &amg] {
+        let mut x = vec![0.0; m * m];
+        let report = cg_precond(&mat_a, &b, &mut x, *mg, &mut SolverOptions::new().rel_tol(1e-10));
+        assert!(report.converged() && report.iterations * 4 < plain.iterations);
+        assert!(err(&x) < 1e-7);
+    }
+    let convective = laplace_2d(m, 0.3);
+    let b = convective.mat_vec(&exact);
+    let amg = Multigrid::aggregation(&convective).unwrap();
+    let mut x = vec![0.0; m * m];
+    let report = gmres_precond(&convective, &b, &mut x, 10, &amg, PrecondSide::Right, &mut SolverOptions::new().rel_tol(1e-10));
+    assert!(report.converged() && report.iterations <= 15);
+    assert!(err(&x) < 1e-7);
+
+    // the 7-point Laplacian on a 9 x 9 x 9 grid
+    let m = 9;
+    let n = m * m * m;
+    let mut mat_a = SparseMatrix::<f64>::new(n, n);
+    for k in 0 .. n {
+        mat_a.set_v(k, k, 6.0);
+        for &stride in &[1, m, m * m] {
+            if (k / stride) % m > 0 { mat_a.set_v(k, k - stride, -1.0); }
+            if (k / stride) % m + 1 < m { mat_a.set_v(k, k + stride, -1.0); }
+        }
+    }
+    let exact: Vec<f64> = (0 .. n).map(|k| (k as f64 * 0.01).cos()).collect();
+    let b = mat_a.mat_vec(&exact);
+    let mg = Multigrid::geometric(&mat_a, &[m, m, m]).unwrap();
+    assert_eq!(mg.sizes(), &[729, 64, 8]);
+    let mut x = vec![0.0; n];
+    let report = mg.solve(&b, &mut x, &mut SolverOptions::new().rel_tol(1e-10));
+    assert!(report.converged() && report.iterations <= 20);
+
+    // small systems skip straight to the coarse solve
+    let small = laplace_2d(5, 0.0);
+    let mg = Multigrid::aggregation(&small).unwrap();
+    assert_eq!(mg.levels(), 1);
+    let b = small.mat_vec(&[1.0; 25]);
+    let mut x = vec![0.0; 25];
+    let report = mg.solve(&b, &mut x, &mut SolverOptions::new());
+    assert_eq!((report.iterations, report.reason), (1, StopReason::Converged));
+    assert!(Multigrid::geometric(&small, &[4, 4]).is_err());
+    let empty = Multigrid::aggregation(&SparseMatrix::<f64>::new(0, 0)).unwrap();
+    assert_eq!((empty.levels(), empty.operator_complexity()), (1, 1.0));
+}
@@ -0,0 +1,535 @@
+extern crate num;
+
+use super::super::matrix::MatOps;
+use super::super::matrix::sparse::SparseMatrix;
+use super::super::decomposition::LU;
+use super::super::scalar::Scalar;
+use super::super::super::error::{self, AlgebraError, AlgebraResult};
+use num::Float;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+// An approximation M of A that is cheap to invert. The Krylov solvers only
+// ever ask for z = M^-1 r.
+pub trait Preconditioner<T> {
+    fn apply(&self, r: &[T]) -> Vec<T>;
+}
+
+// which side of A the preconditioner multiplies: M^-1 A x = M^-1 b or
+// A M^-1 u = b with x = M^-1 u
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PrecondSide {
+    Left,
+    Right,
+}
+
+// M = I, what the unpreconditioned solvers use
+pub struct Identity;
+
+impl<T> Preconditioner<T> for Identity
+where T: Copy
+{
+    fn apply(&self, r: &[T]) -> Vec<T>
+    {
+        r.to_vec()
+    }
+}
+
+// the (column, value) pairs of one row, in column order
+type Row<T> = Vec<(usize, T)>;
+type Rows<T> = Vec<Row<T>>;
+
+fn diagonal<T>(mat: &SparseMatrix<T>) -> AlgebraResult<Vec<T>>
+where T: Float + Display
+{
+    error::check_square(mat.shape())?;
+    (0 .. mat.row()).map(|i| {
+        let d = mat.get_v(i, i);
+        if d == T::zero() { Err(AlgebraError::SingularPivot { index: i }) } else { Ok(d) }
+    }).collect()
+}
+
+// L y = b for a lower triangular L given by its strictly lower rows and its
+// diagonal (None for a unit diagonal)
+fn forward<T>(lower: &[Row<T>], diag: Option<&[T]>, b: &[T]) -> Vec<T>
+where T: Float
+{
+    let mut y = b.to_vec();
+    for i in 0 .. y.len() {
+        for &(j, v) in &lower[i] {
+            y[i] = y[i] - v * y[j];
+        }
+        if let Some(d) = diag {
+            y[i] = y[i] / d[i];
+        }
+    }
+    y
+}
+
+// U x = b for an upper triangular U given by its strictly upper rows and its diagonal
+fn backward<T>(upper: &[Row<T>], diag: &[T], b: &[T]) -> Vec<T>
+where T: Float
+{
+    let mut x = b.to_vec();
+    for i in (0 .. x.len()).rev() {
+        for &(j, v) in &upper[i] {
+            x[i] = x[i] - v * x[j];
+        }
+        x[i] = x[i] / diag[i];
+    }
+    x
+}
+
+// splits a row into its strictly lower part, its diagonal and its strictly upper part
+fn split_row<T>(i: usize, row: Row<T>) -> (Row<T>, T, Row<T>)
+where T: Float
+{
+    let mut lower = Vec::new();
+    let mut upper = Vec::new();
+    let mut d = T::zero();
+    for (j, v) in row {
+        if j < i {
+            lower.push((j, v));
+        }
+        else if j == i {
+            d = v;
+        }
+        else {
+            upper.push((j, v));
+        }
+    }
+    (lower, d, upper)
+}
+
+// M = diag(A)
+#[derive(Clone)]
+pub struct Jacobi<T> {
+    inv_diag: Vec<T>,
+}
+
+impl<T> Jacobi<T>
+where T: Float + Display
+{
+    pub fn new(mat: &SparseMatrix<T>) -> AlgebraResult<Jacobi<T>>
+    {
+        Ok(Jacobi { inv_diag: diagonal(mat)?.into_iter().map(|d| T::one() / d).collect() })
+    }
+}
+
+impl<T> Preconditioner<T> for Jacobi<T>
+where T: Float
+{
+    fn apply(&self, r: &[T]) -> Vec<T>
+    {
+        r.iter().zip(self.inv_diag.iter()).map(|(&r, &d)| r * d).collect()
+    }
+}
+
+// M = the block diagonal of A with blocks of block_size rows (the last one may
+// be smaller), every block factorised by LU
+pub struct BlockJacobi<T>
+where T: Scalar
+{
+    blocks: Vec<(usize, LU<T>)>,
+}
+
+impl<T> BlockJacobi<T>
+where T: Scalar
+{
+    pub fn new(mat: &SparseMatrix<T>, block_size: usize) -> AlgebraResult<BlockJacobi<T>>
+    {
+        assert!(block_size > 0, "the block size must be positive!");
+        error::check_square(mat.shape())?;
+        let n = mat.row();
+        let mut blocks = Vec::new();
+        let mut start = 0;
+        while start < n {
+            let end = (start + block_size).min(n);
+            let block = mat.slice(start, end - 1, start, end - 1).to_dense();
+            let lu = LU::new(&block).map_err(|err| match err {
+                AlgebraError::SingularPivot { index } => AlgebraError::SingularPivot { index: start + index },
+                err => err,
+            })?;
+            blocks.push((start, lu));
+            start = end;
+        }
+        Ok(BlockJacobi { blocks })
+    }
+}
+
+impl<T> Preconditioner<T> for BlockJacobi<T>
+where T: Scalar
+{
+    fn apply(&self, r: &[T]) -> Vec<T>
+    {
+        let mut z = Vec::with_capacity(r.len());
+        for &(start, ref lu) in &self.blocks {
+            z.extend(lu.solve_vec(&r[start .. start + lu.size()]));
+        }
+        z
+    }
+}
+
+// M = (D + w L) D^-1 (D + w U) / (w (2 - w)) with A = L + D + U, the
+// symmetric SOR preconditioner; 0 < w < 2
+#[derive(Clone)]
+pub struct SSOR<T> {
+    omega: T,
+    lower: Rows<T>,
+    diag: Vec<T>,
+    upper: Rows<T>,
+}
+
+impl<T> SSOR<T>
+where T: Float + Display
+{
+    pub fn new(mat: &SparseMatrix<T>, omega: T) -> AlgebraResult<SSOR<T>>
+    {
+        assert!(omega > T::zero() && omega < T::one() + T::one(), "SSOR needs 0 < w < 2!");
+        let diag = diagonal(mat)?;
+        let mut lower = Vec::with_capacity(diag.len());
+        let mut upper = Vec::with_capacity(diag.len());
+        for i in 0 .. diag.len() {
+            let (l, _, u) = split_row(i, mat.row_entries(i));
+            lower.push(l.into_iter().map(|(j, v)| (j, omega * v)).collect());
+            upper.push(u.into_iter().map(|(j, v)| (j, omega * v)).collect());
+        }
+        Ok(SSOR { omega, lower, diag, upper })
+    }
+}
+
+impl<T> Preconditioner<T> for SSOR<T>
+where T: Float
+{
+    fn apply(&self, r: &[T]) -> Vec<T>
+    {
+        let scale = self.omega * (T::one() + T::one() - self.omega);
+        let b: Vec<T> = r.iter().map(|&v| scale * v).collect();
+        let y: Vec<T> = forward(&self.lower, Some(&self.diag), &b).into_iter().zip(self.diag.iter())
+            .map(|(y, &d)| y * d).collect();
+        backward(&self.upper, &self.diag, &y)
+    }
+}
+
+// M = L U with the unit lower L and the upper U of an incomplete factorisation
+#[derive(Clone)]
+pub struct ILU<T> {
+    lower: Rows<T>,
+    diag: Vec<T>,
+    upper: Rows<T>,
+}
+
+impl<T> ILU<T>
+where T: Float + Display
+{
+    // ILU(0): Gaussian elimination that drops every fill-in outside the
+    // pattern of A
+    pub fn ilu0(mat: &SparseMatrix<T>) -> AlgebraResult<ILU<T>>
+    {
+        error::check_square(mat.shape())?;
+        let n = mat.row();
+        let mut lower: Rows<T> = Vec::with_capacity(n);
+        let mut diag = Vec::with_capacity(n);
+        let mut upper: Rows<T> = Vec::with_capacity(n);
+        // position of a column inside the current row
+        let mut pos = vec![None; n];
+        for i in 0 .. n {
+            let mut row = mat.row_entries(i);
+            for (p, &(j, _)) in row.iter().enumerate() {
+                pos[j] = Some(p);
+            }
+            for p in 0 .. row.len() {
+                let (k, v) = row[p];
+                if k >= i {
+                    break;
+                }
+                let l_ik = v / diag[k];
+                row[p].1 = l_ik;
+                for &(j, u_kj) in &upper[k] {
+                    if let Some(q) = pos[j] {
+                        row[q].1 = row[q].1 - l_ik * u_kj;
+                    }
+                }
+            }
+            for &(j, _) in &row {
+                pos[j] = None;
+            }
+            let (l, d, u) = split_row(i, row);
+            if d == T::zero() {
+                return Err(AlgebraError::SingularPivot { index: i });
+            }
+            lower.push(l);
+            diag.push(d);
+            upper.push(u);
+        }
+        Ok(ILU { lower, diag, upper })
+    }
+
+    // ILUT(p, tau): entries below tau times the norm of their row of A are
+    // dropped, and of the rest only the p largest of every row of L and of U
+    // are kept
+    pub fn ilut(mat: &SparseMatrix<T>, fill: usize, tau: T) -> AlgebraResult<ILU<T>>
+    {
+        error::check_square(mat.shape())?;
+        // the dropping and the largest-fill sort compare magnitudes
+        error::check_finite(mat)?;
+        let n = mat.row();
+        let mut lower: Rows<T> = Vec::with_capacity(n);
+        let mut diag = Vec::with_capacity(n);
+        let mut upper: Rows<T> = Vec::with_capacity(n);
+        for i in 0 .. n {
+            let mut w: BTreeMap<usize, T> = mat.row_entries(i).into_iter().collect();
+            let tol = tau * w.values().fold(T::zero(), |sum, &v| sum + v * v).sqrt();
+            let mut next = w.range(.. i).next().map(|(&k, _)| k);
+            while let Some(k) = next {
+                let l_ik = w[&k] / diag[k];
+                if l_ik.abs() < tol {
+                    w.remove(&k);
+                }
+                else {
+                    w.insert(k, l_ik);
+                    for &(j, u_kj) in &upper[k] {
+                        let entry = w.entry(j).or_insert_with(T::zero);
+                        *entry = *entry - l_ik * u_kj;
+                    }
+                }
+                next = w.range(k + 1 .. i).next().map(|(&k, _)| k);
+            }
+            let (l, d, u) = split_row(i, w.into_iter().collect());
+            if d == T::zero() {
+                return Err(AlgebraError::SingularPivot { index: i });
+            }
+            let keep = |part: Vec<(usize, T)>| {
+                let mut part: Vec<(usize, T)> = part.into_iter().filter(|&(_, v)| v.abs() >= tol).collect();
+                if part.len() > fill {
+                    part.sort_by(|a, b| b.1.abs().partial_cmp(&a.1.abs()).unwrap_or(Ordering::Equal));
+                    part.truncate(fill);
+                    part.sort_by_key(|&(j, _)| j);
+                }
+                part
+            };
+            lower.push(keep(l));
+            diag.push(d);
+            upper.push(keep(u));
+        }
+        Ok(ILU { lower, diag, upper })
+    }
+
+    pub fn nnz(&self) -> usize
+    {
+        self.diag.len() + self.lower.iter().chain(self.upper.iter()).map(|row| row.len()).sum::<usize>()
+    }
+}
+
+impl<T> Preconditioner<T> for ILU<T>
+where T: Float
+{
+    fn apply(&self, r: &[T]) -> Vec<T>
+    {
+        backward(&self.upper, &self.diag, &forward(&self.lower, None, r))
+    }
+}
+
+// IC(0): M = L L^T with L restricted to the lower triangle of A, for a
+// symmetric positive definite A; only the lower triangle is read
+#[derive(Clone)]
+pub struct IC0<T> {
+    lower: Rows<T>,
+    diag: Vec<T>,
+}
+
+impl<T> IC0<T>
+where T: Float + Display
+{
+    pub fn new(mat: &SparseMatrix<T>) -> AlgebraResult<IC0<T>>
+    {
+        error::check_square(mat.shape())?;
+        let n = mat.row();
+        let mut lower: Rows<T> = Vec::with_capacity(n);
+        let mut diag = Vec::with_capacity(n);
+        // sum_k l_ik l_jk over the columns k < j both rows have
+        let sparse_dot = |a: &[(usize, T)], b: &[(usize, T)], j: usize| {
+            let (mut p, mut q, mut sum) = (0, 0, T::zero());
+            while p < a.len() && q < b.len() && a[p].0 < j && b[q].0 < j {
+                if a[p].0 == b[q].0 {
+                    sum = sum + a[p].1 * b[q].1;
+                    p += 1;
+                    q += 1;
+                }
+                else if a[p].0 < b[q].0 {
+                    p += 1;
+                }
+                else {
+                    q += 1;
+                }
+            }
+            sum
+        };
+        for i in 0 .. n {
+            let (entries, a_ii, _) = split_row(i, mat.row_entries(i));
+            let mut row: Vec<(usize, T)> = Vec::with_capacity(entries.len());
+            for (j, a_ij) in entries {
+                let l_ij = (a_ij - sparse_dot(&row, &lower[j], j)) / diag[j];
+                row.push((j, l_ij));
+            }
+            let d = a_ii - row.iter().fold(T::zero(), |sum, &(_, v)| sum + v * v);
+            if d <= T::zero() || d.is_nan() {
+                return Err(AlgebraError::NotPositiveDefinite { index: i });
+            }
+            lower.push(row);
+            diag.push(d.sqrt());
+        }
+        Ok(IC0 { lower, diag })
+    }
+}
+
+impl<T> Preconditioner<T> for IC0<T>
+where T: Float
+{
+    fn apply(&self, r: &[T]) -> Vec<T>
+    {
+        let mut z = forward(&self.lower, Some(&self.diag), r);
+        // L^T z = y, walking the rows of L as the columns of L^T
+        for i in (0 .. z.len()).rev() {
+            z[i] = z[i] / self.diag[i];
+            for &(k, v) in &self.lower[i] {
+                z[k] = z[k] - v * z[i];
+            }
+        }
+        z
+    }
+}
+
+#[test]
+fn preconditioner_test() {
+    use super::iterative::{gmres, gmres_precond, cg, cg_precond, bicgstab_precond, cgs_precond, try_solve_gmres_precond};
+    use super::monitor::SolverOptions;
+    use super::super::vector::DenseVector;
+
+    let dist = |a: &[f64], b: &[f64]| a.iter().zip(b.iter()).fold(0.0, |m: f64, (x, y)| m.max((x - y).abs()));
+
+    // a tridiagonal matrix has no fill, so ILU(0), ILUT, IC(0) and one big
+    // block are exact
+    let n = 12;
+    let mut tri = SparseMatrix::<f64>::new(n, n);
+    for i in 0 .. n {
+        tri.set_v(i, i, 4.0);
+        if i + 1 < n {
+            tri.set_v(i, i + 1, -1.0);
+            tri.set_v(i + 1, i, -1.0);
+        }
+    }
+    let x: Vec<f64> = (0 .. n).map(|i| (i as f64).cos()).collect();
+    let b = tri.mat_vec(&x);
+    assert!(dist(&ILU::ilu0(&tri).unwrap().apply(&b), &x) < 1e-12);
+    assert!(dist(&ILU::ilut(&tri, n, 0.0).unwrap().apply(&b), &x) < 1e-12);
+    assert!(dist(&IC0::new(&tri).unwrap().apply(&b), &x) < 1e-12);
+    assert!(dist(&BlockJacobi::new(&tri, n).unwrap().apply(&b), &x) < 1e-12);
+    assert!(dist(&BlockJacobi::new(&tri.diag(), 5).unwrap().apply(&b), &Jacobi::new(&tri).unwrap().apply(&b)) < 1e-15);
+    assert!(dist(&Preconditioner::<f64>::apply(&Identity, &b), &b) == 0.0);
+    // SSOR with w = 1 on a diagonal matrix is Jacobi
+    assert!(dist(&SSOR::new(&tri.diag(), 1.0).unwrap().apply(&b), &Jacobi::new(&tri).unwrap().apply(&b)) < 1e-15);
+
+    let mut singular = tri.clone();
+    singular.set_v(3, 3, 0.0);
+    assert_eq!(Jacobi::new(&singular).err(), Some(AlgebraError::SingularPivot { index: 3 }));
+    // pivoting inside a 2 x 2 block gets around the zero, a 1 x 1 block cannot
+    assert!(BlockJacobi::new(&singular, 2).is_ok());
+    assert!(BlockJacobi::new(&singular, 1).err() == Some(AlgebraError::SingularPivot { index: 3 }));
+    let mut nan = tri.clone();
+    nan.set_v(2, 1, f64::NAN);
+    assert_eq!(ILU::ilut(&nan, 2, 1e-4).err(), Some(AlgebraError::NotFinite { index: (2, 1) }));
+    let mut indefinite = tri.clone();
+    indefinite.set_v(5, 5, -4.0);
+    assert_eq!(IC0::new(&indefinite).err(), Some(AlgebraError::NotPositiveDefinite { index: 5 }));
+
+    // convection-diffusion on a 12 x 12 grid: ILU(0) has to drop fill here
+    let m = 12;
+    let n = m * m;
+    let mut mat_a = SparseMatrix::<f64>::new(n, n);
+    let mut lap = SparseMatrix::<f64>::new(n, n);
+    for i in 0 .. m {
+        for j in 0 .. m {
+            let k = i * m + j;
+            mat_a.set_v(k, k, 4.0);
+            lap.set_v(k, k, 4.0);
+            if j + 1 < m {
+                mat_a.set_v(k, k + 1, -1.5);
+                mat_a.set_v(k + 1, k, -0.5);
+                lap.set_v(k, k + 1, -1.0);
+                lap.set_v(k + 1, k, -1.0);
+            }
+            if i + 1 < m {
+                mat_a.set_v(k, k + m, -1.0);
+                mat_a.set_v(k + m, k, -1.0);
+                lap.set_v(k, k + m, -1.0);
+                lap.set_v(k + m, k, -1.0);
+            }
+        }
+    }
+    let exact: Vec<f64> = (0 .. n).map(|i| 1.0 + (i % 7) as f64).collect();
+    let b = mat_a.mat_vec(&exact);
+    let ilu0 = ILU::ilu0(&mat_a).unwrap();
+    let ilut = ILU::ilut(&mat_a, 10, 1e-4).unwrap();
+    assert!(ilut.nnz() > ilu0.nnz());
+
+    let mut x = vec![0.0; n];
+    let plain = gmres(&mat_a, &b, &mut x, 5, &mut SolverOptions::new().rel_tol(1e-10));
+    assert!(plain.converged());
+    for &side in &[PrecondSide::Left, PrecondSide::Right] {
+        let precs: Vec<Box<dyn Preconditioner<f64>>> = vec![Box::new(ilu0.clone()), Box::new(ILU::ilut(&mat_a, 10, 1e-4).unwrap()),
+                                                            Box::new(SSOR::new(&mat_a, 1.2).unwrap()), Box::new(BlockJacobi::new(&mat_a, m).unwrap())];
+        for prec in precs.iter() {
+            let mut x = vec![0.0; n];
+            let report = gmres_precond(&mat_a, &b, &mut x, 5, prec.as_ref(), side, &mut SolverOptions::new().rel_tol(1e-10));
+            assert!(report.converged() && report.iterations < plain.iterations);
+            assert!(dist(&x, &exact) < 1e-7);
+        }
+    }
+    let mut x = vec![0.0; n];
+    let ilut_report = gmres_precond(&mat_a, &b, &mut x, 5, &ilut, PrecondSide::Right, &mut SolverOptions::new().rel_tol(1e-10));
+    assert!(ilut_report.iterations <= 8);
+    let mut x = vec![0.0; n];
+    assert!(bicgstab_precond(&mat_a, &b, &mut x, &ilu0, &mut SolverOptions::new().rel_tol(1e-10)).converged());
+    assert!(dist(&x, &exact) < 1e-7);
+    let mut x = vec![0.0; n];
+    assert!(cgs_precond(&mat_a, &b, &mut x, &ilu0, &mut SolverOptions::new().rel_tol(1e-10)).converged());
+    assert!(dist(&x, &exact) < 1e-7);
+
+    let b = lap.mat_vec(&exact);
+    let mut x = vec![0.0; n];
+    let plain = cg(&lap, &b, &mut x, &mut SolverOptions::new().rel_tol(1e-10));
+    for prec in &[Box::new(IC0::new(&lap).unwrap()) as Box<dyn Preconditioner<f64>>,
+                  Box::new(SSOR::new(&lap, 1.5).unwrap()), Box::new(Jacobi::new(&lap).unwrap())] {
+        let mut x = vec![0.0; n];
+        let report = cg_precond(&lap, &b, &mut x, prec.as_ref(), &mut SolverOptions::new().rel_tol(1e-10));
+        assert!(report.converged() && report.iterations <= plain.iterations);
+        assert!(dist(&x, &exact) < 1e-7);
+    }
+
+    // left preconditioning judges ||M^-1 (b - A x)|| against ||M^-1 b||; a
+    // diagonal spread of 1e6 makes that far from ||b - A x|| / ||b||
+    let n = 10;
+    let d = |i: usize| 10f64.powf(6.0 * i as f64 / (n - 1) as f64);
+    let mut scaled = SparseMatrix::<f64>::new(n, n);
+    for i in 0 .. n {
+        scaled.set_v(i, i, d(i));
+        if i + 1 < n {
+            scaled.set_v(i, i + 1, 0.5 * d(i));
+            scaled.set_v(i + 1, i, -0.3 * d(i + 1));
+        }
+    }
+    let b: Vec<f64> = (0 .. n).map(|i| d(i) * (1.0 + i as f64).sin()).collect();
+    let jacobi = Jacobi::new(&scaled).unwrap();
+    let err = try_solve_gmres_precond(&scaled, &DenseVector::from_slice(&b), 2, 1, 1e-14, &jacobi, PrecondSide::Left).err();
+    let mut x = vec![0.0; n];
+    gmres_precond(&scaled, &b, &mut x, 2, &jacobi, PrecondSide::Left, &mut SolverOptions::new().max_it(2).rel_tol(1e-14));
+    let r: Vec<f64> = scaled.mat_vec(&x).iter().zip(b.iter()).map(|(&ax, &b_i)| b_i - ax).collect();
+    let l2 = |v: &[f64]| v.iter().fold(0.0, |sum, &v| sum + v * v).sqrt();
+    let expected = l2(&jacobi.apply(&r)) / l2(&jacobi.apply(&b));
+    match err {
+        Some(AlgebraError::NotConverged { iterations: 2, residual }) => assert!((residual - expected).abs() < 1e-8 * expected),
+        other => panic!("expected NotConverged after 2 steps, got {:?}", other),
+    }
+}
@@ -0,0 +1,118 @@
+extern crate num;
+
+use super::super::matrix::MatIndex;
+use super::super::matrix::dense::DenseMatrix;
+use super::super::matrix::sparse::SparseMatrix;
+use super::super::vector::{DenseVector, SparseVector};
+use num::Num;
+use std::fmt::Display;
+
+// What the solvers accept as b in A x = b and hand back as x, of the same
+// type: a matrix with one system per column, or a vector for a single one.
+pub trait RightHandSide<T>: Sized
+{
+    // (rows, columns), a vector being one column
+    fn rhs_shape(&self) -> MatIndex;
+
+    fn rhs_col(&self, col: usize) -> Vec<T>;
+
+    // the solutions, one vector of length row_num per column
+    fn from_rhs_cols(row_num: usize, cols: Vec<Vec<T>>) -> Self;
+}
+
+impl<T> RightHandSide<T> for DenseMatrix<T>
+where T: Num + Clone + Copy + Display
+{
+    fn rhs_shape(&self) -> MatIndex {
+        self.shape()
+    }
+
+    fn rhs_col(&self, col: usize) -> Vec<T> {
+        (0 .. self.row()).map(|i| self.get_v(i, col)).collect()
+    }
+
+    fn from_rhs_cols(row_num: usize, cols: Vec<Vec<T>>) -> DenseMatrix<T> {
+        let mut mat = DenseMatrix::new(row_num, cols.len());
+        for (j, col) in cols.iter().enumerate() {
+            for (i, &v) in col.iter().enumerate() {
+                mat.set_v(i, j, v);
+            }
+        }
+        mat
+    }
+}
+
+impl<T> RightHandSide<T> for SparseMatrix<T>
+where T: Num + Clone + Copy + Display
+{
+    fn rhs_shape(&self) -> MatIndex {
+        self.shape()
+    }
+
+    fn rhs_col(&self, col: usize) -> Vec<T> {
+        (0 .. self.row()).map(|i| self.get_v(i, col)).collect()
+    }
+
+    fn from_rhs_cols(row_num: usize, cols: Vec<Vec<T>>) -> SparseMatrix<T> {
+        let mut mat = SparseMatrix::new(row_num, cols.len());
+        for (j, col) in cols.iter().enumerate() {
+            for (i, &v) in col.iter().enumerate() {
+                mat.set_v(i, j, v);
+            }
+        }
+        mat
+    }
+}
+
+impl<T> RightHandSide<T> for DenseVector<T>
+where T: Num + Clone + Copy + Display
+{
+    fn rhs_shape(&self) -> MatIndex {
+        (self.len(), 1)
+    }
+
+    fn rhs_col(&self, _col: usize) -> Vec<T> {
+        self.to_vec()
+    }
+
+    fn from_rhs_cols(row_num: usize, cols: Vec<Vec<T>>) -> DenseVector<T> {
+        cols.into_iter().next().map_or_else(|| DenseVector::new(row_num), DenseVector::from_vec)
+    }
+}
+
+impl<T> RightHandSide<T> for SparseVector<T>
+where T: Num + Clone + Copy + Display
+{
+    fn rhs_shape(&self) -> MatIndex {
+        (self.len(), 1)
+    }
+
+    fn rhs_col(&self, _col: usize) -> Vec<T> {
+        self.to_dense().into_vec()
+    }
+
+    fn from_rhs_cols(row_num: usize, cols: Vec<Vec<T>>) -> SparseVector<T> {
+        let col = cols.into_iter().next().unwrap_or_else(|| vec![T::zero(); row_num]);
+        SparseVector::from_dense(&DenseVector::from_vec(col))
+    }
+}
+
+#[test]
+fn rhs_test() {
+    use super::{try_solve_ge, try_solve_cg, solve_ge};
+    use super::super::super::error::AlgebraError;
+
+    let mat_a = DenseMatrix::<f64>::from(3, 3, &[4.0, 1.0, 0.0, 1.0, 3.0, 1.0, 0.0, 1.0, 2.0]);
+    let b = DenseVector::from_vec(vec![1.0, 2.0, 3.0]);
+    let x = try_solve_ge(&mat_a, &b).unwrap();
+    assert!((&mat_a * &x - b.clone()).norm_inf() < 1e-12);
+    assert!(x.to_matrix() == DenseMatrix::solve_ge(&mat_a, &b.to_matrix()).unwrap());
+
+    let sparse_a = SparseMatrix::from_dense(&mat_a);
+    let x_cg = SparseMatrix::try_solve_cg(&sparse_a, &b, 50, 1e-12).unwrap();
+    assert!(x_cg.sub(&x).norm_inf() < 1e-10);
+    let x_sparse = solve_ge(&sparse_a, &SparseVector::from_entries(3, vec![(0, 4.0), (1, 1.0)])).unwrap();
+    assert_eq!(x_sparse.to_dense(), DenseVector::unit(3, 0));
+    let short = try_solve_cg(&sparse_a, &DenseVector::<f64>::ones(2), 10, 1e-8);
+    assert_eq!(short.err(), Some(AlgebraError::DimensionMismatch { left: (3, 3), right: (2, 1) }));
+}
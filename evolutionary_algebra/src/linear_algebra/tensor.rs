@@ -0,0 +1,910 @@
+extern crate num;
+
+use super::matrix::dense::{DenseMatrix, Layout};
+use super::super::error::{AlgebraError, AlgebraResult};
+use super::super::parallel::Element;
+use num::{Float, Num, NumCast};
+use std::fmt::Display;
+use std::ops::Range;
+
+// An N-dimensional array stored row-major (the last index runs fastest), e.g.
+// a field sampled on a 3-D grid. Slices, permutations and broadcasts are
+// strided views over the same elements, so they copy nothing until turned
+// into a tensor of their own. Elementwise arithmetic broadcasts the way numpy
+// does: the shapes are aligned from the last axis and an extent of 1
+// stretches to match the other operand.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tensor<T>
+where T: Num + Clone + Copy + Display
+{
+    shape: Vec<usize>,
+    elements: Vec<T>,
+}
+
+// where the elements of a view sit in the underlying storage; a stride of 0
+// repeats an element along a broadcast axis
+#[derive(Debug, Clone)]
+struct Strided {
+    shape: Vec<usize>,
+    strides: Vec<usize>,
+    offset: usize,
+}
+
+#[derive(Clone)]
+pub struct TensorView<'a, T>
+where T: Num + Clone + Copy + Display
+{
+    strided: Strided,
+    elements: &'a [T],
+}
+
+pub struct TensorViewMut<'a, T>
+where T: Num + Clone + Copy + Display
+{
+    strided: Strided,
+    elements: &'a mut [T],
+}
+
+// the storage offsets of a view in row-major order of its indices
+struct Offsets<'s> {
+    strided: &'s Strided,
+    index: Vec<usize>,
+    offset: usize,
+    remaining: usize,
+}
+
+impl<'s> Iterator for Offsets<'s> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize>
+    {
+        if self.remaining == 0 {
+            return None;
+        }
+        let current = self.offset;
+        self.remaining -= 1;
+        // bump the last axis, carrying into the earlier ones
+        for axis in (0 .. self.index.len()).rev() {
+            self.index[axis] += 1;
+            self.offset += self.strided.strides[axis];
+            if self.index[axis] < self.strided.shape[axis] {
+                break;
+            }
+            self.offset -= self.strided.strides[axis] * self.strided.shape[axis];
+            self.index[axis] = 0;
+        }
+        Some(current)
+    }
+}
+
+fn contiguous_strides(shape: &[usize]) -> Vec<usize>
+{
+    let mut strides = vec![1; shape.len()];
+    for axis in (0 .. shape.len().saturating_sub(1)).rev() {
+        strides[axis] = strides[axis + 1] * shape[axis + 1];
+    }
+    strides
+}
+
+// the shape two operands broadcast to
+pub fn broadcast_shape(left: &[usize], right: &[usize]) -> AlgebraResult<Vec<usize>>
+{
+    let ndim = left.len().max(right.len());
+    let extent = |shape: &[usize], axis: usize| {
+        if axis + shape.len() < ndim { 1 } else { shape[axis + shape.len() - ndim] }
+    };
+    (0 .. ndim).map(|axis| {
+        match (extent(left, axis), extent(right, axis)) {
+            (l, r) if l == r || r == 1 => Ok(l),
+            (1, r) => Ok(r),
+            _ => Err(AlgebraError::ShapeMismatch { left: left.to_vec(), right: right.to_vec() }),
+        }
+    }).collect()
+}
+
+impl Strided {
+    fn contiguous(shape: &[usize]) -> Strided
+    {
+        Strided {
+            shape: shape.to_vec(),
+            strides: contiguous_strides(shape),
+            offset: 0,
+        }
+    }
+
+    fn len(&self) -> usize
+    {
+        self.shape.iter().product()
+    }
+
+    fn offsets(&self) -> Offsets<'_>
+    {
+        Offsets {
+            strided: self,
+            index: vec![0; self.shape.len()],
+            offset: self.offset,
+            remaining: self.len(),
+        }
+    }
+
+    fn offset_of(&self, index: &[usize]) -> usize
+    {
+        assert_eq!(index.len(), self.shape.len(), "the index does not match the number of axes!");
+        index.iter().zip(self.shape.iter().zip(self.strides.iter())).fold(self.offset, |off, (&i, (&n, &s))| {
+            if i >= n {
+                panic!("index {:?} is out of bounds for shape {:?}!", index, self.shape);
+            }
+            off + i * s
+        })
+    }
+
+    fn slice(&self, ranges: &[Range<usize>]) -> Strided
+    {
+        assert!(ranges.len() <= self.shape.len(), "more ranges than axes!");
+        let mut res = self.clone();
+        for (axis, range) in ranges.iter().enumerate() {
+            if range.start > range.end || range.end > self.shape[axis] {
+                panic!("range {:?} is out of bounds for axis {} of extent {}!", range, axis, self.shape[axis]);
+            }
+            res.offset += range.start * self.strides[axis];
+            res.shape[axis] = range.end - range.start;
+        }
+        res
+    }
+
+    // the axis dropped, at its first index
+    fn remove_axis(&self, axis: usize) -> Strided
+    {
+        let mut res = self.clone();
+        res.shape.remove(axis);
+        res.strides.remove(axis);
+        res
+    }
+
+    fn index_axis(&self, axis: usize, index: usize) -> Strided
+    {
+        assert!(axis < self.shape.len(), "the axis is out of bounds!");
+        assert!(index < self.shape[axis], "the index is out of bounds!");
+        let mut res = self.remove_axis(axis);
+        res.offset += index * self.strides[axis];
+        res
+    }
+
+    fn permute(&self, axes: &[usize]) -> Strided
+    {
+        let mut seen = vec![false; self.shape.len()];
+        assert_eq!(axes.len(), self.shape.len(), "the permutation does not match the number of axes!");
+        for &axis in axes {
+            assert!(axis < seen.len() && !seen[axis], "the axes are not a permutation!");
+            seen[axis] = true;
+        }
+        Strided {
+            shape: axes.iter().map(|&a| self.shape[a]).collect(),
+            strides: axes.iter().map(|&a| self.strides[a]).collect(),
+            offset: self.offset,
+        }
+    }
+
+    fn broadcast_to(&self, shape: &[usize]) -> AlgebraResult<Strided>
+    {
+        let err = || AlgebraError::ShapeMismatch { left: self.shape.clone(), right: shape.to_vec() };
+        if shape.len() < self.shape.len() {
+            return Err(err());
+        }
+        let lead = shape.len() - self.shape.len();
+        let mut strides = vec![0; shape.len()];
+        for axis in 0 .. self.shape.len() {
+            if self.shape[axis] == shape[lead + axis] {
+                strides[lead + axis] = self.strides[axis];
+            }
+            else if self.shape[axis] != 1 {
+                return Err(err());
+            }
+        }
+        Ok(Strided {
+            shape: shape.to_vec(),
+            strides,
+            offset: self.offset,
+        })
+    }
+}
+
+impl<T> Tensor<T>
+where T: Num + Clone + Copy + Display
+{
+    // zeros of the given shape; the empty shape holds a single scalar
+    pub fn new(shape: &[usize]) -> Tensor<T>
+    {
+        Tensor {
+            shape: shape.to_vec(),
+            elements: vec![T::zero(); shape.iter().product()],
+        }
+    }
+
+    pub fn from_vec(shape: &[usize], elements: Vec<T>) -> Tensor<T>
+    {
+        assert_eq!(elements.len(), shape.iter().product::<usize>(), "the length of elements does not match the shape!");
+        Tensor {
+            shape: shape.to_vec(),
+            elements,
+        }
+    }
+
+    // f of every index, in row-major order
+    pub fn from_fn<F>(shape: &[usize], mut f: F) -> Tensor<T>
+    where F: FnMut(&[usize]) -> T
+    {
+        let strided = Strided::contiguous(shape);
+        let mut index = vec![0; shape.len()];
+        let elements = strided.offsets().map(|off| {
+            for (axis, &stride) in strided.strides.iter().enumerate() {
+                index[axis] = off / stride % shape[axis];
+            }
+            f(&index)
+        }).collect();
+        Tensor {
+            shape: shape.to_vec(),
+            elements,
+        }
+    }
+
+    // the 2-D tensor with the entries of the matrix, whatever its layout
+    pub fn from_matrix(mat: &DenseMatrix<T>) -> Tensor<T>
+    {
+        Tensor {
+            shape: vec![mat.row(), mat.col()],
+            elements: mat.to_layout(Layout::RowMajor).into_vec(),
+        }
+    }
+
+    pub fn to_matrix(&self) -> AlgebraResult<DenseMatrix<T>>
+    {
+        if self.ndim() != 2 {
+            return Err(AlgebraError::RankMismatch { expected: 2, found: self.ndim() });
+        }
+        Ok(DenseMatrix::from_vec(self.shape[0], self.shape[1], Layout::RowMajor, self.elements.clone()))
+    }
+
+    pub fn shape(&self) -> &[usize]
+    {
+        &self.shape
+    }
+
+    pub fn ndim(&self) -> usize
+    {
+        self.shape.len()
+    }
+
+    pub fn len(&self) -> usize
+    {
+        self.elements.len()
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.elements.is_empty()
+    }
+
+    pub fn as_slice(&self) -> &[T]
+    {
+        &self.elements
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T]
+    {
+        &mut self.elements
+    }
+
+    pub fn into_vec(self) -> Vec<T>
+    {
+        self.elements
+    }
+
+    pub fn get(&self, index: &[usize]) -> T
+    {
+        self.elements[Strided::contiguous(&self.shape).offset_of(index)]
+    }
+
+    pub fn set(&mut self, index: &[usize], v: T)
+    {
+        let off = Strided::contiguous(&self.shape).offset_of(index);
+        self.elements[off] = v;
+    }
+
+    pub fn view(&self) -> TensorView<'_, T>
+    {
+        TensorView {
+            strided: Strided::contiguous(&self.shape),
+            elements: &self.elements,
+        }
+    }
+
+    pub fn view_mut(&mut self) -> TensorViewMut<'_, T>
+    {
+        TensorViewMut {
+            strided: Strided::contiguous(&self.shape),
+            elements: &mut self.elements,
+        }
+    }
+
+    // the ranges of the leading axes; the axes left out are kept whole
+    pub fn slice(&self, ranges: &[Range<usize>]) -> TensorView<'_, T>
+    {
+        self.view().slice(ranges)
+    }
+
+    pub fn slice_mut(&mut self, ranges: &[Range<usize>]) -> TensorViewMut<'_, T>
+    {
+        let strided = Strided::contiguous(&self.shape).slice(ranges);
+        TensorViewMut {
+            strided,
+            elements: &mut self.elements,
+        }
+    }
+
+    // the sub-tensor at index along axis, one axis fewer
+    pub fn index_axis(&self, axis: usize, index: usize) -> TensorView<'_, T>
+    {
+        self.view().index_axis(axis, index)
+    }
+
+    // axis i of the view is axis axes[i] of the tensor
+    pub fn permute(&self, axes: &[usize]) -> TensorView<'_, T>
+    {
+        self.view().permute(axes)
+    }
+
+    pub fn reshape(&self, shape: &[usize]) -> Tensor<T>
+    {
+        self.try_reshape(shape).unwrap()
+    }
+
+    // the same elements in the same order under a shape of the same size
+    pub fn try_reshape(&self, shape: &[usize]) -> AlgebraResult<Tensor<T>>
+    {
+        if shape.iter().product::<usize>() != self.len() {
+            return Err(AlgebraError::ShapeMismatch { left: self.shape.clone(), right: shape.to_vec() });
+        }
+        Ok(Tensor {
+            shape: shape.to_vec(),
+            elements: self.elements.clone(),
+        })
+    }
+
+    pub fn map<F>(&self, f: F) -> Tensor<T>
+    where F: Fn(T) -> T
+    {
+        Tensor {
+            shape: self.shape.clone(),
+            elements: self.elements.iter().map(|&v| f(v)).collect(),
+        }
+    }
+
+    pub fn scalar_mul(&self, k: T) -> Tensor<T>
+    {
+        self.map(|v| k * v)
+    }
+
+    pub fn add(&self, other: &Tensor<T>) -> Tensor<T>
+    {
+        self.try_add(other).unwrap()
+    }
+
+    pub fn try_add(&self, other: &Tensor<T>) -> AlgebraResult<Tensor<T>>
+    {
+        self.view().try_add(&other.view())
+    }
+
+    pub fn sub(&self, other: &Tensor<T>) -> Tensor<T>
+    {
+        self.try_sub(other).unwrap()
+    }
+
+    pub fn try_sub(&self, other: &Tensor<T>) -> AlgebraResult<Tensor<T>>
+    {
+        self.view().try_sub(&other.view())
+    }
+
+    // elementwise
+    pub fn mul(&self, other: &Tensor<T>) -> Tensor<T>
+    {
+        self.try_mul(other).unwrap()
+    }
+
+    pub fn try_mul(&self, other: &Tensor<T>) -> AlgebraResult<Tensor<T>>
+    {
+        self.view().try_mul(&other.view())
+    }
+
+    pub fn div(&self, other: &Tensor<T>) -> Tensor<T>
+    {
+        self.try_div(other).unwrap()
+    }
+
+    pub fn try_div(&self, other: &Tensor<T>) -> AlgebraResult<Tensor<T>>
+    {
+        self.view().try_div(&other.view())
+    }
+
+    pub fn sum(&self) -> T
+    {
+        self.elements.iter().fold(T::zero(), |sum, &v| sum + v)
+    }
+
+    pub fn sum_axis(&self, axis: usize) -> Tensor<T>
+    {
+        self.view().sum_axis(axis)
+    }
+
+    // sum over the axes pairs: axes_self[i] of self with axes_other[i] of other
+    pub fn tensordot(&self, other: &Tensor<T>, axes_self: &[usize], axes_other: &[usize]) -> AlgebraResult<Tensor<T>>
+    where T: Element
+    {
+        tensordot(&self.view(), &other.view(), axes_self, axes_other)
+    }
+}
+
+impl<T> Tensor<T>
+where T: Float + Display
+{
+    pub fn max(&self) -> T
+    {
+        self.elements.iter().fold(T::neg_infinity(), |m, &v| m.max(v))
+    }
+
+    pub fn min(&self) -> T
+    {
+        self.elements.iter().fold(T::infinity(), |m, &v| m.min(v))
+    }
+
+    pub fn mean(&self) -> T
+    {
+        self.sum() / T::from(self.len()).unwrap()
+    }
+
+    pub fn max_axis(&self, axis: usize) -> Tensor<T>
+    {
+        self.view().max_axis(axis)
+    }
+
+    pub fn min_axis(&self, axis: usize) -> Tensor<T>
+    {
+        self.view().min_axis(axis)
+    }
+
+    pub fn mean_axis(&self, axis: usize) -> Tensor<T>
+    {
+        self.view().mean_axis(axis)
+    }
+}
+
+impl<'a, T> TensorView<'a, T>
+where T: Num + Clone + Copy + Display
+{
+    pub fn shape(&self) -> &[usize]
+    {
+        &self.strided.shape
+    }
+
+    pub fn ndim(&self) -> usize
+    {
+        self.strided.shape.len()
+    }
+
+    pub fn len(&self) -> usize
+    {
+        self.strided.len()
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.len() == 0
+    }
+
+    pub fn get(&self, index: &[usize]) -> T
+    {
+        self.elements[self.strided.offset_of(index)]
+    }
+
+    // the elements in row-major order of the view
+    pub fn iter(&self) -> impl Iterator<Item = T> + '_
+    {
+        let elements = self.elements;
+        self.strided.offsets().map(move |off| elements[off])
+    }
+
+    pub fn to_tensor(&self) -> Tensor<T>
+    {
+        Tensor {
+            shape: self.strided.shape.clone(),
+            elements: self.iter().collect(),
+        }
+    }
+
+    pub fn slice(&self, ranges: &[Range<usize>]) -> TensorView<'a, T>
+    {
+        TensorView {
+            strided: self.strided.slice(ranges),
+            elements: self.elements,
+        }
+    }
+
+    pub fn index_axis(&self, axis: usize, index: usize) -> TensorView<'a, T>
+    {
+        TensorView {
+            strided: self.strided.index_axis(axis, index),
+            elements: self.elements,
+        }
+    }
+
+    pub fn permute(&self, axes: &[usize]) -> TensorView<'a, T>
+    {
+        TensorView {
+            strided: self.strided.permute(axes),
+            elements: self.elements,
+        }
+    }
+
+    // the view repeated along the axes where it has extent 1 or none at all
+    pub fn broadcast_to(&self, shape: &[usize]) -> AlgebraResult<TensorView<'a, T>>
+    {
+        Ok(TensorView {
+            strided: self.strided.broadcast_to(shape)?,
+            elements: self.elements,
+        })
+    }
+
+    pub fn map<F>(&self, f: F) -> Tensor<T>
+    where F: Fn(T) -> T
+    {
+        Tensor {
+            shape: self.strided.shape.clone(),
+            elements: self.iter().map(f).collect(),
+        }
+    }
+
+    // f of the elements of the two views broadcast together
+    pub fn zip_with<F>(&self, other: &TensorView<T>, f: F) -> AlgebraResult<Tensor<T>>
+    where F: Fn(T, T) -> T
+    {
+        let shape = broadcast_shape(self.shape(), other.shape())?;
+        let (left, right) = (self.broadcast_to(&shape)?, other.broadcast_to(&shape)?);
+        let elements = left.iter().zip(right.iter()).map(|(a, b)| f(a, b)).collect();
+        Ok(Tensor {
+            shape,
+            elements,
+        })
+    }
+
+    pub fn try_add(&self, other: &TensorView<T>) -> AlgebraResult<Tensor<T>>
+    {
+        self.zip_with(other, |a, b| a + b)
+    }
+
+    pub fn try_sub(&self, other: &TensorView<T>) -> AlgebraResult<Tensor<T>>
+    {
+        self.zip_with(other, |a, b| a - b)
+    }
+
+    pub fn try_mul(&self, other: &TensorView<T>) -> AlgebraResult<Tensor<T>>
+    {
+        self.zip_with(other, |a, b| a * b)
+    }
+
+    pub fn try_div(&self, other: &TensorView<T>) -> AlgebraResult<Tensor<T>>
+    {
+        self.zip_with(other, |a, b| a / b)
+    }
+
+    pub fn sum(&self) -> T
+    {
+        self.iter().fold(T::zero(), |sum, v| sum + v)
+    }
+
+    // f folded along every lane of axis, which the result drops
+    pub fn fold_axis<F>(&self, axis: usize, init: T, f: F) -> Tensor<T>
+    where F: Fn(T, T) -> T
+    {
+        assert!(axis < self.ndim(), "the axis is out of bounds!");
+        let lanes = self.strided.remove_axis(axis);
+        let (len, stride) = (self.strided.shape[axis], self.strided.strides[axis]);
+        let elements = lanes.offsets().map(|start| {
+            (0 .. len).fold(init, |acc, k| f(acc, self.elements[start + k * stride]))
+        }).collect();
+        Tensor {
+            shape: lanes.shape,
+            elements,
+        }
+    }
+
+    pub fn sum_axis(&self, axis: usize) -> Tensor<T>
+    {
+        self.fold_axis(axis, T::zero(), |sum, v| sum + v)
+    }
+}
+
+impl<'a, T> TensorView<'a, T>
+where T: Float + Display
+{
+    pub fn max_axis(&self, axis: usize) -> Tensor<T>
+    {
+        self.fold_axis(axis, T::neg_infinity(), T::max)
+    }
+
+    pub fn min_axis(&self, axis: usize) -> Tensor<T>
+    {
+        self.fold_axis(axis, T::infinity(), T::min)
+    }
+
+    pub fn mean_axis(&self, axis: usize) -> Tensor<T>
+    {
+        let count = <T as NumCast>::from(self.strided.shape[axis]).unwrap();
+        self.sum_axis(axis).map(|v| v / count)
+    }
+}
+
+impl<'a, T> TensorViewMut<'a, T>
+where T: Num + Clone + Copy + Display
+{
+    pub fn shape(&self) -> &[usize]
+    {
+        &self.strided.shape
+    }
+
+    pub fn view(&self) -> TensorView<'_, T>
+    {
+        TensorView {
+            strided: self.strided.clone(),
+            elements: self.elements,
+        }
+    }
+
+    pub fn get(&self, index: &[usize]) -> T
+    {
+        self.elements[self.strided.offset_of(index)]
+    }
+
+    pub fn set(&mut self, index: &[usize], v: T)
+    {
+        let off = self.strided.offset_of(index);
+        self.elements[off] = v;
+    }
+
+    pub fn fill(&mut self, v: T)
+    {
+        for off in self.strided.offsets() {
+            self.elements[off] = v;
+        }
+    }
+
+    pub fn map_inplace<F>(&mut self, f: F)
+    where F: Fn(T) -> T
+    {
+        for off in self.strided.offsets() {
+            self.elements[off] = f(self.elements[off]);
+        }
+    }
+
+    // copies src, broadcast to the shape of the view
+    pub fn assign(&mut self, src: &TensorView<T>) -> AlgebraResult<()>
+    {
+        let src = src.broadcast_to(&self.strided.shape)?;
+        for (dst, v) in self.strided.offsets().zip(src.iter()) {
+            self.elements[dst] = v;
+        }
+        Ok(())
+    }
+}
+
+// the contraction of a and b over the paired axes: the free axes of a, then
+// those of b, in their order; the work is one matrix product
+pub fn tensordot<T>(a: &TensorView<T>, b: &TensorView<T>, axes_a: &[usize], axes_b: &[usize]) -> AlgebraResult<Tensor<T>>
+where T: Num + Clone + Copy + Display + Element
+{
+    if axes_a.len() != axes_b.len() {
+        return Err(AlgebraError::AxesMismatch { left: axes_a.len(), right: axes_b.len() });
+    }
+    for (p, (&i, &j)) in axes_a.iter().zip(axes_b.iter()).enumerate() {
+        if i >= a.ndim() {
+            return Err(AlgebraError::AxisOutOfBounds { axis: i, ndim: a.ndim() });
+        }
+        if j >= b.ndim() {
+            return Err(AlgebraError::AxisOutOfBounds { axis: j, ndim: b.ndim() });
+        }
+        if axes_a[.. p].contains(&i) {
+            return Err(AlgebraError::DuplicateAxis { axis: i });
+        }
+        if axes_b[.. p].contains(&j) {
+            return Err(AlgebraError::DuplicateAxis { axis: j });
+        }
+        if a.shape()[i] != b.shape()[j] {
+            return Err(AlgebraError::ShapeMismatch { left: a.shape().to_vec(), right: b.shape().to_vec() });
+        }
+    }
+    let free_a: Vec<usize> = (0 .. a.ndim()).filter(|i| !axes_a.contains(i)).collect();
+    let free_b: Vec<usize> = (0 .. b.ndim()).filter(|j| !axes_b.contains(j)).collect();
+    let extent = |view: &TensorView<T>, axes: &[usize]| axes.iter().map(|&i| view.shape()[i]).product::<usize>();
+    let (m, k, n) = (extent(a, &free_a), extent(a, axes_a), extent(b, &free_b));
+    let perm_a: Vec<usize> = free_a.iter().chain(axes_a.iter()).cloned().collect();
+    let perm_b: Vec<usize> = axes_b.iter().chain(free_b.iter()).cloned().collect();
+    let mat_a = DenseMatrix::from_vec(m, k, Layout::RowMajor, a.permute(&perm_a).iter().collect());
+    let mat_b = DenseMatrix::from_vec(k, n, Layout::RowMajor, b.permute(&perm_b).iter().collect());
+    let shape: Vec<usize> = free_a.iter().map(|&i| a.shape()[i]).chain(free_b.iter().map(|&j| b.shape()[j])).collect();
+    Ok(Tensor::from_vec(&shape, mat_a.dot_mul(&mat_b).to_layout(Layout::RowMajor).into_vec()))
+}
+
+// Einstein summation, e.g. "ij,jk->ik" (a matrix product), "ii->" (the
+// trace), "ijk->kji" or "i,j->ij". Letters left out of the output are summed
+// over; without "->" the output takes the letters used once, alphabetically.
+pub fn einsum<T>(subscripts: &str, operands: &[&TensorView<T>]) -> AlgebraResult<Tensor<T>>
+where T: Num + Clone + Copy + Display + Element
+{
+    let invalid = || AlgebraError::InvalidSubscripts { subscripts: subscripts.to_string() };
+    let spec: String = subscripts.chars().filter(|c| !c.is_whitespace()).collect();
+    let (inputs, output) = match spec.find("->") {
+        Some(p) => (&spec[.. p], Some(&spec[p + 2 ..])),
+        None => (&spec[..], None),
+    };
+    let inputs: Vec<Vec<char>> = inputs.split(',').map(|s| s.chars().collect()).collect();
+    if inputs.len() != operands.len() {
+        return Err(invalid());
+    }
+    // every letter with its extent and the operand it came from
+    let mut letters: Vec<(char, usize, usize)> = Vec::new();
+    for (k, (input, op)) in inputs.iter().zip(operands.iter()).enumerate() {
+        if input.len() != op.ndim() || !input.iter().all(|c| c.is_ascii_alphabetic()) {
+            return Err(invalid());
+        }
+        for (&c, &n) in input.iter().zip(op.shape().iter()) {
+            match letters.iter().find(|l| l.0 == c) {
+                Some(&(_, m, from)) if m != n =>
+                    return Err(AlgebraError::ShapeMismatch { left: operands[from].shape().to_vec(), right: op.shape().to_vec() }),
+                Some(_) => {}
+                None => letters.push((c, n, k)),
+            }
+        }
+    }
+    let output: Vec<char> = match output {
+        Some(out) => {
+            let out: Vec<char> = out.chars().collect();
+            for (i, c) in out.iter().enumerate() {
+                if !letters.iter().any(|l| l.0 == *c) || out[.. i].contains(c) {
+                    return Err(invalid());
+                }
+            }
+            out
+        }
+        None => {
+            let mut once: Vec<char> = letters.iter().map(|l| l.0).filter(|c| {
+                inputs.iter().map(|input| input.iter().filter(|&x| x == c).count()).sum::<usize>() == 1
+            }).collect();
+            once.sort();
+            once
+        }
+    };
+    // the output letters first, so the row-major count over all letters
+    // divided by the number of summed terms is the output offset
+    let all: Vec<char> = output.iter().cloned().chain(letters.iter().map(|l| l.0).filter(|c| !output.contains(c))).collect();
+    let extents: Vec<usize> = all.iter().map(|c| letters.iter().find(|l| l.0 == *c).unwrap().1).collect();
+    let out_shape = extents[.. output.len()].to_vec();
+    let terms: usize = extents[output.len() ..].iter().product();
+    let positions: Vec<Vec<usize>> = inputs.iter().map(|input| {
+        input.iter().map(|c| all.iter().position(|x| x == c).unwrap()).collect()
+    }).collect();
+    let mut res = Tensor::new(&out_shape);
+    let mut index = vec![0; all.len()];
+    for count in 0 .. extents.iter().product::<usize>() {
+        let mut rem = count;
+        for axis in (0 .. all.len()).rev() {
+            index[axis] = rem % extents[axis];
+            rem /= extents[axis];
+        }
+        let term = operands.iter().zip(positions.iter()).fold(T::one(), |prod, (op, pos)| {
+            let at: Vec<usize> = pos.iter().map(|&p| index[p]).collect();
+            prod * op.get(&at)
+        });
+        res.elements[count / terms] = res.elements[count / terms] + term;
+    }
+    Ok(res)
+}
+
+#[test]
+fn tensor_test() {
+    let t = Tensor::from_fn(&[2, 3, 4], |i| (100 * i[0] + 10 * i[1] + i[2]) as f64);
+    assert_eq!((t.ndim(), t.len()), (3, 24));
+    assert_eq!(t.get(&[1, 2, 3]), 123.0);
+    assert_eq!(t.as_slice()[4], 10.0);
+    // views copy nothing until asked to
+    let s = t.slice(&[1 .. 2, 0 .. 3, 1 .. 3]);
+    assert_eq!(s.shape(), &[1, 3, 2]);
+    assert_eq!(s.iter().collect::<Vec<f64>>(), vec![101.0, 102.0, 111.0, 112.0, 121.0, 122.0]);
+    assert_eq!(s.index_axis(1, 2).to_tensor(), Tensor::from_vec(&[1, 2], vec![121.0, 122.0]));
+    let p = t.permute(&[2, 0, 1]);
+    assert_eq!(p.shape(), &[4, 2, 3]);
+    assert_eq!(p.get(&[3, 1, 2]), 123.0);
+    assert_eq!(p.to_tensor().permute(&[1, 2, 0]).to_tensor(), t);
+    let r = t.reshape(&[6, 4]);
+    assert_eq!(r.get(&[5, 3]), 123.0);
+    assert_eq!(t.try_reshape(&[5, 5]).err(), Some(AlgebraError::ShapeMismatch { left: vec![2, 3, 4], right: vec![5, 5] }));
+
+    let mut u = Tensor::<f64>::new(&[3, 3]);
+    u.slice_mut(&[1 .. 3, 0 .. 3]).fill(1.0);
+    u.slice_mut(&[0 .. 3, 2 .. 3]).assign(&Tensor::from_vec(&[1], vec![5.0]).view()).unwrap();
+    u.view_mut().map_inplace(|v| v * 2.0);
+    u.set(&[0, 0], -1.0);
+    assert_eq!(u.as_slice(), &[-1.0, 0.0, 10.0, 2.0, 2.0, 10.0, 2.0, 2.0, 10.0]);
+
+    // lossless both ways, for either layout
+    let mat = DenseMatrix::from_vec(2, 3, Layout::ColMajor, vec![1.0, 4.0, 2.0, 5.0, 3.0, 6.0]);
+    let m = Tensor::from_matrix(&mat);
+    assert_eq!(m.as_slice(), &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+    assert!(m.to_matrix().unwrap() == mat.to_layout(Layout::RowMajor));
+    assert_eq!(t.to_matrix().err(), Some(AlgebraError::RankMismatch { expected: 2, found: 3 }));
+}
+
+#[test]
+fn tensor_broadcast_reduction_test() {
+    let a = Tensor::from_vec(&[2, 3], vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+    let row = Tensor::from_vec(&[3], vec![10.0, 20.0, 30.0]);
+    let col = Tensor::from_vec(&[2, 1], vec![1.0, -1.0]);
+    assert_eq!(a.add(&row).as_slice(), &[11.0, 22.0, 33.0, 14.0, 25.0, 36.0]);
+    assert_eq!(a.mul(&col).as_slice(), &[1.0, 2.0, 3.0, -4.0, -5.0, -6.0]);
+    // [2, 1] and [3] broadcast to [2, 3]
+    assert_eq!(col.sub(&row).shape(), &[2, 3]);
+    assert_eq!(a.div(&Tensor::from_vec(&[], vec![2.0])).get(&[1, 1]), 2.5);
+    assert_eq!(a.try_add(&Tensor::new(&[2])).err(), Some(AlgebraError::ShapeMismatch { left: vec![2, 3], right: vec![2] }));
+    assert_eq!(broadcast_shape(&[4, 1, 3], &[5, 1]), Ok(vec![4, 5, 3]));
+
+    assert_eq!(a.sum(), 21.0);
+    assert_eq!(a.sum_axis(0).as_slice(), &[5.0, 7.0, 9.0]);
+    assert_eq!(a.sum_axis(1).as_slice(), &[6.0, 15.0]);
+    assert_eq!(a.max_axis(0).as_slice(), &[4.0, 5.0, 6.0]);
+    assert_eq!(a.min_axis(1).as_slice(), &[1.0, 4.0]);
+    assert_eq!(a.mean_axis(1).as_slice(), &[2.0, 5.0]);
+    assert_eq!((a.max(), a.min(), a.mean()), (6.0, 1.0, 3.5));
+    // reductions of a strided view, down to a scalar
+    let row_sums = a.permute(&[1, 0]).sum_axis(0);
+    assert_eq!(row_sums.as_slice(), &[6.0, 15.0]);
+    let total = row_sums.sum_axis(0);
+    assert_eq!((total.shape(), total.get(&[])), (&[][..], 21.0));
+}
+
+#[test]
+fn tensor_contraction_test() {
+    let a = Tensor::from_fn(&[2, 3, 4], |i| (i[0] + 2 * i[1] + 3 * i[2]) as f64);
+    let b = Tensor::from_fn(&[4, 3, 5], |i| (i[0] as f64 - i[1] as f64) * (i[2] + 1) as f64);
+    // contract the last two axes of a with the first two of b, transposed
+    let c = a.tensordot(&b, &[1, 2], &[1, 0]).unwrap();
+    assert_eq!(c.shape(), &[2, 5]);
+    for i in 0 .. 2 {
+        for l in 0 .. 5 {
+            let mut expected = 0.0;
+            for j in 0 .. 3 {
+                for k in 0 .. 4 {
+                    expected += a.get(&[i, j, k]) * b.get(&[k, j, l]);
+                }
+            }
+            assert_eq!(c.get(&[i, l]), expected);
+        }
+    }
+    assert_eq!(einsum("ijk,kjl->il", &[&a.view(), &b.view()]).unwrap(), c);
+    assert_eq!(einsum("ijk,kjl", &[&a.view(), &b.view()]).unwrap(), c);
+    assert_eq!(a.tensordot(&b, &[0], &[0]).err(), Some(AlgebraError::ShapeMismatch { left: vec![2, 3, 4], right: vec![4, 3, 5] }));
+    assert_eq!(a.tensordot(&b, &[3], &[0]).err(), Some(AlgebraError::AxisOutOfBounds { axis: 3, ndim: 3 }));
+    assert_eq!(a.tensordot(&b, &[1, 2], &[1]).err(), Some(AlgebraError::AxesMismatch { left: 2, right: 1 }));
+    assert_eq!(a.tensordot(&a, &[1, 1], &[1, 1]).err(), Some(AlgebraError::DuplicateAxis { axis: 1 }));
+
+    // the matrix product, trace, transpose and outer product
+    let m = DenseMatrix::from(2, 2, &[1.0, 2.0, 3.0, 4.0]);
+    let n = DenseMatrix::from(2, 2, &[0.0, 1.0, -1.0, 2.0]);
+    let (tm, tn) = (Tensor::from_matrix(&m), Tensor::from_matrix(&n));
+    assert!(einsum("ij,jk->ik", &[&tm.view(), &tn.view()]).unwrap().to_matrix().unwrap() == m.dot_mul(&n));
+    assert_eq!(einsum("ii->", &[&tm.view()]).unwrap().get(&[]), 5.0);
+    assert_eq!(einsum("ij->ji", &[&tm.view()]).unwrap(), tm.permute(&[1, 0]).to_tensor());
+    let outer = einsum("i,j->ij", &[&tm.index_axis(0, 0), &tn.index_axis(1, 1)]).unwrap();
+    assert_eq!(outer.as_slice(), &[1.0, 2.0, 2.0, 4.0]);
+    let bad = AlgebraError::InvalidSubscripts { subscripts: "ij,jk->il".to_string() };
+    assert_eq!(einsum("ij,jk->il", &[&tm.view(), &tn.view()]).err(), Some(bad));
+    assert!(einsum("ij", &[&tm.view(), &tn.view()]).is_err());
+    assert!(einsum("ij,j->i", &[&tm.view(), &Tensor::<f64>::new(&[3]).view()]).is_err());
+}
@@ -0,0 +1,306 @@
+extern crate num;
+
+use super::sparse_vector::SparseVector;
+use super::super::matrix::dense::DenseMatrix;
+use super::super::scalar::{self, Scalar};
+use super::super::super::error::{AlgebraError, AlgebraResult};
+use num::{Num, Float, Zero};
+use std::fmt::Display;
+use std::iter::FromIterator;
+use std::ops::{Deref, DerefMut};
+
+// A column vector, the b or x of a single linear system. It dereferences to
+// its slice, so len(), iter() and v[i] come from there and it can be handed
+// to every kernel taking &[T] or &mut [T].
+#[derive(Debug, Clone, PartialEq)]
+pub struct DenseVector<T>
+where T: Num + Clone + Copy + Display
+{
+    elements: Vec<T>,
+}
+
+impl<T> DenseVector<T>
+where T: Num + Clone + Copy + Display
+{
+    pub fn new(len: usize) -> DenseVector<T>
+    {
+        DenseVector { elements: vec![T::zero(); len] }
+    }
+
+    pub fn from_vec(elements: Vec<T>) -> DenseVector<T>
+    {
+        DenseVector { elements }
+    }
+
+    pub fn from_slice(slice: &[T]) -> DenseVector<T>
+    {
+        DenseVector { elements: slice.to_vec() }
+    }
+
+    // column col of mat
+    pub fn from_col(mat: &DenseMatrix<T>, col: usize) -> DenseVector<T>
+    {
+        assert!(col < mat.col(), "the column {} is out of bounds!", col);
+        (0 .. mat.row()).map(|i| mat.get_v(i, col)).collect()
+    }
+
+    pub fn ones(len: usize) -> DenseVector<T>
+    {
+        DenseVector { elements: vec![T::one(); len] }
+    }
+
+    // the i-th unit vector of length len
+    pub fn unit(len: usize, i: usize) -> DenseVector<T>
+    {
+        let mut vec = Self::new(len);
+        vec.set_v(i, T::one());
+        vec
+    }
+
+    #[inline]
+    pub fn get_v(&self, i: usize) -> T
+    {
+        self.elements[i]
+    }
+
+    #[inline]
+    pub fn set_v(&mut self, i: usize, v: T)
+    {
+        self.elements[i] = v;
+    }
+
+    pub fn as_slice(&self) -> &[T]
+    {
+        &self.elements
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T]
+    {
+        &mut self.elements
+    }
+
+    pub fn into_vec(self) -> Vec<T>
+    {
+        self.elements
+    }
+
+    // the len x 1 matrix
+    pub fn to_matrix(&self) -> DenseMatrix<T>
+    {
+        DenseMatrix::from(self.len(), 1, &self.elements)
+    }
+
+    pub fn to_sparse(&self) -> SparseVector<T>
+    {
+        SparseVector::from_dense(self)
+    }
+
+    fn zip_with<F>(&self, vec: &DenseVector<T>, f: F) -> DenseVector<T>
+    where F: Fn(T, T) -> T
+    {
+        assert_eq!(self.len(), vec.len(), "the length of the vectors does not match!");
+        self.iter().zip(vec.iter()).map(|(&a, &b)| f(a, b)).collect()
+    }
+
+    pub fn scalar_mul(&self, k: T) -> DenseVector<T>
+    {
+        self.iter().map(|&v| v * k).collect()
+    }
+
+    pub fn neg(&self) -> DenseVector<T>
+    {
+        self.iter().map(|&v| T::zero() - v).collect()
+    }
+
+    pub fn add(&self, vec: &DenseVector<T>) -> DenseVector<T>
+    {
+        self.zip_with(vec, |a, b| a + b)
+    }
+
+    pub fn sub(&self, vec: &DenseVector<T>) -> DenseVector<T>
+    {
+        self.zip_with(vec, |a, b| a - b)
+    }
+
+    pub fn hadamard(&self, vec: &DenseVector<T>) -> DenseVector<T>
+    {
+        self.zip_with(vec, |a, b| a * b)
+    }
+
+    pub fn try_add(&self, vec: &DenseVector<T>) -> AlgebraResult<DenseVector<T>>
+    {
+        check_len(self.len(), vec.len())?;
+        Ok(self.add(vec))
+    }
+
+    pub fn try_sub(&self, vec: &DenseVector<T>) -> AlgebraResult<DenseVector<T>>
+    {
+        check_len(self.len(), vec.len())?;
+        Ok(self.sub(vec))
+    }
+
+    // self += alpha x
+    pub fn axpy(&mut self, alpha: T, x: &DenseVector<T>)
+    {
+        assert_eq!(self.len(), x.len(), "the length of the vectors does not match!");
+        for (y_i, &x_i) in self.elements.iter_mut().zip(x.iter()) {
+            *y_i = *y_i + alpha * x_i;
+        }
+    }
+}
+
+// vectors of lengths left and right as the (rows, 1) shapes of the errors
+pub(crate) fn check_len(left: usize, right: usize) -> AlgebraResult<()>
+{
+    if left == right {
+        Ok(())
+    }
+    else {
+        Err(AlgebraError::DimensionMismatch { left: (left, 1), right: (right, 1) })
+    }
+}
+
+impl<T> DenseVector<T>
+where T: Scalar
+{
+    // sum conj(x_i) y_i, so x.dot(&x) is real for complex x as well
+    pub fn dot(&self, vec: &DenseVector<T>) -> T
+    {
+        assert_eq!(self.len(), vec.len(), "the length of the vectors does not match!");
+        scalar::dot(self, vec)
+    }
+
+    pub fn try_dot(&self, vec: &DenseVector<T>) -> AlgebraResult<T>
+    {
+        check_len(self.len(), vec.len())?;
+        Ok(self.dot(vec))
+    }
+
+    pub fn norm_1(&self) -> T::Real
+    {
+        self.iter().fold(T::Real::zero(), |sum, &v| sum + v.modulus())
+    }
+
+    pub fn norm2(&self) -> T::Real
+    {
+        scalar::norm(self)
+    }
+
+    pub fn norm_inf(&self) -> T::Real
+    {
+        self.iter().fold(T::Real::zero(), |m, &v| m.max(v.modulus()))
+    }
+
+    // scales to unit 2-norm and returns the norm it divided by; the zero
+    // vector is left as it is
+    pub fn normalize(&mut self) -> T::Real
+    {
+        let norm = self.norm2();
+        if norm > T::Real::zero() {
+            let inv = T::from_real(norm.recip());
+            for v in self.elements.iter_mut() {
+                *v = *v * inv;
+            }
+        }
+        norm
+    }
+
+    pub fn normalized(&self) -> DenseVector<T>
+    {
+        let mut vec = self.clone();
+        vec.normalize();
+        vec
+    }
+}
+
+impl<T> Deref for DenseVector<T>
+where T: Num + Clone + Copy + Display
+{
+    type Target = [T];
+    fn deref(&self) -> &[T] {
+        &self.elements
+    }
+}
+
+impl<T> DerefMut for DenseVector<T>
+where T: Num + Clone + Copy + Display
+{
+    fn deref_mut(&mut self) -> &mut [T] {
+        &mut self.elements
+    }
+}
+
+impl<T> From<Vec<T>> for DenseVector<T>
+where T: Num + Clone + Copy + Display
+{
+    fn from(elements: Vec<T>) -> DenseVector<T> {
+        DenseVector { elements }
+    }
+}
+
+impl<T> From<DenseVector<T>> for Vec<T>
+where T: Num + Clone + Copy + Display
+{
+    fn from(vec: DenseVector<T>) -> Vec<T> {
+        vec.elements
+    }
+}
+
+impl<T> FromIterator<T> for DenseVector<T>
+where T: Num + Clone + Copy + Display
+{
+    fn from_iter<I>(iter: I) -> DenseVector<T>
+    where I: IntoIterator<Item = T>
+    {
+        DenseVector { elements: iter.into_iter().collect() }
+    }
+}
+
+impl<T> IntoIterator for DenseVector<T>
+where T: Num + Clone + Copy + Display
+{
+    type Item = T;
+    type IntoIter = ::std::vec::IntoIter<T>;
+    fn into_iter(self) -> ::std::vec::IntoIter<T> {
+        self.elements.into_iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a DenseVector<T>
+where T: Num + Clone + Copy + Display
+{
+    type Item = &'a T;
+    type IntoIter = ::std::slice::Iter<'a, T>;
+    fn into_iter(self) -> ::std::slice::Iter<'a, T> {
+        self.elements.iter()
+    }
+}
+
+#[test]
+fn dense_vector_test() {
+    use num::Complex;
+
+    let x = DenseVector::from_vec(vec![3.0, -4.0, 0.0]);
+    let y: DenseVector<f64> = (1 .. 4).map(|i| i as f64).collect();
+    assert_eq!(x.dot(&y), -5.0);
+    assert_eq!((x.norm_1(), x.norm2(), x.norm_inf()), (7.0, 5.0, 4.0));
+    assert!(x.normalized().sub(&DenseVector::from_slice(&[0.6, -0.8, 0.0])).norm_inf() < 1e-15);
+    let mut zero = DenseVector::<f64>::new(2);
+    assert_eq!(zero.normalize(), 0.0);
+    assert!(zero.iter().all(|&v| v == 0.0));
+
+    let mut z = y.clone();
+    z.axpy(2.0, &x);
+    assert_eq!(z.as_slice(), &[7.0, -6.0, 3.0]);
+    assert_eq!(z[1], -6.0);
+    assert_eq!(x.add(&y).sub(&y), x);
+    assert_eq!(x.try_add(&DenseVector::new(2)).err(), Some(AlgebraError::DimensionMismatch { left: (3, 1), right: (2, 1) }));
+    assert!(x.to_matrix() == DenseMatrix::from(3, 1, &[3.0, -4.0, 0.0]));
+    assert_eq!(DenseVector::from_col(&DenseMatrix::<f64>::eye(3), 1), DenseVector::unit(3, 1));
+    assert_eq!(x.to_sparse().nnz(), 2);
+    assert_eq!(Vec::from(x.clone()).len(), 3);
+
+    let c = DenseVector::from_vec(vec![Complex::new(0.0, 1.0), Complex::new(1.0, 0.0)]);
+    assert_eq!(c.dot(&c), Complex::new(2.0, 0.0));
+    assert_eq!(c.norm2(), 2.0f64.sqrt());
+}
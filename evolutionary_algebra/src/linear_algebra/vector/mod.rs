@@ -0,0 +1,6 @@
+pub mod dense_vector;
+pub mod sparse_vector;
+mod ops;
+
+pub use self::dense_vector::DenseVector;
+pub use self::sparse_vector::SparseVector;
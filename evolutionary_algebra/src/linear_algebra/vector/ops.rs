@@ -0,0 +1,125 @@
+// std::ops for the vector types, and `*` of a matrix by a dense vector as the
+// matrix-vector product.
+use super::dense_vector::DenseVector;
+use super::sparse_vector::SparseVector;
+use super::super::matrix::dense::DenseMatrix;
+use super::super::matrix::sparse::SparseMatrix;
+use super::super::matrix::MatOps;
+use super::super::super::parallel::Element;
+use num::Num;
+use std::fmt::Display;
+use std::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+// as impl_mat_binop in matrix/ops.rs
+macro_rules! impl_vec_binop {
+    ($op_trait:ident, $op_fn:ident, $vec:ident, $method:path) => {
+        impl<'a, 'b, T> $op_trait<&'b $vec<T>> for &'a $vec<T>
+        where T: Num + Clone + Copy + Display
+        {
+            type Output = $vec<T>;
+            fn $op_fn(self, vec: &'b $vec<T>) -> $vec<T> {
+                $method(self, vec)
+            }
+        }
+
+        impl<T> $op_trait<$vec<T>> for $vec<T>
+        where T: Num + Clone + Copy + Display
+        {
+            type Output = $vec<T>;
+            fn $op_fn(self, vec: $vec<T>) -> $vec<T> {
+                $method(&self, &vec)
+            }
+        }
+    };
+}
+
+macro_rules! impl_vec_assign_op {
+    ($op_trait:ident, $op_fn:ident, $vec:ident, $method:path) => {
+        impl<'b, T> $op_trait<&'b $vec<T>> for $vec<T>
+        where T: Num + Clone + Copy + Display
+        {
+            fn $op_fn(&mut self, vec: &'b $vec<T>) {
+                *self = $method(self, vec);
+            }
+        }
+    };
+}
+
+macro_rules! impl_vec_unop_and_scalar {
+    ($vec:ident) => {
+        impl<'a, T> Neg for &'a $vec<T>
+        where T: Num + Clone + Copy + Display
+        {
+            type Output = $vec<T>;
+            fn neg(self) -> $vec<T> {
+                $vec::neg(self)
+            }
+        }
+
+        impl<'a, T> Mul<T> for &'a $vec<T>
+        where T: Num + Clone + Copy + Display
+        {
+            type Output = $vec<T>;
+            fn mul(self, k: T) -> $vec<T> {
+                self.scalar_mul(k)
+            }
+        }
+
+        impl<T> MulAssign<T> for $vec<T>
+        where T: Num + Clone + Copy + Display
+        {
+            fn mul_assign(&mut self, k: T) {
+                *self = self.scalar_mul(k);
+            }
+        }
+    };
+}
+
+impl_vec_binop!(Add, add, DenseVector, DenseVector::add);
+impl_vec_binop!(Sub, sub, DenseVector, DenseVector::sub);
+impl_vec_binop!(Add, add, SparseVector, SparseVector::add);
+impl_vec_binop!(Sub, sub, SparseVector, SparseVector::sub);
+
+impl_vec_assign_op!(AddAssign, add_assign, DenseVector, DenseVector::add);
+impl_vec_assign_op!(SubAssign, sub_assign, DenseVector, DenseVector::sub);
+impl_vec_assign_op!(AddAssign, add_assign, SparseVector, SparseVector::add);
+impl_vec_assign_op!(SubAssign, sub_assign, SparseVector, SparseVector::sub);
+
+impl_vec_unop_and_scalar!(DenseVector);
+impl_vec_unop_and_scalar!(SparseVector);
+
+macro_rules! impl_mat_vec {
+    ($mat:ident) => {
+        impl<'a, 'b, T> Mul<&'b DenseVector<T>> for &'a $mat<T>
+        where T: Num + Clone + Copy + Display + Element
+        {
+            type Output = DenseVector<T>;
+            fn mul(self, vec: &'b DenseVector<T>) -> DenseVector<T> {
+                DenseVector::from_vec(self.mat_vec(vec))
+            }
+        }
+    };
+}
+
+impl_mat_vec!(DenseMatrix);
+impl_mat_vec!(SparseMatrix);
+
+#[test]
+fn vector_ops_test() {
+    let a = DenseMatrix::<f64>::from(2, 2, &[1.0, 2.0, 3.0, 4.0]);
+    let x = DenseVector::from_vec(vec![1.0, -1.0]);
+    assert_eq!(&a * &x, DenseVector::from_vec(vec![-1.0, -1.0]));
+    assert_eq!(&SparseMatrix::from_dense(&a) * &x, &a * &x);
+
+    let mut y = &x * 2.0 - DenseVector::ones(2);
+    y += &x;
+    y -= &DenseVector::unit(2, 0);
+    assert_eq!(y.as_slice(), &[1.0, -4.0]);
+    assert_eq!(-&y, y.scalar_mul(-1.0));
+
+    let mut s = x.to_sparse();
+    s *= 3.0;
+    s += &SparseVector::from_entries(2, vec![(1, 3.0)]);
+    assert_eq!((s.nnz(), s.get_v(0)), (1, 3.0));
+    assert_eq!(&s - &s, SparseVector::new(2));
+}
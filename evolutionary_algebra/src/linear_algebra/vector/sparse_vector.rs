@@ -0,0 +1,325 @@
+extern crate num;
+
+use super::dense_vector::{self, DenseVector};
+use super::super::matrix::dense::DenseMatrix;
+use super::super::matrix::sparse::SparseMatrix;
+use super::super::scalar::Scalar;
+use super::super::super::error::AlgebraResult;
+use num::{Num, Float, Zero};
+use std::fmt::Display;
+
+// A vector of length len storing only its nonzeros, kept sorted by index so
+// that the binary operations are merges.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SparseVector<T>
+where T: Num + Clone + Copy + Display
+{
+    len: usize,
+    indices: Vec<usize>,
+    values: Vec<T>,
+}
+
+impl<T> SparseVector<T>
+where T: Num + Clone + Copy + Display
+{
+    pub fn new(len: usize) -> SparseVector<T>
+    {
+        SparseVector { len, indices: Vec::new(), values: Vec::new() }
+    }
+
+    // repeated indices are summed, zeros are dropped
+    pub fn from_entries<I>(len: usize, entries: I) -> SparseVector<T>
+    where I: IntoIterator<Item = (usize, T)>
+    {
+        let mut entries: Vec<(usize, T)> = entries.into_iter().collect();
+        entries.sort_by_key(|&(i, _)| i);
+        let mut vec = Self::new(len);
+        for (i, v) in entries {
+            assert!(i < len, "the index {} is out of bounds!", i);
+            if vec.indices.last() == Some(&i) {
+                let last = vec.values.len() - 1;
+                vec.values[last] = vec.values[last] + v;
+            }
+            else {
+                vec.indices.push(i);
+                vec.values.push(v);
+            }
+        }
+        vec.prune();
+        vec
+    }
+
+    pub fn from_dense(vec: &DenseVector<T>) -> SparseVector<T>
+    {
+        Self::from_entries(vec.len(), vec.iter().cloned().enumerate().filter(|&(_, v)| v != T::zero()))
+    }
+
+    // column col of mat
+    pub fn from_col(mat: &SparseMatrix<T>, col: usize) -> SparseVector<T>
+    {
+        assert!(col < mat.col(), "the column {} is out of bounds!", col);
+        Self::from_entries(mat.row(), mat.iter().filter(|&(_, j, _)| j == col).map(|(i, _, v)| (i, v)))
+    }
+
+    fn prune(&mut self)
+    {
+        let mut k = 0;
+        for n in 0 .. self.indices.len() {
+            if self.values[n] != T::zero() {
+                self.indices[k] = self.indices[n];
+                self.values[k] = self.values[n];
+                k += 1;
+            }
+        }
+        self.indices.truncate(k);
+        self.values.truncate(k);
+    }
+
+    pub fn len(&self) -> usize
+    {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.len == 0
+    }
+
+    pub fn nnz(&self) -> usize
+    {
+        self.indices.len()
+    }
+
+    pub fn indices(&self) -> &[usize]
+    {
+        &self.indices
+    }
+
+    pub fn values(&self) -> &[T]
+    {
+        &self.values
+    }
+
+    pub fn get_v(&self, i: usize) -> T
+    {
+        assert!(i < self.len, "the index {} is out of bounds!", i);
+        match self.indices.binary_search(&i) {
+            Ok(n) => self.values[n],
+            Err(_) => T::zero(),
+        }
+    }
+
+    // setting zero removes the entry
+    pub fn set_v(&mut self, i: usize, v: T)
+    {
+        assert!(i < self.len, "the index {} is out of bounds!", i);
+        match self.indices.binary_search(&i) {
+            Ok(n) if v == T::zero() => {
+                self.indices.remove(n);
+                self.values.remove(n);
+            },
+            Ok(n) => self.values[n] = v,
+            Err(_) if v == T::zero() => {},
+            Err(n) => {
+                self.indices.insert(n, i);
+                self.values.insert(n, v);
+            },
+        }
+    }
+
+    // the stored (index, value) pairs in increasing index order
+    pub fn iter(&self) -> impl Iterator<Item = (usize, T)> + '_
+    {
+        self.indices.iter().cloned().zip(self.values.iter().cloned())
+    }
+
+    pub fn to_dense(&self) -> DenseVector<T>
+    {
+        let mut vec = DenseVector::new(self.len);
+        for (i, v) in self.iter() {
+            vec.set_v(i, v);
+        }
+        vec
+    }
+
+    // the len x 1 matrix
+    pub fn to_matrix(&self) -> SparseMatrix<T>
+    {
+        let mut mat = SparseMatrix::new(self.len, 1);
+        for (i, v) in self.iter() {
+            mat.set_v(i, 0, v);
+        }
+        mat
+    }
+
+    pub fn to_dense_matrix(&self) -> DenseMatrix<T>
+    {
+        self.to_dense().to_matrix()
+    }
+
+    // f over the union of the two patterns, missing entries are zero
+    fn merge<F>(&self, vec: &SparseVector<T>, f: F) -> SparseVector<T>
+    where F: Fn(T, T) -> T
+    {
+        assert_eq!(self.len, vec.len, "the length of the vectors does not match!");
+        let mut out = Self::new(self.len);
+        let (mut p, mut q) = (0, 0);
+        while p < self.nnz() || q < vec.nnz() {
+            let i = self.indices.get(p).cloned().unwrap_or(usize::MAX);
+            let j = vec.indices.get(q).cloned().unwrap_or(usize::MAX);
+            let (k, a, b) = if i == j {
+                p += 1;
+                q += 1;
+                (i, self.values[p - 1], vec.values[q - 1])
+            }
+            else if i < j {
+                p += 1;
+                (i, self.values[p - 1], T::zero())
+            }
+            else {
+                q += 1;
+                (j, T::zero(), vec.values[q - 1])
+            };
+            out.indices.push(k);
+            out.values.push(f(a, b));
+        }
+        out.prune();
+        out
+    }
+
+    pub fn scalar_mul(&self, k: T) -> SparseVector<T>
+    {
+        let mut vec = SparseVector { len: self.len, indices: self.indices.clone(), values: self.values.iter().map(|&v| v * k).collect() };
+        vec.prune();
+        vec
+    }
+
+    pub fn neg(&self) -> SparseVector<T>
+    {
+        self.scalar_mul(T::zero() - T::one())
+    }
+
+    pub fn add(&self, vec: &SparseVector<T>) -> SparseVector<T>
+    {
+        self.merge(vec, |a, b| a + b)
+    }
+
+    pub fn sub(&self, vec: &SparseVector<T>) -> SparseVector<T>
+    {
+        self.merge(vec, |a, b| a - b)
+    }
+
+    pub fn try_add(&self, vec: &SparseVector<T>) -> AlgebraResult<SparseVector<T>>
+    {
+        dense_vector::check_len(self.len, vec.len)?;
+        Ok(self.add(vec))
+    }
+
+    pub fn try_sub(&self, vec: &SparseVector<T>) -> AlgebraResult<SparseVector<T>>
+    {
+        dense_vector::check_len(self.len, vec.len)?;
+        Ok(self.sub(vec))
+    }
+
+    // self += alpha x
+    pub fn axpy(&mut self, alpha: T, x: &SparseVector<T>)
+    {
+        *self = self.merge(x, |y_i, x_i| y_i + alpha * x_i);
+    }
+}
+
+impl<T> SparseVector<T>
+where T: Scalar
+{
+    // sum conj(x_i) y_i over the common pattern
+    pub fn dot(&self, vec: &SparseVector<T>) -> T
+    {
+        assert_eq!(self.len, vec.len, "the length of the vectors does not match!");
+        let (mut p, mut q, mut sum) = (0, 0, T::zero());
+        while p < self.nnz() && q < vec.nnz() {
+            if self.indices[p] == vec.indices[q] {
+                sum = sum + self.values[p].conj() * vec.values[q];
+                p += 1;
+                q += 1;
+            }
+            else if self.indices[p] < vec.indices[q] {
+                p += 1;
+            }
+            else {
+                q += 1;
+            }
+        }
+        sum
+    }
+
+    pub fn dot_dense(&self, vec: &DenseVector<T>) -> T
+    {
+        assert_eq!(self.len, vec.len(), "the length of the vectors does not match!");
+        self.iter().fold(T::zero(), |sum, (i, v)| sum + v.conj() * vec[i])
+    }
+
+    pub fn try_dot(&self, vec: &SparseVector<T>) -> AlgebraResult<T>
+    {
+        dense_vector::check_len(self.len, vec.len)?;
+        Ok(self.dot(vec))
+    }
+
+    pub fn norm_1(&self) -> T::Real
+    {
+        self.values.iter().fold(T::Real::zero(), |sum, &v| sum + v.modulus())
+    }
+
+    pub fn norm2(&self) -> T::Real
+    {
+        self.values.iter().fold(T::Real::zero(), |sum, &v| sum + v.modulus_sqr()).sqrt()
+    }
+
+    pub fn norm_inf(&self) -> T::Real
+    {
+        self.values.iter().fold(T::Real::zero(), |m, &v| m.max(v.modulus()))
+    }
+
+    // as DenseVector::normalize
+    pub fn normalize(&mut self) -> T::Real
+    {
+        let norm = self.norm2();
+        if norm > T::Real::zero() {
+            let inv = T::from_real(norm.recip());
+            for v in self.values.iter_mut() {
+                *v = *v * inv;
+            }
+        }
+        norm
+    }
+
+    pub fn normalized(&self) -> SparseVector<T>
+    {
+        let mut vec = self.clone();
+        vec.normalize();
+        vec
+    }
+}
+
+#[test]
+fn sparse_vector_test() {
+    let mut x = SparseVector::from_entries(6, vec![(4, 4.0), (1, 1.0), (4, -1.0), (2, 0.0)]);
+    assert_eq!((x.nnz(), x.get_v(4), x.get_v(0)), (2, 3.0, 0.0));
+    x.set_v(5, 2.0);
+    x.set_v(1, 0.0);
+    assert_eq!(x.iter().collect::<Vec<_>>(), vec![(4, 3.0), (5, 2.0)]);
+
+    let y = SparseVector::from_dense(&DenseVector::from_vec(vec![1.0, 0.0, 0.0, 0.0, 2.0, -1.0]));
+    assert_eq!(x.dot(&y), 4.0);
+    assert_eq!(x.dot_dense(&y.to_dense()), 4.0);
+    assert_eq!(x.add(&y).to_dense().as_slice(), &[1.0, 0.0, 0.0, 0.0, 5.0, 1.0]);
+    assert_eq!(x.sub(&x).nnz(), 0);
+    assert_eq!((x.norm_1(), x.norm_inf()), (5.0, 3.0));
+    assert_eq!(y.normalized().norm2(), 1.0);
+
+    let mut z = y.clone();
+    z.axpy(-2.0, &x);
+    assert_eq!(z.iter().collect::<Vec<_>>(), vec![(0, 1.0), (4, -4.0), (5, -5.0)]);
+    assert!(z.try_add(&SparseVector::new(5)).is_err());
+    assert_eq!(SparseVector::from_col(&z.to_matrix(), 0), z);
+    assert!(z.to_dense_matrix() == z.to_matrix().to_dense());
+}
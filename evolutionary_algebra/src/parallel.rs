@@ -0,0 +1,140 @@
+// The data-parallel building blocks of the matrix kernels. With the
+// `parallel` feature they spread the work over the rayon thread pool, without
+// it they are the plain loops, so the kernels call them unconditionally.
+//
+// The row-wise helpers hand every output element to exactly one thread, which
+// computes it in the serial order: their results are bit-for-bit those of the
+// serial build. Only the reductions (dot products and norms) depend on how a
+// sum is split. A deterministic reduction, which the iterative solvers use
+// under SolverOptions::deterministic, cuts the sum into fixed pieces and adds
+// their partial sums in order, with or without the feature: its result
+// depends neither on the number of threads nor on the build, though it may
+// differ in the last bits from the default one.
+#[cfg(feature = "parallel")]
+extern crate rayon;
+
+use std::ops::{Add, Range};
+
+#[cfg(feature = "parallel")]
+use self::rayon::prelude::*;
+
+// below this much work (elements, or multiply-adds) the threads cost more
+// than they save
+#[cfg(feature = "parallel")]
+const MIN_PARALLEL_WORK: usize = 1 << 14;
+// the length of the pieces a deterministic reduction is cut into
+const REDUCE_CHUNK: usize = 1 << 12;
+
+// what the parallel kernels need of an element type; required with and
+// without the feature, so that turning it on does not narrow the public API
+pub trait Element: Send + Sync {}
+impl<T> Element for T where T: Send + Sync {}
+
+// and of the closures run on the threads, which never reach the public API
+#[cfg(feature = "parallel")]
+pub(crate) trait Task: Send + Sync {}
+#[cfg(feature = "parallel")]
+impl<F> Task for F where F: Send + Sync {}
+
+#[cfg(not(feature = "parallel"))]
+pub(crate) trait Task {}
+#[cfg(not(feature = "parallel"))]
+impl<F> Task for F {}
+
+pub fn is_enabled() -> bool
+{
+    cfg!(feature = "parallel")
+}
+
+// runs f on a pool of its own with the given number of threads
+#[cfg(feature = "parallel")]
+pub fn with_threads<R, F>(num_threads: usize, f: F) -> R
+where F: FnOnce() -> R + Send, R: Send
+{
+    let pool = rayon::ThreadPoolBuilder::new().num_threads(num_threads).build()
+        .expect("can not build the thread pool!");
+    pool.install(f)
+}
+
+// f(i, row) for the consecutive rows of row_len elements of data
+pub(crate) fn for_each_row<T, F>(data: &mut [T], row_len: usize, work: usize, f: F)
+where T: Element, F: Fn(usize, &mut [T]) + Task
+{
+    if row_len == 0 {
+        return;
+    }
+    #[cfg(feature = "parallel")]
+    {
+        if work >= MIN_PARALLEL_WORK {
+            data.par_chunks_mut(row_len).enumerate().for_each(|(i, row)| f(i, row));
+            return;
+        }
+    }
+    let _ = work;
+    for (i, row) in data.chunks_mut(row_len).enumerate() {
+        f(i, row);
+    }
+}
+
+// [f(0), f(1), .., f(len - 1)]
+pub(crate) fn map_range<T, F>(len: usize, work: usize, f: F) -> Vec<T>
+where T: Element, F: Fn(usize) -> T + Task
+{
+    #[cfg(feature = "parallel")]
+    {
+        if work >= MIN_PARALLEL_WORK {
+            return (0 .. len).into_par_iter().map(f).collect();
+        }
+    }
+    let _ = work;
+    (0 .. len).map(f).collect()
+}
+
+// the sum of part(r) over ranges r covering 0 .. len
+pub(crate) fn sum<T, F>(len: usize, zero: T, deterministic: bool, part: F) -> T
+where T: Element + Copy + Add<Output = T>, F: Fn(Range<usize>) -> T + Task
+{
+    let chunk = |c: usize| part(c * REDUCE_CHUNK .. len.min((c + 1) * REDUCE_CHUNK));
+    if deterministic {
+        return map_range(len.div_ceil(REDUCE_CHUNK), len, chunk).into_iter().fold(zero, |s, v| s + v);
+    }
+    #[cfg(feature = "parallel")]
+    {
+        if len >= MIN_PARALLEL_WORK {
+            return (0 .. len.div_ceil(REDUCE_CHUNK)).into_par_iter().map(chunk).reduce(|| zero, |a, b| a + b);
+        }
+    }
+    part(0 .. len)
+}
+
+#[test]
+fn parallel_test() {
+    // large enough to be split with the feature on
+    let n = 3 * (1 << 14) + 17;
+    let x: Vec<f64> = (0 .. n).map(|i| ((i * 7919) % 1000) as f64 / 997.0 - 0.5).collect();
+    let serial = x.iter().fold(0.0, |s, &v| s + v * v);
+    let dot = |r: Range<usize>| x[r].iter().fold(0.0, |s, &v| s + v * v);
+    assert!((sum(n, 0.0, false, dot) - serial).abs() < 1e-10 * serial);
+    assert_eq!(sum(5, 0.0, false, dot), x[.. 5].iter().fold(0.0, |s, &v| s + v * v));
+    // the fixed pieces added in order, which the parallel build reproduces
+    let pieces = x.chunks(REDUCE_CHUNK).map(|c| c.iter().fold(0.0, |s, &v| s + v * v)).fold(0.0, |s, v| s + v);
+    assert_eq!(sum(n, 0.0, true, dot).to_bits(), pieces.to_bits());
+
+    let mut rows = vec![0usize; 6 * 5];
+    for_each_row(&mut rows, 5, 30, |i, row| for (j, v) in row.iter_mut().enumerate() {
+        *v = 10 * i + j;
+    });
+    assert_eq!(rows[17], 32);
+    assert_eq!(map_range(n, n, |i| i * i)[n - 1], (n - 1) * (n - 1));
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn deterministic_test() {
+    let n = 50 * REDUCE_CHUNK + 3;
+    let x: Vec<f64> = (0 .. n).map(|i| 1.0 / (1.0 + i as f64)).collect();
+    let dot = |r: Range<usize>| x[r].iter().fold(0.0, |s, &v| s + v * v);
+    let pieces = x.chunks(REDUCE_CHUNK).map(|c| c.iter().fold(0.0, |s, &v| s + v * v)).fold(0.0, |s, v| s + v);
+    let sums: Vec<f64> = [1, 2, 3, 8].iter().map(|&k| with_threads(k, || sum(n, 0.0, true, dot))).collect();
+    assert!(sums.iter().all(|s| s.to_bits() == pieces.to_bits()));
+}